@@ -0,0 +1,33 @@
+use my_game::configs::prelude::*;
+use my_game::core::prelude::*;
+use my_game::gameplay::prelude::*;
+use my_game::prelude::*;
+
+/// Simulated time after which the run stops, in seconds.
+const RUN_DURATION: f32 = 60.0;
+
+/// Runs the world simulation without window, reading the same settings file as the game.
+fn main() {
+    let config = ConfigPlugin::load(SETTINGS_PATH);
+
+    let mut app = App::new();
+    app.add_plugins(HeadlessPlugins { settings: config.settings.clone(), ..default() })
+        .add_plugins(LogPlugin { filter: config.settings.debug.log_filter.clone(), ..default() })
+        .add_systems(Update, stop_after_run_duration_system.run_if(in_state(GameState::InGame)));
+
+    if let Some(path) = scenario_from_args(std::env::args()) {
+        app.insert_resource(StartupScenario(path));
+    }
+    app.run();
+}
+
+fn stop_after_run_duration_system(time: Res<Time>, stats: Res<GameStats>, mut exit_writer: EventWriter<AppExit>) {
+    if time.elapsed_seconds() < RUN_DURATION {
+        return;
+    }
+
+    for (structure, structure_stats) in &stats.structures {
+        info!("{:?}: {:?}", structure, structure_stats);
+    }
+    exit_writer.send(AppExit::Success);
+}
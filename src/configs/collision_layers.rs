@@ -0,0 +1,86 @@
+use avian2d::prelude::*;
+
+/// Physics layers of the colliders of the game.
+#[derive(PhysicsLayer, Clone, Copy, Debug)]
+pub enum GameLayer {
+    /// Background tiles of the level.
+    Level,
+    /// Compound collider of a structure, made of one square per module cell.
+    Hull,
+    /// Modules still attached to a structure, the walls the player walks between.
+    Module,
+    /// Modules blown off a structure.
+    Wreck,
+    Player,
+    Projectile,
+    Debris,
+    /// Static bodies of the world such as ore.
+    Environment,
+}
+
+/// Layers and masks of every kind of collider, kept in one place so both sides of each pair agree.
+///
+/// Structures bump into each other through their hulls, while the player and the projectiles only meet the modules
+/// themselves: the player walks between the interior walls of a structure without being pushed out by its hull, and
+/// a hit always knows which module it damaged. Debris floats through the player.
+pub struct CollisionLayersConfig;
+
+impl CollisionLayersConfig {
+    pub fn level() -> CollisionLayers {
+        CollisionLayers::new(GameLayer::Level, LayerMask::NONE)
+    }
+
+    pub fn hull() -> CollisionLayers {
+        CollisionLayers::new(
+            GameLayer::Hull,
+            [GameLayer::Hull, GameLayer::Wreck, GameLayer::Debris, GameLayer::Environment],
+        )
+    }
+
+    pub fn module() -> CollisionLayers {
+        CollisionLayers::new(GameLayer::Module, [GameLayer::Player, GameLayer::Projectile])
+    }
+
+    pub fn wreck() -> CollisionLayers {
+        CollisionLayers::new(
+            GameLayer::Wreck,
+            [
+                GameLayer::Hull,
+                GameLayer::Wreck,
+                GameLayer::Player,
+                GameLayer::Projectile,
+                GameLayer::Debris,
+                GameLayer::Environment,
+            ],
+        )
+    }
+
+    pub fn player() -> CollisionLayers {
+        CollisionLayers::new(
+            GameLayer::Player,
+            [GameLayer::Module, GameLayer::Wreck, GameLayer::Projectile, GameLayer::Environment],
+        )
+    }
+
+    /// Projectiles also need `ignore_own_projectiles_system` to pass through the structure that fired them.
+    pub fn projectile() -> CollisionLayers {
+        CollisionLayers::new(
+            GameLayer::Projectile,
+            [GameLayer::Module, GameLayer::Wreck, GameLayer::Player, GameLayer::Debris, GameLayer::Environment],
+        )
+    }
+
+    pub fn debris() -> CollisionLayers {
+        CollisionLayers::new(
+            GameLayer::Debris,
+            [GameLayer::Hull, GameLayer::Wreck, GameLayer::Projectile, GameLayer::Debris, GameLayer::Environment],
+        )
+    }
+
+    pub fn environment() -> CollisionLayers {
+        CollisionLayers::new(
+            GameLayer::Environment,
+            [GameLayer::Hull, GameLayer::Wreck, GameLayer::Player, GameLayer::Projectile, GameLayer::Debris],
+        )
+    }
+}
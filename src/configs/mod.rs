@@ -1,5 +1,9 @@
+pub mod collision_layers;
+
 pub mod config;
 
 pub mod plugin_groups;
 
 pub mod prelude;
+
+pub mod settings;
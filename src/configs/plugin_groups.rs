@@ -1,38 +1,179 @@
 use crate::core::prelude::*;
+use crate::core::profiling::ProfilingPlugin;
 use crate::gameplay::prelude::*;
 use crate::ui::prelude::*;
 use crate::world::prelude::*;
 
 use bevy::app::{PluginGroup, PluginGroupBuilder};
 
+#[cfg(feature = "headless")]
+use crate::configs::config::UNIT_SCALE;
+#[cfg(feature = "headless")]
+use crate::configs::settings::{ConfigPlugin, GameSettings};
+#[cfg(feature = "headless")]
+use avian2d::prelude::PhysicsPlugins;
+#[cfg(feature = "headless")]
+use bevy::prelude::MinimalPlugins;
+
 /// A group of plugins that has loading assets involved
 pub struct LoadersPlugins;
 impl PluginGroup for LoadersPlugins {
     fn build(self) -> PluginGroupBuilder {
-        PluginGroupBuilder::start::<Self>().add(StatePlugin).add(SchedulePlugin).add(AssetLoaderPlugin)
+        PluginGroupBuilder::start::<Self>()
+            .add(StatePlugin)
+            .add(SchedulePlugin)
+            .add(AssetLoaderPlugin)
+            .add(GameAssetsPlugin)
+            .add(ProfilingPlugin)
     }
 }
 
+/// The game played in a window: the simulation, with the tools and messages drawn in the window on top.
 pub struct GamePlugins {
     pub debug_enable: bool,
 }
 impl PluginGroup for GamePlugins {
     fn build(self) -> PluginGroupBuilder {
         PluginGroupBuilder::start::<Self>()
-            .add(GridPlugin { debug_enable: self.debug_enable })
+            .add_group(SimulationPlugins { debug_enable: self.debug_enable })
+            .add(EditorPlugin)
+            // Talk to the player through the dialogue box and the toasts
+            .add(HailPlugin)
+            .add(AchievementsPlugin)
+    }
+}
+
+/// Everything that runs the world (grid, structures, movement, combat, crew and AI), shared by the game and the
+/// headless runs so they cannot drift apart. Nothing in it needs a window, a renderer or an audio device.
+pub struct SimulationPlugins {
+    pub debug_enable: bool,
+}
+impl PluginGroup for SimulationPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add(GridPlugin)
             .add(InputsPlugin)
             .add(PlayerPlugin)
             .add(MovementPlugin)
-            .add(StructuresPlugin { debug_enable: self.debug_enable })
+            .add(ManeuverPlugin)
+            .add(AutopilotPlugin)
+            .add(JetpackPlugin)
+            .add(EjectPlugin)
+            .add(StructuresPlugin)
+            .add(NamesPlugin::default())
             .add(OrePlugin)
+            .add(DebrisPlugin)
+            .add(DestructionPlugin)
+            .add(GravitySourcePlugin)
+            .add(WorldBoundsPlugin)
+            .add(DegradationPlugin)
+            .add(CrewPlugin)
+            .add(MedicalPlugin)
+            .add(HealthPlugin)
+            .add(StatusEffectsPlugin)
+            .add(LifeSupportPlugin)
+            .add(ContaminantsPlugin)
+            .add(SavePlugin)
+            .add(DoorsPlugin)
+            .add(DockingPlugin)
+            .add(RepairPlugin)
+            .add(RepairDronesPlugin)
+            .add(CargoPlugin)
+            .add(CraftingPlugin)
+            .add(BuildingPlugin)
+            .add(ClipboardPlugin)
+            .add(LiveryPlugin)
+            .add(UpgradesPlugin)
+            .add(FactionPlugin)
+            .add(SandboxPlugin)
+            .add(ScenarioPlugin)
+            .add(TargetDronePlugin)
+            .add(AiPlugin)
+            .add(ScanPlugin)
+            .add(SensorPlugin)
+            .add(TargetingPlugin)
+            .add(EscortPlugin)
+            .add(EncounterPlugin)
+            .add(DerelictsPlugin)
+            .add(OffscreenBattlePlugin)
+            .add(StatsPlugin)
+            .add(JournalPlugin)
+            .add(ReplayPlugin)
+            .add(TutorialPlugin)
+            .add(PowerPlugin)
+            .add(HeatPlugin)
+            .add(DespawnAuditPlugin { debug_enable: self.debug_enable })
     }
 }
 
-pub struct UtilityPlugins {
-    pub debug_enable: bool,
-}
+pub struct UtilityPlugins;
 impl PluginGroup for UtilityPlugins {
     fn build(self) -> PluginGroupBuilder {
-        PluginGroupBuilder::start::<Self>().add(DebugPlugin { enable: self.debug_enable }).add(CameraPlugin)
+        PluginGroupBuilder::start::<Self>()
+            .add(DebugPlugin)
+            .add(AssetErrorScreenPlugin)
+            .add(CameraPlugin)
+            .add(CullingPlugin)
+            .add(ModuleHealthVisualPlugin::default())
+            .add(WorldTextPlugin)
+            .add(DamagePopupPlugin)
+            .add(DamagePredictionPlugin)
+            .add(RangeRingPlugin)
+            .add(TargetLockIndicatorPlugin)
+            .add(ScanOverlayPlugin)
+            .add(SmokeOverlayPlugin)
+            .add(EffectsPlugin)
+            .add(AudioPlugin)
+            .add(ImpactSoundsPlugin)
+            .add(DpsMeterPlugin)
+            .add(MinimapPlugin)
+            .add(JournalPanelPlugin)
+            .add(SaveMenuPlugin)
+            .add(ScenarioMenuPlugin)
+            .add(FocusNavigationPlugin)
+            .add(ProfilerOverlayPlugin)
+            .add(StructureHudPlugin)
+            .add(InteractionPromptPlugin)
+            .add(KillFeedPlugin)
+            .add(DialoguePlugin)
+            .add(ToastPlugin)
+            .add(ConsolePlugin)
+    }
+}
+
+/// Runs the world simulation (grid, structures, movement, combat, crew and AI) without window nor rendering,
+/// for integration tests and dedicated servers. Every `App::update` steps the simulation by a fixed timestep.
+#[cfg(feature = "headless")]
+pub struct HeadlessPlugins {
+    pub settings: GameSettings,
+    pub timestep: std::time::Duration,
+}
+
+#[cfg(feature = "headless")]
+impl Default for HeadlessPlugins {
+    fn default() -> Self {
+        Self { settings: GameSettings::default(), timestep: HEADLESS_TIMESTEP }
+    }
+}
+
+#[cfg(feature = "headless")]
+impl PluginGroup for HeadlessPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add_group(MinimalPlugins)
+            .add(bevy::transform::TransformPlugin)
+            .add(bevy::hierarchy::HierarchyPlugin)
+            .add(bevy::diagnostic::DiagnosticsPlugin)
+            .add(bevy::input::InputPlugin)
+            .add(bevy::asset::AssetPlugin::default())
+            .add(bevy::scene::ScenePlugin)
+            .add(bevy::state::app::StatesPlugin)
+            .add(HeadlessPlugin { timestep: self.timestep })
+            // Systems drawing gizmos still need their storage
+            .add(bevy::gizmos::GizmoPlugin)
+            .add_group(PhysicsPlugins::default().with_length_unit(UNIT_SCALE))
+            .add(ConfigPlugin::new(self.settings))
+            .add_group(LoadersPlugins)
+            .add_group(SimulationPlugins { debug_enable: false })
     }
 }
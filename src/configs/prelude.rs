@@ -1,2 +1,4 @@
+pub use super::collision_layers::*;
 pub use super::config::*;
 pub use super::plugin_groups::*;
+pub use super::settings::*;
@@ -0,0 +1,127 @@
+use crate::configs::config::{WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::core::asset_loader::DataSettings;
+use crate::core::debug_overlays::DebugOverlays;
+use crate::core::inputs::KeyBindings;
+use crate::core::replay::ReplaySettings;
+use crate::gameplay::derelicts::DerelictSettings;
+use crate::gameplay::movement::MovementSettings;
+use crate::gameplay::repair_drones::RepairPriorities;
+use crate::gameplay::world_bounds::WorldBoundsSettings;
+use crate::ui::audio::AudioSettings;
+use crate::ui::camera::CameraSettings;
+use bevy::prelude::*;
+use bevy::window::PresentMode;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+use thiserror::Error;
+
+/// Settings file read at startup, next to the executable.
+pub const SETTINGS_PATH: &str = "settings.ron";
+
+const DEFAULT_LOG_FILTER: &str = "info,my_game::player=debug,my_game::grid=debug,my_game::structure=debug,my_game::movement=debug,my_game::modules=debug,my_game::structure_combat=debug";
+
+#[derive(Debug, Error)]
+pub enum SettingsError {
+    #[error("Could not read the settings file: {0}")]
+    Io(#[from] io::Error),
+    #[error("Could not parse the settings file: {0}")]
+    Parse(#[from] ron::error::SpannedError),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowSettings {
+    pub width: f32,
+    pub height: f32,
+    pub present_mode: PresentMode,
+}
+
+impl Default for WindowSettings {
+    fn default() -> Self {
+        Self { width: WINDOW_WIDTH, height: WINDOW_HEIGHT, present_mode: PresentMode::Immediate }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DebugSettings {
+    /// Shows every debug overlay at startup, each can still be toggled while playing.
+    pub enabled: bool,
+    pub log_filter: String,
+}
+
+impl Default for DebugSettings {
+    fn default() -> Self {
+        Self { enabled: true, log_filter: DEFAULT_LOG_FILTER.to_string() }
+    }
+}
+
+/// Everything read from the settings file, any missing field keeps its default value.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameSettings {
+    pub window: WindowSettings,
+    pub debug: DebugSettings,
+    pub data: DataSettings,
+    pub key_bindings: KeyBindings,
+    pub camera: CameraSettings,
+    pub audio: AudioSettings,
+    pub movement: MovementSettings,
+    pub replay: ReplaySettings,
+    pub world_bounds: WorldBoundsSettings,
+    pub repair_priorities: RepairPriorities,
+    pub derelicts: DerelictSettings,
+}
+
+impl GameSettings {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SettingsError> {
+        let content = fs::read_to_string(path)?;
+        Ok(ron::from_str(&content)?)
+    }
+}
+
+/// Loads the settings file and inserts its sections as resources.
+/// The window and log settings are needed to build the app, read them from `settings` first.
+pub struct ConfigPlugin {
+    pub settings: GameSettings,
+    load_error: Option<SettingsError>,
+}
+
+impl ConfigPlugin {
+    /// Uses the given settings instead of reading the settings file.
+    pub fn new(settings: GameSettings) -> Self {
+        Self { settings, load_error: None }
+    }
+
+    /// Reads the settings file, a missing or invalid file falls back to the default settings.
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        match GameSettings::load(path) {
+            Ok(settings) => Self { settings, load_error: None },
+            Err(SettingsError::Io(error)) if error.kind() == io::ErrorKind::NotFound => {
+                Self { settings: GameSettings::default(), load_error: None }
+            }
+            Err(error) => Self { settings: GameSettings::default(), load_error: Some(error) },
+        }
+    }
+}
+
+impl Plugin for ConfigPlugin {
+    fn build(&self, app: &mut App) {
+        if let Some(error) = &self.load_error {
+            warn!("{}, using the default settings", error);
+        }
+
+        app.insert_resource(DebugOverlays::all(self.settings.debug.enabled))
+            .insert_resource(self.settings.data.clone())
+            .insert_resource(self.settings.key_bindings.clone())
+            .insert_resource(self.settings.camera.clone())
+            .insert_resource(self.settings.audio.clone())
+            .insert_resource(self.settings.movement.clone())
+            .insert_resource(self.settings.replay.clone())
+            .insert_resource(self.settings.world_bounds.clone())
+            .insert_resource(self.settings.repair_priorities.clone())
+            .insert_resource(self.settings.derelicts.clone());
+    }
+}
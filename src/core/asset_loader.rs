@@ -1,103 +1,268 @@
+use crate::core::asset_validation::{validate_level, validate_structures, AssetLoadError, ValidationError};
 use crate::core::state::GameState;
+use crate::gameplay::livery::Livery;
+use crate::world::module_registry::ModuleRegistry;
+use crate::world::modules::Orientation;
 use bevy::{
-    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    asset::{
+        io::Reader, AssetLoadFailedEvent, AssetLoader, AsyncReadExt, LoadContext, RecursiveDependencyLoadState,
+        UntypedAssetId,
+    },
     prelude::*,
     reflect::TypePath,
 };
-use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
 use thiserror::Error;
 
-#[derive(Debug, Deserialize)]
+pub const LEVEL_PATH: &str = "data/level.json";
+pub const STRUCTURES_PATH: &str = "data/structures.json";
+
+/// Data files the world is built from, read as JSON, RON or TOML depending on their extension.
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DataSettings {
+    pub level: String,
+    pub structures: String,
+}
+
+impl Default for DataSettings {
+    fn default() -> Self {
+        Self { level: LEVEL_PATH.to_string(), structures: STRUCTURES_PATH.to_string() }
+    }
+}
+
+#[derive(Asset, TypePath, Debug, Clone, Deserialize)]
 pub struct Level {
     pub width: u32,
     pub height: u32,
     pub cell_size: f32,
     pub world: Vec<String>,
+    /// Planets and asteroids pulling everything around them.
+    #[serde(default)]
+    pub gravity_sources: Vec<GravitySourceData>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GravitySourceData {
+    pub name: String,
+    pub position: [f32; 2],
+    /// Radius of the body, in meters.
+    pub radius: f32,
+    /// Gravity on the surface of the body, in m/s², it falls off with the square of the distance.
+    pub surface_gravity: f32,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct StructureData {
     pub world_pos: [f32; 2],
     pub structure: Vec<String>,
+    /// Modules facing another way than North, by cell.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub orientations: Vec<ModuleOrientation>,
+    #[serde(default)]
+    pub crew: u32,
+    #[serde(default)]
+    pub rotation: f32,
+    #[serde(default)]
+    pub velocity: [f32; 2],
+    #[serde(default)]
+    pub livery: Livery,
+    /// Faction picking the palette of the modules, the default palette when there is none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub faction: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+impl StructureData {
+    /// Facing of the module in a cell of the layout, North when the data does not say.
+    pub fn orientation_at(&self, cell: (i32, i32)) -> Orientation {
+        self.orientations
+            .iter()
+            .find(|oriented| oriented.cell == cell)
+            .map(|oriented| oriented.facing)
+            .unwrap_or_default()
+    }
+}
+
+/// The facing of the module in a cell of a structure layout.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ModuleOrientation {
+    pub cell: (i32, i32),
+    pub facing: Orientation,
+}
+
+#[derive(Asset, TypePath, Debug, Clone, Deserialize, Serialize)]
 pub struct StructuresData {
     pub structures: Vec<StructureData>,
 }
 
 #[non_exhaustive]
 #[derive(Debug, Error)]
-enum BlobAssetLoaderError {
-    /// An [IO](std::io) Error
+pub enum DataAssetLoaderError {
     #[error("Could not load file: {0}")]
     Io(#[from] std::io::Error),
+    #[error("Invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid RON: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("Invalid TOML: {0}")]
+    Toml(#[from] toml::de::Error),
+    #[error("Unsupported data file extension: {0:?}")]
+    UnsupportedExtension(Option<String>),
+}
+
+/// Text formats the data files can be written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataFormat {
+    Json,
+    Ron,
+    Toml,
+}
+
+impl DataFormat {
+    pub const EXTENSIONS: [&'static str; 3] = ["json", "ron", "toml"];
+
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "json" => Some(DataFormat::Json),
+            "ron" => Some(DataFormat::Ron),
+            "toml" => Some(DataFormat::Toml),
+            _ => None,
+        }
+    }
+
+    pub fn deserialize<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, DataAssetLoaderError> {
+        Ok(match self {
+            DataFormat::Json => serde_json::from_slice(bytes)?,
+            DataFormat::Ron => ron::de::from_bytes(bytes)?,
+            DataFormat::Toml => {
+                let text = std::str::from_utf8(bytes)
+                    .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))?;
+                toml::from_str(text)?
+            }
+        })
+    }
 }
 
-#[derive(Asset, TypePath, Debug, Deserialize)]
-pub struct AssetBlob {
-    pub bytes: Vec<u8>,
+/// Loads a data asset straight from a JSON, RON or TOML file.
+pub struct DataAssetLoader<T>(PhantomData<fn() -> T>);
+
+impl<T> Default for DataAssetLoader<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
 }
 
-#[derive(Default)]
-struct BlobAssetLoader;
-impl AssetLoader for BlobAssetLoader {
-    type Asset = AssetBlob;
+impl<T: Asset + DeserializeOwned> AssetLoader for DataAssetLoader<T> {
+    type Asset = T;
     type Settings = ();
-    type Error = BlobAssetLoaderError;
+    type Error = DataAssetLoaderError;
 
     async fn load<'a>(
         &'a self,
         reader: &'a mut Reader<'_>,
         _settings: &'a (),
-        _load_context: &'a mut LoadContext<'_>,
+        load_context: &'a mut LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
-        info!("Loading Blob...");
+        let extension = load_context.path().extension().and_then(|extension| extension.to_str());
+        let format = extension
+            .and_then(DataFormat::from_extension)
+            .ok_or_else(|| DataAssetLoaderError::UnsupportedExtension(extension.map(str::to_string)))?;
+
         let mut bytes = Vec::new();
         reader.read_to_end(&mut bytes).await?;
+        format.deserialize(&bytes)
+    }
 
-        Ok(AssetBlob { bytes })
+    fn extensions(&self) -> &[&str] {
+        &DataFormat::EXTENSIONS
     }
 }
 
+/// Handles of the data files the world is built from, their assets are only available once loaded and valid.
 #[derive(Resource, Default)]
 pub struct AssetStore {
-    pub level_blob: Handle<AssetBlob>,
-    pub structures_blob: Handle<AssetBlob>,
+    pub level: Handle<Level>,
+    pub structures: Handle<StructuresData>,
 }
 
 pub struct AssetLoaderPlugin;
 impl Plugin for AssetLoaderPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<AssetStore>()
-            .init_asset::<AssetBlob>()
-            .init_asset_loader::<BlobAssetLoader>()
+            .init_resource::<DataSettings>()
+            .init_asset::<Level>()
+            .init_asset::<StructuresData>()
+            .init_asset_loader::<DataAssetLoader<Level>>()
+            .init_asset_loader::<DataAssetLoader<StructuresData>>()
+            .add_event::<AssetLoadError>()
             .add_systems(PreStartup, setup)
-            .add_systems(Update, print_on_load.run_if(in_state(GameState::LoadingAssets)));
+            .add_systems(Update, validate_assets_system.run_if(in_state(GameState::LoadingAssets)));
     }
 }
 
-fn setup(mut state: ResMut<AssetStore>, asset_server: Res<AssetServer>) {
-    // Will use BlobAssetLoader instead of CustomAssetLoader thanks to type inference
-    state.level_blob = asset_server.load("data/level.json");
+fn setup(mut state: ResMut<AssetStore>, asset_server: Res<AssetServer>, data_settings: Res<DataSettings>) {
+    // The data loaders are picked by asset type, the format by the file extension
+    state.level = asset_server.load(data_settings.level.clone());
 
-    state.structures_blob = asset_server.load("data/structures.json");
+    state.structures = asset_server.load(data_settings.structures.clone());
 }
 
-fn print_on_load(
-    state: ResMut<AssetStore>,
-    blob_assets: Res<Assets<AssetBlob>>,
+/// Validates the data files once both are loaded, moving on to build the world or to the asset error screen.
+pub(crate) fn validate_assets_system(
+    state: Res<AssetStore>,
+    data_settings: Res<DataSettings>,
+    asset_server: Res<AssetServer>,
+    levels: Res<Assets<Level>>,
+    structures_assets: Res<Assets<StructuresData>>,
+    module_registry: Res<ModuleRegistry>,
+    mut level_failures: EventReader<AssetLoadFailedEvent<Level>>,
+    mut structures_failures: EventReader<AssetLoadFailedEvent<StructuresData>>,
+    mut error_writer: EventWriter<AssetLoadError>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
-    let level_blob = blob_assets.get(&state.level_blob);
-    let structures_blob = blob_assets.get(&state.structures_blob);
+    let failures: Vec<(String, String)> = level_failures
+        .read()
+        .map(|event| (data_settings.level.clone(), event.error.to_string()))
+        .chain(structures_failures.read().map(|event| (data_settings.structures.clone(), event.error.to_string())))
+        .collect();
+    if !failures.is_empty() {
+        for (path, error) in failures {
+            error!("Could not load {}: {}", path, error);
+            error_writer.send(AssetLoadError { path, errors: vec![ValidationError::Load(error)] });
+        }
+        next_state.set(GameState::AssetError);
+        return;
+    }
+
+    let is_loaded =
+        |id: UntypedAssetId| asset_server.recursive_dependency_load_state(id) == RecursiveDependencyLoadState::Loaded;
+    if !is_loaded(state.level.id().untyped()) || !is_loaded(state.structures.id().untyped()) {
+        return;
+    }
+    // Assets being reloaded are removed until their new version is in
+    let (Some(level), Some(structures)) = (levels.get(&state.level), structures_assets.get(&state.structures)) else {
+        return;
+    };
+    info!("Level loaded from {}", data_settings.level);
+    info!("{} structures loaded from {}", structures.structures.len(), data_settings.structures);
 
-    if level_blob.is_none() && structures_blob.is_none() {
-        info!("Blobs Not Ready");
+    let level_errors = validate_level(level);
+    let structures_errors = validate_structures(structures, &module_registry);
+    if level_errors.is_empty() && structures_errors.is_empty() {
+        next_state.set(GameState::BuildingGrid);
         return;
     }
-    info!("Level Blob Loaded, Size: {:?} Bytes", level_blob.unwrap().bytes.len());
-    info!("Structures Blob Loaded, Size: {:?} Bytes", structures_blob.unwrap().bytes.len());
 
-    next_state.set(GameState::BuildingGrid);
+    for (path, errors) in [(&data_settings.level, level_errors), (&data_settings.structures, structures_errors)] {
+        if errors.is_empty() {
+            continue;
+        }
+        for error in &errors {
+            error!("{}: {}", path, error);
+        }
+        error_writer.send(AssetLoadError { path: path.clone(), errors });
+    }
+    next_state.set(GameState::AssetError);
 }
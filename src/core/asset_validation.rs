@@ -0,0 +1,166 @@
+use crate::core::asset_loader::{Level, StructuresData};
+use crate::gameplay::degradation::DegradationCurves;
+use crate::world::module_registry::ModuleRegistry;
+use crate::world::modules::ModuleType;
+use bevy::prelude::*;
+use std::collections::HashSet;
+use thiserror::Error;
+
+/// Characters of the level rows, outer space and empty cells.
+pub const LEVEL_CELL_SYMBOLS: [char; 3] = ['#', '.', ' '];
+/// Structures must be placed within this distance of the origin on both axes, in meters.
+pub const WORLD_POSITION_LIMIT: f32 = 100_000.0;
+
+/// Sent when a data file could not be loaded, with everything wrong in it.
+#[derive(Event, Debug, Clone)]
+pub struct AssetLoadError {
+    pub path: String,
+    pub errors: Vec<ValidationError>,
+}
+
+/// Something wrong in a data file, rows and characters are counted from 1.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ValidationError {
+    /// The file is missing or could not be deserialized, the message tells the line and character in error.
+    #[error("{0}")]
+    Load(String),
+    #[error("the cell size must be positive, found {0}")]
+    InvalidCellSize(f32),
+    #[error("the level has {found} rows but a height of {height}")]
+    LevelTooTall { height: u32, found: usize },
+    #[error("level row {row} has {found} characters but the level is {width} wide")]
+    LevelRowTooWide { row: usize, width: u32, found: usize },
+    #[error("level row {row}, character {column}: unknown cell '{symbol}'")]
+    UnknownCell { row: usize, column: usize, symbol: char },
+    #[error("structure {structure} has no rows")]
+    EmptyStructure { structure: usize },
+    #[error("structure {structure}, row {row} has {found} characters where the first row has {expected}")]
+    RaggedRow { structure: usize, row: usize, expected: usize, found: usize },
+    #[error("structure {structure}, row {row}, character {column}: unknown module '{symbol}'")]
+    UnknownModule { structure: usize, row: usize, column: usize, symbol: char },
+    #[error(
+        "structure {structure}, row {row}, character {column}: module '{symbol}' does not fill its whole footprint"
+    )]
+    IncompleteFootprint { structure: usize, row: usize, column: usize, symbol: char },
+    #[error("structure {structure}: the orientation of cell {x}, {y} has no module to turn")]
+    OrientationWithoutModule { structure: usize, x: i32, y: i32 },
+    #[error("gravity source {body} needs a positive radius and a surface gravity of at least 0")]
+    InvalidGravitySource { body: usize },
+    #[error("structure {structure} is placed at {x}, {y}, outside of the world")]
+    OutOfBounds { structure: usize, x: f32, y: f32 },
+    #[error("degradation curve for unknown module '{symbol}'")]
+    UnknownCurveModule { symbol: char },
+}
+
+/// Checks the rows of the level fit its size and its gravity sources have a size.
+pub fn validate_level(level: &Level) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+
+    if level.cell_size <= 0.0 || !level.cell_size.is_finite() {
+        errors.push(ValidationError::InvalidCellSize(level.cell_size));
+    }
+    if level.world.len() > level.height as usize {
+        errors.push(ValidationError::LevelTooTall { height: level.height, found: level.world.len() });
+    }
+    for (y, row) in level.world.iter().enumerate() {
+        let found = row.chars().count();
+        if found > level.width as usize {
+            errors.push(ValidationError::LevelRowTooWide { row: y + 1, width: level.width, found });
+        }
+        for (x, symbol) in row.chars().enumerate() {
+            if !LEVEL_CELL_SYMBOLS.contains(&symbol) {
+                errors.push(ValidationError::UnknownCell { row: y + 1, column: x + 1, symbol });
+            }
+        }
+    }
+    for (index, source) in level.gravity_sources.iter().enumerate() {
+        let valid_radius = source.radius > 0.0 && source.radius.is_finite();
+        let valid_gravity = source.surface_gravity >= 0.0 && source.surface_gravity.is_finite();
+        if !valid_radius || !valid_gravity {
+            errors.push(ValidationError::InvalidGravitySource { body: index + 1 });
+        }
+    }
+
+    errors
+}
+
+/// Checks every structure layout only holds known modules in rows of the same length.
+pub fn validate_structures(structures: &StructuresData, module_registry: &ModuleRegistry) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+
+    for (index, structure_data) in structures.structures.iter().enumerate() {
+        let structure = index + 1;
+        let [x, y] = structure_data.world_pos;
+        if !(x.abs() <= WORLD_POSITION_LIMIT && y.abs() <= WORLD_POSITION_LIMIT) {
+            errors.push(ValidationError::OutOfBounds { structure, x, y });
+        }
+
+        let Some(first_row) = structure_data.structure.first().filter(|row| !row.is_empty()) else {
+            errors.push(ValidationError::EmptyStructure { structure });
+            continue;
+        };
+        let expected = first_row.chars().count();
+
+        let rows: Vec<Vec<char>> = structure_data.structure.iter().map(|row| row.chars().collect()).collect();
+        let mut covered = HashSet::new();
+        for (y, row) in rows.iter().enumerate() {
+            let found = row.len();
+            if found != expected {
+                errors.push(ValidationError::RaggedRow { structure, row: y + 1, expected, found });
+            }
+            for (x, &symbol) in row.iter().enumerate() {
+                if !module_registry.is_known_symbol(symbol) {
+                    errors.push(ValidationError::UnknownModule { structure, row: y + 1, column: x + 1, symbol });
+                }
+
+                // A module covering several cells has its symbol in all of them, from its top left cell
+                let anchor = (x as i32, y as i32);
+                let footprint = module_registry.footprint(symbol);
+                if footprint.is_single() || !covered.insert(anchor) {
+                    continue;
+                }
+                let orientation = structure_data.orientation_at(anchor);
+                let mut complete = true;
+                for (cell_x, cell_y) in footprint.cells(anchor, orientation) {
+                    let cell_symbol = rows.get(cell_y as usize).and_then(|row| row.get(cell_x as usize));
+                    complete &= cell_symbol == Some(&symbol);
+                    covered.insert((cell_x, cell_y));
+                }
+                if !complete {
+                    errors.push(ValidationError::IncompleteFootprint { structure, row: y + 1, column: x + 1, symbol });
+                }
+            }
+        }
+        for oriented in &structure_data.orientations {
+            let (x, y) = oriented.cell;
+            let symbol = usize::try_from(y)
+                .ok()
+                .zip(usize::try_from(x).ok())
+                .and_then(|(row, column)| structure_data.structure.get(row)?.chars().nth(column));
+            let is_module = symbol.is_some_and(|symbol| {
+                ModuleType::from_symbol(symbol).is_some() || module_registry.get(symbol).is_some()
+            });
+            if !is_module {
+                errors.push(ValidationError::OrientationWithoutModule { structure, x, y });
+            }
+        }
+    }
+
+    errors
+}
+
+/// Checks every degradation curve is for a built-in or registered module type.
+pub fn validate_degradation_curves(
+    curves: &DegradationCurves,
+    module_registry: &ModuleRegistry,
+) -> Vec<ValidationError> {
+    let mut unknown: Vec<char> = curves
+        .modules
+        .keys()
+        .copied()
+        .filter(|symbol| ModuleType::from_symbol(*symbol).is_none() && module_registry.get(*symbol).is_none())
+        .collect();
+    // Listed in the same order on every load
+    unknown.sort_unstable();
+    unknown.into_iter().map(|symbol| ValidationError::UnknownCurveModule { symbol }).collect()
+}
@@ -0,0 +1,31 @@
+use bevy::prelude::*;
+
+/// Debug drawings shown over the game, toggled at runtime by the `DebugPlugin` hotkeys.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DebugOverlays {
+    /// Cells of the level and of the structures, with the cell the player stands in.
+    pub grid: bool,
+    /// Sealed and exposed rooms of the structures, and their unpowered modules.
+    pub pressurization: bool,
+    /// Colliders, contacts and velocities drawn by the physics engine.
+    pub physics: bool,
+    /// Frame rate and entity count.
+    pub perf_ui: bool,
+}
+
+impl DebugOverlays {
+    /// Every overlay shown, or none.
+    pub fn all(shown: bool) -> Self {
+        Self { grid: shown, pressurization: shown, physics: shown, perf_ui: shown }
+    }
+}
+
+/// Run condition of the systems drawing the grid overlay.
+pub fn grid_overlay_shown(overlays: Option<Res<DebugOverlays>>) -> bool {
+    overlays.is_some_and(|overlays| overlays.grid)
+}
+
+/// Run condition of the systems drawing the pressurization overlay.
+pub fn pressurization_overlay_shown(overlays: Option<Res<DebugOverlays>>) -> bool {
+    overlays.is_some_and(|overlays| overlays.pressurization)
+}
@@ -0,0 +1,75 @@
+use crate::configs::config::UNIT_SCALE;
+use crate::gameplay::debris::DEBRIS_PIECE_SIZE;
+use crate::world::structures::{MODULE_MESH_SCALE_FACTOR, STRUCTURE_CELL_SIZE};
+use bevy::color::palettes::css::{GREY, ORANGE, WHITE, YELLOW};
+use bevy::prelude::*;
+use bevy::sprite::Mesh2dHandle;
+
+pub const PROJECTILE_RADIUS: f32 = 0.25 * UNIT_SCALE; // ballistic rounds, the only ones the cannons fire
+pub const PLAYER_RADIUS: f32 = 1.0 * UNIT_SCALE;
+
+/// Creates the `GameAssets` once every plugin is built, so the startup systems can already use it.
+pub struct GameAssetsPlugin;
+
+impl Plugin for GameAssetsPlugin {
+    fn build(&self, _app: &mut App) {}
+
+    fn finish(&self, app: &mut App) {
+        app.init_resource::<GameAssets>();
+    }
+}
+
+/// Handles of the meshes, materials and fonts used over and over, created once and cloned on every spawn
+/// instead of adding a new asset each time. Materials that get recolored per entity (module colors, debris and
+/// flash fading) cannot be shared, only their meshes are.
+#[derive(Resource, Debug, Clone)]
+pub struct GameAssets {
+    /// Square of a module in a structure cell.
+    pub module_mesh: Mesh2dHandle,
+    /// Square of a piece of debris, a quarter of a destroyed module.
+    pub debris_mesh: Mesh2dHandle,
+    pub projectile_mesh: Mesh2dHandle,
+    pub projectile_material: Handle<ColorMaterial>,
+    pub player_mesh: Mesh2dHandle,
+    pub player_material: Handle<ColorMaterial>,
+    pub grid_cell_material: Handle<ColorMaterial>,
+    pub repair_drone_material: Handle<ColorMaterial>,
+    pub loot_material: Handle<ColorMaterial>,
+    /// Disc of radius 1 scaled to the size of the effects.
+    pub unit_disc: Mesh2dHandle,
+    pub font: Handle<Font>,
+}
+
+impl FromWorld for GameAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        let module_mesh =
+            meshes.add(Rectangle { half_size: Vec2::splat(STRUCTURE_CELL_SIZE / 2.0 * MODULE_MESH_SCALE_FACTOR) });
+        let debris_mesh = meshes.add(Rectangle { half_size: Vec2::splat(DEBRIS_PIECE_SIZE / 2.0) });
+        let projectile_mesh = meshes.add(Circle { radius: PROJECTILE_RADIUS });
+        let player_mesh = meshes.add(Circle { radius: PLAYER_RADIUS });
+        let unit_disc = meshes.add(Circle { radius: 1.0 });
+
+        let mut materials = world.resource_mut::<Assets<ColorMaterial>>();
+        let projectile_material = materials.add(ColorMaterial::from(Color::from(WHITE)));
+        let player_material = materials.add(ColorMaterial::from(Color::WHITE));
+        let grid_cell_material = materials.add(ColorMaterial::from(Color::from(GREY)));
+        let repair_drone_material = materials.add(ColorMaterial::from(Color::from(YELLOW)));
+        let loot_material = materials.add(ColorMaterial::from(Color::from(ORANGE)));
+
+        Self {
+            module_mesh: module_mesh.into(),
+            debris_mesh: debris_mesh.into(),
+            projectile_mesh: projectile_mesh.into(),
+            projectile_material,
+            player_mesh: player_mesh.into(),
+            player_material,
+            grid_cell_material,
+            repair_drone_material,
+            loot_material,
+            unit_disc: unit_disc.into(),
+            // The font embedded in bevy, until the game ships its own
+            font: Handle::default(),
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use crate::core::state::GameState;
+use avian2d::prelude::{Physics, PhysicsTime, TimestepMode};
+use bevy::prelude::*;
+use bevy::render::render_resource::Shader;
+use bevy::time::TimeUpdateStrategy;
+use std::time::Duration;
+
+/// Frame duration of the headless simulation, every update advances the time by exactly this much.
+/// The default fixed timestep, 64 Hz.
+pub const HEADLESS_TIMESTEP: Duration = Duration::from_micros(15_625);
+
+/// Stands in for the rendering plugins when running without a window: registers the mesh and material assets
+/// the simulation spawns so nothing is drawn, and steps the time by a fixed amount, one fixed update per update, so
+/// runs are reproducible.
+/// The shaders are registered for the `GizmoPlugin`, the gizmos are stored and dropped.
+pub struct HeadlessPlugin {
+    pub timestep: Duration,
+}
+
+impl Default for HeadlessPlugin {
+    fn default() -> Self {
+        Self { timestep: HEADLESS_TIMESTEP }
+    }
+}
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Mesh>()
+            .init_asset::<ColorMaterial>()
+            .init_asset::<Shader>()
+            .insert_resource(TimeUpdateStrategy::ManualDuration(self.timestep))
+            .insert_resource(Time::<Fixed>::from_duration(self.timestep))
+            // The physics follow the real time by default, here they step once per update too
+            .insert_resource(Time::new_with(Physics::from_timestep(TimestepMode::FixedOnce { delta: self.timestep })))
+            // The data files load in a different number of frames every run, the clocks only start with the game
+            .add_systems(Startup, stop_clock)
+            .add_systems(OnEnter(GameState::InGame), start_clock);
+    }
+}
+
+fn stop_clock(mut time: ResMut<Time<Virtual>>, mut physics_time: ResMut<Time<Physics>>) {
+    time.pause();
+    physics_time.pause();
+}
+
+fn start_clock(mut time: ResMut<Time<Virtual>>, mut physics_time: ResMut<Time<Physics>>) {
+    time.unpause();
+    physics_time.unpause();
+}
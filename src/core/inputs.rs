@@ -1,61 +1,204 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
+use crate::core::replay::replay_is_playing;
+use crate::core::schedule::InGameSet;
 use crate::core::state::GameState;
+use crate::gameplay::maneuvers::{Maneuver, BURN_DURATION, TURN_AROUND_ANGLE};
+
+const WAYPOINT_MOUSE_BUTTON: MouseButton = MouseButton::Right;
 
 pub struct InputsPlugin;
 
 impl Plugin for InputsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<InputAction>().add_systems(Update, keyboard_input.run_if(in_state(GameState::InGame)));
+        app.init_resource::<KeyBindings>().add_event::<InputAction>().add_systems(
+            Update,
+            (keyboard_input, mouse_input)
+                .in_set(InGameSet::UserInput)
+                .run_if(in_state(GameState::InGame).and_then(not(replay_is_playing))),
+        );
     }
 }
 
 /// An event sent for a player input action.
-#[derive(Event)]
+#[derive(Event, Debug, Clone, Serialize, Deserialize)]
 pub enum InputAction {
     Break,
     Move(Vec3),
     SpacePressed,
     Shoot,
+    Repair,
     Rotate(f32), // Rotation factor: positive for clockwise, negative for counterclockwise
+    QueueManeuver(Maneuver),
+    ClearManeuvers,
+    /// World position for the autopilot to fly the controlled structure to.
+    SetWaypoint(Vec2),
+    Scan,
+    /// Selects the next weapon group fired by the shoot input.
+    CycleWeaponGroup,
+    /// Locks the next enemy structure in sensor range.
+    CycleTarget,
+    /// Lets the cannons aim at the locked target, or fire straight again.
+    ToggleAutoAim,
+    /// Abandons the controlled structure.
+    Eject,
+}
+
+/// Keys sending the player input actions, read from the settings file.
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyBindings {
+    pub move_up: KeyCode,
+    pub move_down: KeyCode,
+    pub move_left: KeyCode,
+    pub move_right: KeyCode,
+    pub brake: KeyCode,
+    pub shoot: KeyCode,
+    pub repair: KeyCode,
+    pub rotate_counterclockwise: KeyCode,
+    pub rotate_clockwise: KeyCode,
+    pub interact: KeyCode,
+    pub queue_turn_around: KeyCode,
+    pub queue_burn: KeyCode,
+    pub queue_all_stop: KeyCode,
+    pub clear_maneuvers: KeyCode,
+    pub scan: KeyCode,
+    pub cycle_weapon_group: KeyCode,
+    pub cycle_target: KeyCode,
+    pub toggle_auto_aim: KeyCode,
+    pub eject: KeyCode,
+    pub start_encounter: KeyCode,
 }
 
-fn keyboard_input(mut input_event_writer: EventWriter<InputAction>, keys: Res<ButtonInput<KeyCode>>) {
-    if keys.just_released(KeyCode::Space) {
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            move_up: KeyCode::KeyW,
+            move_down: KeyCode::KeyS,
+            move_left: KeyCode::KeyA,
+            move_right: KeyCode::KeyD,
+            brake: KeyCode::KeyX,
+            shoot: KeyCode::KeyG,
+            repair: KeyCode::KeyR,
+            rotate_counterclockwise: KeyCode::KeyQ,
+            rotate_clockwise: KeyCode::KeyE,
+            interact: KeyCode::Space,
+            queue_turn_around: KeyCode::KeyT,
+            queue_burn: KeyCode::KeyY,
+            queue_all_stop: KeyCode::KeyU,
+            clear_maneuvers: KeyCode::Backspace,
+            scan: KeyCode::KeyN,
+            cycle_weapon_group: KeyCode::KeyV,
+            cycle_target: KeyCode::KeyO,
+            toggle_auto_aim: KeyCode::KeyP,
+            eject: KeyCode::Delete,
+            start_encounter: KeyCode::F10,
+        }
+    }
+}
+
+fn keyboard_input(
+    mut input_event_writer: EventWriter<InputAction>,
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+) {
+    // Ctrl shortcuts, like the build mode copy, cut and paste, are not flight inputs
+    if keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+
+    if keys.just_released(bindings.interact) {
         input_event_writer.send(InputAction::SpacePressed);
     }
 
     let mut direction = Vec3::ZERO;
 
-    if keys.pressed(KeyCode::KeyW) {
+    if keys.pressed(bindings.move_up) {
         direction.y += 1.0;
     }
-    if keys.pressed(KeyCode::KeyS) {
+    if keys.pressed(bindings.move_down) {
         direction.y -= 1.0;
     }
-    if keys.pressed(KeyCode::KeyA) {
+    if keys.pressed(bindings.move_left) {
         direction.x -= 1.0;
     }
-    if keys.pressed(KeyCode::KeyD) {
+    if keys.pressed(bindings.move_right) {
         direction.x += 1.0;
     }
     if direction.length() > 0.0 {
         input_event_writer.send(InputAction::Move(direction.normalize()));
     }
 
-    if keys.pressed(KeyCode::KeyX) {
+    if keys.pressed(bindings.brake) {
         input_event_writer.send(InputAction::Break);
     }
 
-    if keys.just_pressed(KeyCode::KeyG) {
+    if keys.just_pressed(bindings.shoot) {
         input_event_writer.send(InputAction::Shoot);
     }
 
+    if keys.pressed(bindings.repair) {
+        input_event_writer.send(InputAction::Repair);
+    }
+
     // Handle rotation with rotation factor
-    if keys.pressed(KeyCode::KeyQ) {
+    if keys.pressed(bindings.rotate_counterclockwise) {
         input_event_writer.send(InputAction::Rotate(1.0)); // Counterclockwise rotation
     }
-    if keys.pressed(KeyCode::KeyE) {
+    if keys.pressed(bindings.rotate_clockwise) {
         input_event_writer.send(InputAction::Rotate(-1.0)); // Clockwise rotation
     }
+
+    // Maneuvers flown one after the other by the structure
+    if keys.just_pressed(bindings.queue_turn_around) {
+        input_event_writer.send(InputAction::QueueManeuver(Maneuver::Rotate(TURN_AROUND_ANGLE)));
+    }
+    if keys.just_pressed(bindings.queue_burn) {
+        input_event_writer.send(InputAction::QueueManeuver(Maneuver::Burn(BURN_DURATION)));
+    }
+    if keys.just_pressed(bindings.queue_all_stop) {
+        input_event_writer.send(InputAction::QueueManeuver(Maneuver::AllStop));
+    }
+    if keys.just_pressed(bindings.clear_maneuvers) {
+        input_event_writer.send(InputAction::ClearManeuvers);
+    }
+
+    if keys.just_pressed(bindings.scan) {
+        input_event_writer.send(InputAction::Scan);
+    }
+    if keys.just_pressed(bindings.cycle_weapon_group) {
+        input_event_writer.send(InputAction::CycleWeaponGroup);
+    }
+    if keys.just_pressed(bindings.cycle_target) {
+        input_event_writer.send(InputAction::CycleTarget);
+    }
+    if keys.just_pressed(bindings.toggle_auto_aim) {
+        input_event_writer.send(InputAction::ToggleAutoAim);
+    }
+    if keys.just_pressed(bindings.eject) {
+        input_event_writer.send(InputAction::Eject);
+    }
+}
+
+fn mouse_input(
+    mut input_event_writer: EventWriter<InputAction>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    windows_query: Query<&Window>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+) {
+    if !mouse_buttons.just_pressed(WAYPOINT_MOUSE_BUTTON) {
+        return;
+    }
+    let Ok((camera, camera_transform)) = camera_query.get_single() else {
+        return;
+    };
+    let cursor_position = windows_query
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor));
+    if let Some(cursor_position) = cursor_position {
+        input_event_writer.send(InputAction::SetWaypoint(cursor_position));
+    }
 }
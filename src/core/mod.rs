@@ -1,7 +1,17 @@
 // src/core/mod.rs
 pub mod asset_loader;
+pub mod asset_validation;
+pub mod debug_overlays;
+pub mod game_assets;
+#[cfg(feature = "headless")]
+pub mod headless;
 pub mod inputs;
+pub mod persistence;
 pub mod prelude;
+pub mod profiling;
+pub mod replay;
+pub mod rng;
+pub mod save;
 pub mod schedule;
 pub mod state;
 pub mod utils;
@@ -0,0 +1,95 @@
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+const CHECKSUM_HEADER: &str = "checksum:";
+
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum PersistenceError {
+    /// An [IO](std::io) Error
+    #[error("Could not access file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Missing checksum header")]
+    MissingHeader,
+    #[error("Checksum mismatch, the file is corrupted")]
+    ChecksumMismatch,
+}
+
+/// Path of the backup kept next to a persisted file.
+pub fn backup_path(path: &Path) -> PathBuf {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".bak");
+    PathBuf::from(backup)
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    PathBuf::from(temp)
+}
+
+/// FNV-1a hash, good enough to detect truncated or partially written files.
+fn checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x100000001b3))
+}
+
+/// Writes a file so a crash never leaves it half written.
+/// The data is written with a checksum header in a temporary file which then replaces the
+/// destination, the previous version is kept as a backup.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), PersistenceError> {
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+
+    let temp = temp_path(path);
+    {
+        let mut file = File::create(&temp)?;
+        writeln!(file, "{}{:016x}", CHECKSUM_HEADER, checksum(contents))?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+    }
+
+    if path.exists() {
+        fs::copy(path, backup_path(path))?;
+    }
+    fs::rename(&temp, path)?;
+    Ok(())
+}
+
+/// Reads a file written by [`write_atomic`] and checks it is not corrupted.
+pub fn read_checked(path: &Path) -> Result<Vec<u8>, PersistenceError> {
+    let bytes = fs::read(path)?;
+
+    let header_end = bytes.iter().position(|byte| *byte == b'\n').ok_or(PersistenceError::MissingHeader)?;
+    let header = std::str::from_utf8(&bytes[..header_end]).map_err(|_| PersistenceError::MissingHeader)?;
+    let expected = header
+        .strip_prefix(CHECKSUM_HEADER)
+        .and_then(|hex| u64::from_str_radix(hex.trim(), 16).ok())
+        .ok_or(PersistenceError::MissingHeader)?;
+
+    let contents = bytes[header_end + 1..].to_vec();
+    if checksum(&contents) != expected {
+        return Err(PersistenceError::ChecksumMismatch);
+    }
+    Ok(contents)
+}
+
+/// Reads a file written by [`write_atomic`], falling back to its backup when it is missing or corrupted.
+pub fn read_with_backup(path: &Path) -> Result<Vec<u8>, PersistenceError> {
+    read_checked(path).or_else(|error| {
+        log::warn!("Failed to read {:?} ({}), trying the backup", path, error);
+        read_checked(&backup_path(path))
+    })
+}
+
+/// Removes a file written by [`write_atomic`] along with its backup.
+pub fn remove_with_backup(path: &Path) -> Result<(), PersistenceError> {
+    let backup = backup_path(path);
+    if backup.exists() {
+        fs::remove_file(backup)?;
+    }
+    fs::remove_file(path)?;
+    Ok(())
+}
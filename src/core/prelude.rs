@@ -1,5 +1,13 @@
 // src/core/prelude.rs
 pub use super::asset_loader::*;
+pub use super::asset_validation::*;
+pub use super::debug_overlays::*;
+pub use super::game_assets::*;
+#[cfg(feature = "headless")]
+pub use super::headless::*;
 pub use super::inputs::*;
+pub use super::replay::*;
+pub use super::rng::*;
+pub use super::save::*;
 pub use super::schedule::*;
 pub use super::state::*;
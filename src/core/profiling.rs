@@ -0,0 +1,75 @@
+use bevy::diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic};
+use bevy::prelude::*;
+use bevy::utils::tracing::span::EnteredSpan;
+use std::time::Instant;
+
+/// Registers one diagnostic per instrumented system so their frame cost shows up in the `DiagnosticsStore`.
+pub struct ProfilingPlugin;
+
+impl Plugin for ProfilingPlugin {
+    fn build(&self, app: &mut App) {
+        for path in PROFILED_SYSTEMS {
+            app.register_diagnostic(Diagnostic::new(path.clone()).with_suffix("ms").with_smoothing_factor(10.0));
+        }
+    }
+}
+
+/// Prefix shared by every profiled system diagnostic.
+pub const PROFILE_PREFIX: &str = "game/";
+
+pub static DOORS: DiagnosticPath = DiagnosticPath::const_new("game/doors");
+pub static DEPRESSURIZATION: DiagnosticPath = DiagnosticPath::const_new("game/depressurization");
+pub static PROJECTILE_HITS: DiagnosticPath = DiagnosticPath::const_new("game/projectile_hits");
+pub static MODULE_DESTRUCTION: DiagnosticPath = DiagnosticPath::const_new("game/module_destruction");
+pub static GRID_UPDATES: DiagnosticPath = DiagnosticPath::const_new("game/grid_updates");
+pub static ATMOSPHERE: DiagnosticPath = DiagnosticPath::const_new("game/atmosphere");
+pub static POWER: DiagnosticPath = DiagnosticPath::const_new("game/power");
+pub static AI: DiagnosticPath = DiagnosticPath::const_new("game/ai");
+
+pub static PROFILED_SYSTEMS: [&DiagnosticPath; 8] =
+    [&DOORS, &DEPRESSURIZATION, &PROJECTILE_HITS, &MODULE_DESTRUCTION, &GRID_UPDATES, &ATMOSPHERE, &POWER, &AI];
+
+/// Times a block of work and enters a tracing span for it, so it shows up both in the in-game
+/// profiler overlay and in external tracing tools (e.g. with `bevy/trace_tracy`).
+///
+/// Only one measurement per path is kept each frame, so every instrumented system gets its own path.
+pub struct ProfileScope {
+    path: &'static DiagnosticPath,
+    start: Instant,
+    _span: EnteredSpan,
+}
+
+impl ProfileScope {
+    pub fn enter(path: &'static DiagnosticPath) -> Self {
+        let span = info_span!("game_system", name = path.as_str()).entered();
+        Self { path, start: Instant::now(), _span: span }
+    }
+
+    /// Records the elapsed time in milliseconds.
+    pub fn finish(self, diagnostics: &mut Diagnostics) {
+        let elapsed = self.start.elapsed();
+        diagnostics.add_measurement(self.path, || elapsed.as_secs_f64() * 1000.0);
+    }
+}
+
+/// Prefix shared by every entity count diagnostic.
+pub const ENTITY_COUNT_PREFIX: &str = "entities/";
+
+/// Counts the entities of a subsystem every frame, to spot which one is leaking entities.
+pub trait EntityCountDiagnosticAppExt {
+    /// Registers an `entities/<category>` diagnostic counting the entities with the `T` marker component.
+    fn register_entity_count_diagnostic<T: Component>(&mut self, category: &'static str) -> &mut Self;
+}
+
+impl EntityCountDiagnosticAppExt for App {
+    fn register_entity_count_diagnostic<T: Component>(&mut self, category: &'static str) -> &mut Self {
+        let path = DiagnosticPath::new(format!("{ENTITY_COUNT_PREFIX}{category}"));
+
+        self.register_diagnostic(Diagnostic::new(path.clone())).add_systems(
+            Last,
+            move |query: Query<(), With<T>>, mut diagnostics: Diagnostics| {
+                diagnostics.add_measurement(&path, || query.iter().count() as f64);
+            },
+        )
+    }
+}
@@ -0,0 +1,167 @@
+use crate::core::inputs::InputAction;
+use crate::core::persistence::{read_with_backup, write_atomic, PersistenceError};
+use crate::core::schedule::InGameSet;
+use crate::core::state::GameState;
+use crate::gameplay::derelicts::DerelictGenerator;
+use crate::gameplay::offscreen_battles::OffscreenBattles;
+use crate::world::names::NameGenerator;
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_SIMULATION_SEED: u64 = 0x5E_ED0F_5A1E;
+
+/// Where to record the inputs of the game or which recording to play back, read from the settings file.
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReplaySettings {
+    /// Records the inputs in this file, written when the game exits.
+    pub record: Option<PathBuf>,
+    /// Plays back the inputs recorded in this file instead of reading the keyboard.
+    pub play: Option<PathBuf>,
+    /// Seed of the random generators of a recorded game.
+    pub seed: Option<u64>,
+}
+
+/// Records the player input actions with the simulation tick they were sent on, and plays them back.
+/// While recording or playing every frame advances the simulation by exactly one fixed timestep and the random
+/// generators are seeded from the recording, so a replay of the same data files gives the same game.
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SimulationTick>()
+            .add_systems(FixedFirst, count_simulation_ticks_system.run_if(in_state(GameState::InGame)))
+            .add_systems(
+                Update,
+                (
+                    play_back_inputs_system.in_set(InGameSet::UserInput).run_if(replay_is_playing),
+                    record_inputs_system.after(InGameSet::UserInput).run_if(resource_exists::<ReplayRecording>),
+                ),
+            )
+            .add_systems(Last, write_recording_on_exit_system.run_if(resource_exists::<ReplayRecording>));
+
+        let settings = app.world().get_resource::<ReplaySettings>().cloned().unwrap_or_default();
+        let seed = if let Some(path) = &settings.play {
+            match ReplayFile::load(path) {
+                Ok(replay) => {
+                    info!("Playing back {} inputs from {:?}", replay.inputs.len(), path);
+                    let seed = replay.seed;
+                    app.insert_resource(ReplayPlayback { replay, next: 0 });
+                    seed
+                }
+                Err(error) => {
+                    error!("Could not read the replay {:?}: {}", path, error);
+                    return;
+                }
+            }
+        } else if let Some(path) = &settings.record {
+            let seed = settings.seed.unwrap_or(DEFAULT_SIMULATION_SEED);
+            info!("Recording the inputs in {:?}", path);
+            app.insert_resource(ReplayRecording {
+                path: path.clone(),
+                replay: ReplayFile { seed, inputs: Vec::new() },
+            });
+            seed
+        } else {
+            return;
+        };
+
+        // One fixed update per frame, whatever the frame rate
+        let timestep = Time::<Fixed>::default().timestep();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(timestep))
+            .insert_resource(NameGenerator::new(seed))
+            .insert_resource(OffscreenBattles::with_seed(seed))
+            .insert_resource(DerelictGenerator::new(seed));
+    }
+}
+
+/// Number of fixed updates run in game since the start, the clock of the recordings.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SimulationTick(pub u64);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedInput {
+    pub tick: u64,
+    pub action: InputAction,
+}
+
+/// A recording, the inputs are sorted by tick.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReplayFile {
+    pub seed: u64,
+    pub inputs: Vec<RecordedInput>,
+}
+
+impl ReplayFile {
+    pub fn load(path: &Path) -> Result<Self, PersistenceError> {
+        let json = read_with_backup(path)?;
+        serde_json::from_slice(&json).map_err(|error| PersistenceError::Io(error.into()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), PersistenceError> {
+        let json = serde_json::to_vec(self).map_err(|error| PersistenceError::Io(error.into()))?;
+        write_atomic(path, &json)
+    }
+}
+
+#[derive(Resource, Debug)]
+pub struct ReplayRecording {
+    pub path: PathBuf,
+    pub replay: ReplayFile,
+}
+
+/// Removed once every input is played back, the keyboard then takes over.
+#[derive(Resource, Debug)]
+pub struct ReplayPlayback {
+    pub replay: ReplayFile,
+    next: usize,
+}
+
+/// Run condition of the systems reading the keyboard, the inputs come from the recording during a replay.
+pub fn replay_is_playing(playback: Option<Res<ReplayPlayback>>) -> bool {
+    playback.is_some()
+}
+
+fn count_simulation_ticks_system(mut tick: ResMut<SimulationTick>) {
+    tick.0 += 1;
+}
+
+fn record_inputs_system(
+    mut event_reader: EventReader<InputAction>,
+    tick: Res<SimulationTick>,
+    mut recording: ResMut<ReplayRecording>,
+) {
+    for action in event_reader.read() {
+        recording.replay.inputs.push(RecordedInput { tick: tick.0, action: action.clone() });
+    }
+}
+
+fn play_back_inputs_system(
+    tick: Res<SimulationTick>,
+    mut playback: ResMut<ReplayPlayback>,
+    mut input_event_writer: EventWriter<InputAction>,
+    mut commands: Commands,
+) {
+    while let Some(input) = playback.replay.inputs.get(playback.next).filter(|input| input.tick <= tick.0).cloned() {
+        input_event_writer.send(input.action);
+        playback.next += 1;
+    }
+
+    if playback.next >= playback.replay.inputs.len() {
+        info!("Replay finished at tick {}", tick.0);
+        commands.remove_resource::<ReplayPlayback>();
+    }
+}
+
+fn write_recording_on_exit_system(mut exit_reader: EventReader<AppExit>, recording: Res<ReplayRecording>) {
+    if exit_reader.read().next().is_none() {
+        return;
+    }
+
+    match recording.replay.save(&recording.path) {
+        Ok(()) => info!("{} inputs recorded in {:?}", recording.replay.inputs.len(), recording.path),
+        Err(error) => error!("Could not write the replay {:?}: {}", recording.path, error),
+    }
+}
@@ -0,0 +1,34 @@
+/// Deterministic random numbers (SplitMix64), the same seed always gives the same sequence.
+#[derive(Debug, Clone)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform random number in `[0, 1)`.
+    pub fn roll(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Uniform random number in `[min, max]`.
+    pub fn range(&mut self, min: u32, max: u32) -> u32 {
+        min + (self.next_u64() % (max.saturating_sub(min) as u64 + 1)) as u32
+    }
+
+    /// Uniform random index into a slice of `len` elements, `len` must not be 0.
+    pub fn index(&mut self, len: usize) -> usize {
+        (self.next_u64() % len as u64) as usize
+    }
+}
@@ -0,0 +1,237 @@
+use crate::core::asset_loader::StructuresData;
+use crate::core::game_assets::GameAssets;
+use crate::core::persistence::{read_with_backup, remove_with_backup, write_atomic, PersistenceError};
+use crate::core::state::GameState;
+use crate::gameplay::crew::Crew;
+use crate::gameplay::factions::Faction;
+use crate::gameplay::journal::Journal;
+use crate::gameplay::livery::Livery;
+use crate::gameplay::movement::Aboard;
+use crate::world::prelude::*;
+
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        let settings = SaveSettings::default();
+        app.insert_resource(AutosaveTimer(Timer::from_seconds(settings.autosave_interval, TimerMode::Repeating)))
+            .insert_resource(settings)
+            .add_event::<AutosaveEvent>()
+            .add_event::<LoadGameEvent>()
+            .add_event::<DeleteSaveEvent>()
+            .add_systems(
+                Update,
+                (autosave_timer_system, autosave_system.run_if(on_event::<AutosaveEvent>()))
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(
+                Update,
+                (
+                    load_game_system.run_if(on_event::<LoadGameEvent>()),
+                    delete_save_system.run_if(on_event::<DeleteSaveEvent>()),
+                )
+                    .run_if(in_state(GameState::InGame).or_else(in_state(GameState::Paused))),
+            );
+    }
+}
+
+#[derive(Resource, Debug)]
+pub struct SaveSettings {
+    pub directory: PathBuf,
+    pub autosave_interval: f32, // seconds
+    pub slots: usize,
+}
+
+impl Default for SaveSettings {
+    fn default() -> Self {
+        Self { directory: PathBuf::from("saves"), autosave_interval: 120.0, slots: 3 }
+    }
+}
+
+impl SaveSettings {
+    pub fn slot_path(&self, slot: usize) -> PathBuf {
+        self.directory.join(format!("autosave_{slot}.json"))
+    }
+
+    /// Lists the existing save slots, most recent first.
+    pub fn list_slots(&self) -> Vec<SaveSlot> {
+        let mut slots: Vec<SaveSlot> = (0..self.slots)
+            .filter_map(|index| {
+                let path = self.slot_path(index);
+                let modified = fs::metadata(&path).and_then(|metadata| metadata.modified()).ok()?;
+                Some(SaveSlot { index, path, modified })
+            })
+            .collect();
+        slots.sort_by_key(|slot| std::cmp::Reverse(slot.modified));
+        slots
+    }
+
+    /// The first free slot, or the oldest one once every slot is used.
+    fn next_slot(&self) -> usize {
+        let slots = self.list_slots();
+        (0..self.slots)
+            .find(|index| !slots.iter().any(|slot| slot.index == *index))
+            .or_else(|| slots.last().map(|slot| slot.index))
+            .unwrap_or(0)
+    }
+}
+
+#[derive(Debug)]
+pub struct SaveSlot {
+    pub index: usize,
+    pub path: PathBuf,
+    pub modified: SystemTime,
+}
+
+/// Snapshot of the game written in a save slot.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SaveGame {
+    pub player_pos: [f32; 2],
+    #[serde(default)]
+    pub journal: Journal,
+    #[serde(flatten)]
+    pub structures: StructuresData,
+}
+
+/// Requests an autosave, sent by the autosave timer and by key gameplay moments (docking, mission complete, ...).
+#[derive(Event, Debug, Default)]
+pub struct AutosaveEvent;
+
+#[derive(Event, Debug)]
+pub struct LoadGameEvent {
+    pub slot: usize,
+}
+
+#[derive(Event, Debug)]
+pub struct DeleteSaveEvent {
+    pub slot: usize,
+}
+
+#[derive(Resource, Deref, DerefMut)]
+struct AutosaveTimer(Timer);
+
+fn autosave_timer_system(
+    mut timer: ResMut<AutosaveTimer>,
+    settings: Res<SaveSettings>,
+    time: Res<Time>,
+    mut event_writer: EventWriter<AutosaveEvent>,
+) {
+    if settings.is_changed() {
+        timer.set_duration(std::time::Duration::from_secs_f32(settings.autosave_interval));
+    }
+    if timer.tick(time.delta()).just_finished() {
+        event_writer.send(AutosaveEvent);
+    }
+}
+
+fn autosave_system(
+    mut event_reader: EventReader<AutosaveEvent>,
+    settings: Res<SaveSettings>,
+    structures_query: Query<(&Transform, &LinearVelocity, &Structure, &Crew, &Livery, &Children, Option<&Faction>)>,
+    module_query: Query<&Module>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    journal: Res<Journal>,
+) {
+    // Several requests in the same frame only need one snapshot
+    event_reader.clear();
+
+    let structures = structures_query
+        .iter()
+        .map(|(transform, velocity, structure, crew, livery, children, faction)| {
+            let mut structure_data = structure.to_structure_data(module_query.iter_many(children));
+            structure_data.world_pos = [transform.translation.x, transform.translation.y];
+            structure_data.crew = crew.members;
+            structure_data.rotation = transform.rotation.to_euler(EulerRot::XYZ).2;
+            structure_data.velocity = [velocity.x, velocity.y];
+            structure_data.livery = livery.clone();
+            structure_data.faction = faction.map(|faction| faction.0.clone());
+            structure_data
+        })
+        .collect();
+
+    let player_pos = player_query.get_single().map(|transform| transform.translation().truncate()).unwrap_or_default();
+    let save_game = SaveGame {
+        player_pos: [player_pos.x, player_pos.y],
+        journal: journal.clone(),
+        structures: StructuresData { structures },
+    };
+
+    let slot = settings.next_slot();
+    let path = settings.slot_path(slot);
+    let result = serde_json::to_vec_pretty(&save_game)
+        .map_err(|error| PersistenceError::Io(error.into()))
+        .and_then(|json| write_atomic(&path, &json));
+
+    match result {
+        Ok(()) => info!("Game saved in slot {} ({:?})", slot, path),
+        Err(error) => error!("Failed to save the game in slot {}: {}", slot, error),
+    }
+}
+
+fn load_game_system(
+    mut event_reader: EventReader<LoadGameEvent>,
+    settings: Res<SaveSettings>,
+    structures_query: Query<Entity, With<Structure>>,
+    wreck_query: Query<Entity, (With<Module>, Without<Parent>)>,
+    player_query: Query<Entity, With<Player>>,
+    mut player_resource: ResMut<PlayerResource>,
+    mut journal: ResMut<Journal>,
+    mut commands: Commands,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    game_assets: Res<GameAssets>,
+    module_registry: Res<ModuleRegistry>,
+) {
+    let Some(event) = event_reader.read().last() else {
+        return;
+    };
+
+    let path = settings.slot_path(event.slot);
+    let save_game: SaveGame = match read_with_backup(&path)
+        .map_err(|error| error.to_string())
+        .and_then(|json| serde_json::from_slice(&json).map_err(|error| error.to_string()))
+    {
+        Ok(save_game) => save_game,
+        Err(error) => {
+            error!("Failed to load save slot {} ({:?}): {}", event.slot, path, error);
+            return;
+        }
+    };
+
+    // The player may be piloting or aboard a structure, detach it before clearing the world
+    for player_entity in &player_query {
+        commands.entity(player_entity).remove_parent_in_place().remove::<Aboard>().insert((
+            RigidBody::Dynamic,
+            LinearVelocity::ZERO,
+            Transform::from_xyz(save_game.player_pos[0], save_game.player_pos[1], 5.0),
+        ));
+    }
+    *player_resource = PlayerResource::default();
+    *journal = save_game.journal;
+
+    for entity in structures_query.iter().chain(wreck_query.iter()) {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    for structure_data in &save_game.structures.structures {
+        spawn_structure(&mut commands, &mut materials, &game_assets, &module_registry, structure_data);
+    }
+
+    info!("Loaded save slot {}", event.slot);
+}
+
+fn delete_save_system(mut event_reader: EventReader<DeleteSaveEvent>, settings: Res<SaveSettings>) {
+    for event in event_reader.read() {
+        let path = settings.slot_path(event.slot);
+        match remove_with_backup(&path) {
+            Ok(()) => info!("Deleted save slot {}", event.slot),
+            Err(error) => error!("Failed to delete save slot {} ({:?}): {}", event.slot, path, error),
+        }
+    }
+}
@@ -1,19 +1,44 @@
+use crate::core::replay::replay_is_playing;
 use bevy::prelude::*;
 
 #[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
 pub enum GameState {
     #[default]
     LoadingAssets,
+    /// The data files are invalid, waiting for them to be fixed and reloaded.
+    AssetError,
     BuildingGrid,
     BuildingStructures,
     InGame,
     Paused,
+    /// Designing a structure in the editor, the world is frozen meanwhile.
+    Editor,
 }
 
+/// Whether the player character is alive, only exists during a game and is kept while paused.
+#[derive(SubStates, Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+#[source(GameState = GameState::InGame | GameState::Paused)]
+pub enum PlayerState {
+    #[default]
+    Alive,
+    /// Waiting to respawn.
+    Dead,
+}
+
+const MIN_GAME_SPEED: f32 = 0.25;
+const MAX_GAME_SPEED: f32 = 4.0;
+
 pub struct StatePlugin;
 impl Plugin for StatePlugin {
     fn build(&self, app: &mut App) {
-        app.init_state::<GameState>().add_systems(Update, (game_state_input_events,));
+        app.init_state::<GameState>()
+            .add_sub_state::<PlayerState>()
+            .add_systems(Update, game_state_input_events)
+            // A replay steps exactly one fixed timestep per frame, the speed keys must not change it
+            .add_systems(
+                Update,
+                game_speed_input_events.run_if(in_state(GameState::InGame).and_then(not(replay_is_playing))),
+            );
     }
 }
 
@@ -30,3 +55,18 @@ fn game_state_input_events(
         }
     }
 }
+
+/// Halves or doubles the game speed with the bracket keys, in game.
+fn game_speed_input_events(mut time: ResMut<Time<Virtual>>, keyboard_input: Res<ButtonInput<KeyCode>>) {
+    let speed = time.relative_speed();
+    let new_speed = if keyboard_input.just_pressed(KeyCode::BracketRight) {
+        (speed * 2.0).min(MAX_GAME_SPEED)
+    } else if keyboard_input.just_pressed(KeyCode::BracketLeft) {
+        (speed / 2.0).max(MIN_GAME_SPEED)
+    } else {
+        return;
+    };
+
+    time.set_relative_speed(new_speed);
+    info!("Game speed set to {}x", new_speed);
+}
@@ -0,0 +1,275 @@
+use crate::core::asset_loader::DataAssetLoader;
+use crate::core::persistence::{read_with_backup, write_atomic, PersistenceError};
+use crate::core::prelude::*;
+use crate::ui::toasts::ToastEvent;
+use crate::world::prelude::*;
+
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+pub const ACHIEVEMENTS_PATH: &str = "data/achievements.ron";
+/// Progress of the player across games, kept outside of the save slots.
+pub const ACHIEVEMENTS_PROFILE_PATH: &str = "profile/achievements.json";
+const PROFILE_SAVE_INTERVAL: f32 = 15.0; // seconds, counters are saved at most this often, unlocks right away
+
+/// Achievements defined in `data/achievements.ron`, unlocked from gameplay events and kept in the player profile.
+pub struct AchievementsPlugin;
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AchievementDefinitions>()
+            .insert_resource(AchievementProfile::load(Path::new(ACHIEVEMENTS_PROFILE_PATH)))
+            .init_asset::<AchievementDefinitions>()
+            .init_asset_loader::<DataAssetLoader<AchievementDefinitions>>()
+            .add_event::<AchievementUnlockedEvent>()
+            .add_systems(Startup, load_achievement_definitions)
+            .add_systems(
+                Update,
+                (
+                    apply_achievement_definitions_system,
+                    (count_destroyed_modules_system, track_depressurization_system, measure_player_ship_system),
+                    unlock_achievements_system,
+                    save_profile_system,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+/// What has to be done to unlock an achievement, compared against the profile counters.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub enum AchievementCondition {
+    /// Modules destroyed by the structure the player controls.
+    ModulesDestroyed(u32),
+    /// Times the player stayed aboard a structure with every room open to space until it was sealed again.
+    DepressurizationsSurvived(u32),
+    /// Modules in the structure the player controls.
+    ShipModules(u32),
+}
+
+impl AchievementCondition {
+    /// Progress towards the condition, from 0 to 1.
+    pub fn progress(&self, counters: &AchievementCounters) -> f32 {
+        let (current, target) = match *self {
+            AchievementCondition::ModulesDestroyed(target) => (counters.modules_destroyed, target),
+            AchievementCondition::DepressurizationsSurvived(target) => (counters.depressurizations_survived, target),
+            AchievementCondition::ShipModules(target) => (counters.largest_ship_modules, target),
+        };
+        if target == 0 {
+            1.0
+        } else {
+            (current as f32 / target as f32).min(1.0)
+        }
+    }
+
+    pub fn is_met(&self, counters: &AchievementCounters) -> bool {
+        self.progress(counters) >= 1.0
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AchievementDefinition {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub condition: AchievementCondition,
+}
+
+#[derive(Asset, Resource, TypePath, Debug, Clone, Default, Deserialize)]
+#[serde(transparent)]
+pub struct AchievementDefinitions {
+    pub achievements: Vec<AchievementDefinition>,
+}
+
+/// Gameplay statistics the achievement conditions are evaluated from, accumulated over every game.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AchievementCounters {
+    pub modules_destroyed: u32,
+    pub depressurizations_survived: u32,
+    pub largest_ship_modules: u32,
+}
+
+#[derive(Resource, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AchievementProfile {
+    pub counters: AchievementCounters,
+    /// Ids of the unlocked achievements.
+    pub unlocked: BTreeSet<String>,
+    #[serde(skip)]
+    dirty: bool,
+}
+
+impl AchievementProfile {
+    /// Reads the profile, starting a new one when there is none yet.
+    pub fn load(path: &Path) -> Self {
+        if !path.exists() {
+            return Self::default();
+        }
+
+        let profile = read_with_backup(path)
+            .and_then(|json| serde_json::from_slice(&json).map_err(|error| PersistenceError::Io(error.into())));
+        match profile {
+            Ok(profile) => profile,
+            Err(error) => {
+                warn!("Could not read the achievements profile {:?}: {}", path, error);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), PersistenceError> {
+        let json = serde_json::to_vec_pretty(self).map_err(|error| PersistenceError::Io(error.into()))?;
+        write_atomic(path, &json)
+    }
+
+    pub fn is_unlocked(&self, id: &str) -> bool {
+        self.unlocked.contains(id)
+    }
+
+    fn counters_mut(&mut self) -> &mut AchievementCounters {
+        self.dirty = true;
+        &mut self.counters
+    }
+}
+
+#[derive(Event, Debug)]
+pub struct AchievementUnlockedEvent {
+    pub id: String,
+}
+
+#[derive(Resource)]
+struct AchievementDefinitionsHandle(Handle<AchievementDefinitions>);
+
+fn load_achievement_definitions(asset_server: Res<AssetServer>, mut commands: Commands) {
+    commands.insert_resource(AchievementDefinitionsHandle(asset_server.load(ACHIEVEMENTS_PATH)));
+}
+
+/// Replaces the definitions in use whenever the data file is (re)loaded.
+fn apply_achievement_definitions_system(
+    mut asset_events: EventReader<AssetEvent<AchievementDefinitions>>,
+    handle: Res<AchievementDefinitionsHandle>,
+    definitions_assets: Res<Assets<AchievementDefinitions>>,
+    mut definitions: ResMut<AchievementDefinitions>,
+) {
+    if !asset_events.read().any(|event| event.is_loaded_with_dependencies(&handle.0)) {
+        return;
+    }
+    if let Some(loaded_definitions) = definitions_assets.get(&handle.0) {
+        *definitions = loaded_definitions.clone();
+        info!("{} achievements loaded", definitions.achievements.len());
+    }
+}
+
+fn count_destroyed_modules_system(
+    mut event_reader: EventReader<ModuleDestroyedEvent>,
+    controlled_query: Query<Entity, With<ControlledByPlayer>>,
+    mut profile: ResMut<AchievementProfile>,
+) {
+    let controlled_structure = controlled_query.get_single().ok();
+    let destroyed =
+        event_reader.read().filter(|event| event.source.is_some() && event.source == controlled_structure).count();
+    if destroyed > 0 {
+        profile.counters_mut().modules_destroyed += destroyed as u32;
+    }
+}
+
+/// A depressurization is survived once the structure the player is in has every room open to space,
+/// then gets a sealed room again while the player is still aboard.
+fn track_depressurization_system(
+    structures_query: Query<&Pressurization>,
+    player_query: Query<(), With<Player>>,
+    player_resource: Res<PlayerResource>,
+    mut depressurized_structure: Local<Option<Entity>>,
+    mut profile: ResMut<AchievementProfile>,
+) {
+    let aboard = player_resource.inside_structure.filter(|_| !player_query.is_empty());
+    let Some(structure_entity) = aboard else {
+        *depressurized_structure = None;
+        return;
+    };
+    let Ok(pressurization) = structures_query.get(structure_entity) else {
+        return;
+    };
+    let fully_depressurized =
+        !pressurization.rooms.is_empty() && pressurization.rooms.values().all(|room| room.exposed);
+
+    match *depressurized_structure {
+        Some(structure) if structure != structure_entity => *depressurized_structure = None,
+        Some(_) if !fully_depressurized => {
+            profile.counters_mut().depressurizations_survived += 1;
+            *depressurized_structure = None;
+        }
+        None if fully_depressurized => *depressurized_structure = Some(structure_entity),
+        _ => {}
+    }
+}
+
+fn measure_player_ship_system(
+    controlled_query: Query<Ref<Children>, With<ControlledByPlayer>>,
+    modules_query: Query<(), With<Module>>,
+    mut profile: ResMut<AchievementProfile>,
+) {
+    let Ok(children) = controlled_query.get_single() else {
+        return;
+    };
+    if !children.is_changed() {
+        return;
+    }
+
+    let modules = modules_query.iter_many(children.iter()).count() as u32;
+    if modules > profile.counters.largest_ship_modules {
+        profile.counters_mut().largest_ship_modules = modules;
+    }
+}
+
+fn unlock_achievements_system(
+    definitions: Res<AchievementDefinitions>,
+    mut profile: ResMut<AchievementProfile>,
+    mut unlocked_writer: EventWriter<AchievementUnlockedEvent>,
+    mut toast_writer: EventWriter<ToastEvent>,
+) {
+    if !profile.is_changed() && !definitions.is_changed() {
+        return;
+    }
+
+    let mut unlocked_any = false;
+    for definition in &definitions.achievements {
+        if profile.is_unlocked(&definition.id) || !definition.condition.is_met(&profile.counters) {
+            continue;
+        }
+
+        info!("Achievement unlocked: {}", definition.name);
+        profile.unlocked.insert(definition.id.clone());
+        unlocked_writer.send(AchievementUnlockedEvent { id: definition.id.clone() });
+        toast_writer.send(ToastEvent {
+            title: format!("Achievement unlocked: {}", definition.name),
+            message: definition.description.clone(),
+        });
+        unlocked_any = true;
+    }
+
+    if unlocked_any {
+        // Unlocks are saved right away, a crash must not take them back
+        if let Err(error) = profile.save(Path::new(ACHIEVEMENTS_PROFILE_PATH)) {
+            error!("Could not save the achievements profile: {}", error);
+        }
+        profile.bypass_change_detection().dirty = false;
+    }
+}
+
+fn save_profile_system(time: Res<Time>, mut timer: Local<f32>, mut profile: ResMut<AchievementProfile>) {
+    *timer += time.delta_seconds();
+    if *timer < PROFILE_SAVE_INTERVAL || !profile.dirty {
+        return;
+    }
+    *timer = 0.0;
+
+    if let Err(error) = profile.save(Path::new(ACHIEVEMENTS_PROFILE_PATH)) {
+        error!("Could not save the achievements profile: {}", error);
+    }
+    profile.bypass_change_detection().dirty = false;
+}
@@ -0,0 +1,183 @@
+use crate::core::prelude::*;
+use crate::core::profiling::{ProfileScope, AI};
+use crate::gameplay::scanning::ScanReveals;
+use crate::gameplay::structures_combat::FireCannonsEvent;
+use crate::world::prelude::*;
+
+use crate::prelude::*;
+use bevy::diagnostic::Diagnostics;
+
+const AI_CRUISE_SPEED: f32 = 6.0; // m/s
+const AI_FLEE_SPEED: f32 = 9.0; // m/s
+const AI_RESPONSIVENESS: f32 = 0.5; // fraction of the desired velocity change reached per second
+const AI_ATTACK_RANGE: f32 = 150.0; // meters, cannons fire below this distance
+const AI_KEEP_DISTANCE: f32 = 80.0; // meters kept from the target while attacking
+const AI_FIRE_INTERVAL: f32 = 2.0; // seconds between two volleys
+const AI_TURN_RATE: f32 = 1.5; // rad/s of angular velocity per radian left to turn
+const AI_WAYPOINT_RADIUS: f32 = 20.0; // meters, a waypoint closer than this is reached
+
+/// Flies the structures with an `AiPilot` according to their stance towards their target, the player by default.
+pub struct AiPlugin;
+
+impl Plugin for AiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, ai_pilot_system.run_if(in_state(GameState::InGame)));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AiStance {
+    /// Drifts along, or follows its `AiRoute` when it has one.
+    #[default]
+    Idle,
+    /// Runs away from its target.
+    Flee,
+    /// Closes in on its target and fires its cannons.
+    Attack,
+    /// Comes to a stop, waiting to trade.
+    Trade,
+}
+
+#[derive(Component, Debug)]
+pub struct AiPilot {
+    pub stance: AiStance,
+    /// Entity the stance is directed at, the player when `None` or once the target is gone.
+    pub target: Option<Entity>,
+    fire_cooldown: Timer,
+}
+
+impl AiPilot {
+    pub fn new(stance: AiStance) -> Self {
+        Self { stance, target: None, fire_cooldown: Timer::from_seconds(AI_FIRE_INTERVAL, TimerMode::Repeating) }
+    }
+
+    pub fn with_target(mut self, target: Entity) -> Self {
+        self.target = Some(target);
+        self
+    }
+}
+
+impl Default for AiPilot {
+    fn default() -> Self {
+        Self::new(AiStance::default())
+    }
+}
+
+/// Waypoints followed in order by an idle pilot.
+#[derive(Component, Debug, Clone)]
+pub struct AiRoute {
+    pub waypoints: Vec<Vec2>,
+    next: usize,
+}
+
+impl AiRoute {
+    pub fn new(waypoints: Vec<Vec2>) -> Self {
+        Self { waypoints, next: 0 }
+    }
+
+    pub fn next_waypoint(&self) -> Option<Vec2> {
+        self.waypoints.get(self.next).copied()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.waypoints.len()
+    }
+}
+
+/// Module of a structure closest to breaking, where a scanned target is the easiest to hurt.
+fn weakest_module_position(
+    children: &Children,
+    modules_query: &Query<(&ModuleMaterial, &GlobalTransform), With<Module>>,
+) -> Option<Vec2> {
+    modules_query
+        .iter_many(children)
+        .min_by(|(material1, _), (material2, _)| material1.structural_points.total_cmp(&material2.structural_points))
+        .map(|(_, transform)| transform.translation().truncate())
+}
+
+fn ai_pilot_system(
+    mut pilots_query: Query<
+        (
+            Entity,
+            &mut AiPilot,
+            Option<&mut AiRoute>,
+            &Transform,
+            &mut LinearVelocity,
+            &mut AngularVelocity,
+            Option<&ScanReveals>,
+        ),
+        Without<Player>,
+    >,
+    player_query: Query<Entity, With<Player>>,
+    targets_query: Query<&GlobalTransform>,
+    structures_query: Query<&Children, With<Structure>>,
+    modules_query: Query<(&ModuleMaterial, &GlobalTransform), With<Module>>,
+    player_resource: Res<PlayerResource>,
+    mut fire_writer: EventWriter<FireCannonsEvent>,
+    time: Res<Time>,
+    mut diagnostics: Diagnostics,
+) {
+    let Ok(player_entity) = player_query.get_single() else {
+        return;
+    };
+    let scope = ProfileScope::enter(&AI);
+    let delta_time = time.delta_seconds();
+
+    for (structure_entity, mut pilot, route, transform, mut velocity, mut angular_velocity, reveals) in
+        &mut pilots_query
+    {
+        let target = pilot
+            .target
+            .and_then(|target| targets_query.get(target).ok())
+            .or_else(|| targets_query.get(player_entity).ok())
+            .map(|target_transform| target_transform.translation().truncate());
+        let Some(target) = target else {
+            continue;
+        };
+        let position = transform.translation.truncate();
+        let offset = target - position;
+        let distance = offset.length();
+        let towards_target = offset.normalize_or_zero();
+
+        let desired_velocity = match pilot.stance {
+            AiStance::Idle => match route {
+                Some(mut route) => {
+                    if route.next_waypoint().is_some_and(|waypoint| waypoint.distance(position) < AI_WAYPOINT_RADIUS) {
+                        route.next += 1;
+                    }
+                    route
+                        .next_waypoint()
+                        .map_or(Vec2::ZERO, |waypoint| (waypoint - position).normalize_or_zero() * AI_CRUISE_SPEED)
+                }
+                None => velocity.0,
+            },
+            AiStance::Trade => Vec2::ZERO,
+            AiStance::Flee => -towards_target * AI_FLEE_SPEED,
+            AiStance::Attack if distance > AI_KEEP_DISTANCE => towards_target * AI_CRUISE_SPEED,
+            AiStance::Attack => Vec2::ZERO,
+        };
+        velocity.0 = velocity.0.lerp(desired_velocity, (AI_RESPONSIVENESS * delta_time).min(1.0));
+
+        // Cannons fire along the bow, turn it towards the target while attacking, at its weakest module once a scan
+        // revealed it
+        if pilot.stance == AiStance::Attack {
+            let target_structure =
+                pilot.target.filter(|target| structures_query.contains(*target)).or(player_resource.inside_structure);
+            let aim = target_structure
+                .filter(|target_structure| reveals.is_some_and(|reveals| reveals.reveals(*target_structure)))
+                .and_then(|target_structure| structures_query.get(target_structure).ok())
+                .and_then(|children| weakest_module_position(children, &modules_query))
+                .map_or(towards_target, |weak_spot| (weak_spot - position).normalize_or_zero());
+            let forward = transform.rotation.mul_vec3(Vec3::Y).truncate();
+            angular_velocity.0 = forward.angle_between(aim) * AI_TURN_RATE;
+        }
+
+        if pilot.stance == AiStance::Attack
+            && pilot.fire_cooldown.tick(time.delta()).just_finished()
+            && distance < AI_ATTACK_RANGE
+        {
+            fire_writer.send(FireCannonsEvent { structure_entity });
+        }
+    }
+    scope.finish(&mut diagnostics);
+}
@@ -0,0 +1,158 @@
+use crate::core::prelude::*;
+use crate::gameplay::degradation::{performance, ModulePerformance};
+use crate::gameplay::maneuvers::ManeuverQueue;
+use crate::gameplay::movement::{PilotCommand, PilotingSet};
+use crate::gameplay::power::PowerConsumer;
+use crate::world::prelude::*;
+
+use avian2d::prelude::*;
+use bevy::color::palettes::css::DODGER_BLUE;
+use bevy::prelude::*;
+use std::f32::consts::{PI, TAU};
+
+const POSITION_GAIN: f32 = 0.1; // wanted acceleration per meter to the waypoint
+const VELOCITY_GAIN: f32 = 0.6; // wanted deceleration per m/s of velocity, damps the approach
+const HEADING_GAIN: f32 = 2.0; // rotation command per radian of heading error
+const ANGULAR_VELOCITY_GAIN: f32 = 1.5; // rotation command per rad/s of angular velocity
+const ARRIVAL_RADIUS: f32 = 2.0; // m
+const ARRIVAL_SPEED: f32 = 0.2; // m/s
+const WAYPOINT_MARKER_RADIUS: f32 = 2.0; // m
+
+/// Flies the controlled structure to a waypoint, set with a right click in the world or a click on the minimap.
+/// A PD controller on the position turns the structure so its engines face the way to go and fires them, braking on
+/// the approach. Any movement input from the pilot gives the controls back.
+pub struct AutopilotPlugin;
+
+impl Plugin for AutopilotPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AutopilotArrivedEvent>()
+            .add_systems(
+                Update,
+                (autopilot_input_system, clear_uncontrolled_autopilot_system, draw_waypoint_system)
+                    .chain()
+                    .after(InGameSet::UserInput)
+                    .run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(FixedUpdate, autopilot_system.before(PilotingSet).run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// Waypoint the controlled structure is flying to, removed on arrival.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Autopilot {
+    pub target: Vec2,
+}
+
+#[derive(Event, Debug)]
+pub struct AutopilotArrivedEvent {
+    pub structure_entity: Entity,
+    pub target: Vec2,
+}
+
+/// Wraps an angle difference to -π..π.
+fn wrap_angle(angle: f32) -> f32 {
+    (angle + PI).rem_euclid(TAU) - PI
+}
+
+/// Engages the autopilot on a new waypoint, or cancels it when the pilot steers or queues maneuvers.
+fn autopilot_input_system(
+    mut input_reader: EventReader<InputAction>,
+    controlled_query: Query<(Entity, Has<Autopilot>), With<ControlledByPlayer>>,
+    player_resource: Res<PlayerResource>,
+    mut commands: Commands,
+) {
+    let Ok((structure_entity, engaged)) = controlled_query.get_single() else {
+        return;
+    };
+    if !player_resource.is_controlling_structure {
+        return;
+    }
+
+    for event in input_reader.read() {
+        match event {
+            InputAction::SetWaypoint(target) => {
+                info!("Autopilot engaged to {:?}", target);
+                commands
+                    .entity(structure_entity)
+                    .remove::<(ManeuverQueue, PilotCommand)>()
+                    .insert(Autopilot { target: *target });
+            }
+            InputAction::Move(_) | InputAction::Rotate(_) | InputAction::Break | InputAction::QueueManeuver(_)
+                if engaged =>
+            {
+                debug!("Autopilot cancelled by the pilot");
+                commands.entity(structure_entity).remove::<(Autopilot, PilotCommand)>();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// A structure the pilot left does not keep flying to their waypoint.
+fn clear_uncontrolled_autopilot_system(
+    uncontrolled_query: Query<Entity, (With<Autopilot>, Without<ControlledByPlayer>)>,
+    mut commands: Commands,
+) {
+    for structure_entity in &uncontrolled_query {
+        commands.entity(structure_entity).remove::<(Autopilot, PilotCommand)>();
+    }
+}
+
+/// Steers the structure towards its waypoint through its `PilotCommand`.
+fn autopilot_system(
+    controlled_query: Query<
+        (Entity, &Autopilot, &Transform, &LinearVelocity, &AngularVelocity, &Children),
+        With<ControlledByPlayer>,
+    >,
+    engines_query: Query<(&Transform, Option<&PowerConsumer>, Option<&ModulePerformance>), With<EngineModule>>,
+    mut arrived_writer: EventWriter<AutopilotArrivedEvent>,
+    mut commands: Commands,
+) {
+    for (structure_entity, autopilot, transform, linear_velocity, angular_velocity, children) in &controlled_query {
+        let offset = autopilot.target - transform.translation.truncate();
+        if offset.length() < ARRIVAL_RADIUS && linear_velocity.0.length() < ARRIVAL_SPEED {
+            info!("Autopilot arrived at {:?}", autopilot.target);
+            arrived_writer.send(AutopilotArrivedEvent { structure_entity, target: autopilot.target });
+            commands.entity(structure_entity).remove::<(Autopilot, PilotCommand)>();
+            continue;
+        }
+
+        // Direction the working engines push the structure, in its own frame
+        let engines_thrust: Vec2 = children
+            .iter()
+            .filter_map(|child| engines_query.get(*child).ok())
+            .filter(|(_, power, _)| power.is_none_or(|power| power.powered))
+            .map(|(engine_transform, _, module_performance)| {
+                engine_transform.rotation.mul_vec3(Vec3::Y).truncate() * performance(module_performance)
+            })
+            .sum();
+        if engines_thrust.length() <= f32::EPSILON {
+            warn!("Autopilot disengaged, no engine can fly the structure");
+            commands.entity(structure_entity).remove::<(Autopilot, PilotCommand)>();
+            continue;
+        }
+
+        // PD controller on the position, the derivative term slows down the approach
+        let wanted_acceleration = offset * POSITION_GAIN - linear_velocity.0 * VELOCITY_GAIN;
+
+        // Turn so the engines push along the wanted acceleration
+        let heading = transform.rotation.to_euler(EulerRot::XYZ).2;
+        let engines_angle = engines_thrust.to_angle();
+        let heading_error = wrap_angle(wanted_acceleration.to_angle() - engines_angle - heading);
+        let rotation = (heading_error * HEADING_GAIN - angular_velocity.0 * ANGULAR_VELOCITY_GAIN).clamp(-1.0, 1.0);
+
+        commands.entity(structure_entity).insert(PilotCommand {
+            thrust: wanted_acceleration.clamp_length_max(1.0),
+            rotation,
+            // Going faster than wanted towards the waypoint
+            brake: linear_velocity.0.dot(wanted_acceleration) < 0.0,
+        });
+    }
+}
+
+fn draw_waypoint_system(mut gizmos: Gizmos, controlled_query: Query<(&Autopilot, &Transform)>) {
+    for (autopilot, transform) in &controlled_query {
+        gizmos.circle_2d(autopilot.target, WAYPOINT_MARKER_RADIUS, DODGER_BLUE);
+        gizmos.line_2d(transform.translation.truncate(), autopilot.target, DODGER_BLUE.with_alpha(0.3));
+    }
+}
@@ -0,0 +1,119 @@
+use crate::core::prelude::*;
+use crate::gameplay::building::BUILD_SCRAP_COST;
+use crate::gameplay::power::{CANNON_POWER_DEMAND, ENGINE_POWER_DEMAND, REACTOR_OUTPUT};
+use crate::world::prelude::*;
+
+use crate::prelude::*;
+
+/// Modules of a design made of one material.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MaterialCost {
+    pub modules: u32,
+    /// Mass in kg.
+    pub mass: f32,
+}
+
+/// Figures of a structure design, computed from its layout alone without spawning it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BlueprintReport {
+    pub modules: u32,
+    /// Mass in kg.
+    pub mass: f32,
+    pub materials: HashMap<ModuleMaterialType, MaterialCost>,
+    /// Scrap needed to build every module in build mode.
+    pub scrap_cost: f32,
+    /// Thrust of all the engines in N, they do not all push the same way in flight.
+    pub thrust: f32,
+    pub power_produced: f32,
+    pub power_demanded: f32,
+    /// Rooms sealed from space once the structure is pressurized.
+    pub pressurizable_rooms: usize,
+}
+
+impl BlueprintReport {
+    /// Acceleration the engines give the structure, in m/s².
+    pub fn thrust_to_mass(&self) -> f32 {
+        if self.mass <= 0.0 {
+            return 0.0;
+        }
+        self.thrust / self.mass
+    }
+
+    /// Power left over with every consumer running, negative when the reactors cannot keep up.
+    pub fn power_balance(&self) -> f32 {
+        self.power_produced - self.power_demanded
+    }
+}
+
+/// Computes the mass, the cost, the thrust, the power and the rooms of structure designs from the structures data
+/// format, for the editor and to scale the AI difficulty.
+pub struct BlueprintAnalyzer<'a> {
+    pub module_registry: &'a ModuleRegistry,
+    /// Thrust of every engine module, in N, see `MovementSettings`.
+    pub engine_thrust: f32,
+}
+
+impl<'a> BlueprintAnalyzer<'a> {
+    pub fn new(module_registry: &'a ModuleRegistry, engine_thrust: f32) -> Self {
+        Self { module_registry, engine_thrust }
+    }
+
+    pub fn analyze_all(&self, structures: &StructuresData) -> Vec<BlueprintReport> {
+        structures.structures.iter().map(|structure_data| self.analyze(structure_data)).collect()
+    }
+
+    pub fn analyze(&self, structure_data: &StructureData) -> BlueprintReport {
+        let mut report = BlueprintReport::default();
+        let width = structure_data.structure.iter().map(|row| row.chars().count()).max().unwrap_or_default();
+        let mut structure = Structure::new();
+        structure.grid = Grid::new(width as u32, structure_data.structure.len() as u32, STRUCTURE_CELL_SIZE);
+
+        let mut covered = HashSet::new();
+        for (y, row) in structure_data.structure.iter().enumerate() {
+            for (x, symbol) in row.chars().enumerate() {
+                let module_type = ModuleType::from_symbol(symbol)
+                    .or_else(|| self.module_registry.get(symbol).map(|_| ModuleType::Custom(symbol)));
+                let Some(module_type) = module_type else {
+                    structure.grid.insert(x as i32, y as i32, CellType::Empty);
+                    continue;
+                };
+                structure.grid.insert(x as i32, y as i32, CellType::Module);
+
+                let material_type = match module_type {
+                    ModuleType::Custom(symbol) => self
+                        .module_registry
+                        .get(symbol)
+                        .map_or(module_type.material_type(), |definition| definition.material_type),
+                    _ => module_type.material_type(),
+                };
+                // Every cell weighs the same, a module covering several of them is counted from its top left cell
+                let mass = material_type.module_mass(STRUCTURE_CELL_SIZE);
+                let material_cost = report.materials.entry(material_type).or_default();
+                material_cost.mass += mass;
+                report.mass += mass;
+                let anchor = (x as i32, y as i32);
+                if covered.contains(&anchor) {
+                    continue;
+                }
+                let orientation = structure_data.orientation_at(anchor);
+                covered.extend(self.module_registry.footprint(symbol).cells(anchor, orientation));
+                material_cost.modules += 1;
+                report.modules += 1;
+                report.scrap_cost += BUILD_SCRAP_COST;
+
+                match module_type {
+                    ModuleType::Engine => {
+                        report.thrust += self.engine_thrust;
+                        report.power_demanded += ENGINE_POWER_DEMAND;
+                    }
+                    ModuleType::Cannon => report.power_demanded += CANNON_POWER_DEMAND,
+                    ModuleType::Reactor => report.power_produced += REACTOR_OUTPUT,
+                    _ => {}
+                }
+            }
+        }
+
+        report.pressurizable_rooms = structure.check_pressurization().values().filter(|room| !room.exposed).count();
+        report
+    }
+}
@@ -0,0 +1,524 @@
+use crate::core::prelude::*;
+use crate::gameplay::livery::Paint;
+use crate::gameplay::repair::{DestroyedModules, Scrap};
+use crate::world::prelude::*;
+
+use crate::prelude::*;
+use thiserror::Error;
+
+pub(crate) const BUILD_SCRAP_COST: f32 = 25.0; // scrap spent per placed module
+const BUILD_HISTORY_SIZE: usize = 100; // build actions kept for undo
+const GHOST_ALPHA: f32 = 0.45;
+const GHOST_VALID_COLOR: Color = Color::srgb(0.2, 1.0, 0.3);
+const GHOST_INVALID_COLOR: Color = Color::srgb(1.0, 0.2, 0.2);
+
+/// Lets the player add modules to the structure they are walking in.
+/// Press B to enter build mode, Tab to cycle the module, Q/E to rotate it, left click to place it and Ctrl+Z to
+/// undo.
+pub struct BuildingPlugin;
+
+impl Plugin for BuildingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BuildMode>().init_resource::<BuildHistory>().add_systems(
+            Update,
+            (
+                build_mode_input_system,
+                update_placement_target_system,
+                update_ghost_system,
+                place_module_system,
+                undo_build_system,
+            )
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+/// A module type the player can build.
+#[derive(Debug, Clone, Copy)]
+pub struct PlaceableModule {
+    pub module_type: ModuleType,
+    pub color: Color,
+    pub material_type: ModuleMaterialType,
+}
+
+impl PlaceableModule {
+    const fn new(module_type: ModuleType, color: Srgba, material_type: ModuleMaterialType) -> Self {
+        Self { module_type, color: Color::Srgba(color), material_type }
+    }
+}
+
+/// Built-in modules available in build mode, with the same look as in the structures data files.
+pub const PLACEABLE_MODULES: [PlaceableModule; 13] = [
+    PlaceableModule::new(ModuleType::Wall, GREY, ModuleMaterialType::Steel),
+    PlaceableModule::new(ModuleType::Engine, RED, ModuleMaterialType::Steel),
+    PlaceableModule::new(ModuleType::Cannon, PURPLE, ModuleMaterialType::Aluminum),
+    PlaceableModule::new(ModuleType::CrewQuarters, ORANGE, ModuleMaterialType::Steel),
+    PlaceableModule::new(ModuleType::Reactor, YELLOW, ModuleMaterialType::Steel),
+    PlaceableModule::new(ModuleType::Door, SADDLE_BROWN, ModuleMaterialType::Steel),
+    PlaceableModule::new(ModuleType::Airlock, TEAL, ModuleMaterialType::Steel),
+    PlaceableModule::new(ModuleType::DockingPort, LIME, ModuleMaterialType::Steel),
+    PlaceableModule::new(ModuleType::CargoHold, TAN, ModuleMaterialType::Steel),
+    PlaceableModule::new(ModuleType::Refinery, ORANGE_RED, ModuleMaterialType::Steel),
+    PlaceableModule::new(ModuleType::Accelerator, DARK_CYAN, ModuleMaterialType::Steel),
+    PlaceableModule::new(ModuleType::DroneBay, GOLD, ModuleMaterialType::Steel),
+    PlaceableModule::new(ModuleType::Sensor, MEDIUM_SPRING_GREEN, ModuleMaterialType::Aluminum),
+];
+
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum PlacementError {
+    #[error("Cell is outside of the structure")]
+    OutOfBounds,
+    #[error("Cell is already occupied by a module")]
+    Occupied,
+    #[error("A destroyed module is waiting to be rebuilt in this cell")]
+    AwaitingRebuild,
+    #[error("Modules must be attached to another module")]
+    NotAttached,
+    #[error("The player is standing in this cell")]
+    PlayerInTheWay,
+    #[error("Not enough scrap ({0:.0} needed)")]
+    NotEnoughScrap(f32),
+    #[error("Accelerators in line must face the same way")]
+    MisalignedAccelerator,
+}
+
+/// Checks if a module can be placed in a cell of a structure.
+/// `accelerator_rotation` gives the quarter turns of the accelerator in a cell, if there is one.
+pub fn check_placement(
+    structure: &Structure,
+    cell: (i32, i32),
+    player_cell: (i32, i32),
+    placed: &ModuleBlueprint,
+    accelerator_rotation: impl Fn((i32, i32)) -> Option<u8>,
+    destroyed_modules: Option<&DestroyedModules>,
+    scrap: &Scrap,
+) -> Result<(), PlacementError> {
+    if !structure.is_within_grid_bounds(cell.0, cell.1) {
+        return Err(PlacementError::OutOfBounds);
+    }
+    if structure.grid.get(cell.0, cell.1).is_some_and(|grid_cell| grid_cell.cell_type == CellType::Module) {
+        return Err(PlacementError::Occupied);
+    }
+    if destroyed_modules.is_some_and(|destroyed| destroyed.0.contains_key(&cell)) {
+        return Err(PlacementError::AwaitingRebuild);
+    }
+    let attached = structure
+        .get_adjacent_cells(cell)
+        .into_iter()
+        .any(|(x, y)| structure.grid.get(x, y).is_some_and(|grid_cell| grid_cell.cell_type == CellType::Module));
+    if !attached {
+        return Err(PlacementError::NotAttached);
+    }
+    if cell == player_cell {
+        return Err(PlacementError::PlayerInTheWay);
+    }
+    // The segments of a spinal weapon must all point along its barrel, a crooked one would split it in two
+    if placed.module_type == ModuleType::Accelerator {
+        let (dx, dy) = facing_cell_offset(placed.rotation);
+        let misaligned = [(cell.0 + dx, cell.1 + dy), (cell.0 - dx, cell.1 - dy)]
+            .into_iter()
+            .filter_map(&accelerator_rotation)
+            .any(|rotation| rotation != placed.rotation);
+        if misaligned {
+            return Err(PlacementError::MisalignedAccelerator);
+        }
+    }
+    if scrap.amount < BUILD_SCRAP_COST {
+        return Err(PlacementError::NotEnoughScrap(BUILD_SCRAP_COST));
+    }
+    Ok(())
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct BuildMode {
+    pub active: bool,
+    /// Index in `PLACEABLE_MODULES`.
+    pub selected: usize,
+    /// Quarter turns applied to the placed module, counterclockwise.
+    pub rotation: u8,
+    pub target: Option<PlacementTarget>,
+}
+
+impl BuildMode {
+    pub fn selected_module(&self) -> &PlaceableModule {
+        &PLACEABLE_MODULES[self.selected % PLACEABLE_MODULES.len()]
+    }
+
+    /// Blueprint of the module placed by a click.
+    pub fn selected_blueprint(&self) -> ModuleBlueprint {
+        let placeable = self.selected_module();
+        ModuleBlueprint {
+            module_type: placeable.module_type,
+            color: placeable.color,
+            material_type: placeable.material_type,
+            rotation: self.rotation,
+        }
+    }
+
+    pub fn rotation_quat(&self) -> Quat {
+        Quat::from_rotation_z(self.rotation as f32 * std::f32::consts::FRAC_PI_2)
+    }
+}
+
+/// The hovered cell, with the result of the placement rules for it.
+#[derive(Debug, Clone)]
+pub struct PlacementTarget {
+    pub structure_entity: Entity,
+    pub cell: (i32, i32),
+    pub validity: Result<(), PlacementError>,
+}
+
+#[derive(Component)]
+struct PlacementGhost;
+
+fn build_mode_input_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    player_resource: Res<PlayerResource>,
+    mut build_mode: ResMut<BuildMode>,
+) {
+    // Building is done on foot, the command center keys are used to fly
+    if player_resource.is_controlling_structure || player_resource.inside_structure.is_none() {
+        build_mode.active = false;
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::KeyB) {
+        build_mode.active = !build_mode.active;
+    }
+    if !build_mode.active {
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Tab) {
+        build_mode.selected = (build_mode.selected + 1) % PLACEABLE_MODULES.len();
+    }
+    if keys.just_pressed(KeyCode::KeyQ) {
+        build_mode.rotation = (build_mode.rotation + 1) % 4;
+    }
+    if keys.just_pressed(KeyCode::KeyE) {
+        build_mode.rotation = (build_mode.rotation + 3) % 4;
+    }
+}
+
+/// Finds the structure cell under the mouse cursor and runs the placement rules on it.
+pub(crate) fn update_placement_target_system(
+    mut build_mode: ResMut<BuildMode>,
+    windows_query: Query<&Window>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    player_resource: Res<PlayerResource>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    structures_query: Query<(&Structure, &Transform, Option<&DestroyedModules>)>,
+    accelerators_query: Query<&Transform, With<AcceleratorModule>>,
+    scrap: Res<Scrap>,
+) {
+    build_mode.target = None;
+    if !build_mode.active {
+        return;
+    }
+
+    let Some(structure_entity) = player_resource.inside_structure else {
+        return;
+    };
+    let Ok((structure, structure_transform, destroyed_modules)) = structures_query.get(structure_entity) else {
+        return;
+    };
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let Ok((camera, camera_transform)) = camera_query.get_single() else {
+        return;
+    };
+    let Some(cursor_position) = windows_query
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor))
+    else {
+        return;
+    };
+
+    let cell = structure.world_to_grid(cursor_position.extend(0.0), structure_transform);
+    let player_cell = structure.world_to_grid(player_transform.translation(), structure_transform);
+    let accelerator_rotation = |cell| {
+        structure
+            .module_at(cell)
+            .and_then(|module_entity| accelerators_query.get(module_entity).ok())
+            .map(|transform| quarter_turns(transform.rotation))
+    };
+    let validity = check_placement(
+        structure,
+        cell,
+        player_cell,
+        &build_mode.selected_blueprint(),
+        accelerator_rotation,
+        destroyed_modules,
+        &scrap,
+    );
+
+    build_mode.target = Some(PlacementTarget { structure_entity, cell, validity });
+}
+
+/// Shows a translucent module on the hovered cell, green if it can be placed and red otherwise.
+fn update_ghost_system(
+    build_mode: Res<BuildMode>,
+    mut ghost_query: Query<(Entity, &Parent, &mut Transform, &mut Sprite), With<PlacementGhost>>,
+    structures_query: Query<&Structure>,
+    mut commands: Commands,
+) {
+    let Some(target) = &build_mode.target else {
+        for (ghost_entity, ..) in &ghost_query {
+            commands.entity(ghost_entity).despawn_recursive();
+        }
+        return;
+    };
+    let Ok(structure) = structures_query.get(target.structure_entity) else {
+        return;
+    };
+
+    let translation = structure.grid_cell_center_local_position(target.cell.0, target.cell.1).extend(3.0);
+    let size = Vec2::splat(structure.grid.cell_size * MODULE_MESH_SCALE_FACTOR);
+    let module_color = build_mode.selected_module().color;
+    let validity_color = if target.validity.is_ok() { GHOST_VALID_COLOR } else { GHOST_INVALID_COLOR };
+    let color = module_color.mix(&validity_color, 0.5).with_alpha(GHOST_ALPHA);
+    let transform = Transform::from_translation(translation).with_rotation(build_mode.rotation_quat());
+
+    if let Ok((ghost_entity, parent, mut ghost_transform, mut sprite)) = ghost_query.get_single_mut() {
+        if parent.get() == target.structure_entity {
+            *ghost_transform = transform;
+            sprite.color = color;
+            sprite.custom_size = Some(size);
+            return;
+        }
+        commands.entity(ghost_entity).despawn_recursive();
+    }
+
+    commands.entity(target.structure_entity).with_children(|structure| {
+        structure
+            .spawn((
+                PlacementGhost,
+                SpriteBundle { sprite: Sprite { color, custom_size: Some(size), ..default() }, transform, ..default() },
+            ))
+            .with_children(|ghost| {
+                // Arrow showing the facing of the module
+                ghost.spawn(SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::srgba(1.0, 1.0, 1.0, GHOST_ALPHA),
+                        custom_size: Some(Vec2::new(size.x * 0.15, size.y * 0.5)),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(0.0, size.y * 0.25, 0.1),
+                    ..default()
+                });
+            });
+    });
+}
+
+fn place_module_system(
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    build_mode: Res<BuildMode>,
+    mut structures_query: Query<(&mut Structure, &mut Pressurization)>,
+    mut scrap: ResMut<Scrap>,
+    mut history: ResMut<BuildHistory>,
+    mut commands: Commands,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    game_assets: Res<GameAssets>,
+) {
+    if !mouse_buttons.just_pressed(MouseButton::Left) {
+        return;
+    }
+    // Shift click starts a marquee selection instead
+    if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        return;
+    }
+    let Some(target) = &build_mode.target else {
+        return;
+    };
+    if let Err(error) = &target.validity {
+        debug!("Cannot place module at {:?}: {}", target.cell, error);
+        return;
+    }
+    let Ok((mut structure, mut pressurization)) = structures_query.get_mut(target.structure_entity) else {
+        return;
+    };
+
+    let blueprint = build_mode.selected_blueprint();
+    build_module(
+        &mut commands,
+        target.structure_entity,
+        &mut structure,
+        &mut materials,
+        &game_assets,
+        target.cell,
+        &blueprint,
+    );
+    scrap.amount -= BUILD_SCRAP_COST;
+    history.push(BuildAction::Placed { structure_entity: target.structure_entity, cells: vec![target.cell] });
+
+    // The new module may seal a room
+    let rooms = structure.check_pressurization();
+    pressurization.update_rooms(rooms);
+    debug!("Placed {:?} at {:?}", blueprint.module_type, target.cell);
+}
+
+/// Reverts the last build action with Ctrl+Z, placed modules are refunded.
+fn undo_build_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    build_mode: Res<BuildMode>,
+    mut history: ResMut<BuildHistory>,
+    mut structures_query: Query<(&mut Structure, &mut Pressurization)>,
+    modules_query: Query<(&Module, &ModuleMaterial, &Handle<ColorMaterial>, &Transform, Option<&Paint>)>,
+    mut scrap: ResMut<Scrap>,
+    mut commands: Commands,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    game_assets: Res<GameAssets>,
+) {
+    if !build_mode.active
+        || !keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+        || !keys.just_pressed(KeyCode::KeyZ)
+    {
+        return;
+    }
+    let Some(action) = history.undo.pop() else {
+        return;
+    };
+
+    match action {
+        BuildAction::Placed { structure_entity, cells } => {
+            let Ok((mut structure, mut pressurization)) = structures_query.get_mut(structure_entity) else {
+                return;
+            };
+            for cell in cells {
+                if remove_module(&mut commands, &mut structure, &modules_query, &materials, cell).is_some() {
+                    scrap.amount += BUILD_SCRAP_COST;
+                }
+            }
+            let rooms = structure.check_pressurization();
+            pressurization.update_rooms(rooms);
+        }
+        BuildAction::Removed { structure_entity, modules } => {
+            let Ok((mut structure, mut pressurization)) = structures_query.get_mut(structure_entity) else {
+                return;
+            };
+            for (cell, blueprint) in modules {
+                if structure.grid.get(cell.0, cell.1).is_some_and(|grid_cell| grid_cell.cell_type == CellType::Module) {
+                    continue;
+                }
+                build_module(
+                    &mut commands,
+                    structure_entity,
+                    &mut structure,
+                    &mut materials,
+                    &game_assets,
+                    cell,
+                    &blueprint,
+                );
+            }
+            let rooms = structure.check_pressurization();
+            pressurization.update_rooms(rooms);
+        }
+    }
+}
+
+/// Everything needed to build a module again, used by the undo stack and the clipboard.
+#[derive(Debug, Clone)]
+pub struct ModuleBlueprint {
+    pub module_type: ModuleType,
+    pub color: Color,
+    pub material_type: ModuleMaterialType,
+    /// Quarter turns, counterclockwise.
+    pub rotation: u8,
+}
+
+impl ModuleBlueprint {
+    /// Reads back the blueprint of a module spawned in a structure.
+    pub fn from_module(
+        module: &Module,
+        module_material: &ModuleMaterial,
+        material_handle: &Handle<ColorMaterial>,
+        transform: &Transform,
+        paint: Option<&Paint>,
+        materials: &Assets<ColorMaterial>,
+    ) -> Self {
+        // The livery of the structure the blueprint is built in paints it again
+        let color = paint
+            .map(|paint| paint.base)
+            .or_else(|| materials.get(material_handle).map(|material| material.color))
+            .unwrap_or(Color::WHITE);
+        Self {
+            module_type: module.module_type,
+            color,
+            material_type: module_material.material_type,
+            rotation: quarter_turns(transform.rotation),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum BuildAction {
+    Placed { structure_entity: Entity, cells: Vec<(i32, i32)> },
+    Removed { structure_entity: Entity, modules: Vec<((i32, i32), ModuleBlueprint)> },
+}
+
+/// Build actions that can be undone, most recent last.
+#[derive(Resource, Debug, Default)]
+pub struct BuildHistory {
+    pub undo: Vec<BuildAction>,
+}
+
+impl BuildHistory {
+    pub fn push(&mut self, action: BuildAction) {
+        if self.undo.len() >= BUILD_HISTORY_SIZE {
+            self.undo.remove(0);
+        }
+        self.undo.push(action);
+    }
+}
+
+/// Spawns a module from its blueprint in an empty cell of a structure.
+pub fn build_module(
+    commands: &mut Commands,
+    structure_entity: Entity,
+    structure: &mut Structure,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    game_assets: &GameAssets,
+    cell: (i32, i32),
+    blueprint: &ModuleBlueprint,
+) -> Entity {
+    let translation = structure.grid_cell_center_local_position(cell.0, cell.1).extend(1.0);
+    spawn_module(
+        commands,
+        structure_entity,
+        structure,
+        materials,
+        game_assets,
+        blueprint.module_type,
+        blueprint.color,
+        cell,
+        Orientation::from_quarter_turns(blueprint.rotation),
+        Footprint::SINGLE,
+        translation,
+        MODULE_MESH_SCALE_FACTOR,
+        false,
+        blueprint.material_type,
+    )
+}
+
+/// Removes the module in a cell of a structure, returning its blueprint.
+/// The caller is responsible for segmenting the structure rooms again.
+pub fn remove_module(
+    commands: &mut Commands,
+    structure: &mut Structure,
+    modules_query: &Query<(&Module, &ModuleMaterial, &Handle<ColorMaterial>, &Transform, Option<&Paint>)>,
+    materials: &Assets<ColorMaterial>,
+    cell: (i32, i32),
+) -> Option<ModuleBlueprint> {
+    let module_entity = structure.module_at(cell)?;
+    let (module, module_material, material_handle, transform, paint) = modules_query.get(module_entity).ok()?;
+    let blueprint = ModuleBlueprint::from_module(module, module_material, material_handle, transform, paint, materials);
+
+    structure.remove_module_at(cell);
+    commands.entity(module_entity).despawn_recursive();
+    Some(blueprint)
+}
@@ -0,0 +1,249 @@
+use crate::core::prelude::*;
+use crate::gameplay::debris::Debris;
+use crate::world::prelude::*;
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const HOLD_CAPACITY: f32 = 200.0; // units of cargo per cargo hold
+const PLAYER_CARRY_CAPACITY: f32 = 50.0;
+const COLLECT_MARGIN: f32 = STRUCTURE_CELL_SIZE; // debris and ore this close to the hull are taken in, in meters
+const ORE_MINING_RATE: f32 = 5.0; // units/s pulled from an ore node in reach
+
+/// Cargo holds storing the ore mined and the debris salvaged by the structure flown by the player. The player can
+/// also carry cargo by hand from the holds of a structure to the holds of another one.
+pub struct CargoPlugin;
+
+impl Plugin for CargoPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CargoTransferEvent>()
+            .add_systems(
+                FixedUpdate,
+                (update_cargo_capacity_system, salvage_debris_system, mine_ore_system)
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(Update, carry_cargo_system.run_if(in_state(GameState::InGame)));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CargoKind {
+    Ore,
+    Salvage,
+}
+
+impl fmt::Display for CargoKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CargoKind::Ore => write!(f, "ore"),
+            CargoKind::Salvage => write!(f, "salvage"),
+        }
+    }
+}
+
+/// Cargo of a structure, shared by all its cargo holds. Losing holds loses the cargo that no longer fits.
+#[derive(Component, Debug, Default, Clone, PartialEq)]
+pub struct CargoStorage {
+    pub ore: f32,
+    pub salvage: f32,
+    pub capacity: f32,
+}
+
+impl CargoStorage {
+    pub fn amount(&self, kind: CargoKind) -> f32 {
+        match kind {
+            CargoKind::Ore => self.ore,
+            CargoKind::Salvage => self.salvage,
+        }
+    }
+
+    fn amount_mut(&mut self, kind: CargoKind) -> &mut f32 {
+        match kind {
+            CargoKind::Ore => &mut self.ore,
+            CargoKind::Salvage => &mut self.salvage,
+        }
+    }
+
+    pub fn total(&self) -> f32 {
+        self.ore + self.salvage
+    }
+
+    pub fn free_space(&self) -> f32 {
+        (self.capacity - self.total()).max(0.0)
+    }
+
+    /// Stores as much as fits, returns the amount stored.
+    pub fn store(&mut self, kind: CargoKind, amount: f32) -> f32 {
+        let stored = amount.min(self.free_space());
+        *self.amount_mut(kind) += stored;
+        stored
+    }
+
+    /// Takes up to `amount`, returns the amount taken.
+    pub fn take(&mut self, kind: CargoKind, amount: f32) -> f32 {
+        let stock = self.amount_mut(kind);
+        let taken = amount.min(*stock);
+        *stock -= taken;
+        taken
+    }
+
+    /// Drops the cargo over the capacity, salvage first.
+    fn spill(&mut self) {
+        let excess = self.total() - self.capacity;
+        if excess > 0.0 {
+            let spilled = self.take(CargoKind::Salvage, excess);
+            self.take(CargoKind::Ore, excess - spilled);
+        }
+    }
+}
+
+/// Cargo the player carries by hand between the holds.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct CarriedCargo {
+    pub kind: CargoKind,
+    pub amount: f32,
+}
+
+/// Sent when cargo gets in or out of the holds of a structure, to tell the player.
+#[derive(Event, Debug)]
+pub struct CargoTransferEvent {
+    pub structure_entity: Entity,
+    pub kind: CargoKind,
+    pub amount: f32,
+    pub direction: CargoTransferDirection,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CargoTransferDirection {
+    /// Taken from the holds by the player.
+    PickedUp,
+    /// Put in the holds by the player.
+    Dropped,
+    /// Salvaged or mined by the structure.
+    Collected,
+}
+
+/// Sizes the storage of every structure to its cargo holds.
+fn update_cargo_capacity_system(
+    mut structures_query: Query<(Entity, &Children, Option<&mut CargoStorage>), With<Structure>>,
+    holds_query: Query<(), With<CargoHoldModule>>,
+    mut commands: Commands,
+) {
+    for (structure_entity, children, storage) in &mut structures_query {
+        let holds = children.iter().filter(|child| holds_query.contains(**child)).count();
+        let capacity = holds as f32 * HOLD_CAPACITY;
+        match storage {
+            Some(mut storage) if storage.capacity != capacity => {
+                storage.capacity = capacity;
+                storage.spill();
+            }
+            None if holds > 0 => {
+                commands.entity(structure_entity).insert(CargoStorage { capacity, ..default() });
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Distance from the center of a structure under which something touches its hull.
+fn collect_radius(structure: &Structure) -> f32 {
+    structure.grid.width.max(structure.grid.height) as f32 * structure.grid.cell_size / 2.0 + COLLECT_MARGIN
+}
+
+/// The structure flown by the player scoops the debris it touches into its holds.
+fn salvage_debris_system(
+    mut controlled_query: Query<(Entity, &Position, &Structure, &mut CargoStorage), With<ControlledByPlayer>>,
+    debris_query: Query<(Entity, &Position, &Debris)>,
+    mut transfer_writer: EventWriter<CargoTransferEvent>,
+    mut commands: Commands,
+) {
+    for (structure_entity, structure_position, structure, mut storage) in &mut controlled_query {
+        let radius = collect_radius(structure);
+        for (debris_entity, debris_position, debris) in &debris_query {
+            if storage.free_space() <= 0.0 || structure_position.0.distance(debris_position.0) > radius {
+                continue;
+            }
+            let amount = storage.store(CargoKind::Salvage, debris.salvage_value);
+            commands.entity(debris_entity).despawn_recursive();
+            transfer_writer.send(CargoTransferEvent {
+                structure_entity,
+                kind: CargoKind::Salvage,
+                amount,
+                direction: CargoTransferDirection::Collected,
+            });
+        }
+    }
+}
+
+/// The structure flown by the player mines the ore nodes it touches.
+fn mine_ore_system(
+    mut controlled_query: Query<(&Position, &Structure, &mut CargoStorage), With<ControlledByPlayer>>,
+    ore_query: Query<&GlobalTransform, With<Ore>>,
+    time: Res<Time>,
+) {
+    for (structure_position, structure, mut storage) in &mut controlled_query {
+        let radius = collect_radius(structure);
+        let nodes_in_reach = ore_query
+            .iter()
+            .filter(|ore_transform| structure_position.0.distance(ore_transform.translation().truncate()) <= radius)
+            .count();
+        storage.store(CargoKind::Ore, nodes_in_reach as f32 * ORE_MINING_RATE * time.delta_seconds());
+    }
+}
+
+/// Space on a cargo hold picks up cargo from the holds, or drops the carried cargo in them.
+fn carry_cargo_system(
+    mut input_reader: EventReader<InputAction>,
+    player_query: Query<(Entity, &GlobalTransform, Option<&CarriedCargo>), With<Player>>,
+    player_resource: Res<PlayerResource>,
+    mut structures_query: Query<(&Transform, &Structure, &mut CargoStorage)>,
+    holds_query: Query<(&Module, &Interactable), With<CargoHoldModule>>,
+    mut transfer_writer: EventWriter<CargoTransferEvent>,
+    mut commands: Commands,
+) {
+    let space_pressed = input_reader.read().any(|event| matches!(event, InputAction::SpacePressed));
+    if !space_pressed || player_resource.is_controlling_structure {
+        return;
+    }
+    let (Ok((player_entity, player_transform, carried)), Some(structure_entity)) =
+        (player_query.get_single(), player_resource.inside_structure)
+    else {
+        return;
+    };
+    let Ok((structure_transform, structure, mut storage)) = structures_query.get_mut(structure_entity) else {
+        return;
+    };
+
+    let player_cell = structure.world_to_grid(player_transform.translation(), structure_transform);
+    let on_hold = structure
+        .module_at(player_cell)
+        .and_then(|module_entity| holds_query.get(module_entity).ok())
+        .is_some_and(|(module, interactable)| interactable.is_reachable_from(module, player_cell));
+    if !on_hold {
+        return;
+    }
+
+    let (kind, amount, direction) = if let Some(carried) = carried {
+        let dropped = storage.store(carried.kind, carried.amount);
+        if dropped >= carried.amount {
+            commands.entity(player_entity).remove::<CarriedCargo>();
+        } else {
+            commands.entity(player_entity).insert(CarriedCargo { amount: carried.amount - dropped, ..*carried });
+        }
+        (carried.kind, dropped, CargoTransferDirection::Dropped)
+    } else {
+        let kind = if storage.ore >= storage.salvage { CargoKind::Ore } else { CargoKind::Salvage };
+        let picked_up = storage.take(kind, PLAYER_CARRY_CAPACITY);
+        if picked_up <= 0.0 {
+            return;
+        }
+        commands.entity(player_entity).insert(CarriedCargo { kind, amount: picked_up });
+        (kind, picked_up, CargoTransferDirection::PickedUp)
+    };
+
+    debug!("Cargo {:?}: {} {}", direction, amount, kind);
+    transfer_writer.send(CargoTransferEvent { structure_entity, kind, amount, direction });
+}
@@ -0,0 +1,290 @@
+use crate::core::prelude::*;
+use crate::gameplay::building::*;
+use crate::gameplay::livery::Paint;
+use crate::gameplay::repair::Scrap;
+use crate::world::prelude::*;
+
+use crate::prelude::*;
+
+const SELECTION_COLOR: Color = Color::srgb(0.3, 0.7, 1.0);
+
+/// Copy, cut and paste of rectangular regions of modules while in build mode.
+/// Shift + drag selects a region, Ctrl+C copies it, Ctrl+X cuts it and Ctrl+V pastes it at the hovered cell,
+/// rotated by the build mode rotation (Q/E) and mirrored with F.
+pub struct ClipboardPlugin;
+
+impl Plugin for ClipboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Selection>().init_resource::<Clipboard>().add_systems(
+            Update,
+            (marquee_selection_system, copy_cut_system, mirror_clipboard_system, paste_system, draw_selection_system)
+                .chain()
+                .after(update_placement_target_system)
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+/// Rectangular region of cells selected in a structure, corners included.
+#[derive(Resource, Debug, Default)]
+pub struct Selection {
+    pub structure_entity: Option<Entity>,
+    pub start: (i32, i32),
+    pub end: (i32, i32),
+    pub dragging: bool,
+}
+
+impl Selection {
+    pub fn min(&self) -> (i32, i32) {
+        (self.start.0.min(self.end.0), self.start.1.min(self.end.1))
+    }
+
+    pub fn max(&self) -> (i32, i32) {
+        (self.start.0.max(self.end.0), self.start.1.max(self.end.1))
+    }
+
+    pub fn contains(&self, cell: (i32, i32)) -> bool {
+        let (min, max) = (self.min(), self.max());
+        cell.0 >= min.0 && cell.0 <= max.0 && cell.1 >= min.1 && cell.1 <= max.1
+    }
+}
+
+/// Copied modules, with their cell relative to the top left corner of the copied region.
+#[derive(Resource, Debug, Default)]
+pub struct Clipboard {
+    pub modules: Vec<((i32, i32), ModuleBlueprint)>,
+    pub mirrored: bool,
+}
+
+impl Clipboard {
+    /// Cells and blueprints of the clipboard once rotated and mirrored, relative to the paste cell.
+    pub fn transformed(&self, rotation: u8) -> Vec<((i32, i32), ModuleBlueprint)> {
+        self.modules
+            .iter()
+            .map(|((x, y), blueprint)| {
+                let mut blueprint = blueprint.clone();
+                let (mut x, mut y) = (*x, *y);
+                if self.mirrored {
+                    x = -x;
+                    blueprint.rotation = (4 - blueprint.rotation) % 4;
+                }
+                // Grid rows grow downwards, so a counterclockwise turn maps (x, y) to (y, -x)
+                for _ in 0..rotation % 4 {
+                    (x, y) = (y, -x);
+                }
+                blueprint.rotation = (blueprint.rotation + rotation) % 4;
+                ((x, y), blueprint)
+            })
+            .collect()
+    }
+}
+
+fn ctrl_pressed(keys: &ButtonInput<KeyCode>) -> bool {
+    keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+}
+
+fn marquee_selection_system(
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    build_mode: Res<BuildMode>,
+    mut selection: ResMut<Selection>,
+) {
+    if !build_mode.active {
+        selection.structure_entity = None;
+        return;
+    }
+    let Some(target) = &build_mode.target else {
+        return;
+    };
+
+    let shift_pressed = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if shift_pressed && mouse_buttons.just_pressed(MouseButton::Left) {
+        *selection = Selection {
+            structure_entity: Some(target.structure_entity),
+            start: target.cell,
+            end: target.cell,
+            dragging: true,
+        };
+    } else if selection.dragging && selection.structure_entity == Some(target.structure_entity) {
+        selection.end = target.cell;
+    }
+
+    if mouse_buttons.just_released(MouseButton::Left) {
+        selection.dragging = false;
+    }
+}
+
+fn copy_cut_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    build_mode: Res<BuildMode>,
+    selection: Res<Selection>,
+    mut clipboard: ResMut<Clipboard>,
+    mut history: ResMut<BuildHistory>,
+    mut structures_query: Query<(&mut Structure, &mut Pressurization, &Children)>,
+    modules_query: Query<(&Module, &ModuleMaterial, &Handle<ColorMaterial>, &Transform, Option<&Paint>)>,
+    walkable_modules_query: Query<(), Or<(With<CommandCenterModule>, With<MedicalBayModule>)>>,
+    materials: Res<Assets<ColorMaterial>>,
+    mut commands: Commands,
+) {
+    if !build_mode.active || !ctrl_pressed(&keys) {
+        return;
+    }
+    let cut = keys.just_pressed(KeyCode::KeyX);
+    if !cut && !keys.just_pressed(KeyCode::KeyC) {
+        return;
+    }
+    let Some(structure_entity) = selection.structure_entity else {
+        return;
+    };
+    let Ok((mut structure, mut pressurization, children)) = structures_query.get_mut(structure_entity) else {
+        return;
+    };
+
+    // The command center and other walkable modules are part of the structure identity, they are not copied, nor are
+    // the modules covering several cells
+    let selected_cells: Vec<(i32, i32)> = children
+        .iter()
+        .filter(|child| !walkable_modules_query.contains(**child))
+        .filter_map(|child| modules_query.get(*child).ok())
+        .filter(|(module, ..)| selection.contains(module.inner_grid_pos))
+        .filter(|(module, ..)| module.footprint.is_single())
+        .map(|(module, ..)| module.inner_grid_pos)
+        .collect();
+    if selected_cells.is_empty() {
+        return;
+    }
+
+    let origin = selection.min();
+    let relative = |cell: (i32, i32)| (cell.0 - origin.0, cell.1 - origin.1);
+
+    if cut {
+        let removed: Vec<((i32, i32), ModuleBlueprint)> = selected_cells
+            .into_iter()
+            .filter_map(|cell| {
+                remove_module(&mut commands, &mut structure, &modules_query, &materials, cell)
+                    .map(|blueprint| (cell, blueprint))
+            })
+            .collect();
+        let rooms = structure.check_pressurization();
+        pressurization.update_rooms(rooms);
+
+        clipboard.modules = removed.iter().map(|(cell, blueprint)| (relative(*cell), blueprint.clone())).collect();
+        history.push(BuildAction::Removed { structure_entity, modules: removed });
+    } else {
+        clipboard.modules = modules_query
+            .iter_many(children)
+            .filter(|(module, ..)| selected_cells.contains(&module.inner_grid_pos))
+            .map(|(module, module_material, material_handle, transform, paint)| {
+                let blueprint = ModuleBlueprint::from_module(
+                    module,
+                    module_material,
+                    material_handle,
+                    transform,
+                    paint,
+                    &materials,
+                );
+                (relative(module.inner_grid_pos), blueprint)
+            })
+            .collect();
+    }
+    clipboard.mirrored = false;
+    debug!("{} {} modules", if cut { "Cut" } else { "Copied" }, clipboard.modules.len());
+}
+
+fn mirror_clipboard_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    build_mode: Res<BuildMode>,
+    mut clipboard: ResMut<Clipboard>,
+) {
+    if build_mode.active && keys.just_pressed(KeyCode::KeyF) {
+        clipboard.mirrored = !clipboard.mirrored;
+    }
+}
+
+/// Pastes the clipboard with its top left corner on the hovered cell, in the structure the player is in.
+/// Nothing is pasted if a single module would not fit.
+fn paste_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    build_mode: Res<BuildMode>,
+    clipboard: Res<Clipboard>,
+    mut history: ResMut<BuildHistory>,
+    mut structures_query: Query<(&mut Structure, &mut Pressurization)>,
+    mut scrap: ResMut<Scrap>,
+    mut commands: Commands,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    game_assets: Res<GameAssets>,
+) {
+    if !build_mode.active || !ctrl_pressed(&keys) || !keys.just_pressed(KeyCode::KeyV) {
+        return;
+    }
+    if clipboard.modules.is_empty() {
+        return;
+    }
+    let Some(target) = &build_mode.target else {
+        return;
+    };
+    let Ok((mut structure, mut pressurization)) = structures_query.get_mut(target.structure_entity) else {
+        return;
+    };
+
+    let modules: Vec<((i32, i32), ModuleBlueprint)> = clipboard
+        .transformed(build_mode.rotation)
+        .into_iter()
+        .map(|((x, y), blueprint)| ((target.cell.0 + x, target.cell.1 + y), blueprint))
+        .collect();
+
+    let fits = modules.iter().all(|((x, y), _)| {
+        structure.is_within_grid_bounds(*x, *y)
+            && structure.grid.get(*x, *y).is_none_or(|grid_cell| grid_cell.cell_type != CellType::Module)
+    });
+    if !fits {
+        debug!("Clipboard does not fit at {:?}", target.cell);
+        return;
+    }
+    let cost = modules.len() as f32 * BUILD_SCRAP_COST;
+    if scrap.amount < cost {
+        debug!("Not enough scrap to paste {} modules", modules.len());
+        return;
+    }
+
+    for (cell, blueprint) in &modules {
+        build_module(
+            &mut commands,
+            target.structure_entity,
+            &mut structure,
+            &mut materials,
+            &game_assets,
+            *cell,
+            blueprint,
+        );
+    }
+    scrap.amount -= cost;
+    history.push(BuildAction::Placed {
+        structure_entity: target.structure_entity,
+        cells: modules.into_iter().map(|(cell, _)| cell).collect(),
+    });
+
+    let rooms = structure.check_pressurization();
+    pressurization.update_rooms(rooms);
+}
+
+fn draw_selection_system(
+    mut gizmos: Gizmos,
+    selection: Res<Selection>,
+    structures_query: Query<(&Structure, &Transform)>,
+) {
+    let Some(structure_entity) = selection.structure_entity else {
+        return;
+    };
+    let Ok((structure, structure_transform)) = structures_query.get(structure_entity) else {
+        return;
+    };
+
+    let (min, max) = (selection.min(), selection.max());
+    let top_left = structure.grid_cell_center_world_position(min.0, min.1, structure_transform);
+    let bottom_right = structure.grid_cell_center_world_position(max.0, max.1, structure_transform);
+    let cells = Vec2::new((max.0 - min.0 + 1) as f32, (max.1 - min.1 + 1) as f32);
+    let rotation = structure_transform.rotation.to_euler(EulerRot::XYZ).2;
+
+    gizmos.rect_2d((top_left + bottom_right) / 2.0, rotation, cells * structure.grid.cell_size, SELECTION_COLOR);
+}
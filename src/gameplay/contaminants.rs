@@ -0,0 +1,275 @@
+use crate::core::prelude::*;
+use crate::gameplay::crew::Crew;
+use crate::gameplay::degradation::{performance, ModulePerformance};
+use crate::gameplay::life_support::LifeSupportModule;
+use crate::world::prelude::*;
+
+use bevy::color::palettes::css::LIGHT_STEEL_BLUE;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+const FIRE_DURATION: f32 = 15.0; // seconds a critical hit sets a module on fire
+const FIRE_SMOKE_RATE: f32 = 0.2; // smoke/s released in each cell next to a burning module
+const COOLANT_LEAK_HEALTH: f32 = 0.5; // health ratio under which engines and reactors leak coolant
+const COOLANT_LEAK_RATE: f32 = 0.1; // coolant/s released next to a wrecked engine or reactor
+const DIFFUSION_RATE: f32 = 0.5; // fraction of the difference with each neighbour cell exchanged per second
+const VENT_RATE: f32 = 1.0; // contaminant/s blown out of a cell exposed to space
+const LIFE_SUPPORT_FILTRATION_RATE: f32 = 0.005; // contaminant/s scrubbed from every sealed cell by each life support
+const AIR_FILTER_FILTRATION_RATE: f32 = 0.03; // contaminant/s scrubbed from every sealed cell by each air filter
+const MORALE_CONTAMINATION_DRAIN: f32 = 0.05; // morale/s with every cell fully contaminated
+const CLEAN_LEVEL: f32 = 0.001; // below this a cell is considered clean
+
+/// Contaminants filling the rooms of the structures: smoke from the modules set on fire by critical hits and
+/// coolant leaking from damaged engines and reactors. They spread between neighbour cells, are blown out of the
+/// rooms open to space and scrubbed from the sealed ones by the life support and air filter modules. Breathing them
+/// wears the crew down and smoke around the player blurs the view.
+pub struct ContaminantsPlugin;
+
+impl Plugin for ContaminantsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (attach_contamination_system, ignite_fires_system, contaminated_crew_system)
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        )
+        .add_systems(
+            FixedUpdate,
+            (emit_contaminants_system, spread_contaminants_system).chain().run_if(in_state(GameState::InGame)),
+        )
+        .register_module_type::<AirFilterModule>(
+            ModuleDefinition::new("Air filter", 'S').with_color(Color::from(LIGHT_STEEL_BLUE)),
+        );
+    }
+}
+
+/// Module scrubbing the contaminants out of the sealed rooms of its structure, much faster than the life support.
+#[derive(Component, Debug, Default)]
+pub struct AirFilterModule;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Contaminant {
+    Smoke,
+    Coolant,
+}
+
+/// Contaminants in a cell, each from 0.0 (clean) to 1.0 (saturated).
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ContaminantLevels {
+    pub smoke: f32,
+    pub coolant: f32,
+}
+
+impl ContaminantLevels {
+    pub fn get(&self, contaminant: Contaminant) -> f32 {
+        match contaminant {
+            Contaminant::Smoke => self.smoke,
+            Contaminant::Coolant => self.coolant,
+        }
+    }
+
+    fn get_mut(&mut self, contaminant: Contaminant) -> &mut f32 {
+        match contaminant {
+            Contaminant::Smoke => &mut self.smoke,
+            Contaminant::Coolant => &mut self.coolant,
+        }
+    }
+
+    /// How unbreathable the cell is, from 0.0 to 1.0.
+    pub fn total(&self) -> f32 {
+        (self.smoke + self.coolant).min(1.0)
+    }
+
+    fn is_clean(&self) -> bool {
+        self.smoke < CLEAN_LEVEL && self.coolant < CLEAN_LEVEL
+    }
+}
+
+/// Contaminants of every non module cell of a structure, the clean cells are left out.
+#[derive(Component, Debug, Default)]
+pub struct Contamination {
+    pub cells: HashMap<(i32, i32), ContaminantLevels>,
+}
+
+impl Contamination {
+    pub fn levels_at(&self, cell: (i32, i32)) -> ContaminantLevels {
+        self.cells.get(&cell).copied().unwrap_or_default()
+    }
+
+    /// Average of a contaminant over the given number of cells.
+    pub fn average(&self, contaminant: Contaminant, cells: usize) -> f32 {
+        if cells == 0 {
+            return 0.0;
+        }
+        self.cells.values().map(|levels| levels.get(contaminant)).sum::<f32>() / cells as f32
+    }
+
+    fn release(&mut self, cell: (i32, i32), contaminant: Contaminant, amount: f32) {
+        let level = self.cells.entry(cell).or_default().get_mut(contaminant);
+        *level = (*level + amount).min(1.0);
+    }
+}
+
+/// Module burning after a critical hit, releasing smoke around it until it burns out or runs out of air.
+#[derive(Component, Debug)]
+pub struct OnFire {
+    pub remaining: f32,
+}
+
+const NEIGHBOUR_OFFSETS: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+/// Non module cells next to a cell.
+fn open_neighbours(structure: &Structure, (x, y): (i32, i32)) -> impl Iterator<Item = (i32, i32)> + '_ {
+    NEIGHBOUR_OFFSETS
+        .iter()
+        .map(move |(dx, dy)| (x + dx, y + dy))
+        .filter(|neighbour| structure.grid.cells.get(neighbour).is_some_and(|cell| cell.cell_type != CellType::Module))
+}
+
+fn attach_contamination_system(
+    structures_query: Query<Entity, (Added<Pressurization>, Without<Contamination>)>,
+    mut commands: Commands,
+) {
+    for structure_entity in &structures_query {
+        commands.entity(structure_entity).insert(Contamination::default());
+    }
+}
+
+fn ignite_fires_system(
+    mut damage_reader: EventReader<ModuleTookDamageEvent>,
+    mut fires_query: Query<&mut OnFire>,
+    mut commands: Commands,
+) {
+    for event in damage_reader.read() {
+        if !event.critical || event.remaining_points <= 0.0 {
+            continue;
+        }
+        match fires_query.get_mut(event.module_entity) {
+            Ok(mut fire) => fire.remaining = FIRE_DURATION,
+            Err(_) => {
+                if let Some(mut module_commands) = commands.get_entity(event.module_entity) {
+                    debug!("Module {:?} caught fire", event.module_entity);
+                    module_commands.insert(OnFire { remaining: FIRE_DURATION });
+                }
+            }
+        }
+    }
+}
+
+/// Burning modules release smoke and damaged engines and reactors leak coolant into the cells around them.
+fn emit_contaminants_system(
+    mut structures_query: Query<(&mut Contamination, &Pressurization, &Structure, &Children)>,
+    mut fires_query: Query<(Entity, &Module, &mut OnFire)>,
+    leaking_query: Query<(&Module, &ModuleMaterial), Or<(With<EngineModule>, With<ReactorModule>)>>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    let delta_time = time.delta_seconds();
+
+    for (mut contamination, pressurization, structure, children) in &mut structures_query {
+        let mut fires = fires_query.iter_many_mut(children);
+        while let Some((module_entity, module, mut fire)) = fires.fetch_next() {
+            fire.remaining -= delta_time;
+            // Fire needs air, venting the room puts it out
+            let smothered =
+                !open_neighbours(structure, module.inner_grid_pos).any(|cell| pressurization.is_breathable(cell));
+            if fire.remaining <= 0.0 || smothered {
+                debug!("Fire of module {:?} is out", module_entity);
+                commands.entity(module_entity).remove::<OnFire>();
+                continue;
+            }
+            for cell in open_neighbours(structure, module.inner_grid_pos) {
+                contamination.release(cell, Contaminant::Smoke, FIRE_SMOKE_RATE * delta_time);
+            }
+        }
+
+        for (module, module_material) in leaking_query.iter_many(children) {
+            let health = module_material.health_ratio();
+            if health >= COOLANT_LEAK_HEALTH {
+                continue;
+            }
+            let leak = COOLANT_LEAK_RATE * (1.0 - health / COOLANT_LEAK_HEALTH) * delta_time;
+            for cell in open_neighbours(structure, module.inner_grid_pos) {
+                contamination.release(cell, Contaminant::Coolant, leak);
+            }
+        }
+    }
+}
+
+/// Spreads the contaminants between neighbour cells, vents them out of the exposed rooms and scrubs the sealed ones.
+fn spread_contaminants_system(
+    mut structures_query: Query<(&mut Contamination, &Pressurization, &Structure, Option<&Children>)>,
+    filters_query: Query<
+        (Option<&ModulePerformance>, Has<AirFilterModule>),
+        Or<(With<AirFilterModule>, With<LifeSupportModule>)>,
+    >,
+    time: Res<Time>,
+) {
+    let delta_time = time.delta_seconds();
+
+    for (mut contamination, pressurization, structure, children) in &mut structures_query {
+        if contamination.cells.is_empty() {
+            continue;
+        }
+        let filtration_rate: f32 = children
+            .map(|children| {
+                filters_query
+                    .iter_many(children)
+                    .map(|(module_performance, is_air_filter)| {
+                        let rate =
+                            if is_air_filter { AIR_FILTER_FILTRATION_RATE } else { LIFE_SUPPORT_FILTRATION_RATE };
+                        rate * performance(module_performance)
+                    })
+                    .sum()
+            })
+            .unwrap_or(0.0);
+
+        // Every contaminated cell and its neighbours can change
+        let mut cells: Vec<(i32, i32)> = contamination.cells.keys().copied().collect();
+        cells.extend(cells.clone().into_iter().flat_map(|cell| open_neighbours(structure, cell)));
+        cells.sort_unstable();
+        cells.dedup();
+
+        let mut spread = HashMap::with_capacity(cells.len());
+        for cell in cells {
+            if structure.grid.cells.get(&cell).is_none_or(|grid_cell| grid_cell.cell_type == CellType::Module) {
+                continue;
+            }
+
+            let current = contamination.levels_at(cell);
+            let mut levels = current;
+            for neighbour in open_neighbours(structure, cell) {
+                let neighbour_levels = contamination.levels_at(neighbour);
+                levels.smoke += (neighbour_levels.smoke - current.smoke) * DIFFUSION_RATE * delta_time;
+                levels.coolant += (neighbour_levels.coolant - current.coolant) * DIFFUSION_RATE * delta_time;
+            }
+
+            let clearing = if pressurization.is_exposed(cell) { VENT_RATE } else { filtration_rate } * delta_time;
+            levels.smoke = (levels.smoke - clearing).clamp(0.0, 1.0);
+            levels.coolant = (levels.coolant - clearing).clamp(0.0, 1.0);
+            if !levels.is_clean() {
+                spread.insert(cell, levels);
+            }
+        }
+        contamination.cells = spread;
+    }
+}
+
+/// Crews breathing contaminated air lose their morale.
+fn contaminated_crew_system(
+    mut structures_query: Query<(&mut Crew, &Contamination, &Pressurization)>,
+    time: Res<Time>,
+) {
+    let delta_time = time.delta_seconds();
+
+    for (mut crew, contamination, pressurization) in &mut structures_query {
+        if crew.members == 0 || contamination.cells.is_empty() {
+            continue;
+        }
+        let cells = pressurization.oxygen.len();
+        let contaminated = (contamination.average(Contaminant::Smoke, cells)
+            + contamination.average(Contaminant::Coolant, cells))
+        .min(1.0);
+        crew.morale = (crew.morale - MORALE_CONTAMINATION_DRAIN * contaminated * delta_time).clamp(0.0, 1.0);
+    }
+}
@@ -0,0 +1,191 @@
+use crate::core::asset_loader::DataAssetLoader;
+use crate::core::prelude::*;
+use crate::gameplay::cargo::{CargoKind, CargoStorage};
+use crate::gameplay::degradation::{performance, ModulePerformance};
+use crate::gameplay::repair::Scrap;
+use crate::world::prelude::*;
+
+use crate::prelude::*;
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+
+pub const RECIPES_PATH: &str = "data/recipes.ron";
+
+/// Refineries turning the ore and salvage in the cargo holds of their structure into module materials and scrap,
+/// following the recipes of `data/recipes.ron`. The materials are used to rebuild destroyed modules.
+pub struct CraftingPlugin;
+
+impl Plugin for CraftingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Recipes>()
+            .init_resource::<MaterialStock>()
+            .init_asset::<Recipes>()
+            .init_asset_loader::<DataAssetLoader<Recipes>>()
+            .add_event::<ItemCraftedEvent>()
+            .add_systems(Startup, load_recipes)
+            .add_systems(
+                Update,
+                (apply_recipes_system, attach_refinery_system, refine_system)
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+/// What a recipe makes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CraftedItem {
+    Scrap,
+    Material(ModuleMaterialType),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recipe {
+    pub name: String,
+    /// Cargo consumed when a refinery starts the recipe.
+    pub inputs: Vec<(CargoKind, f32)>,
+    pub output: CraftedItem,
+    pub quantity: u32,
+    /// Seconds of work of a refinery in good condition.
+    pub duration: f32,
+}
+
+impl Recipe {
+    fn can_start(&self, storage: &CargoStorage) -> bool {
+        self.inputs.iter().all(|(kind, amount)| storage.amount(*kind) >= *amount)
+    }
+}
+
+#[derive(Asset, Resource, TypePath, Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Recipes {
+    pub recipes: Vec<Recipe>,
+}
+
+/// Module materials made by the refineries, waiting to be used.
+#[derive(Resource, Debug, Default)]
+pub struct MaterialStock(pub HashMap<ModuleMaterialType, u32>);
+
+impl MaterialStock {
+    pub fn amount(&self, material_type: ModuleMaterialType) -> u32 {
+        self.0.get(&material_type).copied().unwrap_or(0)
+    }
+
+    pub fn add(&mut self, material_type: ModuleMaterialType, quantity: u32) {
+        *self.0.entry(material_type).or_default() += quantity;
+    }
+
+    /// Takes one unit of the material, returns whether there was one.
+    pub fn take(&mut self, material_type: ModuleMaterialType) -> bool {
+        match self.0.get_mut(&material_type) {
+            Some(quantity) if *quantity > 0 => {
+                *quantity -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Work of a refinery module.
+#[derive(Component, Debug, Default)]
+pub struct Refinery {
+    /// Index of the recipe being processed, its inputs are already taken from the holds.
+    pub recipe: Option<usize>,
+    /// Seconds of work done on the recipe.
+    pub progress: f32,
+}
+
+#[derive(Event, Debug)]
+pub struct ItemCraftedEvent {
+    pub structure_entity: Entity,
+    pub recipe: String,
+    pub item: CraftedItem,
+    pub quantity: u32,
+}
+
+#[derive(Resource)]
+struct RecipesHandle(Handle<Recipes>);
+
+fn load_recipes(asset_server: Res<AssetServer>, mut commands: Commands) {
+    commands.insert_resource(RecipesHandle(asset_server.load(RECIPES_PATH)));
+}
+
+/// Replaces the recipes in use whenever the data file is (re)loaded.
+fn apply_recipes_system(
+    mut asset_events: EventReader<AssetEvent<Recipes>>,
+    handle: Res<RecipesHandle>,
+    recipes_assets: Res<Assets<Recipes>>,
+    mut recipes: ResMut<Recipes>,
+) {
+    if !asset_events.read().any(|event| event.is_loaded_with_dependencies(&handle.0)) {
+        return;
+    }
+    if let Some(loaded_recipes) = recipes_assets.get(&handle.0) {
+        *recipes = loaded_recipes.clone();
+        info!("{} recipes loaded", recipes.recipes.len());
+    }
+}
+
+fn attach_refinery_system(refineries_query: Query<Entity, Added<RefineryModule>>, mut commands: Commands) {
+    for refinery_entity in &refineries_query {
+        commands.entity(refinery_entity).insert(Refinery::default());
+    }
+}
+
+/// Idle refineries start the first recipe the cargo of their structure allows, busy ones work on theirs at the
+/// pace of their condition.
+fn refine_system(
+    mut refineries_query: Query<(&mut Refinery, &Parent, Option<&ModulePerformance>)>,
+    mut storages_query: Query<&mut CargoStorage>,
+    recipes: Res<Recipes>,
+    mut material_stock: ResMut<MaterialStock>,
+    mut scrap: ResMut<Scrap>,
+    time: Res<Time>,
+    mut crafted_writer: EventWriter<ItemCraftedEvent>,
+) {
+    for (mut refinery, parent, module_performance) in &mut refineries_query {
+        let structure_entity = parent.get();
+
+        let Some(recipe_index) = refinery.recipe else {
+            let Ok(mut storage) = storages_query.get_mut(structure_entity) else {
+                continue;
+            };
+            let Some(recipe_index) = recipes.recipes.iter().position(|recipe| recipe.can_start(&storage)) else {
+                continue;
+            };
+            for (kind, amount) in &recipes.recipes[recipe_index].inputs {
+                storage.take(*kind, *amount);
+            }
+            refinery.recipe = Some(recipe_index);
+            refinery.progress = 0.0;
+            continue;
+        };
+
+        // The recipes were reloaded under the refinery, what it took is lost
+        let Some(recipe) = recipes.recipes.get(recipe_index) else {
+            refinery.recipe = None;
+            continue;
+        };
+
+        refinery.progress += time.delta_seconds() * performance(module_performance);
+        if refinery.progress < recipe.duration {
+            continue;
+        }
+
+        match recipe.output {
+            CraftedItem::Scrap => scrap.amount += recipe.quantity as f32,
+            CraftedItem::Material(material_type) => material_stock.add(material_type, recipe.quantity),
+        }
+        refinery.recipe = None;
+        refinery.progress = 0.0;
+
+        debug!("Refinery crafted {} x{}", recipe.name, recipe.quantity);
+        crafted_writer.send(ItemCraftedEvent {
+            structure_entity,
+            recipe: recipe.name.clone(),
+            item: recipe.output,
+            quantity: recipe.quantity,
+        });
+    }
+}
@@ -0,0 +1,322 @@
+use crate::core::prelude::*;
+use crate::gameplay::doors::{Airlock, Door};
+use crate::gameplay::medical::{Injury, InjuryCause, InjuryEvent};
+use crate::world::prelude::*;
+
+use bevy::prelude::*;
+use std::collections::{HashSet, VecDeque};
+
+const CREW_PER_QUARTERS: u32 = 4;
+const MORALE_OVERCROWDING_DRAIN: f32 = 0.05; // morale/s at 100% overcrowding
+const MORALE_RECOVERY: f32 = 0.01; // morale/s
+const CREW_WALK_SPEED: f32 = 1.5; // cells/s
+const CREW_MEMBER_SIZE: f32 = 0.3; // fraction of a cell
+const CREW_MEMBER_COLOR: Color = Color::srgb(0.95, 0.8, 0.55);
+const CREW_MEMBER_Z: f32 = 2.0; // above the modules
+
+/// Crew of the structures: their capacity and morale, and the crew members walking inside them to man the Command
+/// Center and the cannons. Critical hits on a station hurt the crew member manning it, who walks to the nearest medical
+/// bay until healed. Crew members caught in a room open to space die.
+pub struct CrewPlugin;
+
+impl Plugin for CrewPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CrewDiedEvent>().add_systems(
+            Update,
+            (
+                update_crew_capacity_system,
+                crew_morale_system,
+                spawn_crew_members_system,
+                station_hit_injury_system.run_if(on_event::<ModuleTookDamageEvent>()),
+                assign_stations_system,
+                crew_walk_system,
+                crew_depressurization_system,
+            )
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+/// Crew living aboard a structure.
+/// The capacity comes from the crew quarters modules, going over it lowers the morale over time.
+#[derive(Component, Debug)]
+pub struct Crew {
+    pub members: u32,
+    pub capacity: u32,
+    pub morale: f32, // 0.0 (mutiny) to 1.0 (happy)
+}
+
+impl Crew {
+    pub fn new(members: u32) -> Self {
+        Self { members, capacity: 0, morale: 1.0 }
+    }
+
+    pub fn is_overcrowded(&self) -> bool {
+        self.members > self.capacity
+    }
+
+    /// Ratio of crew members that do not have a bunk, 0.0 when everyone fits.
+    pub fn overcrowding(&self) -> f32 {
+        if !self.is_overcrowded() {
+            return 0.0;
+        }
+        if self.capacity == 0 {
+            return 1.0;
+        }
+        (self.members - self.capacity) as f32 / self.capacity as f32
+    }
+}
+
+/// Recomputes the crew capacity whenever the modules of a structure change.
+fn update_crew_capacity_system(
+    mut structures_query: Query<(&mut Crew, &Children), Changed<Children>>,
+    quarters_query: Query<(), With<CrewQuartersModule>>,
+) {
+    for (mut crew, children) in &mut structures_query {
+        let quarters = children.iter().filter(|child| quarters_query.contains(**child)).count() as u32;
+
+        crew.capacity = quarters * CREW_PER_QUARTERS;
+        debug!("Structure crew capacity updated: {}/{}", crew.members, crew.capacity);
+    }
+}
+
+fn crew_morale_system(mut crew_query: Query<&mut Crew>, time: Res<Time>) {
+    let delta_time = time.delta_seconds();
+
+    for mut crew in &mut crew_query {
+        let morale = if crew.is_overcrowded() {
+            crew.morale - MORALE_OVERCROWDING_DRAIN * crew.overcrowding().min(1.0) * delta_time
+        } else {
+            crew.morale + MORALE_RECOVERY * delta_time
+        };
+        crew.morale = morale.clamp(0.0, 1.0);
+    }
+}
+
+/// A crew member walking the free cells and inner doors of `structure`, child of it.
+#[derive(Component, Debug)]
+pub struct CrewMember {
+    pub structure: Entity,
+    /// Cell the crew member stands in, or walks out of.
+    pub cell: (i32, i32),
+    /// Module the crew member mans, or walks to.
+    pub station: Option<Entity>,
+    path: VecDeque<(i32, i32)>,
+    /// Progress from `cell` to the next cell of the path, from 0 to 1.
+    step: f32,
+}
+
+/// A station module with a crew member standing next to it.
+#[derive(Component, Debug)]
+pub struct Manned {
+    pub crew_member: Entity,
+}
+
+#[derive(Event, Debug)]
+pub struct CrewDiedEvent {
+    pub crew_member: Entity,
+    pub structure: Entity,
+    pub cell: (i32, i32),
+}
+
+/// Puts the crew of the new structures aboard, one member per sealed free cell at most.
+fn spawn_crew_members_system(
+    structures_query: Query<(Entity, &Structure, &Crew, &Pressurization), Added<Crew>>,
+    mut commands: Commands,
+) {
+    for (structure_entity, structure, crew, pressurization) in &structures_query {
+        let mut cells: Vec<(i32, i32)> = structure
+            .grid
+            .cells
+            .keys()
+            .copied()
+            .filter(|cell| structure.is_walkable(*cell) && !pressurization.is_exposed(*cell))
+            .collect();
+        cells.sort_unstable();
+
+        let size = Vec2::splat(structure.grid.cell_size * CREW_MEMBER_SIZE);
+        for cell in cells.into_iter().take(crew.members as usize) {
+            let translation = structure.grid_cell_center_local_position(cell.0, cell.1).extend(CREW_MEMBER_Z);
+            commands.entity(structure_entity).with_children(|children| {
+                children.spawn((
+                    CrewMember { structure: structure_entity, cell, station: None, path: VecDeque::new(), step: 0.0 },
+                    Injury::default(),
+                    SpriteBundle {
+                        sprite: Sprite { color: CREW_MEMBER_COLOR, custom_size: Some(size), ..default() },
+                        transform: Transform::from_translation(translation),
+                        ..default()
+                    },
+                ));
+            });
+        }
+    }
+}
+
+/// Critical hits on a manned station hurt the crew member manning it.
+fn station_hit_injury_system(
+    mut damage_reader: EventReader<ModuleTookDamageEvent>,
+    manned_query: Query<&Manned>,
+    mut injury_writer: EventWriter<InjuryEvent>,
+) {
+    for event in damage_reader.read().filter(|event| event.critical) {
+        if let Ok(manned) = manned_query.get(event.module_entity) {
+            injury_writer.send(InjuryEvent { entity: manned.crew_member, cause: InjuryCause::Combat });
+        }
+    }
+}
+
+/// Sends the idle crew members to the nearest station nobody mans or walks to yet, and the hurt ones to the nearest
+/// medical bay.
+fn assign_stations_system(
+    mut crew_query: Query<(Entity, &mut CrewMember, &Injury)>,
+    structures_query: Query<&Structure>,
+    stations_query: Query<(), Or<(With<CommandCenterModule>, With<CannonModule>)>>,
+    doors_query: Query<(), (With<Door>, Without<Airlock>)>,
+    medical_bays_query: Query<(), With<MedicalBayModule>>,
+    mut commands: Commands,
+) {
+    let claimed: HashSet<Entity> = crew_query.iter().filter_map(|(_, crew_member, _)| crew_member.station).collect();
+    let mut newly_claimed = HashSet::new();
+
+    for (crew_entity, mut crew_member, injury) in &mut crew_query {
+        // The station is gone, or the way to it got blocked
+        let station_lost = crew_member.station.is_some_and(|station| !stations_query.contains(station));
+        let Ok(structure) = structures_query.get(crew_member.structure) else {
+            continue;
+        };
+        // The crew opens the inner doors on their way and closes them behind, airlocks stay shut.
+        // Medical bays are stood on to be treated, like the player does.
+        let is_medical_bay =
+            |cell: (i32, i32)| structure.module_at(cell).is_some_and(|module| medical_bays_query.contains(module));
+        let is_passable = |cell: (i32, i32)| {
+            structure.is_walkable(cell)
+                || structure.module_at(cell).is_some_and(|module| doors_query.contains(module))
+                || is_medical_bay(cell)
+        };
+        let path_blocked = crew_member.path.iter().any(|cell| !is_passable(*cell));
+        if station_lost || path_blocked {
+            debug!("Crew member {:?} lost the way to their station", crew_entity);
+            crew_member.station = None;
+            crew_member.path.clear();
+        }
+
+        if *injury != Injury::Healthy {
+            if let Some(station) = crew_member.station.take() {
+                if let Some(mut station_commands) = commands.get_entity(station) {
+                    station_commands.remove::<Manned>();
+                }
+            }
+            let destination = crew_member.path.back().copied().unwrap_or(crew_member.cell);
+            if !is_medical_bay(destination) {
+                // Finish the step under way before turning around
+                crew_member.path.truncate(1);
+                let from = crew_member.path.front().copied().unwrap_or(crew_member.cell);
+                if let Some(path) = structure.find_path(from, is_passable, is_medical_bay) {
+                    debug!("Crew member {:?} is hurt and walks to the medical bay", crew_entity);
+                    crew_member.path.extend(path);
+                }
+            }
+            continue;
+        }
+        if crew_member.station.is_some() {
+            continue;
+        }
+
+        let free_station_next_to = |cell: (i32, i32)| {
+            structure.get_adjacent_cells(cell).into_iter().find_map(|adjacent| {
+                let station = structure.module_at(adjacent)?;
+                let free = !claimed.contains(&station) && !newly_claimed.contains(&station);
+                (free && stations_query.contains(station)).then_some(station)
+            })
+        };
+        let Some(path) =
+            structure.find_path(crew_member.cell, is_passable, |cell| free_station_next_to(cell).is_some())
+        else {
+            continue;
+        };
+        let goal = path.last().copied().unwrap_or(crew_member.cell);
+        let Some(station) = free_station_next_to(goal) else {
+            continue;
+        };
+
+        newly_claimed.insert(station);
+        crew_member.station = Some(station);
+        crew_member.path = path.into();
+        crew_member.step = 0.0;
+    }
+}
+
+/// Walks the crew members along their path, they man their station once they stand next to it.
+fn crew_walk_system(
+    mut crew_query: Query<(Entity, &mut CrewMember, &mut Transform)>,
+    structures_query: Query<&Structure>,
+    manned_query: Query<(), With<Manned>>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (crew_entity, mut crew_member, mut transform) in &mut crew_query {
+        let Ok(structure) = structures_query.get(crew_member.structure) else {
+            continue;
+        };
+
+        // Keep the progress left over when a cell is reached, the crew member goes on towards the next one
+        if !crew_member.path.is_empty() {
+            crew_member.step += CREW_WALK_SPEED * time.delta_seconds();
+        }
+        while crew_member.step >= 1.0 {
+            let Some(next) = crew_member.path.pop_front() else {
+                break;
+            };
+            crew_member.step -= 1.0;
+            crew_member.cell = next;
+        }
+
+        let from = structure.grid_cell_center_local_position(crew_member.cell.0, crew_member.cell.1);
+        let Some(&next) = crew_member.path.front() else {
+            crew_member.step = 0.0;
+            transform.translation = from.extend(CREW_MEMBER_Z);
+            if let Some(station) = crew_member.station.filter(|station| !manned_query.contains(*station)) {
+                commands.entity(station).insert(Manned { crew_member: crew_entity });
+            }
+            continue;
+        };
+        let to = structure.grid_cell_center_local_position(next.0, next.1);
+        transform.translation = from.lerp(to, crew_member.step).extend(CREW_MEMBER_Z);
+    }
+}
+
+/// Crew members standing in the rooms just breached are blown out with the air.
+fn crew_depressurization_system(
+    mut event_reader: EventReader<StructureDepressurizationEvent>,
+    crew_query: Query<(Entity, &CrewMember)>,
+    mut structures_query: Query<&mut Crew>,
+    mut died_writer: EventWriter<CrewDiedEvent>,
+    mut commands: Commands,
+) {
+    for event in event_reader.read() {
+        for (crew_entity, crew_member) in &crew_query {
+            if crew_member.structure != event.depressurized_structure
+                || !event.breached_cells.contains(&crew_member.cell)
+            {
+                continue;
+            }
+
+            if let Some(station) = crew_member.station {
+                if let Some(mut station_commands) = commands.get_entity(station) {
+                    station_commands.remove::<Manned>();
+                }
+            }
+            commands.entity(crew_entity).despawn_recursive();
+            if let Ok(mut crew) = structures_query.get_mut(crew_member.structure) {
+                crew.members = crew.members.saturating_sub(1);
+            }
+            died_writer.send(CrewDiedEvent {
+                crew_member: crew_entity,
+                structure: crew_member.structure,
+                cell: crew_member.cell,
+            });
+            info!("A crew member died in the depressurization of {:?}", crew_member.structure);
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use crate::configs::collision_layers::CollisionLayersConfig;
+use crate::core::prelude::*;
+use crate::core::profiling::EntityCountDiagnosticAppExt;
+use crate::world::prelude::*;
+
+use crate::prelude::*;
+
+const DEBRIS_PIECES_PER_AXIS: i32 = 2; // a destroyed module breaks into 2x2 pieces
+pub const DEBRIS_PIECE_SIZE: f32 = STRUCTURE_CELL_SIZE / DEBRIS_PIECES_PER_AXIS as f32;
+const DEBRIS_LIFETIME: f32 = 30.0; // seconds before a piece of debris disappears
+const DEBRIS_FADE_TIME: f32 = 5.0; // last seconds of the lifetime where the debris fades out
+const DEBRIS_SCATTER_SPEED: f32 = 20.0; // m/s added away from the module center
+
+pub struct DebrisPlugin;
+
+impl Plugin for DebrisPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, debris_decay_system.run_if(in_state(GameState::InGame)))
+            .register_entity_count_diagnostic::<Debris>("debris");
+    }
+}
+
+/// A piece of a destroyed module drifting in space.
+/// It decays over time and carries a salvage value that can be collected before it is gone.
+#[derive(Component, Debug)]
+pub struct Debris {
+    pub decay: Timer,
+    pub salvage_value: f32,
+}
+
+/// Breaks a destroyed module into small pieces of debris.
+/// The pieces inherit the velocity the module had at `module_transform` and are scattered away from its center.
+pub fn spawn_debris(
+    commands: &mut Commands,
+    game_assets: &GameAssets,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    module_transform: &GlobalTransform,
+    material_type: &ModuleMaterialType,
+    color: Color,
+    inherited_velocity: Vec2,
+    inherited_angular_velocity: f32,
+) {
+    let properties = material_type.properties();
+    let (module_size, piece_size) = (STRUCTURE_CELL_SIZE, DEBRIS_PIECE_SIZE);
+    let salvage_value = piece_size.powi(2) * properties.thickness * properties.density;
+
+    let (_, rotation, center) = module_transform.to_scale_rotation_translation();
+
+    for x in 0..DEBRIS_PIECES_PER_AXIS {
+        for y in 0..DEBRIS_PIECES_PER_AXIS {
+            let local_offset = Vec2::new(
+                (x as f32 + 0.5) * piece_size - module_size / 2.0,
+                (y as f32 + 0.5) * piece_size - module_size / 2.0,
+            );
+            let offset = (rotation * local_offset.extend(0.0)).truncate();
+
+            commands.spawn((
+                Debris { decay: Timer::from_seconds(DEBRIS_LIFETIME, TimerMode::Once), salvage_value },
+                RigidBody::Dynamic,
+                Collider::rectangle(piece_size, piece_size),
+                CollisionLayersConfig::debris(),
+                LinearVelocity(inherited_velocity + offset.normalize_or_zero() * DEBRIS_SCATTER_SPEED),
+                AngularVelocity(inherited_angular_velocity),
+                MaterialMesh2dBundle {
+                    mesh: game_assets.debris_mesh.clone(),
+                    material: materials.add(ColorMaterial::from(color)),
+                    transform: Transform { translation: center + offset.extend(0.0), rotation, ..default() },
+                    ..default()
+                },
+            ));
+        }
+    }
+}
+
+/// Fades out the debris at the end of its lifetime and despawns it once decayed.
+fn debris_decay_system(
+    mut debris_query: Query<(Entity, &mut Debris, &Handle<ColorMaterial>)>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, mut debris, material_handle) in &mut debris_query {
+        if debris.decay.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let remaining = debris.decay.remaining_secs();
+        if remaining < DEBRIS_FADE_TIME {
+            if let Some(material) = materials.get_mut(material_handle) {
+                material.color.set_alpha(remaining / DEBRIS_FADE_TIME);
+            }
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use crate::core::asset_loader::{validate_assets_system, DataAssetLoader};
+use crate::core::asset_validation::{validate_degradation_curves, AssetLoadError};
+use crate::core::state::GameState;
+use crate::world::module_registry::ModuleRegistry;
+use crate::world::modules::{Module, ModuleMaterial};
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+pub const DEGRADATION_CURVES_PATH: &str = "data/degradation.ron";
+
+/// Damaged modules work worse instead of working fully until destroyed: every module gets a performance multiplier
+/// from its remaining structural points, following the degradation curve of its type.
+pub struct DegradationPlugin;
+
+impl Plugin for DegradationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DegradationCurves>()
+            .init_asset::<DegradationCurves>()
+            .init_asset_loader::<DataAssetLoader<DegradationCurves>>()
+            .add_systems(Startup, load_degradation_curves)
+            // Curves are checked as soon as they are loaded, an invalid file stops the game like the world data
+            .add_systems(Update, apply_degradation_curves_system.after(validate_assets_system))
+            .add_systems(
+                Update,
+                update_module_performance_system
+                    .after(apply_degradation_curves_system)
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+/// Performance multiplier as a function of the structural points fraction, linear between its points.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(transparent)]
+pub struct DegradationCurve {
+    /// `(structural points fraction, performance)` pairs sorted by fraction.
+    pub points: Vec<(f32, f32)>,
+}
+
+impl DegradationCurve {
+    /// Performance proportional to the structural points left.
+    pub fn linear() -> Self {
+        Self { points: vec![(0.0, 0.0), (1.0, 1.0)] }
+    }
+
+    pub fn sample(&self, fraction: f32) -> f32 {
+        let Some(&(first_fraction, first_performance)) = self.points.first() else {
+            return 1.0;
+        };
+        if fraction <= first_fraction {
+            return first_performance;
+        }
+
+        for window in self.points.windows(2) {
+            let ((from_fraction, from_performance), (to_fraction, to_performance)) = (window[0], window[1]);
+            if fraction <= to_fraction {
+                let t = (fraction - from_fraction) / (to_fraction - from_fraction).max(f32::EPSILON);
+                return from_performance + (to_performance - from_performance) * t;
+            }
+        }
+        self.points.last().map_or(1.0, |&(_, performance)| performance)
+    }
+}
+
+/// Degradation curves by module symbol, read from `data/degradation.ron`.
+#[derive(Asset, Resource, TypePath, Debug, Clone, Deserialize)]
+pub struct DegradationCurves {
+    /// Curve of the module types without their own.
+    pub default: DegradationCurve,
+    pub modules: HashMap<char, DegradationCurve>,
+}
+
+impl Default for DegradationCurves {
+    fn default() -> Self {
+        Self { default: DegradationCurve::linear(), modules: HashMap::new() }
+    }
+}
+
+impl DegradationCurves {
+    pub fn get(&self, symbol: char) -> &DegradationCurve {
+        self.modules.get(&symbol).unwrap_or(&self.default)
+    }
+}
+
+/// How well a module works, from 0 (not at all) to 1 (like new).
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct ModulePerformance(pub f32);
+
+impl Default for ModulePerformance {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// Performance multiplier of an optional `ModulePerformance`, modules not rated yet work fully.
+pub fn performance(module_performance: Option<&ModulePerformance>) -> f32 {
+    module_performance.map_or(1.0, |module_performance| module_performance.0)
+}
+
+#[derive(Resource)]
+struct DegradationCurvesHandle(Handle<DegradationCurves>);
+
+fn load_degradation_curves(asset_server: Res<AssetServer>, mut commands: Commands) {
+    commands.insert_resource(DegradationCurvesHandle(asset_server.load(DEGRADATION_CURVES_PATH)));
+}
+
+/// Replaces the curves in use whenever the data file is (re)loaded, unless it has curves for unknown modules.
+fn apply_degradation_curves_system(
+    mut asset_events: EventReader<AssetEvent<DegradationCurves>>,
+    handle: Res<DegradationCurvesHandle>,
+    curves_assets: Res<Assets<DegradationCurves>>,
+    module_registry: Res<ModuleRegistry>,
+    mut curves: ResMut<DegradationCurves>,
+    mut error_writer: EventWriter<AssetLoadError>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !asset_events.read().any(|event| event.is_loaded_with_dependencies(&handle.0)) {
+        return;
+    }
+    if let Some(loaded_curves) = curves_assets.get(&handle.0) {
+        let errors = validate_degradation_curves(loaded_curves, &module_registry);
+        if !errors.is_empty() {
+            for error in &errors {
+                error!("{}: {}", DEGRADATION_CURVES_PATH, error);
+            }
+            error_writer.send(AssetLoadError { path: DEGRADATION_CURVES_PATH.to_string(), errors });
+            next_state.set(GameState::AssetError);
+            return;
+        }
+        *curves = loaded_curves.clone();
+        info!("Degradation curves loaded for {} module types", curves.modules.len());
+    }
+}
+
+fn update_module_performance_system(
+    mut modules_query: Query<(Entity, &Module, Ref<ModuleMaterial>, Option<&mut ModulePerformance>)>,
+    curves: Res<DegradationCurves>,
+    mut commands: Commands,
+) {
+    for (module_entity, module, module_material, module_performance) in &mut modules_query {
+        if !curves.is_changed() && !module_material.is_changed() {
+            continue;
+        }
+        let fraction = if module_material.max_structural_points > 0.0 {
+            (module_material.structural_points / module_material.max_structural_points).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        let performance = curves.get(module.module_type.symbol()).sample(fraction).clamp(0.0, 1.0);
+
+        match module_performance {
+            Some(mut module_performance) => {
+                if module_performance.0 != performance {
+                    module_performance.0 = performance;
+                }
+            }
+            None => {
+                commands.entity(module_entity).insert(ModulePerformance(performance));
+            }
+        }
+    }
+}
@@ -0,0 +1,460 @@
+use crate::core::prelude::*;
+use crate::gameplay::livery::Livery;
+use crate::gameplay::repair::Scrap;
+use crate::world::prelude::*;
+
+use bevy::prelude::*;
+use bevy::sprite::MaterialMesh2dBundle;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+const DEFAULT_DERELICT_SEED: u64 = 0xDE2E_11C7;
+const MAX_ATTEMPTS: u32 = 20; // layouts tried before keeping one with sealed compartments
+const CELLS_PER_LOOT: usize = 12; // reachable floor cells for each loot crate
+const LOOT_SCRAP: f32 = 25.0;
+const LOOT_RADIUS: f32 = 0.2 * STRUCTURE_CELL_SIZE;
+const LOOT_Z: f32 = 2.0;
+
+/// How many derelicts are generated around the world, read from the settings file.
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DerelictSettings {
+    pub count: u32,
+    /// Smallest and largest width and height of the derelicts, hull included, in cells.
+    pub min_size: (u32, u32),
+    pub max_size: (u32, u32),
+    /// Distance of the derelicts from the center of the world, in meters.
+    pub distance: f32,
+}
+
+impl Default for DerelictSettings {
+    fn default() -> Self {
+        Self { count: 2, min_size: (8, 6), max_size: (14, 10), distance: 400.0 }
+    }
+}
+
+/// Spawns abandoned structures with generated interiors, so the boarding targets are not all hand authored.
+/// Their rooms and corridors come from a wave function collapse over the structure grid, scrap crates are left in
+/// the far corners for the player to pick up.
+pub struct DerelictsPlugin;
+
+impl Plugin for DerelictsPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = app.world().get_resource::<DerelictSettings>().cloned().unwrap_or_default();
+        app.insert_resource(settings)
+            .insert_resource(DerelictGenerator::new(DEFAULT_DERELICT_SEED))
+            .add_event::<LootCollectedEvent>()
+            .add_systems(OnEnter(GameState::BuildingStructures), spawn_derelicts_system)
+            .add_systems(Update, collect_loot_system.run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// Marks a structure spawned by the `DerelictGenerator`.
+#[derive(Component, Debug)]
+pub struct Derelict;
+
+/// Scrap crate lying in a cell of a derelict, child of the structure.
+#[derive(Component, Debug)]
+pub struct Loot {
+    pub cell: (i32, i32),
+    pub scrap: f32,
+}
+
+#[derive(Event, Debug)]
+pub struct LootCollectedEvent {
+    pub structure_entity: Entity,
+    pub scrap: f32,
+}
+
+/// Interior tiles of the generator. Every side is either open floor or a wall going on into the next cell, two
+/// neighbors fit when the sides they share match, so walls always run into the hull or another wall and close rooms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tile {
+    Floor,
+    WallHorizontal,
+    WallVertical,
+    Corner([bool; 4]),
+    Junction([bool; 4]),
+    WallCross,
+    /// Door in a horizontal wall, passing up and down.
+    DoorHorizontal,
+    DoorVertical,
+}
+
+const UP: usize = 0;
+const RIGHT: usize = 1;
+const DOWN: usize = 2;
+const LEFT: usize = 3;
+const OFFSETS: [(i32, i32); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)]; // rows go down in the structure data
+
+const TILES: [(Tile, f32); 14] = [
+    (Tile::Floor, 8.0),
+    (Tile::WallHorizontal, 1.0),
+    (Tile::WallVertical, 1.0),
+    (Tile::Corner([true, true, false, false]), 0.3),
+    (Tile::Corner([false, true, true, false]), 0.3),
+    (Tile::Corner([false, false, true, true]), 0.3),
+    (Tile::Corner([true, false, false, true]), 0.3),
+    (Tile::Junction([false, true, true, true]), 0.3),
+    (Tile::Junction([true, false, true, true]), 0.3),
+    (Tile::Junction([true, true, false, true]), 0.3),
+    (Tile::Junction([true, true, true, false]), 0.3),
+    (Tile::WallCross, 0.1),
+    (Tile::DoorHorizontal, 0.3),
+    (Tile::DoorVertical, 0.3),
+];
+
+impl Tile {
+    /// Whether a wall leaves the tile on each side (up, right, down, left).
+    fn walls(&self) -> [bool; 4] {
+        match self {
+            Tile::Floor => [false; 4],
+            Tile::WallHorizontal | Tile::DoorHorizontal => [false, true, false, true],
+            Tile::WallVertical | Tile::DoorVertical => [true, false, true, false],
+            Tile::Corner(walls) | Tile::Junction(walls) => *walls,
+            Tile::WallCross => [true; 4],
+        }
+    }
+
+    fn symbol(&self) -> char {
+        match self {
+            Tile::Floor => '#',
+            Tile::DoorHorizontal | Tile::DoorVertical => 'D',
+            _ => 'W',
+        }
+    }
+}
+
+/// Interior and loot of a generated derelict.
+#[derive(Debug, Clone)]
+pub struct DerelictLayout {
+    /// Rows of module symbols, in the structures data format.
+    pub rows: Vec<String>,
+    pub loot: Vec<(i32, i32)>,
+}
+
+impl DerelictLayout {
+    pub fn to_structure_data(&self, world_pos: Vec2, rotation: f32) -> StructureData {
+        StructureData {
+            world_pos: world_pos.to_array(),
+            structure: self.rows.clone(),
+            orientations: Vec::new(),
+            crew: 0,
+            rotation,
+            velocity: [0.0, 0.0],
+            livery: Livery::default(),
+            faction: None,
+        }
+    }
+}
+
+/// Deterministic derelict generator, the same seed always gives the same derelicts.
+#[derive(Resource, Debug, Clone)]
+pub struct DerelictGenerator {
+    rng: SeededRng,
+}
+
+impl DerelictGenerator {
+    pub fn new(seed: u64) -> Self {
+        Self { rng: SeededRng::new(seed) }
+    }
+
+    /// Generates a derelict of `width` by `height` cells, hull included.
+    pub fn generate(&mut self, width: u32, height: u32) -> DerelictLayout {
+        let (width, height) = (width.max(4) as i32, height.max(4) as i32);
+        let mut fallback = None;
+
+        for _ in 0..MAX_ATTEMPTS {
+            let Some(interior) = self.collapse(width - 2, height - 2) else {
+                continue;
+            };
+            let mut cells = vec![vec!['W'; width as usize]; height as usize];
+            for (y, row) in interior.chunks((width - 2) as usize).enumerate() {
+                for (x, tile) in row.iter().enumerate() {
+                    cells[y + 1][x + 1] = tile.symbol();
+                }
+            }
+            let Some(airlock) = self.place_airlock(&mut cells) else {
+                continue;
+            };
+
+            let distances = floor_distances(&cells, airlock);
+            let floor_count = cells.iter().flatten().filter(|symbol| **symbol == '#').count();
+            let layout = DerelictLayout {
+                loot: pick_loot_cells(&distances),
+                rows: cells.into_iter().map(String::from_iter).collect(),
+            };
+            if distances.len() == floor_count {
+                return layout;
+            }
+            fallback.get_or_insert(layout);
+        }
+
+        // Every attempt left a sealed compartment or failed, the last resort is a single hold
+        fallback.unwrap_or_else(|| {
+            let mut rows: Vec<String> = (0..height)
+                .map(|y| {
+                    (0..width)
+                        .map(|x| if x == 0 || y == 0 || x == width - 1 || y == height - 1 { 'W' } else { '#' })
+                        .collect()
+                })
+                .collect();
+            rows[(height / 2) as usize].replace_range(0..1, "A");
+            DerelictLayout { rows, loot: vec![(width - 2, height / 2)] }
+        })
+    }
+
+    /// Wave function collapse of the interior tiles, `None` on a contradiction.
+    fn collapse(&mut self, width: i32, height: i32) -> Option<Vec<Tile>> {
+        let all_tiles: u32 = (1 << TILES.len()) - 1;
+        let mut options = vec![all_tiles; (width * height) as usize];
+        let index = |x: i32, y: i32| (y * width + x) as usize;
+
+        loop {
+            // Collapse the most constrained cell, ties broken at random
+            let mut lowest: Option<(usize, f32)> = None;
+            for (i, cell_options) in options.iter().enumerate() {
+                let count = cell_options.count_ones();
+                if count <= 1 {
+                    continue;
+                }
+                let entropy = count as f32 + self.rng.roll() * 0.5;
+                if lowest.is_none_or(|(_, lowest_entropy)| entropy < lowest_entropy) {
+                    lowest = Some((i, entropy));
+                }
+            }
+            let Some((cell, _)) = lowest else {
+                break;
+            };
+
+            let total: f32 = (0..TILES.len()).filter(|t| options[cell] & (1 << t) != 0).map(|t| TILES[t].1).sum();
+            let mut pick = self.rng.roll() * total;
+            let chosen = (0..TILES.len())
+                .filter(|t| options[cell] & (1 << t) != 0)
+                .find(|t| {
+                    pick -= TILES[*t].1;
+                    pick <= 0.0
+                })
+                .unwrap_or_else(|| options[cell].trailing_zeros() as usize);
+            options[cell] = 1 << chosen;
+
+            // Propagate the constraints to the neighbors until nothing changes
+            let mut stack = vec![cell];
+            while let Some(current) = stack.pop() {
+                let (x, y) = ((current as i32) % width, (current as i32) / width);
+                for side in [UP, RIGHT, DOWN, LEFT] {
+                    let (nx, ny) = (x + OFFSETS[side].0, y + OFFSETS[side].1);
+                    if nx < 0 || ny < 0 || nx >= width || ny >= height {
+                        continue;
+                    }
+                    let neighbor = index(nx, ny);
+                    let allowed = (0..TILES.len())
+                        .filter(|t| options[neighbor] & (1 << t) != 0)
+                        .filter(|t| {
+                            (0..TILES.len()).any(|s| {
+                                options[current] & (1 << s) != 0
+                                    && TILES[s].0.walls()[side] == TILES[*t].0.walls()[(side + 2) % 4]
+                            })
+                        })
+                        .fold(0, |mask, t| mask | (1 << t));
+                    if allowed == 0 {
+                        return None;
+                    }
+                    if allowed != options[neighbor] {
+                        options[neighbor] = allowed;
+                        stack.push(neighbor);
+                    }
+                }
+            }
+        }
+
+        options
+            .iter()
+            .map(|cell_options| TILES.get(cell_options.trailing_zeros() as usize).map(|(tile, _)| *tile))
+            .collect()
+    }
+
+    /// Opens an airlock in the hull next to a floor cell, returns the floor cell behind it.
+    fn place_airlock(&mut self, cells: &mut [Vec<char>]) -> Option<(i32, i32)> {
+        let (width, height) = (cells[0].len() as i32, cells.len() as i32);
+        let candidates: Vec<((i32, i32), (i32, i32))> = (1..width - 1)
+            .flat_map(|x| [((x, 0), (x, 1)), ((x, height - 1), (x, height - 2))])
+            .chain((1..height - 1).flat_map(|y| [((0, y), (1, y)), ((width - 1, y), (width - 2, y))]))
+            .filter(|(_, (x, y))| cells[*y as usize][*x as usize] == '#')
+            .collect();
+        if candidates.is_empty() {
+            return None;
+        }
+        let ((x, y), inside) = candidates[self.rng.index(candidates.len())];
+        cells[y as usize][x as usize] = 'A';
+        Some(inside)
+    }
+}
+
+/// Walking distance from `start` to every floor cell reachable through the floor and the doors.
+fn floor_distances(cells: &[Vec<char>], start: (i32, i32)) -> Vec<((i32, i32), u32)> {
+    let (width, height) = (cells[0].len() as i32, cells.len() as i32);
+    let mut visited = vec![vec![false; width as usize]; height as usize];
+    let mut distances = Vec::new();
+    let mut queue = VecDeque::from([(start, 0)]);
+    visited[start.1 as usize][start.0 as usize] = true;
+
+    while let Some(((x, y), distance)) = queue.pop_front() {
+        if cells[y as usize][x as usize] == '#' {
+            distances.push(((x, y), distance));
+        }
+        for (dx, dy) in OFFSETS {
+            let (nx, ny) = (x + dx, y + dy);
+            if nx < 0 || ny < 0 || nx >= width || ny >= height || visited[ny as usize][nx as usize] {
+                continue;
+            }
+            if matches!(cells[ny as usize][nx as usize], '#' | 'D') {
+                visited[ny as usize][nx as usize] = true;
+                queue.push_back(((nx, ny), distance + 1));
+            }
+        }
+    }
+    distances
+}
+
+/// The reachable floor cells farthest from the airlock, never two crates side by side.
+fn pick_loot_cells(distances: &[((i32, i32), u32)]) -> Vec<(i32, i32)> {
+    let count = (distances.len() / CELLS_PER_LOOT).max(1);
+    let mut by_distance = distances.to_vec();
+    by_distance.sort_by(|(_, distance1), (_, distance2)| distance2.cmp(distance1));
+
+    let mut loot: Vec<(i32, i32)> = Vec::new();
+    for (cell, _) in by_distance {
+        if loot.len() >= count {
+            break;
+        }
+        if loot.iter().all(|other| (other.0 - cell.0).abs() + (other.1 - cell.1).abs() > 1) {
+            loot.push(cell);
+        }
+    }
+    loot
+}
+
+fn spawn_derelicts_system(
+    settings: Res<DerelictSettings>,
+    mut generator: ResMut<DerelictGenerator>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    game_assets: Res<GameAssets>,
+    module_registry: Res<ModuleRegistry>,
+    mut commands: Commands,
+) {
+    for i in 0..settings.count {
+        let width = generator.rng.range(settings.min_size.0, settings.max_size.0);
+        let height = generator.rng.range(settings.min_size.1, settings.max_size.1);
+        let layout = generator.generate(width, height);
+
+        let angle = (i as f32 + generator.rng.roll()) / settings.count as f32 * std::f32::consts::TAU;
+        let world_pos = Vec2::from_angle(angle) * settings.distance;
+        let rotation = generator.rng.roll() * std::f32::consts::TAU;
+        let structure_data = layout.to_structure_data(world_pos, rotation);
+
+        let structure_entity =
+            spawn_structure(&mut commands, &mut materials, &game_assets, &module_registry, &structure_data);
+        commands.entity(structure_entity).insert((Derelict, Name::new("Derelict")));
+
+        let mut structure = Structure::new();
+        structure.grid = Grid::new(width, height, STRUCTURE_CELL_SIZE);
+        for cell in layout.loot {
+            let translation = structure.grid_cell_center_local_position(cell.0, cell.1).extend(LOOT_Z);
+            let loot_entity = commands
+                .spawn((
+                    Loot { cell, scrap: LOOT_SCRAP },
+                    MaterialMesh2dBundle {
+                        mesh: game_assets.unit_disc.clone(),
+                        material: game_assets.loot_material.clone(),
+                        transform: Transform::from_translation(translation).with_scale(Vec3::splat(LOOT_RADIUS)),
+                        ..default()
+                    },
+                    Name::new("Loot"),
+                ))
+                .id();
+            commands.entity(structure_entity).add_child(loot_entity);
+        }
+        debug!("Spawned a {}x{} derelict at {:?}", width, height, world_pos);
+    }
+}
+
+/// The player picks up the loot of the cell they walk in.
+fn collect_loot_system(
+    player_query: Query<&GlobalTransform, With<Player>>,
+    player_resource: Res<PlayerResource>,
+    structures_query: Query<(&Transform, &Structure), With<Derelict>>,
+    loot_query: Query<(Entity, &Loot, &Parent)>,
+    mut scrap: ResMut<Scrap>,
+    mut collected_writer: EventWriter<LootCollectedEvent>,
+    mut commands: Commands,
+) {
+    let (Ok(player_transform), Some(structure_entity)) = (player_query.get_single(), player_resource.inside_structure)
+    else {
+        return;
+    };
+    let Ok((structure_transform, structure)) = structures_query.get(structure_entity) else {
+        return;
+    };
+    let player_cell = structure.world_to_grid(player_transform.translation(), structure_transform);
+
+    for (loot_entity, loot, parent) in &loot_query {
+        if parent.get() != structure_entity || loot.cell != player_cell {
+            continue;
+        }
+        scrap.amount += loot.scrap;
+        commands.entity(loot_entity).despawn_recursive();
+        debug!("Picked up {} scrap from a derelict", loot.scrap);
+        collected_writer.send(LootCollectedEvent { structure_entity, scrap: loot.scrap });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interior_symbols(tiles: &[Tile]) -> String {
+        tiles.iter().map(Tile::symbol).collect()
+    }
+
+    #[test]
+    fn same_seed_collapses_the_same_interior() {
+        let first = DerelictGenerator::new(42).collapse(10, 8).map(|tiles| interior_symbols(&tiles));
+        let second = DerelictGenerator::new(42).collapse(10, 8).map(|tiles| interior_symbols(&tiles));
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn same_seed_generates_the_same_layout() {
+        let first = DerelictGenerator::new(7).generate(12, 9);
+        let second = DerelictGenerator::new(7).generate(12, 9);
+        assert_eq!(first.rows, second.rows);
+        assert_eq!(first.loot, second.loot);
+    }
+
+    #[test]
+    fn floor_distances_walk_through_doors_only() {
+        let cells: Vec<Vec<char>> =
+            ["WWWWW", "A#D#W", "WWWWW", "W##WW"].iter().map(|row| row.chars().collect()).collect();
+        let distances = floor_distances(&cells, (1, 1));
+        assert_eq!(distances, vec![((1, 1), 0), ((3, 1), 2)]);
+    }
+
+    #[test]
+    fn loot_lands_only_on_reachable_floor_cells() {
+        for seed in 0..20 {
+            let layout = DerelictGenerator::new(seed).generate(14, 10);
+            let cells: Vec<Vec<char>> = layout.rows.iter().map(|row| row.chars().collect()).collect();
+            assert!(!layout.loot.is_empty());
+            for (x, y) in &layout.loot {
+                assert_eq!(cells[*y as usize][*x as usize], '#', "loot of seed {} is not on the floor", seed);
+            }
+        }
+    }
+
+    #[test]
+    fn loot_picks_the_farthest_cells() {
+        let distances: Vec<((i32, i32), u32)> = (0..CELLS_PER_LOOT as i32).map(|x| ((x, 0), x as u32)).collect();
+        assert_eq!(pick_loot_cells(&distances), vec![(CELLS_PER_LOOT as i32 - 1, 0)]);
+    }
+}
@@ -0,0 +1,179 @@
+use crate::core::prelude::*;
+use crate::gameplay::ai::AiPilot;
+use crate::gameplay::docking::{DockedStructures, DockingJoint, DockingPort};
+use crate::gameplay::movement::Aboard;
+use crate::gameplay::scanning::{ScanChannel, ScanReveals};
+use crate::gameplay::targeting::TargetLock;
+use crate::world::prelude::*;
+
+use bevy::ecs::entity::Entities;
+use bevy::prelude::*;
+use bevy::time::common_conditions::on_timer;
+use std::time::Duration;
+
+const AUDIT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Whatever despawns a module or a structure, the references other entities hold to it are cleaned up when its
+/// component is removed: the grid cell and the rooms of the structure of a module, the docking ports docked to it,
+/// and the targets, locks, scans, docks and boarding of a structure. With the debug flag, the references left dangling are
+/// reported every second.
+pub struct DespawnAuditPlugin {
+    pub debug_enable: bool,
+}
+
+impl Plugin for DespawnAuditPlugin {
+    fn build(&self, app: &mut App) {
+        app.observe(forget_removed_module).observe(forget_removed_structure);
+
+        if self.debug_enable {
+            app.add_systems(
+                PostUpdate,
+                report_dangling_references_system.run_if(in_state(GameState::InGame)).run_if(on_timer(AUDIT_INTERVAL)),
+            );
+        }
+    }
+}
+
+/// Empties the cell of a module despawned without being taken out of its structure first.
+fn forget_removed_module(
+    trigger: Trigger<OnRemove, Module>,
+    modules_query: Query<(&Module, &Parent)>,
+    mut structures_query: Query<(&mut Structure, &mut Pressurization)>,
+    mut ports_query: Query<&mut DockingPort>,
+) {
+    let module_entity = trigger.entity();
+
+    if let Ok((module, parent)) = modules_query.get(module_entity) {
+        if let Ok((mut structure, mut pressurization)) = structures_query.get_mut(parent.get()) {
+            if structure.module_at(module.inner_grid_pos) == Some(module_entity) {
+                debug!("Module {:?} despawned while still in its structure grid", module_entity);
+                structure.remove_module_at(module.inner_grid_pos);
+                let rooms = structure.check_pressurization();
+                pressurization.update_rooms(rooms);
+            }
+        }
+    }
+
+    for mut docking_port in &mut ports_query {
+        if docking_port.docked_to == Some(module_entity) {
+            docking_port.docked_to = None;
+        }
+    }
+}
+
+/// Drops every reference to a despawned structure.
+fn forget_removed_structure(
+    trigger: Trigger<OnRemove, Structure>,
+    mut pilots_query: Query<&mut AiPilot>,
+    locks_query: Query<(Entity, &TargetLock)>,
+    scan_channels_query: Query<(Entity, &ScanChannel)>,
+    mut scan_reveals_query: Query<&mut ScanReveals>,
+    mut docked_query: Query<&mut DockedStructures>,
+    joints_query: Query<(Entity, &DockingJoint)>,
+    aboard_query: Query<(Entity, &Aboard)>,
+    mut player_resource: ResMut<PlayerResource>,
+    mut commands: Commands,
+) {
+    let structure_entity = trigger.entity();
+
+    for mut pilot in &mut pilots_query {
+        if pilot.target == Some(structure_entity) {
+            pilot.target = None;
+        }
+    }
+    for (locker, lock) in &locks_query {
+        if lock.target == structure_entity {
+            commands.entity(locker).remove::<TargetLock>();
+        }
+    }
+    for (scanner, scan_channel) in &scan_channels_query {
+        if scan_channel.target == structure_entity {
+            commands.entity(scanner).remove::<ScanChannel>();
+        }
+    }
+    for mut scan_reveals in &mut scan_reveals_query {
+        scan_reveals.0.remove(&structure_entity);
+    }
+
+    for mut docked_structures in &mut docked_query {
+        docked_structures.0.retain(|docked| *docked != structure_entity);
+    }
+    for (joint_entity, joint) in &joints_query {
+        if joint.structures.contains(&structure_entity) {
+            commands.entity(joint_entity).despawn_recursive();
+        }
+    }
+
+    for (player_entity, aboard) in &aboard_query {
+        if aboard.structure_entity == structure_entity {
+            commands.entity(player_entity).remove::<Aboard>();
+        }
+    }
+    if player_resource.inside_structure == Some(structure_entity) {
+        player_resource.inside_structure = None;
+        player_resource.is_controlling_structure = false;
+    }
+}
+
+/// Logs the references to entities that do not exist anymore, left by a despawn path the observers do not cover.
+fn report_dangling_references_system(
+    entities: &Entities,
+    structures_query: Query<(Entity, &Structure)>,
+    pilots_query: Query<(Entity, &AiPilot)>,
+    locks_query: Query<(Entity, &TargetLock)>,
+    scanners_query: Query<(Entity, Option<&ScanChannel>, Option<&ScanReveals>)>,
+    docked_query: Query<(Entity, &DockedStructures)>,
+    ports_query: Query<(Entity, &DockingPort)>,
+    aboard_query: Query<(Entity, &Aboard)>,
+    player_resource: Res<PlayerResource>,
+) {
+    let mut dangling = 0;
+    let mut check = |holder: &dyn std::fmt::Debug, reference: &str, target: Entity| {
+        if !entities.contains(target) {
+            error!("Dangling {} {:?} held by {:?}", reference, target, holder);
+            dangling += 1;
+        }
+    };
+
+    for (structure_entity, structure) in &structures_query {
+        for (cell, module_entity) in structure.modules() {
+            check(&(structure_entity, cell), "module", module_entity);
+        }
+    }
+    for (pilot_entity, pilot) in &pilots_query {
+        if let Some(target) = pilot.target {
+            check(&pilot_entity, "AI target", target);
+        }
+    }
+    for (locker, lock) in &locks_query {
+        check(&locker, "target lock", lock.target);
+    }
+    for (scanner, scan_channel, scan_reveals) in &scanners_query {
+        if let Some(scan_channel) = scan_channel {
+            check(&scanner, "scan target", scan_channel.target);
+        }
+        for target in scan_reveals.into_iter().flat_map(|scan_reveals| scan_reveals.0.keys()) {
+            check(&scanner, "scan reveal", *target);
+        }
+    }
+    for (structure_entity, docked_structures) in &docked_query {
+        for docked in &docked_structures.0 {
+            check(&structure_entity, "docked structure", *docked);
+        }
+    }
+    for (port_entity, docking_port) in &ports_query {
+        if let Some(docked_to) = docking_port.docked_to {
+            check(&port_entity, "docking port", docked_to);
+        }
+    }
+    for (player_entity, aboard) in &aboard_query {
+        check(&player_entity, "boarded structure", aboard.structure_entity);
+    }
+    if let Some(inside_structure) = player_resource.inside_structure {
+        check(&"PlayerResource", "structure", inside_structure);
+    }
+
+    if dangling > 0 {
+        warn!("Despawn audit found {} dangling references", dangling);
+    }
+}
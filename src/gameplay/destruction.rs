@@ -0,0 +1,137 @@
+use crate::core::prelude::*;
+use crate::gameplay::debris::spawn_debris;
+use crate::gameplay::movement::structure_point_velocity;
+use crate::gameplay::structures_combat::handle_module_destroyed_system;
+use crate::world::prelude::*;
+
+use crate::prelude::*;
+
+/// Ship death: a structure losing its last Command Center is destroyed. Its remaining modules break up into debris,
+/// the player flying it is thrown out into space and a `StructureDestroyedEvent` credits the kill.
+pub struct DestructionPlugin;
+
+impl Plugin for DestructionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<StructureDestroyedEvent>().add_systems(
+            Update,
+            structure_destruction_system
+                .run_if(on_event::<ModuleDestroyedEvent>())
+                .before(handle_module_destroyed_system),
+        );
+    }
+}
+
+/// Sent when a structure is destroyed, right before it is despawned.
+#[derive(Event, Debug)]
+pub struct StructureDestroyedEvent {
+    pub structure_entity: Entity,
+    /// Structure that destroyed the last Command Center, if known.
+    pub source: Option<Entity>,
+    pub position: Vec2,
+    /// Name of the ship, the structure is gone by the time the event is read.
+    pub name: Option<String>,
+}
+
+/// Destroys the structures whose last Command Center was destroyed this frame.
+/// Runs before the destroyed modules are turned into debris one by one, so the whole structure breaks up at once.
+fn structure_destruction_system(
+    mut event_reader: EventReader<ModuleDestroyedEvent>,
+    command_centers_query: Query<&Parent, With<CommandCenterModule>>,
+    structures_query: Query<
+        (
+            &Children,
+            &Transform,
+            &LinearVelocity,
+            &AngularVelocity,
+            &CenterOfMass,
+            Option<&ControlledByPlayer>,
+            Option<&StructureName>,
+        ),
+        With<Structure>,
+    >,
+    modules_query: Query<(&GlobalTransform, &ModuleMaterial, &Handle<ColorMaterial>), With<Module>>,
+    mut players_query: Query<(&GlobalTransform, &mut LinearVelocity), (With<Player>, Without<Structure>)>,
+    mut destroyed_writer: EventWriter<StructureDestroyedEvent>,
+    mut player_resource: ResMut<PlayerResource>,
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let events: Vec<&ModuleDestroyedEvent> = event_reader.read().collect();
+    let destroyed_modules: HashSet<Entity> = events.iter().map(|event| event.destroyed_entity).collect();
+
+    // Structures that lost a Command Center, with whoever destroyed the first one
+    let mut doomed: HashMap<Entity, Option<Entity>> = HashMap::new();
+    for event in &events {
+        if let Ok(parent) = command_centers_query.get(event.destroyed_entity) {
+            doomed.entry(parent.get()).or_insert(event.source);
+        }
+    }
+
+    for (structure_entity, source) in doomed {
+        let Ok((
+            children,
+            structure_transform,
+            linear_velocity,
+            angular_velocity,
+            center_of_mass,
+            controlled_by,
+            structure_name,
+        )) = structures_query.get(structure_entity)
+        else {
+            continue;
+        };
+        // Another bridge can still fly the structure
+        if children.iter().any(|child| command_centers_query.contains(*child) && !destroyed_modules.contains(child)) {
+            continue;
+        }
+        info!("Structure {:?} lost its last Command Center and is destroyed", structure_entity);
+
+        // The pilot is carried by the structure as its child, they are released before it is despawned
+        if let Some(controlled_by) = controlled_by {
+            if let Ok((player_transform, mut player_velocity)) = players_query.get_mut(controlled_by.player_entity) {
+                *player_velocity = LinearVelocity(structure_point_velocity(
+                    linear_velocity,
+                    angular_velocity,
+                    structure_transform,
+                    center_of_mass,
+                    player_transform.translation().truncate(),
+                ));
+            }
+            commands.entity(controlled_by.player_entity).remove_parent_in_place().insert(RigidBody::Dynamic);
+            commands.entity(structure_entity).remove::<ControlledByPlayer>();
+            player_resource.is_controlling_structure = false;
+        }
+
+        // Every module left breaks up into debris moving along with the structure
+        for (module_transform, module_material, material_handle) in modules_query.iter_many(children) {
+            let velocity = structure_point_velocity(
+                linear_velocity,
+                angular_velocity,
+                structure_transform,
+                center_of_mass,
+                module_transform.translation().truncate(),
+            );
+            let color = materials.get(material_handle).map(|material| material.color).unwrap_or(Color::WHITE);
+
+            spawn_debris(
+                &mut commands,
+                &game_assets,
+                &mut materials,
+                module_transform,
+                &module_material.material_type,
+                color,
+                velocity,
+                angular_velocity.0,
+            );
+        }
+
+        destroyed_writer.send(StructureDestroyedEvent {
+            structure_entity,
+            source,
+            position: structure_transform.translation.truncate(),
+            name: structure_name.map(|name| name.ship.clone()),
+        });
+        commands.entity(structure_entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,253 @@
+use crate::core::prelude::*;
+use crate::world::prelude::*;
+
+use crate::prelude::*;
+
+const DOCKING_DISTANCE: f32 = STRUCTURE_CELL_SIZE * 1.25; // between the port centers, in meters
+const DOCKING_MAX_ANGLE: f32 = 0.17; // rad, about 10 degrees between the structures
+const DOCKING_MAX_SPEED: f32 = 3.0; // m/s relative speed of the structures
+const REARM_DISTANCE: f32 = DOCKING_DISTANCE * 2.0; // undocked ports dock again once they went this far apart
+
+/// Lets two structures dock through a pair of docking ports: flying a port slowly against a port of another
+/// structure aligned with it joins the structures with a fixed joint. Docked structures are flown together and the
+/// player can walk from one to the other through the ports, until the player undocks them from a port.
+pub struct DockingPlugin;
+
+impl Plugin for DockingPlugin {
+    fn build(&self, app: &mut App) {
+        // Docking autosaves, the event is registered here too for the headless simulation which does not save
+        app.add_event::<DockedEvent>()
+            .add_event::<UndockedEvent>()
+            .add_event::<AutosaveEvent>()
+            .add_systems(Update, attach_docking_port_system)
+            .add_systems(
+                FixedUpdate,
+                (release_broken_docks_system, rearm_ports_system, dock_ports_system)
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(Update, undock_interaction_system.run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// State of a `DockingPortModule`.
+#[derive(Component, Debug, Default)]
+pub struct DockingPort {
+    /// Port of the other structure this one is docked to.
+    pub docked_to: Option<Entity>,
+    /// Undocked ports stay disarmed until they are clear of the other ports, so they do not dock again right away.
+    disarmed: bool,
+}
+
+/// Joint entity holding two docked structures together.
+#[derive(Component, Debug)]
+pub struct DockingJoint {
+    pub ports: [Entity; 2],
+    pub structures: [Entity; 2],
+}
+
+/// Structures docked to this one, flown along with it.
+#[derive(Component, Debug, Default, Clone)]
+pub struct DockedStructures(pub Vec<Entity>);
+
+#[derive(Event, Debug)]
+pub struct DockedEvent {
+    pub structures: [Entity; 2],
+}
+
+#[derive(Event, Debug)]
+pub struct UndockedEvent {
+    pub structures: [Entity; 2],
+}
+
+fn attach_docking_port_system(port_query: Query<Entity, Added<DockingPortModule>>, mut commands: Commands) {
+    for entity in &port_query {
+        commands.entity(entity).insert(DockingPort::default());
+    }
+}
+
+/// Offset from a port to the cell next to it towards `target`, along the grid axis closest to the target.
+fn cell_offset_towards(port_local: Vec2, target_local: Vec2) -> Vec2 {
+    let delta = target_local - port_local;
+    let axis = if delta.x.abs() > delta.y.abs() { Vec2::X * delta.x.signum() } else { Vec2::Y * delta.y.signum() };
+    axis * STRUCTURE_CELL_SIZE
+}
+
+fn dock_ports_system(
+    mut ports_query: Query<(Entity, &Parent, &Transform, &GlobalTransform, &mut DockingPort)>,
+    mut structures_query: Query<(&Transform, &LinearVelocity, Option<&mut DockedStructures>), With<Structure>>,
+    mut docked_writer: EventWriter<DockedEvent>,
+    mut autosave_writer: EventWriter<AutosaveEvent>,
+    mut commands: Commands,
+) {
+    let free_ports: Vec<(Entity, Entity, Vec2, Vec2)> = ports_query
+        .iter()
+        .filter(|(.., port)| port.docked_to.is_none() && !port.disarmed)
+        .map(|(entity, parent, transform, global_transform, _)| {
+            (entity, parent.get(), transform.translation.truncate(), global_transform.translation().truncate())
+        })
+        .collect();
+
+    let mut docked_ports: Vec<Entity> = Vec::new();
+    for (i, &(port1, structure1, local1, world1)) in free_ports.iter().enumerate() {
+        for &(port2, structure2, local2, world2) in &free_ports[i + 1..] {
+            if structure1 == structure2
+                || docked_ports.contains(&port1)
+                || docked_ports.contains(&port2)
+                || world1.distance(world2) > DOCKING_DISTANCE
+            {
+                continue;
+            }
+            let Ok([(transform1, velocity1, _), (transform2, velocity2, _)]) =
+                structures_query.get_many([structure1, structure2])
+            else {
+                continue;
+            };
+            let rotation1 = transform1.rotation.to_euler(EulerRot::XYZ).2;
+            let rotation2 = transform2.rotation.to_euler(EulerRot::XYZ).2;
+            let angle =
+                (rotation1 - rotation2 + std::f32::consts::PI).rem_euclid(std::f32::consts::TAU) - std::f32::consts::PI;
+            if angle.abs() > DOCKING_MAX_ANGLE || velocity1.0.distance(velocity2.0) > DOCKING_MAX_SPEED {
+                continue;
+            }
+
+            // The ports meet halfway, the joint keeps the structures at the same rotation
+            let target1 = transform1.compute_affine().inverse().transform_point3(world2.extend(0.0)).truncate();
+            let offset = cell_offset_towards(local1, target1);
+            let joint = FixedJoint::new(structure1, structure2)
+                .with_local_anchor_1(local1 + offset / 2.0)
+                .with_local_anchor_2(local2 - offset / 2.0);
+            commands.spawn((
+                joint,
+                DockingJoint { ports: [port1, port2], structures: [structure1, structure2] },
+                Name::new("Docking joint"),
+            ));
+
+            for (port, other_port) in [(port1, port2), (port2, port1)] {
+                if let Ok((.., mut docking_port)) = ports_query.get_mut(port) {
+                    docking_port.docked_to = Some(other_port);
+                }
+            }
+            for (structure, partner) in [(structure1, structure2), (structure2, structure1)] {
+                match structures_query.get_mut(structure) {
+                    Ok((_, _, Some(mut docked))) => docked.0.push(partner),
+                    Ok((_, _, None)) => {
+                        commands.entity(structure).insert(DockedStructures(vec![partner]));
+                    }
+                    Err(_) => {}
+                }
+            }
+
+            docked_ports.extend([port1, port2]);
+            debug!("Structures {:?} and {:?} docked", structure1, structure2);
+            docked_writer.send(DockedEvent { structures: [structure1, structure2] });
+            autosave_writer.send(AutosaveEvent);
+        }
+    }
+}
+
+/// Splits the two structures held by a docking joint.
+fn undock(
+    joint_entity: Entity,
+    joint: &DockingJoint,
+    ports_query: &mut Query<&mut DockingPort>,
+    docked_query: &mut Query<(&mut DockedStructures, &mut ExternalForce)>,
+    commands: &mut Commands,
+) {
+    commands.entity(joint_entity).despawn_recursive();
+    for port in joint.ports {
+        if let Ok(mut docking_port) = ports_query.get_mut(port) {
+            docking_port.docked_to = None;
+            docking_port.disarmed = true;
+        }
+    }
+    for (structure, partner) in [(joint.structures[0], joint.structures[1]), (joint.structures[1], joint.structures[0])]
+    {
+        if let Ok((mut docked, mut external_force)) = docked_query.get_mut(structure) {
+            docked.0.retain(|docked_structure| *docked_structure != partner);
+            if docked.0.is_empty() {
+                commands.entity(structure).remove::<DockedStructures>();
+            }
+            // The thrust given while flown together must not keep pushing it
+            external_force.clear();
+        }
+    }
+}
+
+/// Undocks when a port or one of the structures is destroyed.
+fn release_broken_docks_system(
+    joints_query: Query<(Entity, &DockingJoint)>,
+    mut ports_query: Query<&mut DockingPort>,
+    mut docked_query: Query<(&mut DockedStructures, &mut ExternalForce)>,
+    mut undocked_writer: EventWriter<UndockedEvent>,
+    mut commands: Commands,
+) {
+    for (joint_entity, joint) in &joints_query {
+        if joint.ports.iter().all(|port| ports_query.contains(*port)) {
+            continue;
+        }
+        undock(joint_entity, joint, &mut ports_query, &mut docked_query, &mut commands);
+        undocked_writer.send(UndockedEvent { structures: joint.structures });
+    }
+}
+
+fn rearm_ports_system(mut ports_query: Query<(Entity, &GlobalTransform, &mut DockingPort)>) {
+    let positions: Vec<(Entity, Vec2)> =
+        ports_query.iter().map(|(entity, transform, _)| (entity, transform.translation().truncate())).collect();
+
+    for (entity, transform, mut port) in &mut ports_query {
+        if !port.disarmed {
+            continue;
+        }
+        let position = transform.translation().truncate();
+        let clear = positions
+            .iter()
+            .all(|(other, other_position)| *other == entity || position.distance(*other_position) > REARM_DISTANCE);
+        if clear {
+            port.disarmed = false;
+        }
+    }
+}
+
+/// Undocks the structures when the player presses space standing on a docked port.
+fn undock_interaction_system(
+    mut input_reader: EventReader<InputAction>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    player_resource: Res<PlayerResource>,
+    structures_query: Query<(&Transform, &Structure)>,
+    port_modules_query: Query<(&Module, &Interactable), With<DockingPortModule>>,
+    joints_query: Query<(Entity, &DockingJoint)>,
+    mut ports_query: Query<&mut DockingPort>,
+    mut docked_query: Query<(&mut DockedStructures, &mut ExternalForce)>,
+    mut undocked_writer: EventWriter<UndockedEvent>,
+    mut commands: Commands,
+) {
+    let space_pressed = input_reader.read().any(|event| matches!(event, InputAction::SpacePressed));
+    if !space_pressed || player_resource.is_controlling_structure {
+        return;
+    }
+    let (Ok(player_transform), Some(structure_entity)) = (player_query.get_single(), player_resource.inside_structure)
+    else {
+        return;
+    };
+    let Ok((structure_transform, structure)) = structures_query.get(structure_entity) else {
+        return;
+    };
+
+    let player_cell = structure.world_to_grid(player_transform.translation(), structure_transform);
+    let Some(port_entity) = structure.module_at(player_cell) else {
+        return;
+    };
+    let Ok((module, interactable)) = port_modules_query.get(port_entity) else {
+        return;
+    };
+    if !interactable.is_reachable_from(module, player_cell) {
+        return;
+    }
+
+    if let Some((joint_entity, joint)) = joints_query.iter().find(|(_, joint)| joint.ports.contains(&port_entity)) {
+        undock(joint_entity, joint, &mut ports_query, &mut docked_query, &mut commands);
+        debug!("Structures {:?} and {:?} undocked", joint.structures[0], joint.structures[1]);
+        undocked_writer.send(UndockedEvent { structures: joint.structures });
+    }
+}
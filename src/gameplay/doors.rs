@@ -0,0 +1,155 @@
+use crate::core::prelude::*;
+use crate::core::profiling::{ProfileScope, DOORS};
+use crate::world::prelude::*;
+
+use crate::prelude::*;
+use bevy::diagnostic::Diagnostics;
+
+const AIRLOCK_CYCLE_TIME: f32 = 3.0; // seconds before an open airlock closes itself
+const OPEN_DOOR_ALPHA: f32 = 0.25;
+
+pub struct DoorsPlugin;
+
+impl Plugin for DoorsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DoorToggledEvent>().add_systems(Update, attach_door_components_system).add_systems(
+            Update,
+            (
+                door_interaction_system,
+                airlock_auto_close_system,
+                door_state_system.run_if(on_event::<DoorToggledEvent>()),
+            )
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+/// A module that can be opened to walk through it.
+/// Closed doors seal rooms like any other module, open doors let the air through.
+#[derive(Component, Debug, Default)]
+pub struct Door {
+    pub open: bool,
+}
+
+/// A door that closes itself after a while and is designed to be opened to space without
+/// explosively venting the room behind it.
+#[derive(Component, Debug)]
+pub struct Airlock {
+    pub cycle_timer: Timer,
+}
+
+#[derive(Event, Debug)]
+pub struct DoorToggledEvent {
+    pub door_entity: Entity,
+}
+
+fn attach_door_components_system(
+    door_query: Query<Entity, Added<DoorModule>>,
+    airlock_query: Query<Entity, Added<AirlockModule>>,
+    mut commands: Commands,
+) {
+    for entity in &door_query {
+        commands.entity(entity).insert(Door::default());
+    }
+    for entity in &airlock_query {
+        commands.entity(entity).insert((
+            Door::default(),
+            Airlock { cycle_timer: Timer::from_seconds(AIRLOCK_CYCLE_TIME, TimerMode::Once) },
+        ));
+    }
+}
+
+/// Toggles the doors next to the player when space is pressed.
+fn door_interaction_system(
+    mut input_reader: EventReader<InputAction>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    structures_query: Query<(&Transform, &Structure, &Children)>,
+    mut door_query: Query<(&Module, &Interactable, &mut Door)>,
+    player_resource: Res<PlayerResource>,
+    mut event_writer: EventWriter<DoorToggledEvent>,
+) {
+    let space_pressed = input_reader.read().any(|event| matches!(event, InputAction::SpacePressed));
+    if !space_pressed || player_resource.is_controlling_structure {
+        return;
+    }
+
+    for player_transform in &player_query {
+        for (structure_transform, structure, children) in &structures_query {
+            // Not bounded to the grid, so doors on the hull can be opened from outside
+            let (player_x, player_y) = structure.world_to_grid(player_transform.translation(), structure_transform);
+
+            for child in children {
+                if let Ok((module, interactable, mut door)) = door_query.get_mut(*child) {
+                    if interactable.is_reachable_from(module, (player_x, player_y)) {
+                        door.open = !door.open;
+                        event_writer.send(DoorToggledEvent { door_entity: *child });
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn airlock_auto_close_system(
+    mut airlock_query: Query<(Entity, &mut Door, &mut Airlock)>,
+    time: Res<Time>,
+    mut event_writer: EventWriter<DoorToggledEvent>,
+) {
+    for (entity, mut door, mut airlock) in &mut airlock_query {
+        if !door.open {
+            airlock.cycle_timer.reset();
+            continue;
+        }
+        if airlock.cycle_timer.tick(time.delta()).just_finished() {
+            door.open = false;
+            event_writer.send(DoorToggledEvent { door_entity: entity });
+        }
+    }
+}
+
+/// Applies the new door state to the collider, the visuals and the structure rooms.
+fn door_state_system(
+    mut event_reader: EventReader<DoorToggledEvent>,
+    door_query: Query<(&Door, &Module, &Parent, &Handle<ColorMaterial>, Option<&Airlock>)>,
+    mut structures_query: Query<(&mut Structure, &mut Pressurization)>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut depressurization_writer: EventWriter<StructureDepressurizationEvent>,
+    mut commands: Commands,
+    mut diagnostics: Diagnostics,
+) {
+    let scope = ProfileScope::enter(&DOORS);
+    for event in event_reader.read() {
+        let Ok((door, module, parent, material_handle, airlock)) = door_query.get(event.door_entity) else {
+            continue;
+        };
+        let Ok((mut structure, mut pressurization)) = structures_query.get_mut(parent.get()) else {
+            continue;
+        };
+
+        let (x, y) = module.inner_grid_pos;
+        if door.open {
+            // An open door is a sensor, it keeps its shape but nothing collides with it
+            commands.entity(event.door_entity).insert(Sensor);
+            structure.grid.set_cell_type_to_empty(x, y);
+        } else {
+            commands.entity(event.door_entity).remove::<Sensor>();
+            structure.grid.insert(x, y, CellType::Module);
+        }
+
+        if let Some(material) = materials.get_mut(material_handle) {
+            material.color.set_alpha(if door.open { OPEN_DOOR_ALPHA } else { 1.0 });
+        }
+
+        let rooms = structure.check_pressurization();
+        let breached_cells = pressurization.update_rooms(rooms);
+
+        // Airlocks let the air out slowly, plain doors open to space explosively vent the room
+        if !breached_cells.is_empty() && airlock.is_none() {
+            depressurization_writer
+                .send(StructureDepressurizationEvent { depressurized_structure: parent.get(), breached_cells });
+        }
+        debug!("Door at ({}, {}) is now {}", x, y, if door.open { "open" } else { "closed" });
+    }
+    scope.finish(&mut diagnostics);
+}
@@ -0,0 +1,499 @@
+use crate::core::asset_loader::{ModuleOrientation, StructureData, StructuresData};
+use crate::core::persistence::{write_atomic, PersistenceError};
+use crate::core::prelude::*;
+use crate::gameplay::blueprints::BlueprintAnalyzer;
+use crate::gameplay::building::PLACEABLE_MODULES;
+use crate::gameplay::factions::builtin_module_color;
+use crate::gameplay::livery::Livery;
+use crate::gameplay::movement::MovementSettings;
+use crate::world::prelude::*;
+
+use crate::prelude::*;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use thiserror::Error;
+
+const EDITOR_TOGGLE_KEY: KeyCode = KeyCode::F11;
+const EDITOR_CANVAS_CENTER: Vec2 = Vec2::new(-20_000.0, 20_000.0); // far from everything else in the world
+const EDITOR_GRID_SIZE: u32 = 16; // cells on each side of the canvas
+const EDITOR_GRID_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.15);
+const EDITOR_FONT_SIZE: f32 = 16.0;
+pub const DESIGNS_DIRECTORY: &str = "designs";
+
+/// Structure editor: press F11 while playing to design a structure on an empty grid, the world is frozen meanwhile.
+/// Left click places the selected module, right click deletes it, Tab cycles the modules, Q/E rotate them and Ctrl+S
+/// saves the design as a structures data file in the `designs` directory, once it has a Command Center and all its
+/// modules are connected. A panel on the right shows the mass, cost, thrust, power and rooms of the design. F11 or
+/// Escape goes back to the game.
+pub struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EditorDesign>()
+            .init_resource::<EditorTool>()
+            .add_systems(Update, enter_editor_system.run_if(in_state(GameState::InGame)))
+            .add_systems(OnEnter(GameState::Editor), enter_editor)
+            .add_systems(OnExit(GameState::Editor), exit_editor)
+            .add_systems(
+                Update,
+                (
+                    exit_editor_system,
+                    editor_tool_input_system,
+                    edit_design_system,
+                    save_design_system,
+                    draw_design_system,
+                    update_editor_status_system,
+                    update_blueprint_panel_system,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Editor)),
+            );
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum DesignError {
+    #[error("The design has no module")]
+    Empty,
+    #[error("The design needs a Command Center")]
+    MissingCommandCenter,
+    #[error("The module at {0:?} is not connected to the rest of the design")]
+    Disconnected((i32, i32)),
+}
+
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum SaveDesignError {
+    #[error(transparent)]
+    Invalid(#[from] DesignError),
+    #[error("Could not write the design: {0}")]
+    Write(#[from] PersistenceError),
+    #[error("Could not serialize the design: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// A module of the design, with its quarter turns counterclockwise.
+/// A module covering several cells is in each of them, with the same top left `anchor` cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DesignModule {
+    pub module_type: ModuleType,
+    pub rotation: u8,
+    pub anchor: (i32, i32),
+}
+
+/// The structure being designed, kept between two editor sessions.
+#[derive(Resource, Debug, Default, Clone)]
+pub struct EditorDesign {
+    pub modules: HashMap<(i32, i32), DesignModule>,
+}
+
+impl EditorDesign {
+    /// Checks the design has a Command Center and its modules form a single piece.
+    pub fn validate(&self) -> Result<(), DesignError> {
+        let Some(&start) = self.modules.keys().min() else {
+            return Err(DesignError::Empty);
+        };
+        if !self.modules.values().any(|module| module.module_type == ModuleType::CommandCenter) {
+            return Err(DesignError::MissingCommandCenter);
+        }
+
+        let mut connected = HashSet::from([start]);
+        let mut queue = VecDeque::from([start]);
+        while let Some((x, y)) = queue.pop_front() {
+            for next in [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)] {
+                if self.modules.contains_key(&next) && connected.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        let mut disconnected: Vec<(i32, i32)> =
+            self.modules.keys().filter(|cell| !connected.contains(cell)).copied().collect();
+        disconnected.sort_unstable();
+        disconnected.first().map_or(Ok(()), |cell| Err(DesignError::Disconnected(*cell)))
+    }
+
+    /// Removes the module in a cell, with every other cell it covers.
+    pub fn remove_module_at(&mut self, cell: (i32, i32)) {
+        if let Some(removed) = self.modules.remove(&cell) {
+            self.modules.retain(|_, module| module.anchor != removed.anchor);
+        }
+    }
+
+    /// The design in the data file format, cropped to its modules and surrounded by a row of empty cells so its
+    /// rooms can be sealed.
+    pub fn to_structure_data(&self) -> StructureData {
+        let min_x = self.modules.keys().map(|(x, _)| *x).min().unwrap_or_default();
+        let min_y = self.modules.keys().map(|(_, y)| *y).min().unwrap_or_default();
+        let max_x = self.modules.keys().map(|(x, _)| *x).max().unwrap_or_default();
+        let max_y = self.modules.keys().map(|(_, y)| *y).max().unwrap_or_default();
+        let symbols = self
+            .modules
+            .iter()
+            .map(|((x, y), module)| ((x - min_x + 1, y - min_y + 1), module.module_type.symbol()))
+            .collect();
+        let mut orientations: Vec<ModuleOrientation> = self
+            .modules
+            .iter()
+            .filter(|(cell, module)| module.anchor == **cell)
+            .map(|((x, y), module)| ModuleOrientation {
+                cell: (x - min_x + 1, y - min_y + 1),
+                facing: Orientation::from_quarter_turns(module.rotation),
+            })
+            .filter(|oriented| oriented.facing != Orientation::North)
+            .collect();
+        orientations.sort_unstable_by_key(|oriented| (oriented.cell.1, oriented.cell.0));
+
+        StructureData {
+            world_pos: [0.0, 0.0],
+            structure: layout_rows((max_x - min_x + 3) as u32, (max_y - min_y + 3) as u32, &symbols),
+            orientations,
+            crew: 0,
+            rotation: 0.0,
+            velocity: [0.0, 0.0],
+            livery: Livery::default(),
+            faction: None,
+        }
+    }
+}
+
+/// The module placed by a click in the editor.
+#[derive(Resource, Debug, Default)]
+pub struct EditorTool {
+    /// Index in the modules of `editor_modules`.
+    pub selected: usize,
+    pub rotation: u8,
+    /// Result of the last save, shown under the design.
+    pub status: Option<String>,
+}
+
+/// Where the camera was before entering the editor.
+#[derive(Resource, Debug)]
+struct EditorReturn {
+    camera_translation: Vec3,
+}
+
+/// Entities drawing the design, redrawn whenever it changes.
+#[derive(Component, Debug, Default)]
+pub struct EditorEntity;
+
+#[derive(Component)]
+struct EditorStatusText;
+
+#[derive(Component)]
+struct EditorBlueprintText;
+
+/// Every module the editor can place: the Command Center and the medical bay, the modules of the build mode, and the
+/// module types registered by plugins.
+pub fn editor_modules(module_registry: &ModuleRegistry) -> Vec<(ModuleType, Color)> {
+    let mut modules: Vec<(ModuleType, Color)> = [ModuleType::CommandCenter, ModuleType::MedicalBay]
+        .into_iter()
+        .map(|module_type| (module_type, builtin_module_color(module_type)))
+        .chain(PLACEABLE_MODULES.iter().map(|placeable| (placeable.module_type, placeable.color)))
+        .collect();
+    let mut registered: Vec<&ModuleDefinition> = module_registry.definitions().collect();
+    registered.sort_by_key(|definition| definition.symbol);
+    modules.extend(registered.into_iter().map(|definition| (ModuleType::Custom(definition.symbol), definition.color)));
+    modules
+}
+
+fn canvas_origin() -> Vec2 {
+    EDITOR_CANVAS_CENTER - Vec2::splat(EDITOR_GRID_SIZE as f32 * STRUCTURE_CELL_SIZE / 2.0)
+}
+
+/// World position of the center of a canvas cell, the rows go down like in the data files.
+fn cell_center(cell: (i32, i32)) -> Vec2 {
+    let top_left = canvas_origin() + Vec2::new(0.0, EDITOR_GRID_SIZE as f32 * STRUCTURE_CELL_SIZE);
+    top_left + Vec2::new(cell.0 as f32 + 0.5, -(cell.1 as f32 + 0.5)) * STRUCTURE_CELL_SIZE
+}
+
+fn cursor_cell(
+    windows_query: &Query<&Window>,
+    camera_query: &Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+) -> Option<(i32, i32)> {
+    let (camera, camera_transform) = camera_query.get_single().ok()?;
+    let cursor = windows_query
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor))?;
+
+    let offset = (cursor - canvas_origin()) / STRUCTURE_CELL_SIZE;
+    let cell = (offset.x.floor() as i32, EDITOR_GRID_SIZE as i32 - 1 - offset.y.floor() as i32);
+    let inside = (0..EDITOR_GRID_SIZE as i32).contains(&cell.0) && (0..EDITOR_GRID_SIZE as i32).contains(&cell.1);
+    inside.then_some(cell)
+}
+
+fn enter_editor_system(keys: Res<ButtonInput<KeyCode>>, mut next_state: ResMut<NextState<GameState>>) {
+    if keys.just_pressed(EDITOR_TOGGLE_KEY) {
+        next_state.set(GameState::Editor);
+    }
+}
+
+fn exit_editor_system(keys: Res<ButtonInput<KeyCode>>, mut next_state: ResMut<NextState<GameState>>) {
+    if keys.any_just_pressed([EDITOR_TOGGLE_KEY, KeyCode::Escape]) {
+        next_state.set(GameState::InGame);
+    }
+}
+
+fn enter_editor(
+    mut camera_query: Query<&mut Transform, With<Camera2d>>,
+    mut physics_time: ResMut<Time<Physics>>,
+    mut design: ResMut<EditorDesign>,
+    mut commands: Commands,
+) {
+    physics_time.pause();
+    if let Ok(mut camera_transform) = camera_query.get_single_mut() {
+        commands.insert_resource(EditorReturn { camera_translation: camera_transform.translation });
+        camera_transform.translation = EDITOR_CANVAS_CENTER.extend(camera_transform.translation.z);
+    }
+    // Draws the design kept from the last session
+    design.set_changed();
+
+    commands.spawn((
+        EditorEntity,
+        EditorStatusText,
+        TextBundle::from_section("", TextStyle { font_size: EDITOR_FONT_SIZE, color: Color::WHITE, ..default() })
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.0),
+                left: Val::Percent(35.0),
+                ..default()
+            }),
+    ));
+    commands.spawn((
+        EditorEntity,
+        EditorBlueprintText,
+        TextBundle::from_section("", TextStyle { font_size: EDITOR_FONT_SIZE, color: Color::WHITE, ..default() })
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.0),
+                right: Val::Px(10.0),
+                ..default()
+            }),
+    ));
+}
+
+fn exit_editor(
+    mut camera_query: Query<&mut Transform, With<Camera2d>>,
+    editor_return: Option<Res<EditorReturn>>,
+    mut physics_time: ResMut<Time<Physics>>,
+    editor_query: Query<Entity, With<EditorEntity>>,
+    mut commands: Commands,
+) {
+    physics_time.unpause();
+    if let (Ok(mut camera_transform), Some(editor_return)) = (camera_query.get_single_mut(), editor_return) {
+        camera_transform.translation = editor_return.camera_translation;
+    }
+    commands.remove_resource::<EditorReturn>();
+    for entity in &editor_query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn editor_tool_input_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    module_registry: Res<ModuleRegistry>,
+    mut tool: ResMut<EditorTool>,
+) {
+    if keys.just_pressed(KeyCode::Tab) {
+        tool.selected = (tool.selected + 1) % editor_modules(&module_registry).len();
+    }
+    if keys.just_pressed(KeyCode::KeyQ) {
+        tool.rotation = (tool.rotation + 1) % 4;
+    }
+    if keys.just_pressed(KeyCode::KeyE) {
+        tool.rotation = (tool.rotation + 3) % 4;
+    }
+}
+
+/// Places the selected module with a left click and deletes the clicked one with a right click.
+fn edit_design_system(
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    windows_query: Query<&Window>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    module_registry: Res<ModuleRegistry>,
+    tool: Res<EditorTool>,
+    mut design: ResMut<EditorDesign>,
+) {
+    let place = mouse_buttons.just_pressed(MouseButton::Left);
+    let delete = mouse_buttons.just_pressed(MouseButton::Right);
+    if !place && !delete {
+        return;
+    }
+    let Some(cell) = cursor_cell(&windows_query, &camera_query) else {
+        return;
+    };
+
+    if delete {
+        design.remove_module_at(cell);
+    } else {
+        let modules = editor_modules(&module_registry);
+        let (module_type, _) = modules[tool.selected % modules.len()];
+        let orientation = Orientation::from_quarter_turns(tool.rotation);
+        let cells: Vec<(i32, i32)> = module_registry.footprint(module_type.symbol()).cells(cell, orientation).collect();
+        let fits = cells
+            .iter()
+            .all(|(x, y)| (0..EDITOR_GRID_SIZE as i32).contains(x) && (0..EDITOR_GRID_SIZE as i32).contains(y));
+        if !fits {
+            return;
+        }
+        // The placed module replaces every module it overlaps
+        for covered in &cells {
+            design.remove_module_at(*covered);
+        }
+        for covered in cells {
+            design.modules.insert(covered, DesignModule { module_type, rotation: tool.rotation, anchor: cell });
+        }
+    }
+}
+
+/// Saves the design with Ctrl+S as a structures data file, which can be set as the structures file of the settings.
+fn save_design_system(keys: Res<ButtonInput<KeyCode>>, design: Res<EditorDesign>, mut tool: ResMut<EditorTool>) {
+    if !keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) || !keys.just_pressed(KeyCode::KeyS) {
+        return;
+    }
+
+    let status = match save_design(&design) {
+        Ok(path) => {
+            info!("Design saved to {}", path.display());
+            format!("Saved to {}", path.display())
+        }
+        Err(error) => {
+            warn!("Design not saved: {}", error);
+            format!("Not saved: {error}")
+        }
+    };
+    tool.status = Some(status);
+}
+
+fn save_design(design: &EditorDesign) -> Result<PathBuf, SaveDesignError> {
+    design.validate()?;
+    let data = StructuresData { structures: vec![design.to_structure_data()] };
+    let json = serde_json::to_vec_pretty(&data)?;
+
+    let seconds = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs());
+    let path = PathBuf::from(DESIGNS_DIRECTORY).join(format!("design_{seconds}.json"));
+    write_atomic(&path, &json)?;
+    Ok(path)
+}
+
+/// Draws the canvas grid every frame, and respawns the module sprites when the design changes.
+fn draw_design_system(
+    mut gizmos: Gizmos,
+    design: Res<EditorDesign>,
+    module_registry: Res<ModuleRegistry>,
+    sprites_query: Query<Entity, (With<EditorEntity>, With<Sprite>)>,
+    mut commands: Commands,
+) {
+    gizmos.grid_2d(
+        EDITOR_CANVAS_CENTER,
+        0.0,
+        UVec2::splat(EDITOR_GRID_SIZE),
+        Vec2::splat(STRUCTURE_CELL_SIZE),
+        EDITOR_GRID_COLOR,
+    );
+    if !design.is_changed() {
+        return;
+    }
+
+    for sprite_entity in &sprites_query {
+        commands.entity(sprite_entity).despawn_recursive();
+    }
+    let modules = editor_modules(&module_registry);
+    let size = Vec2::splat(STRUCTURE_CELL_SIZE * MODULE_MESH_SCALE_FACTOR);
+    for (cell, module) in &design.modules {
+        let color = modules
+            .iter()
+            .find(|(module_type, _)| *module_type == module.module_type)
+            .map_or(Color::from(GREY), |(_, color)| *color);
+        let rotation = Quat::from_rotation_z(module.rotation as f32 * std::f32::consts::FRAC_PI_2);
+        commands
+            .spawn((
+                EditorEntity,
+                SpriteBundle {
+                    sprite: Sprite { color, custom_size: Some(size), ..default() },
+                    transform: Transform::from_translation(cell_center(*cell).extend(1.0)).with_rotation(rotation),
+                    ..default()
+                },
+            ))
+            .with_children(|sprite| {
+                // Arrow showing the facing of the module, like the build mode ghost
+                sprite.spawn(SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::srgba(1.0, 1.0, 1.0, 0.6),
+                        custom_size: Some(Vec2::new(size.x * 0.15, size.y * 0.5)),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(0.0, size.y * 0.25, 0.1),
+                    ..default()
+                });
+            });
+    }
+}
+
+fn update_editor_status_system(
+    design: Res<EditorDesign>,
+    tool: Res<EditorTool>,
+    module_registry: Res<ModuleRegistry>,
+    mut text_query: Query<&mut Text, With<EditorStatusText>>,
+) {
+    if !design.is_changed() && !tool.is_changed() {
+        return;
+    }
+    let Ok(mut text) = text_query.get_single_mut() else {
+        return;
+    };
+
+    let modules = editor_modules(&module_registry);
+    let (module_type, _) = modules[tool.selected % modules.len()];
+    let name = match module_type {
+        ModuleType::Custom(symbol) => module_registry.get(symbol).map_or("Module", |definition| definition.name),
+        _ => module_type.name(),
+    };
+    let validity = match design.validate() {
+        Ok(()) => "Ready to save".to_string(),
+        Err(error) => error.to_string(),
+    };
+    text.sections[0].value = format!(
+        "Editor - {name}, facing {}°\n{validity}\n{}",
+        tool.rotation as u32 * 90,
+        tool.status.as_deref().unwrap_or("Tab: module, Q/E: rotate, Ctrl+S: save, F11: back to the game"),
+    );
+}
+
+fn update_blueprint_panel_system(
+    design: Res<EditorDesign>,
+    module_registry: Res<ModuleRegistry>,
+    movement_settings: Res<MovementSettings>,
+    mut text_query: Query<&mut Text, With<EditorBlueprintText>>,
+) {
+    let Ok(mut text) = text_query.get_single_mut() else {
+        return;
+    };
+    // The panel is spawned after the design was marked changed on entering the editor
+    if !design.is_changed() && !text.sections[0].value.is_empty() {
+        return;
+    }
+
+    let analyzer = BlueprintAnalyzer::new(&module_registry, movement_settings.engine_thrust);
+    let report = analyzer.analyze(&design.to_structure_data());
+    let mut materials: Vec<String> = report
+        .materials
+        .iter()
+        .map(|(material_type, cost)| format!("  {material_type:?}: {} modules, {:.0} kg", cost.modules, cost.mass))
+        .collect();
+    materials.sort_unstable();
+    text.sections[0].value = format!(
+        "Blueprint\nModules: {}\nMass: {:.0} kg\n{}\nCost: {:.0} scrap\nThrust: {:.0} kN ({:.1} m/s²)\nPower: {:.0} / {:.0} ({:+.0})\nSealed rooms: {}",
+        report.modules,
+        report.mass,
+        materials.join("\n"),
+        report.scrap_cost,
+        report.thrust / 1000.0,
+        report.thrust_to_mass(),
+        report.power_demanded,
+        report.power_produced,
+        report.power_balance(),
+        report.pressurizable_rooms,
+    );
+}
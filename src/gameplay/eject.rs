@@ -0,0 +1,139 @@
+use crate::core::prelude::*;
+use crate::gameplay::movement::structure_point_velocity;
+use crate::world::prelude::*;
+
+use crate::prelude::*;
+
+const EJECT_KICK_SPEED: f32 = 8.0; // m/s away from the hull, on top of the velocity of the structure
+const EJECT_INVULNERABILITY: f32 = 3.0; // seconds
+
+/// Abandoning ship: the eject input releases the controlled structure at once and throws the player to the nearest
+/// pressurized cell or out of the hull, whichever is closer, pushing them away from the structure. The player cannot
+/// be hurt for a few seconds, enough to get clear of a ship about to be destroyed.
+pub struct EjectPlugin;
+
+impl Plugin for EjectPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PlayerEjectedEvent>()
+            .add_systems(Update, (eject_system, invulnerability_system).chain().run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// Sent when the player abandons the structure they were flying.
+#[derive(Event, Debug)]
+pub struct PlayerEjectedEvent {
+    pub player_entity: Entity,
+    pub structure_entity: Entity,
+}
+
+/// Characters that cannot be injured until the timer finishes.
+#[derive(Component, Debug, Deref, DerefMut)]
+pub struct Invulnerable(pub Timer);
+
+impl Invulnerable {
+    pub fn new(duration: f32) -> Self {
+        Self(Timer::from_seconds(duration, TimerMode::Once))
+    }
+}
+
+/// Closest cell of the structure to escape to, from the player cell: a breathable cell or a cell just outside the
+/// grid.
+fn escape_cell(structure: &Structure, pressurization: Option<&Pressurization>, from: (i32, i32)) -> (i32, i32) {
+    let width = structure.grid.width as i32;
+    let height = structure.grid.height as i32;
+    let exterior_cells =
+        (-1..=width).flat_map(|x| [(x, -1), (x, height)]).chain((0..height).flat_map(|y| [(-1, y), (width, y)]));
+    let pressurized_cells = pressurization
+        .into_iter()
+        .flat_map(|pressurization| pressurization.oxygen.keys().filter(|cell| pressurization.is_breathable(**cell)))
+        .copied();
+
+    let distance = |cell: &(i32, i32)| (cell.0 - from.0).pow(2) + (cell.1 - from.1).pow(2);
+    pressurized_cells.chain(exterior_cells).filter(|cell| *cell != from).min_by_key(distance).unwrap_or((-1, -1))
+}
+
+fn eject_system(
+    mut input_reader: EventReader<InputAction>,
+    controlled_query: Query<(
+        Entity,
+        &ControlledByPlayer,
+        &Structure,
+        &Transform,
+        &LinearVelocity,
+        &AngularVelocity,
+        &CenterOfMass,
+        Option<&Pressurization>,
+    )>,
+    players_query: Query<&GlobalTransform, With<Player>>,
+    mut modules_query: Query<&mut Module>,
+    mut ejected_writer: EventWriter<PlayerEjectedEvent>,
+    mut player_resource: ResMut<PlayerResource>,
+    mut commands: Commands,
+) {
+    if !input_reader.read().any(|event| matches!(event, InputAction::Eject)) {
+        return;
+    }
+    let Ok((
+        structure_entity,
+        controlled_by,
+        structure,
+        structure_transform,
+        linear_velocity,
+        angular_velocity,
+        center_of_mass,
+        pressurization,
+    )) = controlled_query.get_single()
+    else {
+        return;
+    };
+    let player_entity = controlled_by.player_entity;
+    let Ok(player_transform) = players_query.get(player_entity) else {
+        return;
+    };
+
+    // Release the control like leaving the Command Center
+    for mut module in &mut modules_query {
+        if module.entity_connected == Some(player_entity) {
+            module.entity_connected = None;
+        }
+    }
+    commands.entity(structure_entity).remove::<ControlledByPlayer>();
+    player_resource.is_controlling_structure = false;
+
+    let player_cell = structure.world_to_grid(player_transform.translation(), structure_transform);
+    let cell = escape_cell(structure, pressurization, player_cell);
+    let position =
+        structure_transform.transform_point(structure.grid_cell_center_local_position(cell.0, cell.1).extend(0.0));
+
+    // Thrown away from the center of the hull, along with the structure
+    let away = (position - structure_transform.translation).truncate().normalize_or_zero();
+    let velocity = structure_point_velocity(
+        linear_velocity,
+        angular_velocity,
+        structure_transform,
+        center_of_mass,
+        position.truncate(),
+    ) + away * EJECT_KICK_SPEED;
+
+    // The pilot is carried by the structure as its child, their new transform is already in world space
+    commands.entity(player_entity).remove_parent().insert((
+        Transform::from_translation(position.truncate().extend(player_transform.translation().z)),
+        RigidBody::Dynamic,
+        LinearVelocity(velocity),
+        Invulnerable::new(EJECT_INVULNERABILITY),
+    ));
+    info!("Player ejected from structure {:?} to cell {:?}", structure_entity, cell);
+    ejected_writer.send(PlayerEjectedEvent { player_entity, structure_entity });
+}
+
+fn invulnerability_system(
+    mut invulnerable_query: Query<(Entity, &mut Invulnerable)>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, mut invulnerable) in &mut invulnerable_query {
+        if invulnerable.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Invulnerable>();
+        }
+    }
+}
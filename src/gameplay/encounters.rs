@@ -0,0 +1,260 @@
+use crate::core::asset_loader::{DataAssetLoader, StructureData};
+use crate::core::prelude::*;
+use crate::gameplay::ai::{AiPilot, AiStance};
+use crate::gameplay::health::PlayerDiedEvent;
+use crate::gameplay::livery::Livery;
+use crate::gameplay::offscreen_battles::AlwaysSimulated;
+use crate::gameplay::scenario::ScenarioEndedEvent;
+use crate::world::prelude::*;
+
+use crate::prelude::*;
+use bevy::utils::HashMap;
+use serde::Deserialize;
+
+pub const ENCOUNTERS_PATH: &str = "data/encounters.ron";
+
+/// Combat encounters: press the start encounter key (F10 by default) while flying or aboard a structure to fight the
+/// waves of enemy structures read from `data/encounters.ron`. A wave is cleared once the Command Center of every ship
+/// in it is destroyed, clearing the last one wins the encounter and autosaves, losing the Command Center of the
+/// defended structure, or dying, loses it.
+pub struct EncounterPlugin;
+
+impl Plugin for EncounterPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Encounter>()
+            .init_asset::<EncounterData>()
+            .init_asset_loader::<DataAssetLoader<EncounterData>>()
+            .add_systems(Startup, load_encounter_data)
+            .add_systems(
+                Update,
+                (start_encounter_system, spawn_encounter_waves_system, track_encounter_system)
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+/// Enemy structure layout, spawned at the offset of every ship using it.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EncounterPrefab {
+    pub structure: Vec<String>,
+    #[serde(default)]
+    pub orientations: Vec<ModuleOrientation>,
+    #[serde(default)]
+    pub crew: u32,
+    #[serde(default)]
+    pub livery: Livery,
+    #[serde(default)]
+    pub faction: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EncounterShip {
+    pub prefab: String,
+    /// Meters from the player when the wave spawns.
+    pub offset: Vec2,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EncounterWave {
+    /// Seconds between the previous wave being cleared, or the start of the encounter, and this wave.
+    pub delay: f32,
+    pub ships: Vec<EncounterShip>,
+}
+
+#[derive(Asset, TypePath, Debug, Clone, Deserialize)]
+pub struct EncounterData {
+    pub name: String,
+    pub prefabs: HashMap<String, EncounterPrefab>,
+    pub waves: Vec<EncounterWave>,
+}
+
+#[derive(Resource)]
+struct EncounterDataHandle(Handle<EncounterData>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EncounterState {
+    #[default]
+    Inactive,
+    /// Waiting for the delay of the next wave.
+    Incoming,
+    /// A wave is fighting.
+    Fighting,
+    Victory,
+    Defeat,
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct Encounter {
+    pub state: EncounterState,
+    /// Structure the player defends, the encounter is lost with its Command Center.
+    pub defended: Option<Entity>,
+    /// Waves spawned so far.
+    pub wave: usize,
+    /// Enemy structures of the current wave still able to fight.
+    pub enemies: Vec<Entity>,
+    wave_timer: Timer,
+}
+
+/// Structure spawned by an encounter wave.
+#[derive(Component, Debug)]
+pub struct EncounterEnemy;
+
+fn load_encounter_data(asset_server: Res<AssetServer>, mut commands: Commands) {
+    commands.insert_resource(EncounterDataHandle(asset_server.load(ENCOUNTERS_PATH)));
+}
+
+fn start_encounter_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut encounter: ResMut<Encounter>,
+    handle: Res<EncounterDataHandle>,
+    encounter_assets: Res<Assets<EncounterData>>,
+    controlled_query: Query<Entity, With<ControlledByPlayer>>,
+    player_resource: Res<PlayerResource>,
+) {
+    if !keys.just_pressed(bindings.start_encounter) {
+        return;
+    }
+    if matches!(encounter.state, EncounterState::Incoming | EncounterState::Fighting) {
+        info!("An encounter is already underway");
+        return;
+    }
+    let Some(data) = encounter_assets.get(&handle.0) else {
+        warn!("The encounters are not loaded yet");
+        return;
+    };
+    let Some(defended) = controlled_query.get_single().ok().or(player_resource.inside_structure) else {
+        info!("Board a structure to start an encounter");
+        return;
+    };
+    let Some(first_wave) = data.waves.first() else {
+        return;
+    };
+
+    *encounter = Encounter {
+        state: EncounterState::Incoming,
+        defended: Some(defended),
+        wave_timer: Timer::from_seconds(first_wave.delay, TimerMode::Once),
+        ..default()
+    };
+    info!("Encounter {} started, {} waves incoming", data.name, data.waves.len());
+}
+
+fn spawn_encounter_waves_system(
+    mut encounter: ResMut<Encounter>,
+    handle: Res<EncounterDataHandle>,
+    encounter_assets: Res<Assets<EncounterData>>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    time: Res<Time>,
+    mut commands: Commands,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    game_assets: Res<GameAssets>,
+    module_registry: Res<ModuleRegistry>,
+) {
+    if encounter.state != EncounterState::Incoming || !encounter.wave_timer.tick(time.delta()).finished() {
+        return;
+    }
+    let (Some(data), Some(defended)) = (encounter_assets.get(&handle.0), encounter.defended) else {
+        return;
+    };
+    let Some(wave) = data.waves.get(encounter.wave) else {
+        return;
+    };
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+
+    let center = player_transform.translation().truncate();
+    let mut enemies = Vec::new();
+    for ship in &wave.ships {
+        let Some(prefab) = data.prefabs.get(&ship.prefab) else {
+            warn!("Unknown encounter prefab {}", ship.prefab);
+            continue;
+        };
+        let position = center + ship.offset;
+        let structure_data = StructureData {
+            world_pos: position.to_array(),
+            structure: prefab.structure.clone(),
+            orientations: prefab.orientations.clone(),
+            crew: prefab.crew,
+            rotation: 0.0,
+            velocity: [0.0, 0.0],
+            livery: prefab.livery.clone(),
+            faction: prefab.faction.clone(),
+        };
+        let enemy = spawn_structure(&mut commands, &mut materials, &game_assets, &module_registry, &structure_data);
+        commands.entity(enemy).insert((
+            EncounterEnemy,
+            AiPilot::new(AiStance::Attack).with_target(defended),
+            // The outcome follows their Command Centers, they must not be resolved off-screen
+            AlwaysSimulated,
+        ));
+        enemies.push(enemy);
+    }
+
+    encounter.wave += 1;
+    encounter.enemies = enemies;
+    encounter.state = EncounterState::Fighting;
+    info!("Encounter wave {}/{} incoming with {} ships", encounter.wave, data.waves.len(), encounter.enemies.len());
+}
+
+/// Follows the destroyed Command Centers to clear the waves and to lose the defended structure.
+fn track_encounter_system(
+    mut destroyed_reader: EventReader<ModuleDestroyedEvent>,
+    mut died_reader: EventReader<PlayerDiedEvent>,
+    mut encounter: ResMut<Encounter>,
+    handle: Res<EncounterDataHandle>,
+    encounter_assets: Res<Assets<EncounterData>>,
+    command_centers_query: Query<&Parent, With<CommandCenterModule>>,
+    structures_query: Query<(), With<Structure>>,
+    mut ended_writer: EventWriter<ScenarioEndedEvent>,
+    mut autosave_writer: EventWriter<AutosaveEvent>,
+) {
+    let player_died = died_reader.read().count() > 0;
+    if !matches!(encounter.state, EncounterState::Incoming | EncounterState::Fighting) {
+        destroyed_reader.clear();
+        return;
+    }
+    let Some(data) = encounter_assets.get(&handle.0) else {
+        return;
+    };
+
+    let mut defended_lost =
+        player_died || encounter.defended.is_some_and(|defended| !structures_query.contains(defended));
+    for event in destroyed_reader.read() {
+        let Ok(parent) = command_centers_query.get(event.destroyed_entity) else {
+            continue;
+        };
+        if Some(parent.get()) == encounter.defended {
+            defended_lost = true;
+        }
+        encounter.enemies.retain(|enemy| *enemy != parent.get());
+    }
+    // Enemies removed by other means, like wrecked off-screen, are out of the fight too
+    encounter.enemies.retain(|enemy| structures_query.contains(*enemy));
+
+    let outcome = if defended_lost {
+        EncounterState::Defeat
+    } else if encounter.state == EncounterState::Fighting && encounter.enemies.is_empty() {
+        match data.waves.get(encounter.wave) {
+            Some(next_wave) => {
+                encounter.wave_timer = Timer::from_seconds(next_wave.delay, TimerMode::Once);
+                encounter.state = EncounterState::Incoming;
+                info!("Encounter wave {}/{} cleared", encounter.wave, data.waves.len());
+                return;
+            }
+            None => EncounterState::Victory,
+        }
+    } else {
+        return;
+    };
+
+    encounter.state = outcome;
+    let victory = outcome == EncounterState::Victory;
+    info!("Encounter {} {}", data.name, if victory { "won" } else { "lost" });
+    ended_writer.send(ScenarioEndedEvent { name: data.name.clone(), victory });
+    if victory {
+        autosave_writer.send(AutosaveEvent);
+    }
+}
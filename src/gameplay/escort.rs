@@ -0,0 +1,281 @@
+use crate::core::asset_loader::StructureData;
+use crate::core::prelude::*;
+use crate::gameplay::ai::{AiPilot, AiRoute, AiStance};
+use crate::gameplay::livery::Livery;
+use crate::gameplay::offscreen_battles::AlwaysSimulated;
+use crate::world::prelude::*;
+
+use crate::prelude::*;
+
+const ESCORT_START_KEY: KeyCode = KeyCode::F8;
+const FREIGHTER_LAYOUT: [&str; 5] = ["WWWW", "CQ#W", "W##W", "WRRW", "WEEW"];
+const PIRATE_LAYOUT: [&str; 3] = ["!W!", "CRW", "WEW"];
+const FREIGHTER_FACTION: &str = "traders";
+const PIRATE_FACTION: &str = "pirates";
+const FREIGHTER_SPAWN_OFFSET: Vec2 = Vec2::new(0.0, 60.0); // meters from the player
+const ROUTE_LEG: Vec2 = Vec2::new(300.0, 0.0); // meters between two waypoints
+const ROUTE_WAYPOINTS: usize = 3;
+const WAVES: u32 = 3;
+const WAVE_INTERVAL: f32 = 40.0; // seconds
+const PIRATES_PER_WAVE: u32 = 2; // first wave, each wave brings one more pirate
+const PIRATE_SPAWN_DISTANCE: f32 = 250.0; // meters from the freighter
+const MIN_ESCORTEE_INTEGRITY: f32 = 0.25; // fraction of the freighter structural points, the mission fails below
+
+/// Escort missions: press F8 to have a friendly freighter fly a route while waves of pirates attack it.
+/// The mission succeeds once the freighter reaches the end of its route with enough of its hull left.
+pub struct EscortPlugin;
+
+impl Plugin for EscortPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EscortMission>()
+            .add_systems(OnEnter(GameState::BuildingStructures), spawn_escort_hud)
+            .add_systems(
+                Update,
+                (
+                    start_escort_mission_system,
+                    spawn_pirate_waves_system,
+                    track_escort_mission_system,
+                    update_escort_hud_system,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EscortMissionState {
+    #[default]
+    Inactive,
+    Active,
+    Succeeded,
+    Failed,
+}
+
+#[derive(Resource, Debug)]
+pub struct EscortMission {
+    pub state: EscortMissionState,
+    pub escortee: Option<Entity>,
+    /// Fraction of its structural points the escortee has left.
+    pub integrity: f32,
+    pub waves_spawned: u32,
+    wave_timer: Timer,
+}
+
+impl Default for EscortMission {
+    fn default() -> Self {
+        Self {
+            state: EscortMissionState::default(),
+            escortee: None,
+            integrity: 1.0,
+            waves_spawned: 0,
+            wave_timer: Timer::from_seconds(WAVE_INTERVAL, TimerMode::Repeating),
+        }
+    }
+}
+
+/// The structure protected in an escort mission.
+#[derive(Component, Debug)]
+pub struct Escortee {
+    max_structural_points: f32,
+}
+
+/// Structure spawned by a pirate wave.
+#[derive(Component, Debug)]
+pub struct Pirate;
+
+#[derive(Component)]
+struct EscortHud;
+
+fn layout(rows: &[&str]) -> Vec<String> {
+    rows.iter().map(|row| row.to_string()).collect()
+}
+
+fn structural_points(children: Option<&Children>, modules_query: &Query<&ModuleMaterial>) -> f32 {
+    children.map_or(0.0, |children| {
+        modules_query.iter_many(children).map(|module_material| module_material.structural_points).sum()
+    })
+}
+
+fn start_escort_mission_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut mission: ResMut<EscortMission>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    mut commands: Commands,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    game_assets: Res<GameAssets>,
+    module_registry: Res<ModuleRegistry>,
+) {
+    if !keys.just_pressed(ESCORT_START_KEY) {
+        return;
+    }
+    if mission.state == EscortMissionState::Active {
+        info!("An escort mission is already underway");
+        return;
+    }
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+
+    let start = player_transform.translation().truncate() + FREIGHTER_SPAWN_OFFSET;
+    let freighter_data = StructureData {
+        world_pos: start.to_array(),
+        structure: layout(&FREIGHTER_LAYOUT),
+        orientations: Vec::new(),
+        crew: 2,
+        rotation: 0.0,
+        velocity: [0.0, 0.0],
+        livery: Livery::default(),
+        faction: Some(FREIGHTER_FACTION.to_string()),
+    };
+    let freighter = spawn_structure(&mut commands, &mut materials, &game_assets, &module_registry, &freighter_data);
+    let waypoints = (1..=ROUTE_WAYPOINTS).map(|leg| start + ROUTE_LEG * leg as f32).collect();
+    commands.entity(freighter).insert((
+        // Filled in once its modules are spawned
+        Escortee { max_structural_points: 0.0 },
+        AiPilot::new(AiStance::Idle),
+        AiRoute::new(waypoints),
+        // The mission follows its hull, it must not be resolved off-screen
+        AlwaysSimulated,
+    ));
+
+    *mission = EscortMission { state: EscortMissionState::Active, escortee: Some(freighter), ..default() };
+    info!("Escort mission started, protect the freighter until the end of its route");
+}
+
+fn spawn_pirate_waves_system(
+    mut mission: ResMut<EscortMission>,
+    escortee_query: Query<&Transform, With<Escortee>>,
+    time: Res<Time>,
+    mut commands: Commands,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    game_assets: Res<GameAssets>,
+    module_registry: Res<ModuleRegistry>,
+) {
+    if mission.state != EscortMissionState::Active || mission.waves_spawned >= WAVES {
+        return;
+    }
+    // The first wave comes right away
+    let first_wave = mission.waves_spawned == 0;
+    if !mission.wave_timer.tick(time.delta()).just_finished() && !first_wave {
+        return;
+    }
+    let Some(escortee) = mission.escortee else {
+        return;
+    };
+    let Ok(escortee_transform) = escortee_query.get(escortee) else {
+        return;
+    };
+
+    let pirates = PIRATES_PER_WAVE + mission.waves_spawned;
+    let center = escortee_transform.translation.truncate();
+    // Each wave comes from another side
+    let wave_angle = mission.waves_spawned as f32 * 2.0;
+    for i in 0..pirates {
+        let angle = wave_angle + (i as f32 - (pirates - 1) as f32 / 2.0) * 0.3;
+        let position = center + Vec2::from_angle(angle) * PIRATE_SPAWN_DISTANCE;
+        let pirate_data = StructureData {
+            world_pos: position.to_array(),
+            structure: layout(&PIRATE_LAYOUT),
+            orientations: Vec::new(),
+            crew: 0,
+            rotation: 0.0,
+            velocity: [0.0, 0.0],
+            livery: Livery::default(),
+            faction: Some(PIRATE_FACTION.to_string()),
+        };
+        let pirate = spawn_structure(&mut commands, &mut materials, &game_assets, &module_registry, &pirate_data);
+        commands.entity(pirate).insert((Pirate, AiPilot::new(AiStance::Attack).with_target(escortee)));
+    }
+
+    mission.waves_spawned += 1;
+    mission.wave_timer.reset();
+    info!("Pirate wave {}/{} incoming with {} ships", mission.waves_spawned, WAVES, pirates);
+}
+
+fn track_escort_mission_system(
+    mut mission: ResMut<EscortMission>,
+    mut escortee_query: Query<(&mut Escortee, Option<&Children>, Option<&AiRoute>)>,
+    mut pirates_query: Query<&mut AiPilot, With<Pirate>>,
+    modules_query: Query<&ModuleMaterial>,
+    mut autosave_writer: EventWriter<AutosaveEvent>,
+) {
+    if mission.state != EscortMissionState::Active {
+        return;
+    }
+    let Some(escortee) = mission.escortee else {
+        return;
+    };
+
+    let outcome = match escortee_query.get_mut(escortee) {
+        Ok((mut escortee, children, route)) => {
+            let structural_points = structural_points(children, &modules_query);
+            if escortee.max_structural_points <= 0.0 {
+                escortee.max_structural_points = structural_points;
+            }
+            mission.integrity = if escortee.max_structural_points > 0.0 {
+                (structural_points / escortee.max_structural_points).clamp(0.0, 1.0)
+            } else {
+                1.0
+            };
+
+            if mission.integrity < MIN_ESCORTEE_INTEGRITY {
+                Some(EscortMissionState::Failed)
+            } else if route.is_some_and(AiRoute::is_finished) {
+                Some(EscortMissionState::Succeeded)
+            } else {
+                None
+            }
+        }
+        // The freighter was destroyed
+        Err(_) => {
+            mission.integrity = 0.0;
+            Some(EscortMissionState::Failed)
+        }
+    };
+
+    let Some(outcome) = outcome else {
+        return;
+    };
+    mission.state = outcome;
+    if outcome == EscortMissionState::Succeeded {
+        // The pirates left give up on the convoy
+        for mut pilot in &mut pirates_query {
+            pilot.stance = AiStance::Flee;
+        }
+        info!("Escort mission succeeded, the freighter arrived with {:.0}% of its hull", mission.integrity * 100.0);
+        autosave_writer.send(AutosaveEvent);
+    } else {
+        info!("Escort mission failed, the freighter was lost");
+    }
+}
+
+fn spawn_escort_hud(mut commands: Commands) {
+    commands.spawn((
+        EscortHud,
+        TextBundle::from_section("", TextStyle { font_size: 18.0, color: Color::WHITE, ..default() }).with_style(
+            Style { position_type: PositionType::Absolute, top: Val::Px(10.0), left: Val::Percent(40.0), ..default() },
+        ),
+    ));
+}
+
+fn update_escort_hud_system(mission: Res<EscortMission>, mut hud_query: Query<&mut Text, With<EscortHud>>) {
+    if !mission.is_changed() {
+        return;
+    }
+    let Ok(mut text) = hud_query.get_single_mut() else {
+        return;
+    };
+
+    text.sections[0].value = match mission.state {
+        EscortMissionState::Inactive => String::new(),
+        EscortMissionState::Active => format!(
+            "Escort the freighter - hull {:.0}% - wave {}/{}",
+            mission.integrity * 100.0,
+            mission.waves_spawned,
+            WAVES
+        ),
+        EscortMissionState::Succeeded => "Escort mission succeeded".to_string(),
+        EscortMissionState::Failed => "Escort mission failed".to_string(),
+    };
+}
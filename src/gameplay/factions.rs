@@ -0,0 +1,168 @@
+use crate::core::asset_loader::DataAssetLoader;
+use crate::core::prelude::*;
+use crate::gameplay::livery::{paint_modules_system, Livery, Paint};
+use crate::gameplay::upgrades::{tier_tint, ModuleTier};
+use crate::world::prelude::*;
+
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub const PALETTES_PATH: &str = "data/palettes.ron";
+
+/// Colors of the built-in modules when no palette is loaded or a palette leaves them out.
+pub const BUILTIN_MODULE_COLORS: [(char, Srgba); 15] = [
+    ('C', BLUE),
+    ('E', RED),
+    ('W', GREY),
+    ('!', PURPLE),
+    ('Q', ORANGE),
+    ('R', YELLOW),
+    ('M', WHITE),
+    ('D', SADDLE_BROWN),
+    ('A', TEAL),
+    ('P', LIME),
+    ('H', TAN),
+    ('F', ORANGE_RED),
+    ('X', DARK_CYAN),
+    ('B', GOLD),
+    ('N', MEDIUM_SPRING_GREEN),
+];
+
+/// Module colors by faction, read from `data/palettes.ron` so ships can be reskinned without code changes.
+/// Structures without a faction use the default palette, factions only list the colors they change.
+pub struct FactionPlugin;
+
+impl Plugin for FactionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FactionPalettes>()
+            .init_asset::<FactionPalettes>()
+            .init_asset_loader::<DataAssetLoader<FactionPalettes>>()
+            .add_systems(Startup, load_faction_palettes)
+            .add_systems(
+                Update,
+                (apply_faction_palettes_system, paint_faction_modules_system.before(paint_modules_system))
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+/// Faction a structure belongs to, picks its palette.
+#[derive(Component, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Faction(pub String);
+
+/// sRGB colors by module symbol.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(transparent)]
+pub struct ModulePalette {
+    pub colors: HashMap<char, [f32; 3]>,
+}
+
+impl ModulePalette {
+    pub fn builtin() -> Self {
+        let colors = BUILTIN_MODULE_COLORS
+            .iter()
+            .map(|(symbol, color)| (*symbol, [color.red, color.green, color.blue]))
+            .collect();
+        Self { colors }
+    }
+
+    pub fn color(&self, symbol: char) -> Option<Color> {
+        self.colors.get(&symbol).map(|[red, green, blue]| Color::srgb(*red, *green, *blue))
+    }
+}
+
+#[derive(Asset, Resource, TypePath, Debug, Clone, Deserialize)]
+pub struct FactionPalettes {
+    pub default: ModulePalette,
+    #[serde(default)]
+    pub factions: HashMap<String, ModulePalette>,
+}
+
+impl Default for FactionPalettes {
+    fn default() -> Self {
+        Self { default: ModulePalette::builtin(), factions: HashMap::new() }
+    }
+}
+
+impl FactionPalettes {
+    /// Color of a module in the palette of a faction, falling back to the default palette.
+    /// `None` for module types no palette knows, they keep the color they were built with.
+    pub fn color(&self, faction: Option<&Faction>, symbol: char) -> Option<Color> {
+        faction
+            .and_then(|faction| self.factions.get(&faction.0))
+            .and_then(|palette| palette.color(symbol))
+            .or_else(|| self.default.color(symbol))
+    }
+}
+
+/// Color of a built-in module before any palette is applied.
+pub fn builtin_module_color(module_type: ModuleType) -> Color {
+    BUILTIN_MODULE_COLORS
+        .iter()
+        .find(|(symbol, _)| *symbol == module_type.symbol())
+        .map_or(Color::from(GREY), |(_, color)| Color::from(*color))
+}
+
+#[derive(Resource)]
+struct FactionPalettesHandle(Handle<FactionPalettes>);
+
+fn load_faction_palettes(asset_server: Res<AssetServer>, mut commands: Commands) {
+    commands.insert_resource(FactionPalettesHandle(asset_server.load(PALETTES_PATH)));
+}
+
+/// Replaces the palettes in use whenever the data file is (re)loaded.
+fn apply_faction_palettes_system(
+    mut asset_events: EventReader<AssetEvent<FactionPalettes>>,
+    handle: Res<FactionPalettesHandle>,
+    palettes_assets: Res<Assets<FactionPalettes>>,
+    mut palettes: ResMut<FactionPalettes>,
+) {
+    if !asset_events.read().any(|event| event.is_loaded_with_dependencies(&handle.0)) {
+        return;
+    }
+    if let Some(loaded_palettes) = palettes_assets.get(&handle.0) {
+        *palettes = loaded_palettes.clone();
+        info!("Module palettes loaded for {} factions", palettes.factions.len());
+    }
+}
+
+/// Sets the base color of the modules from the palette of their structure, the livery is painted over it.
+fn paint_faction_modules_system(
+    palettes: Res<FactionPalettes>,
+    structures_query: Query<(Entity, Option<Ref<Faction>>, &Livery, &Children), With<Structure>>,
+    new_modules_query: Query<&Parent, Added<Module>>,
+    mut modules_query: Query<(&Module, &Handle<ColorMaterial>, Option<&mut Paint>, Option<&ModuleTier>)>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let extended_structures: HashSet<Entity> = new_modules_query.iter().map(|parent| parent.get()).collect();
+
+    for (structure_entity, faction, livery, children) in &structures_query {
+        let faction_changed = faction.as_ref().is_some_and(|faction| faction.is_changed());
+        if !palettes.is_changed() && !faction_changed && !extended_structures.contains(&structure_entity) {
+            continue;
+        }
+
+        let mut modules = modules_query.iter_many_mut(children);
+        while let Some((module, material_handle, paint, module_tier)) = modules.fetch_next() {
+            let Some(base) = palettes.color(faction.as_deref(), module.module_type.symbol()) else {
+                continue;
+            };
+            let Some(material) = materials.get_mut(material_handle) else {
+                continue;
+            };
+
+            // Modules not painted yet get their livery from `paint_modules_system`
+            let color = match paint {
+                Some(mut paint) => {
+                    paint.base = base;
+                    paint.color = tier_tint(livery.paint(base), module_tier);
+                    paint.color
+                }
+                None => base,
+            };
+            // Keep the transparency of open doors
+            material.color = color.with_alpha(material.color.alpha());
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use crate::configs::collision_layers::CollisionLayersConfig;
+use crate::core::asset_loader::{AssetStore, Level};
+use crate::core::prelude::*;
+
+use crate::prelude::*;
+
+const GRAVITY_SOURCE_COLOR: Color = Color::srgb(0.35, 0.3, 0.45);
+const GRAVITY_SOURCE_Z: f32 = -5.0; // below everything flying around them
+
+/// Planets and asteroids read from the level file, pulling the projectiles, debris, players and structures with an
+/// inverse-square force. Space has no global gravity, there is only the pull of these bodies.
+pub struct GravitySourcePlugin;
+
+impl Plugin for GravitySourcePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Gravity(Vector::ZERO))
+            .add_systems(OnEnter(GameState::BuildingStructures), spawn_gravity_sources)
+            .add_systems(FixedUpdate, gravity_system.run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// A body pulling every dynamic body toward its center.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct GravitySource {
+    pub radius: f32,
+    pub surface_gravity: f32,
+}
+
+impl GravitySource {
+    /// Acceleration toward the source for a body `offset` meters away from its center, in m/s².
+    /// Within the radius the pull stays the surface one, the collider keeps the bodies out anyway.
+    pub fn acceleration(&self, offset: Vec2) -> Vec2 {
+        let distance = offset.length().max(self.radius);
+        -offset.normalize_or_zero() * self.surface_gravity * (self.radius / distance).powi(2)
+    }
+}
+
+fn spawn_gravity_sources(
+    asset_store: Res<AssetStore>,
+    levels: Res<Assets<Level>>,
+    sources_query: Query<(), With<GravitySource>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut commands: Commands,
+) {
+    // The sources outlive a world rebuilt without reloading the level
+    if !sources_query.is_empty() {
+        return;
+    }
+    let Some(level) = levels.get(&asset_store.level) else {
+        return;
+    };
+
+    let material = materials.add(ColorMaterial::from(GRAVITY_SOURCE_COLOR));
+    for source in &level.gravity_sources {
+        commands.spawn((
+            GravitySource { radius: source.radius, surface_gravity: source.surface_gravity },
+            RigidBody::Static,
+            Collider::circle(source.radius),
+            CollisionLayersConfig::environment(),
+            MaterialMesh2dBundle {
+                mesh: meshes.add(Circle { radius: source.radius }).into(),
+                material: material.clone(),
+                transform: Transform::from_translation(Vec2::from(source.position).extend(GRAVITY_SOURCE_Z)),
+                ..default()
+            },
+            Name::new(source.name.clone()),
+        ));
+    }
+    info!("Spawned {} gravity sources", level.gravity_sources.len());
+}
+
+/// Adds the pull of every source to the velocity of the dynamic bodies, whatever their mass.
+fn gravity_system(
+    sources_query: Query<(&GravitySource, &GlobalTransform)>,
+    mut bodies_query: Query<(&RigidBody, &GlobalTransform, &mut LinearVelocity), Without<GravitySource>>,
+    time: Res<Time>,
+) {
+    if sources_query.is_empty() {
+        return;
+    }
+    let delta_seconds = time.delta_seconds();
+
+    for (rigid_body, body_transform, mut velocity) in &mut bodies_query {
+        if !rigid_body.is_dynamic() {
+            continue;
+        }
+        let position = body_transform.translation().truncate();
+        let acceleration: Vec2 = sources_query
+            .iter()
+            .map(|(source, source_transform)| source.acceleration(position - source_transform.translation().truncate()))
+            .sum();
+        velocity.0 += acceleration * delta_seconds;
+    }
+}
@@ -0,0 +1,179 @@
+use crate::core::prelude::*;
+use crate::gameplay::ai::{AiPilot, AiStance};
+use crate::gameplay::life_support::Oxygen;
+use crate::gameplay::repair::Scrap;
+use crate::ui::dialogue::{DialogueChoiceEvent, OpenDialogueEvent};
+use crate::world::prelude::*;
+
+use crate::prelude::*;
+
+const HAIL_KEY: KeyCode = KeyCode::KeyH;
+const HAIL_RANGE: f32 = 300.0; // meters
+const HAIL_TOPIC: &str = "hail";
+const HAIL_REPLY_TOPIC: &str = "hail_reply";
+const TRADE_TOPIC: &str = "hail_trade";
+const SURRENDER_STRENGTH_RATIO: f32 = 1.5; // how much stronger than the hailed ship the player must be
+const SURRENDER_TRIBUTE: f32 = 50.0; // scrap handed over by a ship surrendering
+const OXYGEN_REFILL_PRICE: f32 = 40.0; // scrap
+
+/// Press H to hail the closest named ship: demand its surrender, ask to trade or taunt it, and its AI answers by
+/// fleeing, attacking or stopping to trade.
+pub struct HailPlugin;
+
+impl Plugin for HailPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (hail_input_system, hail_choice_system).chain().run_if(in_state(GameState::InGame)));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HailChoice {
+    DemandSurrender,
+    RequestTrade,
+    Taunt,
+    CloseChannel,
+}
+
+impl HailChoice {
+    pub const ALL: [HailChoice; 4] =
+        [HailChoice::DemandSurrender, HailChoice::RequestTrade, HailChoice::Taunt, HailChoice::CloseChannel];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            HailChoice::DemandSurrender => "Demand their surrender",
+            HailChoice::RequestTrade => "Request a trade",
+            HailChoice::Taunt => "Taunt them",
+            HailChoice::CloseChannel => "Close the channel",
+        }
+    }
+}
+
+/// Total structural points of the modules of a structure, used to weigh a surrender demand.
+fn structure_strength(children: Option<&Children>, modules_query: &Query<&ModuleMaterial>) -> f32 {
+    children.map_or(0.0, |children| {
+        modules_query.iter_many(children).map(|module_material| module_material.structural_points).sum()
+    })
+}
+
+fn hail_input_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    player_resource: Res<PlayerResource>,
+    ships_query: Query<(Entity, &Transform, &StructureName, Option<&AiPilot>), Without<ControlledByPlayer>>,
+    mut dialogue_writer: EventWriter<OpenDialogueEvent>,
+) {
+    if !keys.just_pressed(HAIL_KEY) {
+        return;
+    }
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let player_position = player_transform.translation().truncate();
+
+    let closest = ships_query
+        .iter()
+        .filter(|(entity, ..)| player_resource.inside_structure != Some(*entity))
+        .map(|(entity, transform, name, pilot)| {
+            (entity, name, pilot, transform.translation.truncate().distance(player_position))
+        })
+        .filter(|(.., distance)| *distance < HAIL_RANGE)
+        .min_by(|(.., a), (.., b)| a.total_cmp(b));
+    let Some((ship_entity, name, pilot, _)) = closest else {
+        info!("No ship in range to hail");
+        return;
+    };
+
+    let greeting = match pilot.map(|pilot| pilot.stance).unwrap_or_default() {
+        AiStance::Idle => format!("This is the {}. State your business.", name.ship),
+        AiStance::Trade => "Still interested in a deal?".to_string(),
+        AiStance::Attack => "You will get nothing but cannon fire from us!".to_string(),
+        AiStance::Flee => "Leave us alone!".to_string(),
+    };
+    dialogue_writer.send(OpenDialogueEvent {
+        topic: HAIL_TOPIC,
+        subject: Some(ship_entity),
+        speaker: name.to_string(),
+        text: greeting,
+        options: HailChoice::ALL.iter().map(|choice| choice.label().to_string()).collect(),
+    });
+}
+
+fn hail_choice_system(
+    mut choice_reader: EventReader<DialogueChoiceEvent>,
+    mut dialogue_writer: EventWriter<OpenDialogueEvent>,
+    ships_query: Query<(&StructureName, Option<&Children>)>,
+    controlled_query: Query<Option<&Children>, With<ControlledByPlayer>>,
+    modules_query: Query<&ModuleMaterial>,
+    mut oxygen_query: Query<&mut Oxygen, With<Player>>,
+    mut scrap: ResMut<Scrap>,
+    mut commands: Commands,
+) {
+    for event in choice_reader.read() {
+        let Some(ship_entity) = event.subject else {
+            continue;
+        };
+        let Ok((name, ship_children)) = ships_query.get(ship_entity) else {
+            continue;
+        };
+        let reply = |text: &str| OpenDialogueEvent {
+            topic: HAIL_REPLY_TOPIC,
+            subject: Some(ship_entity),
+            speaker: name.to_string(),
+            text: text.to_string(),
+            options: vec!["Close the channel".to_string()],
+        };
+
+        match event.topic {
+            HAIL_TOPIC => match HailChoice::ALL.get(event.choice) {
+                Some(HailChoice::DemandSurrender) => {
+                    let player_strength = controlled_query
+                        .get_single()
+                        .map_or(0.0, |children| structure_strength(children, &modules_query));
+                    let ship_strength = structure_strength(ship_children, &modules_query);
+
+                    if player_strength > ship_strength * SURRENDER_STRENGTH_RATIO {
+                        scrap.amount += SURRENDER_TRIBUTE;
+                        commands.entity(ship_entity).insert(AiPilot::new(AiStance::Flee));
+                        dialogue_writer.send(reply("All right, all right! Take the scrap and let us go."));
+                        info!("The {} surrendered {} scrap", name, SURRENDER_TRIBUTE);
+                    } else {
+                        commands.entity(ship_entity).insert(AiPilot::new(AiStance::Attack));
+                        dialogue_writer.send(reply("Surrender? To you? Battle stations!"));
+                        info!("The {} refused to surrender and attacks", name);
+                    }
+                }
+                Some(HailChoice::RequestTrade) => {
+                    commands.entity(ship_entity).insert(AiPilot::new(AiStance::Trade));
+                    dialogue_writer.send(OpenDialogueEvent {
+                        topic: TRADE_TOPIC,
+                        subject: Some(ship_entity),
+                        speaker: name.to_string(),
+                        text: format!("We can refill your suit oxygen for {:.0} scrap.", OXYGEN_REFILL_PRICE),
+                        options: vec!["Deal".to_string(), "No thanks".to_string()],
+                    });
+                }
+                Some(HailChoice::Taunt) => {
+                    commands.entity(ship_entity).insert(AiPilot::new(AiStance::Attack));
+                    dialogue_writer.send(reply("You will regret those words!"));
+                    info!("The {} was taunted and attacks", name);
+                }
+                Some(HailChoice::CloseChannel) | None => {}
+            },
+            TRADE_TOPIC if event.choice == 0 => {
+                if scrap.amount < OXYGEN_REFILL_PRICE {
+                    dialogue_writer.send(reply("Come back when you can pay."));
+                    continue;
+                }
+                scrap.amount -= OXYGEN_REFILL_PRICE;
+                for mut oxygen in &mut oxygen_query {
+                    oxygen.level = oxygen.capacity;
+                }
+                dialogue_writer.send(reply("Pleasure doing business."));
+            }
+            TRADE_TOPIC => {
+                commands.entity(ship_entity).insert(AiPilot::new(AiStance::Idle));
+            }
+            _ => {}
+        }
+    }
+}
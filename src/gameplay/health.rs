@@ -0,0 +1,202 @@
+use crate::configs::collision_layers::CollisionLayersConfig;
+use crate::core::prelude::*;
+use crate::gameplay::eject::Invulnerable;
+use crate::gameplay::jetpack::Jetpack;
+use crate::gameplay::life_support::Oxygen;
+use crate::gameplay::medical::{Injury, InjuryCause, InjuryEvent};
+use crate::world::prelude::*;
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+pub const PLAYER_MAX_HEALTH: f32 = 100.0;
+const COMBAT_DAMAGE: f32 = 35.0; // per projectile hit
+const VACUUM_DAMAGE: f32 = 20.0; // per decompression blast or suffocation tick
+const RESPAWN_DELAY: f32 = 5.0; // seconds
+const RESPAWN_Z: f32 = 5.0;
+
+/// Hit points of the player, lowered by the injuries. Dying takes the player out of the world for a few seconds,
+/// then brings them back at the Command Center of the last structure they were aboard, or at the spawn point.
+pub struct HealthPlugin;
+
+impl Plugin for HealthPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlayerSpawnPoint>().add_event::<PlayerDiedEvent>().add_systems(
+            Update,
+            (
+                damage_player_system.run_if(on_event::<InjuryEvent>()),
+                respawn_player_system.run_if(in_state(PlayerState::Dead)),
+            )
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Self {
+        Self { current: max, max }
+    }
+
+    pub fn damage(&mut self, amount: f32) {
+        self.current = (self.current - amount).max(0.0);
+    }
+
+    pub fn restore(&mut self) {
+        self.current = self.max;
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current <= 0.0
+    }
+
+    /// Remaining health, from 0 to 1.
+    pub fn fraction(&self) -> f32 {
+        if self.max > 0.0 {
+            self.current / self.max
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Where the player comes back when no Command Center can take them.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct PlayerSpawnPoint(pub Vec2);
+
+impl Default for PlayerSpawnPoint {
+    fn default() -> Self {
+        Self(Vec2::new(-25.0, 0.0))
+    }
+}
+
+#[derive(Event, Debug)]
+pub struct PlayerDiedEvent {
+    pub player_entity: Entity,
+    pub cause: InjuryCause,
+    pub position: Vec2,
+}
+
+/// Put on the dead player in place of `Player`, so the systems acting on the player leave them alone.
+#[derive(Component, Debug)]
+pub struct Respawning {
+    pub timer: Timer,
+    /// Structure the player was aboard when they died, respawns at its Command Center.
+    pub structure: Option<Entity>,
+}
+
+fn damage_for(cause: InjuryCause) -> f32 {
+    match cause {
+        InjuryCause::Combat => COMBAT_DAMAGE,
+        InjuryCause::Vacuum => VACUUM_DAMAGE,
+    }
+}
+
+/// Lowers the health of the injured player, killing them once it runs out. Invulnerable players are not hurt.
+fn damage_player_system(
+    mut injury_reader: EventReader<InjuryEvent>,
+    mut player_query: Query<(&mut Health, &GlobalTransform), (With<Player>, Without<Invulnerable>)>,
+    mut modules_query: Query<&mut Module>,
+    controlled_query: Query<(Entity, &ControlledByPlayer)>,
+    mut player_resource: ResMut<PlayerResource>,
+    mut died_writer: EventWriter<PlayerDiedEvent>,
+    mut next_state: ResMut<NextState<PlayerState>>,
+    mut commands: Commands,
+) {
+    for event in injury_reader.read() {
+        let Ok((mut health, player_transform)) = player_query.get_mut(event.entity) else {
+            continue;
+        };
+        if health.is_dead() {
+            continue;
+        }
+        health.damage(damage_for(event.cause));
+        debug!("Player took {:?} damage, {} health left", event.cause, health.current);
+        if !health.is_dead() {
+            continue;
+        }
+
+        let player_entity = event.entity;
+        // Whatever the player was flying is left adrift
+        for (structure_entity, controlled_by) in &controlled_query {
+            if controlled_by.player_entity == player_entity {
+                commands.entity(structure_entity).remove::<ControlledByPlayer>();
+            }
+        }
+        for mut module in &mut modules_query {
+            if module.entity_connected == Some(player_entity) {
+                module.entity_connected = None;
+            }
+        }
+
+        let position = player_transform.translation().truncate();
+        info!("Player killed by {:?} at {:?}", event.cause, position);
+        commands.entity(player_entity).remove_parent_in_place().remove::<(Player, RigidBody, Collider)>().insert((
+            Respawning {
+                timer: Timer::from_seconds(RESPAWN_DELAY, TimerMode::Once),
+                structure: player_resource.inside_structure,
+            },
+            Visibility::Hidden,
+        ));
+        player_resource.inside_structure = None;
+        player_resource.is_controlling_structure = false;
+
+        died_writer.send(PlayerDiedEvent { player_entity, cause: event.cause, position });
+        next_state.set(PlayerState::Dead);
+    }
+}
+
+fn respawn_player_system(
+    mut dead_query: Query<(
+        Entity,
+        &mut Respawning,
+        &mut Health,
+        &mut Transform,
+        Option<&mut Oxygen>,
+        Option<&mut Jetpack>,
+    )>,
+    structures_query: Query<&Children, With<Structure>>,
+    command_centers_query: Query<&GlobalTransform, With<CommandCenterModule>>,
+    spawn_point: Res<PlayerSpawnPoint>,
+    time: Res<Time>,
+    mut next_state: ResMut<NextState<PlayerState>>,
+    mut commands: Commands,
+) {
+    for (player_entity, mut respawning, mut health, mut transform, oxygen, jetpack) in &mut dead_query {
+        if !respawning.timer.tick(time.delta()).finished() {
+            continue;
+        }
+
+        let command_center = respawning
+            .structure
+            .and_then(|structure_entity| structures_query.get(structure_entity).ok())
+            .and_then(|children| command_centers_query.iter_many(children).next());
+        let position = command_center.map_or(spawn_point.0, |transform| transform.translation().truncate());
+        transform.translation = position.extend(RESPAWN_Z);
+
+        health.restore();
+        if let Some(mut oxygen) = oxygen {
+            *oxygen = Oxygen::default();
+        }
+        if let Some(mut jetpack) = jetpack {
+            jetpack.refill();
+        }
+        commands.entity(player_entity).remove::<Respawning>().insert((
+            Player,
+            RigidBody::Dynamic,
+            Collider::circle(PLAYER_RADIUS),
+            CollisionLayersConfig::player(),
+            LinearVelocity::ZERO,
+            Injury::Healthy,
+            Visibility::Visible,
+        ));
+        info!("Player respawned at {:?}", position);
+        next_state.set(PlayerState::Alive);
+    }
+}
@@ -0,0 +1,150 @@
+use crate::core::prelude::*;
+use crate::gameplay::degradation::{performance, ModulePerformance};
+use crate::gameplay::movement::MovementSettings;
+use crate::gameplay::structures_combat::CannonFiredEvent;
+use crate::world::prelude::*;
+
+use avian2d::prelude::ExternalForce;
+use bevy::color::palettes::css::ORANGE_RED;
+use bevy::prelude::*;
+
+const HEAT_CAPACITY: f32 = 100.0; // heat a structure holds before overheating
+const CANNON_SHOT_HEAT: f32 = 6.0; // heat added by every projectile fired
+const ENGINE_HEAT_RATE: f32 = 3.0; // heat/s added by each engine at full thrust
+const PASSIVE_DISSIPATION: f32 = 2.0; // heat/s radiated by the hull of every structure
+const RADIATOR_DISSIPATION: f32 = 8.0; // heat/s radiated by each radiator module, less as it gets damaged
+const COOLED_DOWN_FRACTION: f32 = 0.4; // fraction of the capacity under which an overheated structure can fire again
+
+/// Heat of the structures: firing cannons and running engines heat the structure up, its hull and radiator modules
+/// shed the heat. Reaching the heat capacity overheats the structure, its weapons stay offline until it cools down,
+/// which paces sustained combat.
+pub struct HeatPlugin;
+
+impl Plugin for HeatPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<StructureOverheatedEvent>()
+            .add_systems(Update, attach_heat_store_system)
+            .add_systems(
+                FixedUpdate,
+                (cannon_heat_system, engine_heat_system, dissipate_heat_system)
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            )
+            .register_module_type::<RadiatorModule>(
+                ModuleDefinition::new("Radiator", 'V').with_color(Color::from(ORANGE_RED)),
+            );
+    }
+}
+
+/// Module shedding the heat of its structure into space.
+#[derive(Component, Debug, Default)]
+pub struct RadiatorModule;
+
+/// Heat accumulated by a structure.
+#[derive(Component, Debug)]
+pub struct HeatStore {
+    pub heat: f32,
+    pub capacity: f32,
+    /// Set when the heat reaches the capacity, until it falls back under `COOLED_DOWN_FRACTION` of it.
+    pub overheated: bool,
+}
+
+impl Default for HeatStore {
+    fn default() -> Self {
+        Self { heat: 0.0, capacity: HEAT_CAPACITY, overheated: false }
+    }
+}
+
+impl HeatStore {
+    pub fn fraction(&self) -> f32 {
+        self.heat / self.capacity
+    }
+
+    /// Overheated structures cannot fire their weapons.
+    pub fn is_overheated(&self) -> bool {
+        self.overheated
+    }
+
+    /// Returns true when this heat overheats the structure.
+    fn add(&mut self, heat: f32) -> bool {
+        self.heat = (self.heat + heat).min(self.capacity);
+        if !self.overheated && self.heat >= self.capacity {
+            self.overheated = true;
+            return true;
+        }
+        false
+    }
+
+    fn dissipate(&mut self, heat: f32) {
+        self.heat = (self.heat - heat).max(0.0);
+        if self.overheated && self.fraction() < COOLED_DOWN_FRACTION {
+            self.overheated = false;
+        }
+    }
+}
+
+/// Sent when a structure overheats, its weapons are offline until it cools down.
+#[derive(Event, Debug)]
+pub struct StructureOverheatedEvent {
+    pub structure_entity: Entity,
+}
+
+fn attach_heat_store_system(
+    structures_query: Query<Entity, (Added<Structure>, Without<HeatStore>)>,
+    mut commands: Commands,
+) {
+    for structure_entity in &structures_query {
+        commands.entity(structure_entity).insert(HeatStore::default());
+    }
+}
+
+fn cannon_heat_system(
+    mut fired_reader: EventReader<CannonFiredEvent>,
+    mut heat_query: Query<&mut HeatStore>,
+    mut overheated_writer: EventWriter<StructureOverheatedEvent>,
+) {
+    for event in fired_reader.read() {
+        if let Ok(mut heat_store) = heat_query.get_mut(event.structure_entity) {
+            if heat_store.add(CANNON_SHOT_HEAT) {
+                overheated_writer.send(StructureOverheatedEvent { structure_entity: event.structure_entity });
+            }
+        }
+    }
+}
+
+/// Engines heat their structure with their thrust, read from the force they apply this tick.
+fn engine_heat_system(
+    mut structures_query: Query<(Entity, &mut HeatStore, &ExternalForce)>,
+    mut overheated_writer: EventWriter<StructureOverheatedEvent>,
+    settings: Res<MovementSettings>,
+    time: Res<Time>,
+) {
+    for (structure_entity, mut heat_store, external_force) in &mut structures_query {
+        let engines_at_full_thrust = external_force.force().length() / settings.engine_thrust.max(f32::EPSILON);
+        if engines_at_full_thrust <= 0.0 {
+            continue;
+        }
+        if heat_store.add(engines_at_full_thrust * ENGINE_HEAT_RATE * time.delta_seconds()) {
+            overheated_writer.send(StructureOverheatedEvent { structure_entity });
+        }
+    }
+}
+
+fn dissipate_heat_system(
+    mut structures_query: Query<(&mut HeatStore, Option<&Children>)>,
+    radiator_query: Query<Option<&ModulePerformance>, With<RadiatorModule>>,
+    time: Res<Time>,
+) {
+    for (mut heat_store, childrens) in &mut structures_query {
+        if heat_store.heat <= 0.0 {
+            continue;
+        }
+        let radiators: f32 = childrens
+            .into_iter()
+            .flatten()
+            .filter_map(|child| radiator_query.get(*child).ok())
+            .map(|module_performance| performance(module_performance))
+            .sum();
+        heat_store.dissipate((PASSIVE_DISSIPATION + radiators * RADIATOR_DISSIPATION) * time.delta_seconds());
+    }
+}
@@ -0,0 +1,116 @@
+use crate::core::prelude::*;
+use crate::world::prelude::*;
+
+use bevy::prelude::*;
+
+const JETPACK_FUEL_CAPACITY: f32 = 100.0;
+const JETPACK_FUEL_BURN: f32 = 6.0; // fuel/s at full thrust
+const JETPACK_REFUEL_RATE: f32 = 20.0; // fuel/s inside a pressurized room
+
+/// Suit thrusters moving the player outside of the structures. They burn fuel refilled in the pressurized rooms,
+/// a player running dry in open space drifts with whatever velocity they had until they reach a structure.
+pub struct JetpackPlugin;
+
+impl Plugin for JetpackPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<JetpackEmptyEvent>().add_systems(
+            FixedUpdate,
+            (refuel_jetpack_system, drifting_system).chain().run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Jetpack {
+    pub fuel: f32,
+    pub capacity: f32,
+}
+
+impl Default for Jetpack {
+    fn default() -> Self {
+        Self { fuel: JETPACK_FUEL_CAPACITY, capacity: JETPACK_FUEL_CAPACITY }
+    }
+}
+
+impl Jetpack {
+    /// Burns the fuel needed for a thrust of `throttle` (0 to 1) during `delta_time` seconds.
+    /// Returns the fraction of the thrust the remaining fuel could give.
+    pub fn burn(&mut self, throttle: f32, delta_time: f32) -> f32 {
+        let needed = JETPACK_FUEL_BURN * throttle.clamp(0.0, 1.0) * delta_time;
+        if needed <= 0.0 {
+            return 1.0;
+        }
+        let burned = needed.min(self.fuel);
+        self.fuel -= burned;
+        burned / needed
+    }
+
+    pub fn refill(&mut self) {
+        self.fuel = self.capacity;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fuel <= 0.0
+    }
+
+    pub fn fraction(&self) -> f32 {
+        self.fuel / self.capacity
+    }
+}
+
+/// Out of fuel in open space, the player cannot change their velocity anymore.
+#[derive(Component, Debug)]
+pub struct Drifting;
+
+/// Sent when the jetpack of the player runs dry outside of the structures.
+#[derive(Event, Debug)]
+pub struct JetpackEmptyEvent {
+    pub player_entity: Entity,
+}
+
+/// Fraction of the requested thrust the player gets: full inside a structure, limited by the jetpack fuel outside.
+pub fn player_thrust(jetpack: Option<&mut Jetpack>, inside_structure: bool, throttle: f32, delta_time: f32) -> f32 {
+    match jetpack {
+        Some(jetpack) if !inside_structure => jetpack.burn(throttle, delta_time),
+        _ => 1.0,
+    }
+}
+
+fn refuel_jetpack_system(
+    mut player_query: Query<(&GlobalTransform, &mut Jetpack), With<Player>>,
+    structures_query: Query<(&Transform, &Structure, &Pressurization)>,
+    player_resource: Res<PlayerResource>,
+    time: Res<Time>,
+) {
+    for (player_transform, mut jetpack) in &mut player_query {
+        let pressurized = player_resource
+            .inside_structure
+            .and_then(|structure_entity| structures_query.get(structure_entity).ok())
+            .is_some_and(|(structure_transform, structure, pressurization)| {
+                let grid_pos = structure.world_to_grid(player_transform.translation(), structure_transform);
+                pressurization.is_breathable(grid_pos)
+            });
+
+        if pressurized && jetpack.fuel < jetpack.capacity {
+            jetpack.fuel = (jetpack.fuel + JETPACK_REFUEL_RATE * time.delta_seconds()).min(jetpack.capacity);
+        }
+    }
+}
+
+fn drifting_system(
+    player_query: Query<(Entity, &Jetpack, Has<Drifting>), With<Player>>,
+    player_resource: Res<PlayerResource>,
+    mut empty_writer: EventWriter<JetpackEmptyEvent>,
+    mut commands: Commands,
+) {
+    for (player_entity, jetpack, drifting) in &player_query {
+        let stranded = jetpack.is_empty() && player_resource.inside_structure.is_none();
+        if stranded && !drifting {
+            debug!("Player ran out of jetpack fuel, drifting.");
+            commands.entity(player_entity).insert(Drifting);
+            empty_writer.send(JetpackEmptyEvent { player_entity });
+        } else if !stranded && drifting {
+            commands.entity(player_entity).remove::<Drifting>();
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use crate::core::prelude::*;
+use crate::gameplay::destruction::StructureDestroyedEvent;
+use crate::gameplay::docking::DockedEvent;
+use crate::gameplay::escort::{EscortMission, EscortMissionState};
+use crate::gameplay::health::{Health, PlayerDiedEvent};
+use crate::gameplay::scenario::ScenarioEndedEvent;
+use crate::world::prelude::*;
+
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+const NEAR_DEATH_HEALTH: f32 = 0.15; // fraction of the player health, below it the player is close to dying
+const ESCAPED_HEALTH: f32 = 0.5; // fraction of the player health to get back to for the escape to be recorded
+
+/// Captain's journal written automatically from the notable moments of the game: the first kill, the stations docked
+/// at, the missions completed and the near death escapes. Kept in the save slots.
+pub struct JournalPlugin;
+
+impl Plugin for JournalPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Journal>().add_systems(
+            Update,
+            (
+                play_time_system,
+                first_kill_entry_system,
+                station_visited_entries_system,
+                mission_completed_entries_system,
+                near_death_entries_system,
+            )
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JournalEntryKind {
+    FirstKill,
+    StationVisited,
+    MissionCompleted,
+    NearDeathEscape,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub kind: JournalEntryKind,
+    /// Play time in seconds when it happened.
+    pub time: f32,
+    pub position: [f32; 2],
+    pub text: String,
+}
+
+impl JournalEntry {
+    /// Play time as hours and minutes, like `02:15`.
+    pub fn timestamp(&self) -> String {
+        let minutes = (self.time / 60.0) as u32;
+        format!("{:02}:{:02}", minutes / 60, minutes % 60)
+    }
+}
+
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Journal {
+    /// Entries, the oldest first.
+    pub entries: Vec<JournalEntry>,
+    /// Seconds played over every session of this game.
+    pub play_time: f32,
+    /// Names of the stations already docked at, only the first visit is written down.
+    pub visited_stations: BTreeSet<String>,
+}
+
+impl Journal {
+    pub fn record(&mut self, kind: JournalEntryKind, position: Vec2, text: String) {
+        info!("Journal: {}", text);
+        self.entries.push(JournalEntry { kind, time: self.play_time, position: position.to_array(), text });
+    }
+
+    pub fn has(&self, kind: JournalEntryKind) -> bool {
+        self.entries.iter().any(|entry| entry.kind == kind)
+    }
+}
+
+fn play_time_system(mut journal: ResMut<Journal>, time: Res<Time>) {
+    journal.play_time += time.delta_seconds();
+}
+
+/// Destroying another structure counts as a kill.
+fn first_kill_entry_system(
+    mut event_reader: EventReader<StructureDestroyedEvent>,
+    controlled_query: Query<Entity, With<ControlledByPlayer>>,
+    mut journal: ResMut<Journal>,
+) {
+    if journal.has(JournalEntryKind::FirstKill) {
+        event_reader.clear();
+        return;
+    }
+    let controlled_structure = controlled_query.get_single().ok();
+
+    for event in event_reader.read() {
+        if event.source.is_none() || event.source != controlled_structure {
+            continue;
+        }
+        let ship = event.name.clone().unwrap_or_else(|| "a structure".to_string());
+        journal.record(JournalEntryKind::FirstKill, event.position, format!("First kill: destroyed {ship}"));
+        return;
+    }
+}
+
+fn station_visited_entries_system(
+    mut event_reader: EventReader<DockedEvent>,
+    structures_query: Query<(&GlobalTransform, Option<&StructureName>)>,
+    controlled_query: Query<Entity, With<ControlledByPlayer>>,
+    player_resource: Res<PlayerResource>,
+    mut journal: ResMut<Journal>,
+) {
+    let player_structure = controlled_query.get_single().ok().or(player_resource.inside_structure);
+
+    for event in event_reader.read() {
+        let [first, second] = event.structures;
+        let station = match player_structure {
+            Some(structure) if structure == first => second,
+            Some(structure) if structure == second => first,
+            _ => continue,
+        };
+        let Ok((station_transform, Some(name))) = structures_query.get(station) else {
+            continue;
+        };
+        if !journal.visited_stations.insert(name.ship.clone()) {
+            continue;
+        }
+        journal.record(
+            JournalEntryKind::StationVisited,
+            station_transform.translation().truncate(),
+            format!("Docked at {} for the first time", name.ship),
+        );
+    }
+}
+
+fn mission_completed_entries_system(
+    mut scenario_reader: EventReader<ScenarioEndedEvent>,
+    escort_mission: Res<EscortMission>,
+    mut last_escort_state: Local<EscortMissionState>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    mut journal: ResMut<Journal>,
+) {
+    let position = player_query.get_single().map(|transform| transform.translation().truncate()).unwrap_or_default();
+
+    for event in scenario_reader.read() {
+        if event.victory {
+            journal.record(JournalEntryKind::MissionCompleted, position, format!("Completed {}", event.name));
+        }
+    }
+
+    if escort_mission.state != *last_escort_state {
+        *last_escort_state = escort_mission.state;
+        if escort_mission.state == EscortMissionState::Succeeded {
+            journal.record(
+                JournalEntryKind::MissionCompleted,
+                position,
+                format!("Escorted a freighter home with {:.0}% of its hull", escort_mission.integrity * 100.0),
+            );
+        }
+    }
+}
+
+/// The player dropped close to death and recovered, dying in between is no escape.
+fn near_death_entries_system(
+    mut died_reader: EventReader<PlayerDiedEvent>,
+    player_query: Query<(&Health, &GlobalTransform), With<Player>>,
+    mut near_death: Local<bool>,
+    mut journal: ResMut<Journal>,
+) {
+    if died_reader.read().count() > 0 {
+        *near_death = false;
+    }
+    let Ok((health, player_transform)) = player_query.get_single() else {
+        return;
+    };
+
+    if health.is_dead() {
+        *near_death = false;
+    } else if health.fraction() < NEAR_DEATH_HEALTH {
+        *near_death = true;
+    } else if *near_death && health.fraction() >= ESCAPED_HEALTH {
+        *near_death = false;
+        journal.record(
+            JournalEntryKind::NearDeathEscape,
+            player_transform.translation().truncate(),
+            "Pulled through after nearly dying".to_string(),
+        );
+    }
+}
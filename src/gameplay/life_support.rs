@@ -0,0 +1,138 @@
+use crate::core::prelude::*;
+use crate::core::profiling::{ProfileScope, ATMOSPHERE};
+use crate::gameplay::degradation::{performance, ModulePerformance};
+use crate::world::prelude::*;
+
+use bevy::color::palettes::css::AQUA;
+use bevy::diagnostic::Diagnostics;
+use bevy::prelude::*;
+
+const CELL_OXYGEN_VENT_RATE: f32 = 0.5; // oxygen/s lost by a cell exposed to space
+const CELL_OXYGEN_REFILL_RATE: f32 = 0.05; // oxygen/s restored in a sealed cell
+const LIFE_SUPPORT_REFILL_RATE: f32 = 0.1; // extra oxygen/s restored in every sealed cell by each life support module
+const PLAYER_OXYGEN_CAPACITY: f32 = 100.0;
+const PLAYER_OXYGEN_DRAIN: f32 = 2.0; // oxygen/s while breathing from the suit
+const PLAYER_OXYGEN_REFILL: f32 = 10.0; // oxygen/s while inside a breathable room
+const SUFFOCATION_INTERVAL: f32 = 3.0; // seconds between suffocation events
+
+pub struct LifeSupportPlugin;
+
+impl Plugin for LifeSupportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PlayerSuffocatingEvent>()
+            .add_systems(
+                FixedUpdate,
+                (atmosphere_system, player_oxygen_system).chain().run_if(in_state(GameState::InGame)),
+            )
+            .register_module_type::<LifeSupportModule>(
+                ModuleDefinition::new("Life support", 'L')
+                    .with_color(Color::from(AQUA))
+                    .with_footprint(Footprint::new(2, 2)),
+            );
+    }
+}
+
+/// Module scrubbing and refilling the air of the sealed rooms of its structure, less as it gets damaged.
+/// It covers 2x2 cells.
+#[derive(Component, Debug, Default)]
+pub struct LifeSupportModule;
+
+/// Oxygen carried by a character, refilled inside breathable rooms.
+#[derive(Component, Debug)]
+pub struct Oxygen {
+    pub level: f32,
+    pub capacity: f32,
+    suffocation_timer: Timer,
+}
+
+impl Default for Oxygen {
+    fn default() -> Self {
+        Self {
+            level: PLAYER_OXYGEN_CAPACITY,
+            capacity: PLAYER_OXYGEN_CAPACITY,
+            suffocation_timer: Timer::from_seconds(SUFFOCATION_INTERVAL, TimerMode::Repeating),
+        }
+    }
+}
+
+impl Oxygen {
+    pub fn is_empty(&self) -> bool {
+        self.level <= 0.0
+    }
+
+    pub fn fraction(&self) -> f32 {
+        self.level / self.capacity
+    }
+}
+
+/// Sent periodically while the player has no oxygen left.
+#[derive(Event, Debug)]
+pub struct PlayerSuffocatingEvent {
+    pub player_entity: Entity,
+}
+
+/// Vents the oxygen of exposed cells and slowly refills the sealed ones.
+fn atmosphere_system(
+    mut structures_query: Query<(&mut Pressurization, &Structure, Option<&Children>)>,
+    life_support_query: Query<Option<&ModulePerformance>, With<LifeSupportModule>>,
+    time: Res<Time>,
+    mut diagnostics: Diagnostics,
+) {
+    let scope = ProfileScope::enter(&ATMOSPHERE);
+    let delta_time = time.delta_seconds();
+
+    for (mut pressurization, structure, children) in &mut structures_query {
+        let life_support: f32 =
+            children.map(|children| life_support_query.iter_many(children).map(performance).sum()).unwrap_or(0.0);
+        let refill_rate = CELL_OXYGEN_REFILL_RATE + LIFE_SUPPORT_REFILL_RATE * life_support;
+
+        for (&cell_pos, cell) in &structure.grid.cells {
+            if cell.cell_type == CellType::Module {
+                pressurization.oxygen.remove(&cell_pos);
+                continue;
+            }
+
+            let exposed = pressurization.is_exposed(cell_pos);
+            let oxygen = pressurization.oxygen.entry(cell_pos).or_insert(0.0);
+            *oxygen = if exposed {
+                (*oxygen - CELL_OXYGEN_VENT_RATE * delta_time).max(0.0)
+            } else {
+                (*oxygen + refill_rate * delta_time).min(1.0)
+            };
+        }
+    }
+    scope.finish(&mut diagnostics);
+}
+
+/// Refills the player oxygen inside breathable rooms and drains it anywhere else.
+fn player_oxygen_system(
+    mut player_query: Query<(Entity, &GlobalTransform, &mut Oxygen), With<Player>>,
+    structures_query: Query<(&Transform, &Structure, &Pressurization)>,
+    player_resource: Res<PlayerResource>,
+    mut event_writer: EventWriter<PlayerSuffocatingEvent>,
+    time: Res<Time>,
+) {
+    let delta_time = time.delta_seconds();
+
+    for (player_entity, player_transform, mut oxygen) in &mut player_query {
+        let breathable = player_resource
+            .inside_structure
+            .and_then(|structure_entity| structures_query.get(structure_entity).ok())
+            .is_some_and(|(structure_transform, structure, pressurization)| {
+                let grid_pos = structure.world_to_grid(player_transform.translation(), structure_transform);
+                pressurization.is_breathable(grid_pos)
+            });
+
+        if breathable {
+            oxygen.level = (oxygen.level + PLAYER_OXYGEN_REFILL * delta_time).min(oxygen.capacity);
+            oxygen.suffocation_timer.reset();
+            continue;
+        }
+
+        oxygen.level = (oxygen.level - PLAYER_OXYGEN_DRAIN * delta_time).max(0.0);
+        if oxygen.is_empty() && oxygen.suffocation_timer.tick(time.delta()).just_finished() {
+            debug!("Player is suffocating.");
+            event_writer.send(PlayerSuffocatingEvent { player_entity });
+        }
+    }
+}
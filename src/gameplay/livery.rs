@@ -0,0 +1,226 @@
+use crate::core::prelude::*;
+use crate::gameplay::building::BuildMode;
+use crate::gameplay::upgrades::{tier_tint, ModuleTier};
+use crate::world::prelude::*;
+
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const LIVERY_CYCLE_KEY: KeyCode = KeyCode::KeyL;
+const DECAL_CYCLE_KEY: KeyCode = KeyCode::KeyK;
+const DECAL_Z: f32 = 0.5; // above the module mesh, under its health bar
+const DECAL_THICKNESS: f32 = 0.6; // meters
+
+/// Color schemes offered in build mode, as an sRGB tint and how much of it covers the module colors.
+pub const LIVERY_PRESETS: [([f32; 3], f32); 6] = [
+    ([1.0, 1.0, 1.0], 0.0),
+    ([0.85, 0.85, 0.9], 0.6),
+    ([0.15, 0.2, 0.45], 0.5),
+    ([0.6, 0.1, 0.1], 0.45),
+    ([0.9, 0.7, 0.1], 0.4),
+    ([0.1, 0.1, 0.1], 0.65),
+];
+
+/// Hull customization: a color scheme tinting every module of a structure and decals painted on some of them.
+/// Press L in build mode to cycle the color scheme and K to cycle the decal of the hovered module.
+pub struct LiveryPlugin;
+
+impl Plugin for LiveryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (livery_input_system, paint_modules_system).chain().run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DecalKind {
+    Stripe,
+    Chevron,
+    Emblem,
+}
+
+impl DecalKind {
+    /// The next decal in the build mode cycle, `None` clears the cell.
+    pub fn next(kind: Option<DecalKind>) -> Option<DecalKind> {
+        match kind {
+            None => Some(DecalKind::Stripe),
+            Some(DecalKind::Stripe) => Some(DecalKind::Chevron),
+            Some(DecalKind::Chevron) => Some(DecalKind::Emblem),
+            Some(DecalKind::Emblem) => None,
+        }
+    }
+}
+
+/// A decal painted on the module of a cell.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Decal {
+    pub cell: (i32, i32),
+    pub kind: DecalKind,
+    /// sRGB color of the decal.
+    pub color: [f32; 3],
+    /// Quarter turns, counterclockwise.
+    #[serde(default)]
+    pub rotation: u8,
+}
+
+/// Color scheme and decals of a structure, saved with it in the structures data and the save games.
+#[derive(Component, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Livery {
+    /// sRGB color mixed into the module colors.
+    pub tint: [f32; 3],
+    /// How much of the tint covers the module colors, from 0.0 (none) to 1.0 (only the tint).
+    pub strength: f32,
+    #[serde(default)]
+    pub decals: Vec<Decal>,
+}
+
+impl Default for Livery {
+    fn default() -> Self {
+        let (tint, strength) = LIVERY_PRESETS[0];
+        Self { tint, strength, decals: Vec::new() }
+    }
+}
+
+impl Livery {
+    pub fn tint_color(&self) -> Color {
+        Color::srgb(self.tint[0], self.tint[1], self.tint[2])
+    }
+
+    /// Applies the color scheme to the color a module was built with.
+    pub fn paint(&self, color: Color) -> Color {
+        color.mix(&self.tint_color(), self.strength.clamp(0.0, 1.0)).with_alpha(color.alpha())
+    }
+
+    pub fn decal_at(&self, cell: (i32, i32)) -> Option<&Decal> {
+        self.decals.iter().find(|decal| decal.cell == cell)
+    }
+
+    /// Replaces the decal of a cell, `None` removes it.
+    pub fn set_decal(&mut self, cell: (i32, i32), decal: Option<Decal>) {
+        self.decals.retain(|decal| decal.cell != cell);
+        self.decals.extend(decal);
+    }
+}
+
+/// Color of a module once painted, with the color it was built with to paint it again.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Paint {
+    pub base: Color,
+    pub color: Color,
+}
+
+/// Sprite of a decal, child of the module it is painted on.
+#[derive(Component, Debug)]
+pub struct LiveryDecal;
+
+fn livery_input_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    build_mode: Res<BuildMode>,
+    mut structures_query: Query<&mut Livery>,
+) {
+    if !build_mode.active || !keys.any_just_pressed([LIVERY_CYCLE_KEY, DECAL_CYCLE_KEY]) {
+        return;
+    }
+    let Some(target) = &build_mode.target else {
+        return;
+    };
+    let Ok(mut livery) = structures_query.get_mut(target.structure_entity) else {
+        return;
+    };
+
+    if keys.just_pressed(LIVERY_CYCLE_KEY) {
+        let current =
+            LIVERY_PRESETS.iter().position(|(tint, strength)| *tint == livery.tint && *strength == livery.strength);
+        let (tint, strength) = LIVERY_PRESETS[current.map_or(0, |index| (index + 1) % LIVERY_PRESETS.len())];
+        livery.tint = tint;
+        livery.strength = strength;
+    }
+
+    if keys.just_pressed(DECAL_CYCLE_KEY) {
+        let current = livery.decal_at(target.cell).map(|decal| decal.kind);
+        let decal = DecalKind::next(current).map(|kind| Decal {
+            cell: target.cell,
+            kind,
+            color: [0.95, 0.95, 0.95],
+            rotation: build_mode.rotation,
+        });
+        livery.set_decal(target.cell, decal);
+    }
+}
+
+/// Paints the modules of the structures whose livery changed, and the modules added to a painted structure.
+pub(crate) fn paint_modules_system(
+    structures_query: Query<(Entity, Ref<Livery>, &Structure, &Children)>,
+    new_modules_query: Query<&Parent, Added<Module>>,
+    modules_query: Query<(&Module, &Handle<ColorMaterial>, Option<&Paint>, Option<&ModuleTier>, Option<&Children>)>,
+    decals_query: Query<(), With<LiveryDecal>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut commands: Commands,
+) {
+    let extended_structures: HashSet<Entity> = new_modules_query.iter().map(|parent| parent.get()).collect();
+
+    for (structure_entity, livery, structure, children) in &structures_query {
+        if !livery.is_changed() && !extended_structures.contains(&structure_entity) {
+            continue;
+        }
+
+        let module_size = structure.grid.cell_size * MODULE_MESH_SCALE_FACTOR;
+        for &module_entity in children {
+            let Ok((module, material_handle, paint, module_tier, module_children)) = modules_query.get(module_entity)
+            else {
+                continue;
+            };
+            let Some(material) = materials.get_mut(material_handle) else {
+                continue;
+            };
+
+            let base = paint.map_or(material.color, |paint| paint.base);
+            let color = tier_tint(livery.paint(base), module_tier);
+            // Keep the transparency of open doors
+            material.color = color.with_alpha(material.color.alpha());
+            commands.entity(module_entity).insert(Paint { base, color });
+
+            for &child in module_children.into_iter().flatten() {
+                if decals_query.contains(child) {
+                    commands.entity(child).despawn_recursive();
+                }
+            }
+            if let Some(decal) = livery.decal_at(module.inner_grid_pos) {
+                spawn_decal(&mut commands, module_entity, decal, module_size);
+            }
+        }
+    }
+}
+
+fn spawn_decal(commands: &mut Commands, module_entity: Entity, decal: &Decal, module_size: f32) {
+    let color = Color::srgb(decal.color[0], decal.color[1], decal.color[2]);
+    let rotation = Quat::from_rotation_z(decal.rotation as f32 * std::f32::consts::FRAC_PI_2);
+    let stroke = |size: Vec2, translation: Vec2, angle: f32| SpriteBundle {
+        sprite: Sprite { color, custom_size: Some(size), ..default() },
+        transform: Transform::from_translation(rotation * translation.extend(DECAL_Z))
+            .with_rotation(rotation * Quat::from_rotation_z(angle)),
+        ..default()
+    };
+
+    commands.entity(module_entity).with_children(|module| {
+        match decal.kind {
+            DecalKind::Stripe => {
+                module.spawn((LiveryDecal, stroke(Vec2::new(module_size, DECAL_THICKNESS), Vec2::ZERO, 0.0)));
+            }
+            DecalKind::Chevron => {
+                // Two strokes meeting in a point towards the bow
+                let stroke_size = Vec2::new(module_size * 0.5, DECAL_THICKNESS);
+                let offset = module_size * 0.17;
+                let angle = std::f32::consts::FRAC_PI_4;
+                module.spawn((LiveryDecal, stroke(stroke_size, Vec2::new(-offset, 0.0), angle)));
+                module.spawn((LiveryDecal, stroke(stroke_size, Vec2::new(offset, 0.0), -angle)));
+            }
+            DecalKind::Emblem => {
+                let size = Vec2::splat(module_size * 0.4);
+                module.spawn((LiveryDecal, stroke(size, Vec2::ZERO, std::f32::consts::FRAC_PI_4)));
+            }
+        }
+    });
+}
@@ -0,0 +1,230 @@
+use crate::core::prelude::*;
+use crate::gameplay::movement::{PilotCommand, PilotingSet};
+use crate::world::prelude::*;
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::f32::consts::{PI, TAU};
+use std::fmt;
+
+pub const TURN_AROUND_ANGLE: f32 = 180.0; // degrees turned by the turn around key
+pub const BURN_DURATION: f32 = 3.0; // seconds of thrust queued by the burn key
+const ROTATION_TOLERANCE: f32 = 0.01; // rad, close enough to the requested heading
+const STILL_ANGULAR_SPEED: f32 = 0.01; // rad/s
+const STILL_SPEED: f32 = 0.05; // m/s
+
+/// Maneuvers queued by the pilot of the controlled structure and flown one after the other, for precise turns and
+/// burns that are hard to time by hand with Newtonian handling. The keyboard still works on top of them, clearing
+/// the queue gives the controls back.
+pub struct ManeuverPlugin;
+
+impl Plugin for ManeuverPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (queue_maneuvers_system, clear_uncontrolled_maneuvers_system)
+                .chain()
+                .after(InGameSet::UserInput)
+                .run_if(in_state(GameState::InGame)),
+        )
+        .add_systems(FixedUpdate, fly_maneuvers_system.before(PilotingSet).run_if(in_state(GameState::InGame)));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Maneuver {
+    /// Turns by this many degrees, positive for counterclockwise.
+    Rotate(f32),
+    /// Fires the engines forward for this many seconds.
+    Burn(f32),
+    /// Brakes and stops turning until the structure is still.
+    AllStop,
+}
+
+impl fmt::Display for Maneuver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Maneuver::Rotate(degrees) => write!(f, "rotate {:.0}°", degrees),
+            Maneuver::Burn(seconds) => write!(f, "burn {:.0}s", seconds),
+            Maneuver::AllStop => write!(f, "all stop"),
+        }
+    }
+}
+
+/// How far the maneuver being flown went.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ManeuverProgress {
+    Rotate { remaining_angle: f32 },
+    Burn { remaining_time: f32 },
+    AllStop,
+}
+
+impl ManeuverProgress {
+    fn start(maneuver: Maneuver) -> Self {
+        match maneuver {
+            Maneuver::Rotate(degrees) => ManeuverProgress::Rotate { remaining_angle: degrees.to_radians() },
+            Maneuver::Burn(seconds) => ManeuverProgress::Burn { remaining_time: seconds },
+            Maneuver::AllStop => ManeuverProgress::AllStop,
+        }
+    }
+}
+
+/// Maneuvers of the controlled structure, removed once they are all flown.
+#[derive(Component, Debug, Default)]
+pub struct ManeuverQueue {
+    pub queued: VecDeque<Maneuver>,
+    current: Option<(Maneuver, ManeuverProgress)>,
+    /// Angular acceleration seen while turning, in rad/s², to know when to counter the turn.
+    angular_acceleration: Option<f32>,
+    /// Rotation command, angular velocity and heading of the previous tick.
+    last_tick: Option<(f32, f32, f32)>,
+}
+
+impl ManeuverQueue {
+    pub fn current(&self) -> Option<Maneuver> {
+        self.current.map(|(maneuver, _)| maneuver)
+    }
+
+    /// Learns how fast the structure turns from the effect of the previous rotation command.
+    fn measure_turn(&mut self, angular_velocity: f32, delta_time: f32) {
+        let Some((rotation, last_angular_velocity, _)) = self.last_tick else {
+            return;
+        };
+        if rotation.abs() < 0.5 || delta_time <= 0.0 {
+            return;
+        }
+        // Turning at full speed does not accelerate anymore, the largest value seen is the one to trust
+        let measured = (angular_velocity - last_angular_velocity).abs() / (rotation.abs() * delta_time);
+        if measured > f32::EPSILON {
+            self.angular_acceleration = Some(self.angular_acceleration.map_or(measured, |known| known.max(measured)));
+        }
+    }
+
+    /// Rotation command bringing the structure to rest at the requested heading, turning as fast as it can stop.
+    fn rotation_towards(&self, remaining_angle: f32, angular_velocity: f32, delta_time: f32) -> f32 {
+        let Some(angular_acceleration) = self.angular_acceleration.filter(|_| delta_time > 0.0) else {
+            // Full command until its effect could be measured
+            let direction = if remaining_angle != 0.0 { remaining_angle } else { -angular_velocity };
+            return if direction == 0.0 { 0.0 } else { direction.signum() };
+        };
+        let wanted_angular_velocity =
+            remaining_angle.signum() * (2.0 * angular_acceleration * remaining_angle.abs()).sqrt();
+        ((wanted_angular_velocity - angular_velocity) / (angular_acceleration * delta_time)).clamp(-1.0, 1.0)
+    }
+}
+
+/// Wraps an angle difference to -π..π.
+fn wrap_angle(angle: f32) -> f32 {
+    (angle + PI).rem_euclid(TAU) - PI
+}
+
+fn queue_maneuvers_system(
+    mut input_reader: EventReader<InputAction>,
+    mut controlled_query: Query<(Entity, Option<&mut ManeuverQueue>), With<ControlledByPlayer>>,
+    player_resource: Res<PlayerResource>,
+    mut commands: Commands,
+) {
+    let Ok((structure_entity, mut maneuver_queue)) = controlled_query.get_single_mut() else {
+        return;
+    };
+    if !player_resource.is_controlling_structure {
+        return;
+    }
+
+    let mut queued = VecDeque::new();
+    for event in input_reader.read() {
+        match event {
+            InputAction::QueueManeuver(maneuver) => {
+                debug!("Maneuver queued: {}", maneuver);
+                queued.push_back(*maneuver);
+            }
+            InputAction::ClearManeuvers => {
+                queued.clear();
+                maneuver_queue = None;
+                commands.entity(structure_entity).remove::<(ManeuverQueue, PilotCommand)>();
+            }
+            _ => {}
+        }
+    }
+    if queued.is_empty() {
+        return;
+    }
+
+    match maneuver_queue {
+        Some(mut maneuver_queue) => maneuver_queue.queued.extend(queued),
+        None => {
+            commands.entity(structure_entity).insert(ManeuverQueue { queued, ..default() });
+        }
+    }
+}
+
+/// A structure the pilot left does not keep flying their maneuvers.
+fn clear_uncontrolled_maneuvers_system(
+    uncontrolled_query: Query<Entity, (With<ManeuverQueue>, Without<ControlledByPlayer>)>,
+    mut commands: Commands,
+) {
+    for structure_entity in &uncontrolled_query {
+        commands.entity(structure_entity).remove::<(ManeuverQueue, PilotCommand)>();
+    }
+}
+
+/// Flies the first queued maneuver through the `PilotCommand` of the structure, then moves on to the next one.
+fn fly_maneuvers_system(
+    mut controlled_query: Query<
+        (Entity, &mut ManeuverQueue, &Transform, &LinearVelocity, &AngularVelocity),
+        With<ControlledByPlayer>,
+    >,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    let delta_time = time.delta_seconds();
+
+    for (structure_entity, mut maneuver_queue, transform, linear_velocity, angular_velocity) in &mut controlled_query {
+        let heading = transform.rotation.to_euler(EulerRot::XYZ).2;
+        let turned = maneuver_queue.last_tick.map_or(0.0, |(_, _, last_heading)| wrap_angle(heading - last_heading));
+        maneuver_queue.measure_turn(angular_velocity.0, delta_time);
+
+        if maneuver_queue.current.is_none() {
+            maneuver_queue.current =
+                maneuver_queue.queued.pop_front().map(|maneuver| (maneuver, ManeuverProgress::start(maneuver)));
+        }
+        let Some((maneuver, progress)) = maneuver_queue.current else {
+            commands.entity(structure_entity).remove::<(ManeuverQueue, PilotCommand)>();
+            continue;
+        };
+
+        let (command, progress) = match progress {
+            ManeuverProgress::Rotate { remaining_angle } => {
+                let remaining_angle = remaining_angle - turned;
+                let done = remaining_angle.abs() < ROTATION_TOLERANCE && angular_velocity.0.abs() < STILL_ANGULAR_SPEED;
+                let rotation = maneuver_queue.rotation_towards(remaining_angle, angular_velocity.0, delta_time);
+                (
+                    PilotCommand { rotation, ..default() },
+                    (!done).then_some(ManeuverProgress::Rotate { remaining_angle }),
+                )
+            }
+            ManeuverProgress::Burn { remaining_time } => {
+                let forward = transform.rotation.mul_vec3(Vec3::Y).truncate();
+                let remaining_time = remaining_time - delta_time;
+                (
+                    PilotCommand { thrust: forward, ..default() },
+                    (remaining_time > 0.0).then_some(ManeuverProgress::Burn { remaining_time }),
+                )
+            }
+            ManeuverProgress::AllStop => {
+                let done = linear_velocity.0.length() < STILL_SPEED && angular_velocity.0.abs() < STILL_ANGULAR_SPEED;
+                let rotation = maneuver_queue.rotation_towards(0.0, angular_velocity.0, delta_time);
+                (PilotCommand { rotation, brake: true, ..default() }, (!done).then_some(ManeuverProgress::AllStop))
+            }
+        };
+
+        if progress.is_none() {
+            debug!("Maneuver done: {}", maneuver);
+        }
+        maneuver_queue.current = progress.map(|progress| (maneuver, progress));
+        maneuver_queue.last_tick = Some((command.rotation, angular_velocity.0, heading));
+        commands.entity(structure_entity).insert(command);
+    }
+}
@@ -0,0 +1,151 @@
+use crate::core::prelude::*;
+use crate::gameplay::eject::Invulnerable;
+use crate::gameplay::life_support::PlayerSuffocatingEvent;
+use crate::world::prelude::*;
+
+use bevy::prelude::*;
+
+const RECOVERY_TIME: f32 = 5.0; // seconds in a medical bay to heal one injury level
+
+pub struct MedicalPlugin;
+
+impl Plugin for MedicalPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<InjuryEvent>().add_systems(
+            Update,
+            (
+                vacuum_injury_system.run_if(on_event::<StructureDepressurizationEvent>()),
+                suffocation_injury_system.run_if(on_event::<PlayerSuffocatingEvent>()),
+                apply_injury_system.run_if(on_event::<InjuryEvent>()),
+                medical_bay_recovery_system,
+            )
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+/// Health condition of a character.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Injury {
+    #[default]
+    Healthy,
+    Wounded,
+    Incapacitated,
+}
+
+impl Injury {
+    /// The next, more severe, injury level.
+    pub fn worsen(self) -> Self {
+        match self {
+            Injury::Healthy => Injury::Wounded,
+            Injury::Wounded | Injury::Incapacitated => Injury::Incapacitated,
+        }
+    }
+
+    /// The next, less severe, injury level.
+    pub fn heal(self) -> Self {
+        match self {
+            Injury::Incapacitated => Injury::Wounded,
+            Injury::Wounded | Injury::Healthy => Injury::Healthy,
+        }
+    }
+
+    pub fn is_incapacitated(&self) -> bool {
+        matches!(self, Injury::Incapacitated)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum InjuryCause {
+    Combat,
+    Vacuum,
+}
+
+/// Sent when a character gets hurt, each event worsens its `Injury` by one level.
+#[derive(Event, Debug)]
+pub struct InjuryEvent {
+    pub entity: Entity,
+    pub cause: InjuryCause,
+}
+
+/// Tracks the time a character has spent being treated in a medical bay.
+#[derive(Component, Deref, DerefMut)]
+struct Recovering(Timer);
+
+/// Characters standing in a room that gets breached are hurt by the decompression.
+fn vacuum_injury_system(
+    mut depressurization_reader: EventReader<StructureDepressurizationEvent>,
+    mut injury_writer: EventWriter<InjuryEvent>,
+    player_query: Query<(Entity, &GlobalTransform), With<Player>>,
+    structures_query: Query<(&Transform, &Structure)>,
+) {
+    for event in depressurization_reader.read() {
+        let Ok((structure_transform, structure)) = structures_query.get(event.depressurized_structure) else {
+            continue;
+        };
+        for (player_entity, player_transform) in &player_query {
+            let grid_pos = structure.world_to_grid(player_transform.translation(), structure_transform);
+            if event.breached_cells.contains(&grid_pos) {
+                injury_writer.send(InjuryEvent { entity: player_entity, cause: InjuryCause::Vacuum });
+            }
+        }
+    }
+}
+
+fn suffocation_injury_system(
+    mut suffocating_reader: EventReader<PlayerSuffocatingEvent>,
+    mut injury_writer: EventWriter<InjuryEvent>,
+) {
+    for event in suffocating_reader.read() {
+        injury_writer.send(InjuryEvent { entity: event.player_entity, cause: InjuryCause::Vacuum });
+    }
+}
+
+fn apply_injury_system(
+    mut injury_reader: EventReader<InjuryEvent>,
+    mut injury_query: Query<&mut Injury, Without<Invulnerable>>,
+) {
+    for event in injury_reader.read() {
+        if let Ok(mut injury) = injury_query.get_mut(event.entity) {
+            *injury = injury.worsen();
+            debug!("Character injured by {:?}, now {:?}", event.cause, *injury);
+        }
+    }
+}
+
+/// Heals characters, the player or crew members, standing on a medical bay cell one injury level every
+/// `RECOVERY_TIME` seconds.
+fn medical_bay_recovery_system(
+    mut character_query: Query<(Entity, &GlobalTransform, &mut Injury, Option<&mut Recovering>)>,
+    structures_query: Query<(&Transform, &Structure)>,
+    medical_bay_query: Query<(), With<MedicalBayModule>>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (character_entity, character_transform, mut injury, recovering) in &mut character_query {
+        let in_medical_bay = *injury != Injury::Healthy
+            && structures_query.iter().any(|(structure_transform, structure)| {
+                let grid_pos = structure.world_to_grid(character_transform.translation(), structure_transform);
+                structure.module_at(grid_pos).is_some_and(|module_entity| medical_bay_query.contains(module_entity))
+            });
+
+        match (in_medical_bay, recovering) {
+            (true, Some(mut recovering)) => {
+                if recovering.tick(time.delta()).just_finished() {
+                    *injury = injury.heal();
+                    debug!("Character recovered in the medical bay, now {:?}", *injury);
+                }
+            }
+            (true, None) => {
+                commands
+                    .entity(character_entity)
+                    .insert(Recovering(Timer::from_seconds(RECOVERY_TIME, TimerMode::Repeating)));
+            }
+            (false, Some(_)) => {
+                commands.entity(character_entity).remove::<Recovering>();
+            }
+            (false, None) => {}
+        }
+    }
+}
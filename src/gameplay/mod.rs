@@ -1,3 +1,51 @@
+pub mod achievements;
+pub mod ai;
+pub mod autopilot;
+pub mod blueprints;
+pub mod building;
+pub mod cargo;
+pub mod clipboard;
+pub mod contaminants;
+pub mod crafting;
+pub mod crew;
+pub mod debris;
+pub mod degradation;
+pub mod derelicts;
+pub mod despawn_audit;
+pub mod destruction;
+pub mod docking;
+pub mod doors;
+pub mod editor;
+pub mod eject;
+pub mod encounters;
+pub mod escort;
+pub mod factions;
+pub mod gravity;
+pub mod hails;
+pub mod health;
+pub mod heat;
+pub mod jetpack;
+pub mod journal;
+pub mod life_support;
+pub mod livery;
+pub mod maneuvers;
+pub mod medical;
 pub mod movement;
+pub mod offscreen_battles;
+pub mod power;
 pub mod prelude;
+pub mod repair;
+pub mod repair_drones;
+pub mod sandbox;
+pub mod scanning;
+pub mod scenario;
+pub mod sensors;
+pub mod stats;
+pub mod status_effects;
 pub mod structures_combat;
+pub mod target_drones;
+pub mod targeting;
+pub mod tutorial;
+pub mod upgrades;
+pub mod world_bounds;
+pub mod wrecks;
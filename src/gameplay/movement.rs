@@ -1,55 +1,189 @@
 use crate::core::prelude::*;
+use crate::gameplay::degradation::{performance, ModulePerformance};
+use crate::gameplay::docking::DockedStructures;
+use crate::gameplay::jetpack::{player_thrust, Jetpack};
+use crate::gameplay::medical::Injury;
+use crate::gameplay::power::PowerConsumer;
+use crate::gameplay::status_effects::{is_disabled, StatusEffects};
+use crate::gameplay::upgrades::{tier_output, ModuleTier};
 use crate::world::prelude::*;
 
 use avian2d::math::Vector;
 use avian2d::prelude::*;
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
-const STRUCTURE_MOVE_SPEED: f32 = 10.0; // m/s
-const PLAYER_MOVE_SPEED: f32 = 1.45; // m/s
-const PLAYER_DECELERATION_FACTOR: f32 = 2.0; // m/s
+const MAX_ANGULAR_ACCELERATION: f32 = 1.0; // rad/s²
+const MAX_ANGULAR_SPEED: f32 = 1.5; // rad/s
+const ROTATION_STOPPING_ANGLE: f32 = std::f32::consts::FRAC_PI_4; // rad, angle needed to stop a full speed turn
 
 pub struct MovementPlugin;
 
 impl Plugin for MovementPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
+        app.init_resource::<MovementSettings>().add_systems(
             FixedUpdate,
             (
+                inherit_structure_velocity_system.before(player_move_system).before(player_stop_system),
                 player_move_system,
                 structure_move_system,
                 structure_rotate_system,
                 player_stop_system,
                 structure_stop_system,
             )
+                .in_set(PilotingSet)
                 .run_if(in_state(GameState::InGame)),
         );
     }
 }
 
+/// Systems moving the player and the structures they fly, automated piloting runs before them.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PilotingSet;
+
+/// Piloting of the controlled structure asked by an automated system, applied on top of the keyboard.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct PilotCommand {
+    /// World direction to fire the engines towards, of length 1 at most.
+    pub thrust: Vec2,
+    /// Rotation factor from -1 to 1, positive for counterclockwise.
+    pub rotation: f32,
+    pub brake: bool,
+}
+
+/// Structure the player walks inside. Its motion carries the player along: every tick the player velocity gets the
+/// change of velocity of the structure under them, and walking speeds are relative to it, so a structure
+/// accelerating or turning does not slide the player into its walls.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Aboard {
+    pub structure_entity: Entity,
+    /// Velocity of the structure at the player position on the previous tick, unknown right after boarding.
+    frame_velocity: Option<Vec2>,
+}
+
+impl Aboard {
+    pub fn new(structure_entity: Entity) -> Self {
+        Self { structure_entity, frame_velocity: None }
+    }
+
+    /// Velocity the walking speeds are relative to.
+    pub fn frame_velocity(&self) -> Vec2 {
+        self.frame_velocity.unwrap_or(Vec2::ZERO)
+    }
+}
+
+/// Velocity of a point of a structure, in world space, from its linear and angular velocity.
+pub fn structure_point_velocity(
+    linear_velocity: &LinearVelocity,
+    angular_velocity: &AngularVelocity,
+    structure_transform: &Transform,
+    center_of_mass: &CenterOfMass,
+    point: Vec2,
+) -> Vec2 {
+    let world_center_of_mass = structure_transform.transform_point(center_of_mass.0.extend(0.0)).truncate();
+    linear_velocity.0 + angular_velocity.0 * (point - world_center_of_mass).perp()
+}
+
+/// Speeds and forces of the player and the structures they fly, read from the settings file.
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MovementSettings {
+    /// Acceleration of the player walking or floating, in m/s².
+    pub player_move_speed: f32,
+    /// Top speed of the player, in m/s.
+    pub player_max_speed: f32,
+    /// Deceleration when braking, in m/s², also used by the controlled structure.
+    pub player_deceleration: f32,
+    /// Top speed of the controlled structure, in m/s.
+    pub structure_max_speed: f32,
+    /// Thrust of every engine module, in N.
+    pub engine_thrust: f32,
+    /// Torque of the reaction control system, in N.m, lets structures without engines turn slowly.
+    pub rcs_torque: f32,
+}
+
+impl Default for MovementSettings {
+    fn default() -> Self {
+        Self {
+            player_move_speed: 1.45,
+            player_max_speed: 5.0,
+            player_deceleration: 2.0,
+            structure_max_speed: 10.0,
+            engine_thrust: 5_000_000.0,
+            rcs_torque: 1_000_000.0,
+        }
+    }
+}
+
+/// Gives the players aboard a structure the change of velocity of the structure under them.
+fn inherit_structure_velocity_system(
+    mut player_query: Query<(Entity, &mut LinearVelocity, &GlobalTransform, &mut Aboard, Has<RigidBody>), With<Player>>,
+    structures_query: Query<(&LinearVelocity, &AngularVelocity, &Transform, &CenterOfMass), Without<Player>>,
+    mut commands: Commands,
+) {
+    for (player_entity, mut velocity, player_transform, mut aboard, has_rigid_body) in &mut player_query {
+        let Ok((linear_velocity, angular_velocity, structure_transform, center_of_mass)) =
+            structures_query.get(aboard.structure_entity)
+        else {
+            commands.entity(player_entity).remove::<Aboard>();
+            continue;
+        };
+        // A pilot has no body and is carried by the structure they are attached to
+        if !has_rigid_body {
+            aboard.frame_velocity = None;
+            continue;
+        }
+
+        let frame_velocity = structure_point_velocity(
+            linear_velocity,
+            angular_velocity,
+            structure_transform,
+            center_of_mass,
+            player_transform.translation().truncate(),
+        );
+        if let Some(last_frame_velocity) = aboard.frame_velocity {
+            velocity.0 += frame_velocity - last_frame_velocity;
+        }
+        aboard.frame_velocity = Some(frame_velocity);
+    }
+}
+
 fn player_move_system(
-    mut query: Query<&mut LinearVelocity, With<Player>>,
+    mut query: Query<(&mut LinearVelocity, Option<&Injury>, Option<&mut Jetpack>, Option<&Aboard>), With<Player>>,
     mut input_reader: EventReader<InputAction>,
     time: Res<Time>,
     player_resource: Res<PlayerResource>,
+    settings: Res<MovementSettings>,
 ) {
     if player_resource.is_controlling_structure {
         return;
     }
 
     let delta_time = time.delta_seconds();
-    let max_speed = 5.0; // Maximum speed in m/s
+    let max_speed = settings.player_max_speed;
 
     for event in input_reader.read() {
         match event {
             InputAction::Move(direction) => {
-                for mut velocity in &mut query {
-                    velocity.x += direction.x * PLAYER_MOVE_SPEED * delta_time;
-                    velocity.y += direction.y * PLAYER_MOVE_SPEED * delta_time;
+                for (mut velocity, injury, mut jetpack, aboard) in &mut query {
+                    // Incapacitated characters cannot move by themselves
+                    if injury.is_some_and(|injury| injury.is_incapacitated()) {
+                        continue;
+                    }
+                    // Out of the structures the jetpack pushes the player, as long as it has fuel
+                    let thrust = player_thrust(
+                        jetpack.as_deref_mut(),
+                        player_resource.inside_structure.is_some(),
+                        direction.length(),
+                        delta_time,
+                    );
+                    // Aboard a structure the player walks relative to it
+                    let frame_velocity = aboard.map_or(Vec2::ZERO, Aboard::frame_velocity);
+                    let mut relative_velocity = velocity.0 - frame_velocity;
+                    relative_velocity += direction.truncate() * settings.player_move_speed * thrust * delta_time;
 
                     // Clamp the velocity to the maximum speed
-                    let new_velocity = Vec2::new(velocity.x, velocity.y).clamp_length_max(max_speed);
-                    *velocity = LinearVelocity(new_velocity);
+                    *velocity = LinearVelocity(frame_velocity + relative_velocity.clamp_length_max(max_speed));
                 }
             }
             _ => {}
@@ -58,137 +192,250 @@ fn player_move_system(
 }
 
 fn player_stop_system(
-    mut query: Query<&mut LinearVelocity, With<Player>>,
+    mut query: Query<(&mut LinearVelocity, Option<&mut Jetpack>, Option<&Aboard>), With<Player>>,
     mut input_reader: EventReader<InputAction>,
     time: Res<Time>,
+    player_resource: Res<PlayerResource>,
+    settings: Res<MovementSettings>,
 ) {
     let delta_time = time.delta_seconds();
-    let deceleration_factor = PLAYER_DECELERATION_FACTOR;
+    let deceleration_factor = settings.player_deceleration;
 
     for event in input_reader.read() {
         if matches!(event, InputAction::Break) {
-            for mut velocity in &mut query {
-                velocity.0 = apply_deceleration(velocity.0, deceleration_factor, delta_time);
+            for (mut velocity, mut jetpack, aboard) in &mut query {
+                // Aboard a structure the player stops relative to it
+                let frame_velocity = aboard.map_or(Vec2::ZERO, Aboard::frame_velocity);
+                if velocity.0 == frame_velocity {
+                    continue;
+                }
+                let thrust =
+                    player_thrust(jetpack.as_deref_mut(), player_resource.inside_structure.is_some(), 1.0, delta_time);
+                velocity.0 = frame_velocity
+                    + apply_deceleration(velocity.0 - frame_velocity, deceleration_factor * thrust, delta_time);
             }
         }
     }
 }
 
 fn structure_stop_system(
-    mut controlled_structure_query: Query<&mut LinearVelocity, With<ControlledByPlayer>>,
+    mut controlled_structure_query: Query<(&mut LinearVelocity, Option<&PilotCommand>), With<ControlledByPlayer>>,
     mut input_reader: EventReader<InputAction>,
     time: Res<Time>,
+    settings: Res<MovementSettings>,
 ) {
     let delta_time = time.delta_seconds();
-    let deceleration_factor = PLAYER_DECELERATION_FACTOR;
+    let deceleration_factor = settings.player_deceleration;
+    let brake_pressed = input_reader.read().any(|event| matches!(event, InputAction::Break));
 
-    for event in input_reader.read() {
-        for (mut velocity) in &mut controlled_structure_query {
-            match event {
-                InputAction::Break => {
-                    // Apply deceleration in the opposite direction of the current velocity
-                    let mut velocity_vector = velocity.0;
-
-                    // Check if velocity is non-zero to avoid unnecessary calculations
-                    if velocity_vector.length_squared() > 0.0 {
-                        // Calculate the deceleration to apply
-                        let deceleration = -velocity_vector.normalize() * deceleration_factor * delta_time;
-
-                        // Apply deceleration to the velocity
-                        velocity_vector += deceleration;
-
-                        // Prevent overshooting: Stop the player if velocity is close to zero
-                        if velocity_vector.length_squared() < (deceleration_factor * delta_time).powi(2) {
-                            velocity_vector = Vector::ZERO;
-                        }
-
-                        // Update the player's velocity
-                        velocity.0 = velocity_vector;
-                    }
-                }
-                _ => {}
-            }
+    for (mut velocity, pilot_command) in &mut controlled_structure_query {
+        if !brake_pressed && !pilot_command.is_some_and(|pilot_command| pilot_command.brake) {
+            continue;
         }
+        // Apply deceleration in the opposite direction of the current velocity, stopping once it is close to zero
+        velocity.0 = apply_deceleration(velocity.0, deceleration_factor, delta_time);
     }
 }
 
 // TODO: Refactor to use observers
+/// Drives the controlled structure with its engines.
+/// Every `Engine` module pushes along its own facing, so only engines pointing roughly towards the requested
+/// direction fire. Each thrust is applied at the engine position, so an unbalanced layout also induces torque
+/// around the center of mass and losing engines degrades handling.
+/// The structures docked to the controlled one fire their engines too, and a `PilotCommand` adds to the keyboard.
 fn structure_move_system(
-    mut controlled_structure_query: Query<
-        (&mut ExternalForce, &mut LinearVelocity, &AngularVelocity, &ControlledByPlayer, &Children),
+    controlled_query: Query<(Entity, Option<&DockedStructures>, Option<&PilotCommand>), With<ControlledByPlayer>>,
+    mut structures_query: Query<
+        (&mut ExternalForce, &mut LinearVelocity, &Transform, &CenterOfMass, &Children),
         With<Structure>,
     >,
-    player_resource: ResMut<PlayerResource>,
+    player_resource: Res<PlayerResource>,
     mut input_reader: EventReader<InputAction>,
-    mut child_query: Query<&mut Module>,
-    time: Res<Time>,
-    mut commands: Commands,
+    child_query: EngineQuery,
+    settings: Res<MovementSettings>,
 ) {
-    let mut able_to_move = false;
-    if player_resource.is_controlling_structure {
-        let delta_time = time.delta_seconds();
-        let structure_max_speed = 10.0; // Maximum speed in m/s
-        let engine_force = 100.0; // Force generated by each engine in Newtons
-
-        // Get structure controlled by player should be unique
-        let (mut external_force, mut structure_velocity, structure_angular_v, controlled_by, childrens) =
-            controlled_structure_query.single_mut();
-
-        for child in childrens {
-            if let Ok(module) = child_query.get_mut(*child) {
-                // Check if a structure has at least one engine module as child
-                if matches!(module.module_type, ModuleType::Engine) {
-                    able_to_move = true;
-                }
-            }
+    let mut input_direction = Vec2::ZERO;
+    for event in input_reader.read() {
+        if let InputAction::Move(direction) = event {
+            input_direction += direction.truncate();
         }
+    }
 
-        if able_to_move {
-            for event in input_reader.read() {
-                match event {
-                    InputAction::Move(direction) => {
-                        structure_velocity.x += direction.x * STRUCTURE_MOVE_SPEED * delta_time;
-                        structure_velocity.y += direction.y * STRUCTURE_MOVE_SPEED * delta_time;
-
-                        // Clamp the velocity to the maximum speed
-                        let new_max_velocity =
-                            Vec2::new(structure_velocity.x, structure_velocity.y).clamp_length_max(structure_max_speed);
-                        *structure_velocity = LinearVelocity(new_max_velocity);
-                    }
-                    _ => {}
-                }
+    // Get structure controlled by player should be unique
+    let Ok((controlled_entity, docked, pilot_command)) = controlled_query.get_single() else {
+        return;
+    };
+    if let Some(pilot_command) = pilot_command {
+        input_direction += pilot_command.thrust;
+    }
+    let flown_structures =
+        std::iter::once(controlled_entity).chain(docked.into_iter().flat_map(|docked| docked.0.iter().copied()));
+
+    for structure_entity in flown_structures {
+        let Ok((mut external_force, mut structure_velocity, structure_transform, center_of_mass, childrens)) =
+            structures_query.get_mut(structure_entity)
+        else {
+            continue;
+        };
+
+        // Forces are persistent, so the previous tick thrust must not keep pushing the structure
+        external_force.clear();
+
+        if !player_resource.is_controlling_structure || input_direction == Vec2::ZERO {
+            continue;
+        }
+
+        fire_engines(
+            &mut external_force,
+            structure_transform,
+            center_of_mass,
+            childrens,
+            input_direction.normalize(),
+            &child_query,
+            &settings,
+        );
+
+        // Clamp the velocity to the maximum speed
+        structure_velocity.0 = structure_velocity.0.clamp_length_max(settings.structure_max_speed);
+    }
+}
+
+type EngineQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Module,
+        &'static Transform,
+        Option<&'static PowerConsumer>,
+        Option<&'static ModulePerformance>,
+        Option<&'static ModuleTier>,
+        Option<&'static StatusEffects>,
+    ),
+    With<EngineModule>,
+>;
+
+/// Thrust of an engine, none without power or disabled by a status effect, less when damaged and more when upgraded.
+fn engine_output(
+    power: Option<&PowerConsumer>,
+    module_performance: Option<&ModulePerformance>,
+    module_tier: Option<&ModuleTier>,
+    status_effects: Option<&StatusEffects>,
+    settings: &MovementSettings,
+) -> f32 {
+    if power.is_some_and(|power| !power.powered) || is_disabled(status_effects) {
+        return 0.0;
+    }
+    settings.engine_thrust * performance(module_performance) * tier_output(module_tier)
+}
+
+/// Applies the thrust of the engines of a structure pushing towards `input_direction`.
+fn fire_engines(
+    external_force: &mut ExternalForce,
+    structure_transform: &Transform,
+    center_of_mass: &CenterOfMass,
+    childrens: &Children,
+    input_direction: Vec2,
+    child_query: &EngineQuery,
+    settings: &MovementSettings,
+) {
+    let structure_position = structure_transform.translation.truncate();
+    let world_center_of_mass =
+        structure_position + structure_transform.rotation.mul_vec3(center_of_mass.0.extend(0.0)).truncate();
+
+    for child in childrens {
+        if let Ok((module, module_transform, power, module_performance, module_tier, status_effects)) =
+            child_query.get(*child)
+        {
+            let output = engine_output(power, module_performance, module_tier, status_effects, settings);
+            if output <= 0.0 {
+                continue;
             }
+
+            // Engines push along the module orientation in world space
+            let thrust_direction =
+                structure_transform.rotation.mul_vec3(module.orientation.forward().extend(0.0)).truncate().normalize();
+
+            // Only the component of the thrust that helps the requested direction is used
+            let throttle = thrust_direction.dot(input_direction);
+            if throttle <= 0.0 {
+                continue;
+            }
+
+            let engine_position =
+                structure_position + structure_transform.rotation.mul_vec3(module_transform.translation).truncate();
+
+            external_force.apply_force_at_point(
+                thrust_direction * output * throttle,
+                engine_position,
+                world_center_of_mass,
+            );
         }
     }
 }
 
+/// Turning limits of a structure derived from its engines torque and its moment of inertia.
+/// Small structures turn quickly, capital ships turn ponderously.
+fn angular_limits(torque: f32, inertia: f32) -> (f32, f32) {
+    if inertia <= 0.0 {
+        return (MAX_ANGULAR_ACCELERATION, MAX_ANGULAR_SPEED);
+    }
+    let angular_acceleration = (torque / inertia).min(MAX_ANGULAR_ACCELERATION);
+    // Fastest turn rate the structure can still stop within the stopping angle
+    let max_angular_speed = (2.0 * angular_acceleration * ROTATION_STOPPING_ANGLE).sqrt().min(MAX_ANGULAR_SPEED);
+    (angular_acceleration, max_angular_speed)
+}
+
+/// Turns the controlled structure, and the structures docked to it as a single body, from the keyboard and the
+/// `PilotCommand`.
 fn structure_rotate_system(
-    mut controlled_structure_query: Query<
-        (&mut AngularVelocity, &LinearVelocity),
-        (With<Structure>, With<ControlledByPlayer>),
-    >,
+    controlled_query: Query<(Entity, Option<&DockedStructures>, Option<&PilotCommand>), With<ControlledByPlayer>>,
+    mut structures_query: Query<(&mut AngularVelocity, &Inertia, &CenterOfMass, &Children), With<Structure>>,
+    engine_query: EngineQuery,
     mut input_reader: EventReader<InputAction>,
     time: Res<Time>,
+    settings: Res<MovementSettings>,
 ) {
     let delta_time = time.delta_seconds();
-    let rotation_speed = 0.1; // Base rotation speed in radians per second
-    let max_rotation_speed = 0.2; // Maximum rotation speed in radians per second
+    let Ok((controlled_entity, docked, pilot_command)) = controlled_query.get_single() else {
+        return;
+    };
+    let flown_structures: Vec<Entity> = std::iter::once(controlled_entity)
+        .chain(docked.into_iter().flat_map(|docked| docked.0.iter().copied()))
+        .collect();
 
+    let mut factor = pilot_command.map_or(0.0, |pilot_command| pilot_command.rotation);
     for event in input_reader.read() {
-        match event {
-            InputAction::Rotate(factor) => {
-                if let Ok((mut structure_angular_v, structure_velocity)) = controlled_structure_query.get_single_mut() {
-                    // Apply the rotation factor to the angular velocity
-                    structure_angular_v.0 += factor * rotation_speed * delta_time;
-
-                    // Clamp the angular velocity to the maximum speed
-                    let new_max_angular_velocity = structure_angular_v.0.clamp(-max_rotation_speed, max_rotation_speed);
-                    *structure_angular_v = AngularVelocity(new_max_angular_velocity);
-                }
-            }
-            _ => {}
+        if let InputAction::Rotate(rotation_factor) = event {
+            factor += rotation_factor;
         }
     }
+    if factor == 0.0 {
+        return;
+    }
+
+    // Every engine able to fire can push sideways around the center of mass
+    let mut engines_torque = 0.0;
+    let mut total_inertia = 0.0;
+    for (_, inertia, center_of_mass, childrens) in structures_query.iter_many(&flown_structures) {
+        engines_torque += engine_query
+            .iter_many(childrens)
+            .map(|(_, transform, power, module_performance, module_tier, status_effects)| {
+                engine_output(power, module_performance, module_tier, status_effects, &settings)
+                    * transform.translation.truncate().distance(center_of_mass.0)
+            })
+            .sum::<f32>();
+        total_inertia += inertia.0;
+    }
+    let (angular_acceleration, max_angular_speed) = angular_limits(engines_torque + settings.rcs_torque, total_inertia);
+
+    let mut structures = structures_query.iter_many_mut(&flown_structures);
+    while let Some((mut structure_angular_v, ..)) = structures.fetch_next() {
+        // Apply the rotation factor to the angular velocity
+        structure_angular_v.0 += factor * angular_acceleration * delta_time;
+
+        // Clamp the angular velocity to the maximum speed
+        structure_angular_v.0 = structure_angular_v.0.clamp(-max_angular_speed, max_angular_speed);
+    }
 }
 
 fn apply_deceleration(mut velocity: Vector, deceleration_factor: f32, delta_time: f32) -> Vector {
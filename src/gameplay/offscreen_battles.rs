@@ -0,0 +1,438 @@
+use crate::core::asset_loader::StructureData;
+use crate::core::prelude::*;
+use crate::gameplay::ai::{AiPilot, AiStance};
+use crate::gameplay::crew::Crew;
+use crate::gameplay::degradation::{performance, ModulePerformance};
+use crate::gameplay::factions::Faction;
+use crate::gameplay::livery::Livery;
+use crate::gameplay::wrecks::Wreck;
+use crate::world::prelude::*;
+
+use crate::prelude::*;
+use std::collections::HashSet;
+
+const ABSTRACTION_RADIUS: f32 = 800.0; // meters, battles farther than this from the player are resolved abstractly
+const SIMULATION_RADIUS: f32 = 600.0; // meters, closer than this an abstract battle is simulated again
+const ROUND_INTERVAL: f32 = 1.0; // seconds between two exchanges of fire
+const CANNON_HIT_CHANCE: f32 = 0.5;
+const CANNON_DAMAGE: f32 = 60.0; // structural points per cannon hit
+const WRECK_SCATTER: f32 = 40.0; // meters, how far from their position destroyed ships drift
+const WRECK_INTEGRITY: f32 = 0.15; // fraction of the structural points left on the modules of a wreck
+const BATTLE_SEED: u64 = 0xBA77_1E5E_ED00_0001;
+
+/// Fights between AI ships far from the player are resolved with dice rolls on an abstract model of the ships,
+/// without entities nor physics. The ships come back as entities, damaged or wrecked, when the player gets close
+/// or once the battle is over.
+pub struct OffscreenBattlePlugin;
+
+impl Plugin for OffscreenBattlePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OffscreenBattles>().add_event::<OffscreenBattleResolvedEvent>().add_systems(
+            Update,
+            (
+                abstract_distant_battles_system,
+                resolve_offscreen_battles_system,
+                materialize_battles_system,
+                apply_hull_integrity_system,
+            )
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+/// Structures that are never turned into an abstract ship, like mission critical ones.
+#[derive(Component, Debug, Default)]
+pub struct AlwaysSimulated;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BattleSide {
+    Attackers,
+    Defenders,
+}
+
+/// A ship of an abstract battle, with what is needed to spawn it back.
+#[derive(Debug, Clone)]
+pub struct AbstractShip {
+    pub data: StructureData,
+    pub name: Option<StructureName>,
+    pub stance: Option<AiStance>,
+    pub hull: f32,
+    pub max_hull: f32,
+    /// Number of working cannons, damaged ones counting for a fraction.
+    pub cannons: f32,
+}
+
+impl AbstractShip {
+    pub fn integrity(&self) -> f32 {
+        if self.max_hull > 0.0 {
+            (self.hull / self.max_hull).clamp(0.0, 1.0)
+        } else {
+            1.0
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AbstractBattle {
+    pub center: Vec2,
+    pub attackers: Vec<AbstractShip>,
+    pub defenders: Vec<AbstractShip>,
+    pub wrecks: Vec<AbstractShip>,
+    round_timer: Timer,
+}
+
+impl AbstractBattle {
+    pub fn winner(&self) -> Option<BattleSide> {
+        match (self.attackers.is_empty(), self.defenders.is_empty()) {
+            (false, true) => Some(BattleSide::Attackers),
+            (true, false) => Some(BattleSide::Defenders),
+            _ => None,
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.attackers.is_empty() || self.defenders.is_empty()
+    }
+}
+
+#[derive(Resource, Debug)]
+pub struct OffscreenBattles {
+    pub battles: Vec<AbstractBattle>,
+    rng: SeededRng,
+}
+
+impl Default for OffscreenBattles {
+    fn default() -> Self {
+        Self { battles: Vec::new(), rng: SeededRng::new(BATTLE_SEED) }
+    }
+}
+
+impl OffscreenBattles {
+    pub fn with_seed(seed: u64) -> Self {
+        Self { battles: Vec::new(), rng: SeededRng::new(seed) }
+    }
+
+    /// Uniform random number in `[0, 1)`.
+    fn roll(&mut self) -> f32 {
+        self.rng.roll()
+    }
+}
+
+/// Sent when an abstract battle ends without the player around.
+#[derive(Event, Debug)]
+pub struct OffscreenBattleResolvedEvent {
+    pub center: Vec2,
+    pub winner: Option<BattleSide>,
+    pub survivors: usize,
+    pub wrecks: usize,
+}
+
+/// Fraction of structural points to set on the modules of a structure spawned from an abstract ship.
+#[derive(Component, Debug)]
+struct HullIntegrity(f32);
+
+type ShipQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Structure,
+        &'static Transform,
+        &'static LinearVelocity,
+        &'static Livery,
+        &'static Crew,
+        &'static Children,
+        Option<&'static Faction>,
+    ),
+>;
+
+fn abstract_ship(
+    entity: Entity,
+    ships_query: &ShipQuery,
+    modules_query: &Query<(&Module, &ModuleMaterial, Option<&ModulePerformance>, Has<CannonModule>)>,
+    name: Option<&StructureName>,
+    stance: Option<AiStance>,
+) -> Option<AbstractShip> {
+    let (structure, transform, velocity, livery, crew, children, faction) = ships_query.get(entity).ok()?;
+
+    let mut data = structure.to_structure_data(modules_query.iter_many(children).map(|(module, ..)| module));
+    data.world_pos = [transform.translation.x, transform.translation.y];
+    data.rotation = transform.rotation.to_euler(EulerRot::XYZ).2;
+    data.velocity = [velocity.x, velocity.y];
+    data.crew = crew.members;
+    data.livery = livery.clone();
+    data.faction = faction.map(|faction| faction.0.clone());
+
+    let (mut hull, mut max_hull, mut cannons) = (0.0, 0.0, 0.0);
+    for (_, module_material, module_performance, is_cannon) in modules_query.iter_many(children) {
+        hull += module_material.structural_points;
+        // Lost modules are not part of the layout anymore, the ship is rated from what is left of it
+        max_hull += module_material.max_structural_points;
+        if is_cannon {
+            cannons += performance(module_performance);
+        }
+    }
+
+    Some(AbstractShip { data, name: name.cloned(), stance, hull, max_hull, cannons })
+}
+
+/// Turns the AI ships fighting each other far from the player into abstract battles.
+fn abstract_distant_battles_system(
+    pilots_query: Query<(Entity, &AiPilot)>,
+    positions_query: Query<&GlobalTransform, With<Structure>>,
+    simulated_query: Query<(), Or<(With<AlwaysSimulated>, With<ControlledByPlayer>)>>,
+    names_query: Query<&StructureName>,
+    ships_query: ShipQuery,
+    modules_query: Query<(&Module, &ModuleMaterial, Option<&ModulePerformance>, Has<CannonModule>)>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    player_resource: Res<PlayerResource>,
+    mut battles: ResMut<OffscreenBattles>,
+    mut commands: Commands,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let player_position = player_transform.translation().truncate();
+    let is_distant = |entity: Entity| {
+        player_resource.inside_structure != Some(entity)
+            && !simulated_query.contains(entity)
+            && positions_query.get(entity).is_ok_and(|transform| {
+                transform.translation().truncate().distance(player_position) > ABSTRACTION_RADIUS
+            })
+    };
+
+    // Every structure under attack by AI ships makes a battle
+    let mut taken = HashSet::new();
+    let mut targets: Vec<Entity> = pilots_query
+        .iter()
+        .filter(|(_, pilot)| pilot.stance == AiStance::Attack)
+        .filter_map(|(_, pilot)| pilot.target)
+        .filter(|target| positions_query.contains(*target))
+        .collect();
+    targets.sort();
+    targets.dedup();
+
+    for target in targets {
+        let attackers: Vec<Entity> = pilots_query
+            .iter()
+            .filter(|(entity, pilot)| {
+                pilot.stance == AiStance::Attack && pilot.target == Some(target) && !taken.contains(entity)
+            })
+            .map(|(entity, _)| entity)
+            .collect();
+        if taken.contains(&target)
+            || attackers.is_empty()
+            || !is_distant(target)
+            || !attackers.iter().all(|a| is_distant(*a))
+        {
+            continue;
+        }
+
+        let to_abstract_ship = |entity: Entity| {
+            let stance = pilots_query.get(entity).ok().map(|(_, pilot)| pilot.stance);
+            abstract_ship(entity, &ships_query, &modules_query, names_query.get(entity).ok(), stance)
+        };
+        let defenders: Vec<AbstractShip> = to_abstract_ship(target).into_iter().collect();
+        let attacker_ships: Vec<AbstractShip> =
+            attackers.iter().filter_map(|entity| to_abstract_ship(*entity)).collect();
+        if defenders.is_empty() || attacker_ships.is_empty() {
+            continue;
+        }
+
+        let participants: Vec<Entity> = attackers.iter().copied().chain([target]).collect();
+        let center = participants
+            .iter()
+            .filter_map(|entity| positions_query.get(*entity).ok())
+            .map(|transform| transform.translation().truncate())
+            .sum::<Vec2>()
+            / participants.len() as f32;
+        for entity in participants {
+            taken.insert(entity);
+            commands.entity(entity).despawn_recursive();
+        }
+
+        info!(
+            "Battle of {} against {} ships at {:.0}, {:.0} resolved off-screen",
+            attacker_ships.len(),
+            defenders.len(),
+            center.x,
+            center.y
+        );
+        battles.battles.push(AbstractBattle {
+            center,
+            attackers: attacker_ships,
+            defenders,
+            wrecks: Vec::new(),
+            round_timer: Timer::from_seconds(ROUND_INTERVAL, TimerMode::Repeating),
+        });
+    }
+}
+
+/// One exchange of fire: every cannon rolls to hit a random ship of the other side.
+fn fire_round(battles: &mut OffscreenBattles, battle_index: usize, side: BattleSide) {
+    let cannons: f32 = {
+        let battle = &battles.battles[battle_index];
+        let shooters = if side == BattleSide::Attackers { &battle.attackers } else { &battle.defenders };
+        shooters.iter().map(|ship| ship.cannons).sum()
+    };
+
+    // Damaged cannons count for a fraction of a shot
+    let shots = cannons.floor() as usize + usize::from(battles.roll() < cannons.fract());
+    for _ in 0..shots {
+        let (hit_roll, target_roll) = (battles.roll(), battles.roll());
+        let battle = &mut battles.battles[battle_index];
+        let targets = if side == BattleSide::Attackers { &mut battle.defenders } else { &mut battle.attackers };
+        if targets.is_empty() {
+            return;
+        }
+        if hit_roll < CANNON_HIT_CHANCE {
+            let target = ((target_roll * targets.len() as f32) as usize).min(targets.len() - 1);
+            targets[target].hull -= CANNON_DAMAGE;
+        }
+    }
+}
+
+fn resolve_offscreen_battles_system(
+    mut battles: ResMut<OffscreenBattles>,
+    time: Res<Time>,
+    mut resolved_writer: EventWriter<OffscreenBattleResolvedEvent>,
+    mut commands: Commands,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    game_assets: Res<GameAssets>,
+    module_registry: Res<ModuleRegistry>,
+) {
+    for battle_index in 0..battles.battles.len() {
+        if !battles.battles[battle_index].round_timer.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        // Both sides fire at the same time, the shots of destroyed ships still land
+        fire_round(&mut battles, battle_index, BattleSide::Attackers);
+        fire_round(&mut battles, battle_index, BattleSide::Defenders);
+
+        let mut destroyed = Vec::new();
+        let battle = &mut battles.battles[battle_index];
+        for ships in [&mut battle.attackers, &mut battle.defenders] {
+            let (lost, left): (Vec<_>, Vec<_>) = ships.drain(..).partition(|ship| ship.hull <= 0.0);
+            *ships = left;
+            destroyed.extend(lost);
+        }
+        for mut wreck in destroyed {
+            let (scatter_x, scatter_y) = (battles.roll() - 0.5, battles.roll() - 0.5);
+            wreck.data.world_pos[0] += scatter_x * 2.0 * WRECK_SCATTER;
+            wreck.data.world_pos[1] += scatter_y * 2.0 * WRECK_SCATTER;
+            battles.battles[battle_index].wrecks.push(wreck);
+        }
+    }
+
+    // Finished battles leave their survivors and wrecks behind
+    let (finished, ongoing): (Vec<_>, Vec<_>) = battles.battles.drain(..).partition(AbstractBattle::is_over);
+    battles.battles = ongoing;
+    for battle in finished {
+        let winner = battle.winner();
+        let survivors = battle.attackers.len() + battle.defenders.len();
+        info!(
+            "Off-screen battle at {:.0}, {:.0} is over: {:?} won, {} survivors, {} wrecks",
+            battle.center.x,
+            battle.center.y,
+            winner,
+            survivors,
+            battle.wrecks.len()
+        );
+        resolved_writer.send(OffscreenBattleResolvedEvent {
+            center: battle.center,
+            winner,
+            survivors,
+            wrecks: battle.wrecks.len(),
+        });
+        spawn_battle(battle, &mut commands, &mut materials, &game_assets, &module_registry);
+    }
+}
+
+/// Brings the battles the player comes close to back into the full simulation.
+fn materialize_battles_system(
+    mut battles: ResMut<OffscreenBattles>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    mut commands: Commands,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    game_assets: Res<GameAssets>,
+    module_registry: Res<ModuleRegistry>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let player_position = player_transform.translation().truncate();
+
+    let (nearby, distant): (Vec<_>, Vec<_>) =
+        battles.battles.drain(..).partition(|battle| battle.center.distance(player_position) < SIMULATION_RADIUS);
+    battles.battles = distant;
+    for battle in nearby {
+        info!("Battle at {:.0}, {:.0} is simulated again", battle.center.x, battle.center.y);
+        spawn_battle(battle, &mut commands, &mut materials, &game_assets, &module_registry);
+    }
+}
+
+fn spawn_abstract_ship(
+    ship: &AbstractShip,
+    commands: &mut Commands,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    game_assets: &GameAssets,
+    module_registry: &ModuleRegistry,
+) -> Entity {
+    let entity = spawn_structure(commands, materials, game_assets, module_registry, &ship.data);
+    if let Some(name) = &ship.name {
+        commands.entity(entity).insert(name.clone());
+    }
+    entity
+}
+
+/// Spawns the ships of a battle, the fight goes on if both sides are still there.
+fn spawn_battle(
+    battle: AbstractBattle,
+    commands: &mut Commands,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    game_assets: &GameAssets,
+    module_registry: &ModuleRegistry,
+) {
+    let defenders: Vec<Entity> = battle
+        .defenders
+        .iter()
+        .map(|ship| {
+            let entity = spawn_abstract_ship(ship, commands, materials, game_assets, module_registry);
+            commands.entity(entity).insert(HullIntegrity(ship.integrity()));
+            if let Some(stance) = ship.stance {
+                commands.entity(entity).insert(AiPilot::new(stance));
+            }
+            entity
+        })
+        .collect();
+
+    for (index, ship) in battle.attackers.iter().enumerate() {
+        let entity = spawn_abstract_ship(ship, commands, materials, game_assets, module_registry);
+        // Spread the attackers over the defenders left, idle once there is nobody to fight
+        let pilot = match defenders.get(index % defenders.len().max(1)) {
+            Some(target) => AiPilot::new(AiStance::Attack).with_target(*target),
+            None => AiPilot::new(AiStance::Idle),
+        };
+        commands.entity(entity).insert((pilot, HullIntegrity(ship.integrity())));
+    }
+
+    for ship in &battle.wrecks {
+        let entity = spawn_abstract_ship(ship, commands, materials, game_assets, module_registry);
+        commands.entity(entity).insert((Wreck, HullIntegrity(WRECK_INTEGRITY)));
+    }
+}
+
+fn apply_hull_integrity_system(
+    structures_query: Query<(Entity, &HullIntegrity, &Children)>,
+    mut modules_query: Query<&mut ModuleMaterial>,
+    mut commands: Commands,
+) {
+    for (structure_entity, hull_integrity, children) in &structures_query {
+        let mut modules = modules_query.iter_many_mut(children);
+        while let Some(mut module_material) = modules.fetch_next() {
+            module_material.structural_points = module_material.max_structural_points * hull_integrity.0;
+        }
+        commands.entity(structure_entity).remove::<HullIntegrity>();
+    }
+}
@@ -0,0 +1,152 @@
+use crate::core::prelude::*;
+use crate::core::profiling::{ProfileScope, POWER};
+use crate::world::prelude::*;
+
+use bevy::diagnostic::Diagnostics;
+use bevy::prelude::*;
+
+pub const REACTOR_OUTPUT: f32 = 100.0;
+pub const ENGINE_POWER_DEMAND: f32 = 30.0;
+pub const CANNON_POWER_DEMAND: f32 = 20.0;
+
+pub struct PowerPlugin;
+
+impl Plugin for PowerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, attach_power_components_system)
+            .add_systems(
+                FixedUpdate,
+                (insert_power_network_system, power_distribution_system).chain().run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(
+                Update,
+                debug_draw_unpowered_modules.run_if(in_state(GameState::InGame)).run_if(pressurization_overlay_shown),
+            );
+    }
+}
+
+/// Produces power for every consumer of the same structure.
+#[derive(Component, Debug)]
+pub struct Reactor {
+    pub output: f32,
+}
+
+/// A module that needs power to work.
+/// When the structure demand is above the reactors output, consumers are powered by `priority`
+/// (lower value first) and the remaining ones are disabled.
+#[derive(Component, Debug)]
+pub struct PowerConsumer {
+    pub demand: f32,
+    pub priority: u32,
+    pub powered: bool,
+}
+
+impl PowerConsumer {
+    pub fn new(demand: f32, priority: u32) -> Self {
+        Self { demand, priority, powered: false }
+    }
+}
+
+/// Power budget of a structure, recomputed every fixed tick.
+#[derive(Component, Debug, Default)]
+pub struct PowerNetwork {
+    pub produced: f32,
+    pub demanded: f32,
+    pub allocated: f32,
+}
+
+impl PowerNetwork {
+    pub fn is_overdrawn(&self) -> bool {
+        self.demanded > self.produced
+    }
+}
+
+/// Attaches the power components matching the behavior of newly spawned modules.
+fn attach_power_components_system(
+    reactor_query: Query<Entity, Added<ReactorModule>>,
+    engine_query: Query<Entity, Added<EngineModule>>,
+    cannon_query: Query<Entity, Added<CannonModule>>,
+    mut commands: Commands,
+) {
+    for entity in &reactor_query {
+        commands.entity(entity).insert(Reactor { output: REACTOR_OUTPUT });
+    }
+    for entity in &engine_query {
+        commands.entity(entity).insert(PowerConsumer::new(ENGINE_POWER_DEMAND, 0));
+    }
+    for entity in &cannon_query {
+        commands.entity(entity).insert(PowerConsumer::new(CANNON_POWER_DEMAND, 1));
+    }
+}
+
+fn insert_power_network_system(query: Query<Entity, (With<Structure>, Without<PowerNetwork>)>, mut commands: Commands) {
+    for structure_entity in &query {
+        commands.entity(structure_entity).insert(PowerNetwork::default());
+    }
+}
+
+fn power_distribution_system(
+    mut structures_query: Query<(&mut PowerNetwork, &Children)>,
+    reactor_query: Query<&Reactor>,
+    mut consumer_query: Query<(Entity, &mut PowerConsumer)>,
+    mut diagnostics: Diagnostics,
+) {
+    let scope = ProfileScope::enter(&POWER);
+    for (mut network, children) in &mut structures_query {
+        let produced: f32 = children.iter().filter_map(|child| reactor_query.get(*child).ok()).map(|r| r.output).sum();
+
+        let mut consumers: Vec<(Entity, u32, f32)> = children
+            .iter()
+            .filter_map(|child| consumer_query.get(*child).ok())
+            .map(|(entity, consumer)| (entity, consumer.priority, consumer.demand))
+            .collect();
+        consumers.sort_by_key(|(entity, priority, _)| (*priority, *entity));
+
+        let mut available = produced;
+        let mut demanded = 0.0;
+        for (entity, _, demand) in consumers {
+            demanded += demand;
+            let powered = demand <= available;
+            if powered {
+                available -= demand;
+            }
+
+            if let Ok((_, mut consumer)) = consumer_query.get_mut(entity) {
+                if consumer.powered != powered {
+                    consumer.powered = powered;
+                }
+            }
+        }
+
+        network.produced = produced;
+        network.demanded = demanded;
+        network.allocated = produced - available;
+    }
+    scope.finish(&mut diagnostics);
+}
+
+fn debug_draw_unpowered_modules(
+    mut gizmos: Gizmos,
+    structures_query: Query<(&Transform, &Structure)>,
+    consumer_query: Query<(&Module, &PowerConsumer, &Parent)>,
+) {
+    for (module, consumer, parent) in &consumer_query {
+        if consumer.powered {
+            continue;
+        }
+        if let Ok((structure_transform, structure)) = structures_query.get(parent.get()) {
+            let cell_world_pos = structure.grid_cell_center_world_position(
+                module.inner_grid_pos.0,
+                module.inner_grid_pos.1,
+                structure_transform,
+            );
+
+            gizmos.rect_2d(
+                cell_world_pos,
+                structure_transform.rotation.to_euler(EulerRot::XYZ).2,
+                Vec2::splat(structure.grid.cell_size * 0.5),
+                Color::srgb(1.0, 1.0, 0.0), // Yellow for unpowered
+            );
+        }
+    }
+}
@@ -1,2 +1,50 @@
+pub use super::achievements::*;
+pub use super::ai::*;
+pub use super::autopilot::*;
+pub use super::blueprints::*;
+pub use super::building::*;
+pub use super::cargo::*;
+pub use super::clipboard::*;
+pub use super::contaminants::*;
+pub use super::crafting::*;
+pub use super::crew::*;
+pub use super::debris::*;
+pub use super::degradation::*;
+pub use super::derelicts::*;
+pub use super::despawn_audit::*;
+pub use super::destruction::*;
+pub use super::docking::*;
+pub use super::doors::*;
+pub use super::editor::*;
+pub use super::eject::*;
+pub use super::encounters::*;
+pub use super::escort::*;
+pub use super::factions::*;
+pub use super::gravity::*;
+pub use super::hails::*;
+pub use super::health::*;
+pub use super::heat::*;
+pub use super::jetpack::*;
+pub use super::journal::*;
+pub use super::life_support::*;
+pub use super::livery::*;
+pub use super::maneuvers::*;
+pub use super::medical::*;
 pub use super::movement::*;
+pub use super::offscreen_battles::*;
+pub use super::power::*;
+pub use super::repair::*;
+pub use super::repair_drones::*;
+pub use super::sandbox::*;
+pub use super::scanning::*;
+pub use super::scenario::*;
+pub use super::sensors::*;
+pub use super::stats::*;
+pub use super::status_effects::*;
 pub use super::structures_combat::*;
+pub use super::target_drones::*;
+pub use super::targeting::*;
+pub use super::tutorial::*;
+pub use super::upgrades::*;
+pub use super::world_bounds::*;
+pub use super::wrecks::*;
@@ -0,0 +1,246 @@
+use crate::core::prelude::*;
+use crate::gameplay::crafting::MaterialStock;
+use crate::gameplay::medical::Injury;
+use crate::world::prelude::*;
+
+use crate::prelude::*;
+
+const REPAIR_RATE: f32 = 20.0; // structural points/s restored on a damaged module
+const REPAIR_SCRAP_PER_POINT: f32 = 0.1; // scrap spent per structural point restored
+const REBUILD_TIME: f32 = 4.0; // seconds of work to rebuild a destroyed module
+const REBUILD_SCRAP_COST: f32 = 20.0; // scrap spent when the rebuilt module is put in place
+const STARTING_SCRAP: f32 = 100.0;
+
+pub struct RepairPlugin;
+
+impl Plugin for RepairPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<RepairEvent>().insert_resource(Scrap { amount: STARTING_SCRAP }).add_systems(
+            Update,
+            (track_destroyed_modules_system, player_repair_input_system, repair_system)
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+/// Salvaged material spent to repair and rebuild modules.
+#[derive(Resource, Debug)]
+pub struct Scrap {
+    pub amount: f32,
+}
+
+impl Scrap {
+    /// Whether there is scrap left to work on a module, rebuilding a destroyed one takes its whole cost.
+    pub fn can_repair(&self, destroyed: bool) -> bool {
+        if destroyed {
+            self.amount >= REBUILD_SCRAP_COST
+        } else {
+            self.amount > 0.0
+        }
+    }
+}
+
+/// Asks to work on a cell of a structure for this frame.
+/// A damaged module gets its structural points back, a destroyed one is rebuilt after enough work.
+#[derive(Event, Debug)]
+pub struct RepairEvent {
+    pub structure_entity: Entity,
+    pub cell: (i32, i32),
+}
+
+/// What a destroyed module was, so it can be rebuilt in place.
+#[derive(Debug, Clone)]
+pub struct DestroyedModule {
+    pub module_type: ModuleType,
+    pub material_type: ModuleMaterialType,
+    pub color: Color,
+    pub orientation: Orientation,
+    pub footprint: Footprint,
+    pub rebuild_progress: f32, // seconds of work already done
+}
+
+/// Modules of a structure destroyed in combat, by cell.
+#[derive(Component, Debug, Default)]
+pub struct DestroyedModules(pub HashMap<(i32, i32), DestroyedModule>);
+
+/// Remembers destroyed modules before they are despawned, so the player can rebuild them later.
+fn track_destroyed_modules_system(
+    mut event_reader: EventReader<ModuleDestroyedEvent>,
+    module_query: Query<(&Module, &ModuleMaterial, &Handle<ColorMaterial>, &Parent)>,
+    mut destroyed_query: Query<&mut DestroyedModules>,
+    materials: Res<Assets<ColorMaterial>>,
+    mut commands: Commands,
+) {
+    for event in event_reader.read() {
+        let Ok((module, module_material, material_handle, parent)) = module_query.get(event.destroyed_entity) else {
+            continue;
+        };
+
+        let destroyed_module = DestroyedModule {
+            module_type: module.module_type,
+            material_type: module_material.material_type,
+            color: materials.get(material_handle).map(|material| material.color).unwrap_or(Color::WHITE),
+            orientation: module.orientation,
+            footprint: module.footprint,
+            rebuild_progress: 0.0,
+        };
+
+        if let Ok(mut destroyed_modules) = destroyed_query.get_mut(parent.get()) {
+            destroyed_modules.0.insert(event.inner_grid_pos, destroyed_module);
+        } else {
+            let destroyed_modules = DestroyedModules(HashMap::from([(event.inner_grid_pos, destroyed_module)]));
+            commands.entity(parent.get()).insert(destroyed_modules);
+        }
+    }
+}
+
+/// While the repair key is held, works on the damaged or destroyed module closest to the player.
+fn player_repair_input_system(
+    mut input_reader: EventReader<InputAction>,
+    player_query: Query<(&GlobalTransform, Option<&Injury>), With<Player>>,
+    player_resource: Res<PlayerResource>,
+    structures_query: Query<(&Structure, &Transform, &Children, Option<&DestroyedModules>)>,
+    modules_query: Query<(&Module, &ModuleMaterial)>,
+    mut repair_writer: EventWriter<RepairEvent>,
+) {
+    if !input_reader.read().any(|event| matches!(event, InputAction::Repair)) {
+        return;
+    }
+    if player_resource.is_controlling_structure {
+        return;
+    }
+    let Some(structure_entity) = player_resource.inside_structure else {
+        return;
+    };
+    let Ok((player_transform, injury)) = player_query.get_single() else {
+        return;
+    };
+    if injury.is_some_and(|injury| injury.is_incapacitated()) {
+        return;
+    }
+    let Ok((structure, structure_transform, children, destroyed_modules)) = structures_query.get(structure_entity)
+    else {
+        return;
+    };
+
+    let player_cell = structure.world_to_grid(player_transform.translation(), structure_transform);
+    let mut reachable_cells = structure.get_adjacent_cells(player_cell);
+    reachable_cells.insert(0, player_cell);
+
+    let damaged_cells: HashSet<(i32, i32)> = children
+        .iter()
+        .filter_map(|child| modules_query.get(*child).ok())
+        .filter(|(_, material)| material.structural_points < material.max_structural_points)
+        .map(|(module, _)| module.inner_grid_pos)
+        .collect();
+
+    let target = reachable_cells.into_iter().find(|cell| {
+        damaged_cells.contains(cell) || destroyed_modules.is_some_and(|destroyed| destroyed.0.contains_key(cell))
+    });
+
+    if let Some(cell) = target {
+        repair_writer.send(RepairEvent { structure_entity, cell });
+    }
+}
+
+/// Restores the structural points of damaged modules and rebuilds destroyed ones, spending scrap, or a refined unit
+/// of the module material when there is one.
+/// Rebuilding a module closes the hull again, so the structure rooms are segmented again.
+fn repair_system(
+    mut event_reader: EventReader<RepairEvent>,
+    mut structures_query: Query<(&mut Structure, &mut Pressurization, Option<&mut DestroyedModules>)>,
+    mut modules_query: Query<&mut ModuleMaterial, With<Module>>,
+    mut scrap: ResMut<Scrap>,
+    mut material_stock: Option<ResMut<MaterialStock>>,
+    time: Res<Time>,
+    mut commands: Commands,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    game_assets: Res<GameAssets>,
+    module_registry: Res<ModuleRegistry>,
+) {
+    let delta_time = time.delta_seconds();
+
+    for event in event_reader.read() {
+        let Ok((mut structure, mut pressurization, destroyed_modules)) =
+            structures_query.get_mut(event.structure_entity)
+        else {
+            continue;
+        };
+
+        // Damaged module still in place
+        if let Some(module_entity) = structure.module_at(event.cell) {
+            let Ok(mut module_material) = modules_query.get_mut(module_entity) else {
+                continue;
+            };
+            let missing = module_material.max_structural_points - module_material.structural_points;
+            let affordable = scrap.amount / REPAIR_SCRAP_PER_POINT;
+            let restored = (REPAIR_RATE * delta_time).min(missing).min(affordable);
+
+            if restored > 0.0 {
+                module_material.structural_points += restored;
+                scrap.amount -= restored * REPAIR_SCRAP_PER_POINT;
+            }
+            continue;
+        }
+
+        // Destroyed module waiting to be rebuilt
+        let Some(mut destroyed_modules) = destroyed_modules else {
+            continue;
+        };
+        let Some(destroyed_module) = destroyed_modules.0.get_mut(&event.cell) else {
+            continue;
+        };
+        // A module covering several cells needs all of them free again
+        if destroyed_module.footprint.cells(event.cell, destroyed_module.orientation).any(|cell| {
+            structure.grid.get(cell.0, cell.1).is_some_and(|grid_cell| grid_cell.cell_type == CellType::Module)
+        }) {
+            continue;
+        }
+        // A refined unit of the module material replaces the scrap
+        let material_in_stock =
+            material_stock.as_ref().is_some_and(|stock| stock.amount(destroyed_module.material_type) > 0);
+        if !material_in_stock && scrap.amount < REBUILD_SCRAP_COST {
+            continue;
+        }
+
+        destroyed_module.rebuild_progress += delta_time;
+        if destroyed_module.rebuild_progress < REBUILD_TIME {
+            continue;
+        }
+
+        let Some(destroyed_module) = destroyed_modules.0.remove(&event.cell) else {
+            continue;
+        };
+        let material_used = material_stock.as_mut().is_some_and(|stock| stock.take(destroyed_module.material_type));
+        if !material_used {
+            scrap.amount -= REBUILD_SCRAP_COST;
+        }
+
+        let translation = structure.grid_cell_center_local_position(event.cell.0, event.cell.1).extend(1.0);
+        let module_entity = spawn_module(
+            &mut commands,
+            event.structure_entity,
+            &mut structure,
+            &mut materials,
+            &game_assets,
+            destroyed_module.module_type,
+            destroyed_module.color,
+            event.cell,
+            destroyed_module.orientation,
+            destroyed_module.footprint,
+            translation,
+            MODULE_MESH_SCALE_FACTOR,
+            false,
+            destroyed_module.material_type,
+        );
+        if let ModuleType::Custom(symbol) = destroyed_module.module_type {
+            module_registry.apply_spawn_hooks(symbol, &mut commands.entity(module_entity));
+        }
+
+        // The rebuilt module may seal a breached room again
+        let rooms = structure.check_pressurization();
+        pressurization.update_rooms(rooms);
+        debug!("Rebuilt {:?} at {:?}", destroyed_module.module_type, event.cell);
+    }
+}
@@ -0,0 +1,373 @@
+use crate::configs::config::UNIT_SCALE;
+use crate::core::prelude::*;
+use crate::gameplay::repair::{DestroyedModules, RepairEvent, Scrap};
+use crate::world::prelude::*;
+
+use bevy::prelude::*;
+use bevy::sprite::MaterialMesh2dBundle;
+use bevy::utils::HashSet;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+const DRONES_PER_BAY: usize = 2;
+const DRONE_LAUNCH_INTERVAL: f32 = 3.0; // seconds between two drones leaving the same bay
+const DRONE_MAX_SPEED: f32 = 4.0; // m/s over the hull
+const DRONE_MAX_ACCELERATION: f32 = 8.0; // m/s²
+const DRONE_SLOWING_RADIUS: f32 = 1.5; // meters from the last cell of the path where the drones start braking
+const DRONE_WAYPOINT_RADIUS: f32 = 0.3; // meters from a cell of the path to head for the next one
+const DRONE_ARRIVED_SPEED: f32 = 0.2; // m/s, slow enough to settle on the last cell of the path
+const DRONE_RADIUS: f32 = 0.3 * UNIT_SCALE;
+const DRONE_Z: f32 = 3.0; // above the modules
+
+/// How urgent each kind of repair is for the drones, read from the settings file.
+/// Damaged modules of a higher priority are always handled first, a priority of 0 leaves them to the player.
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RepairPriorities {
+    pub engines: u8,
+    /// Destroyed walls, doors and airlocks letting the air out.
+    pub hull_breaches: u8,
+    pub weapons: u8,
+    pub other: u8,
+}
+
+impl Default for RepairPriorities {
+    fn default() -> Self {
+        Self { engines: 3, hull_breaches: 3, weapons: 2, other: 1 }
+    }
+}
+
+impl RepairPriorities {
+    pub fn priority(&self, category: RepairCategory) -> u8 {
+        match category {
+            RepairCategory::Engine => self.engines,
+            RepairCategory::HullBreach => self.hull_breaches,
+            RepairCategory::Weapon => self.weapons,
+            RepairCategory::Other => self.other,
+        }
+    }
+
+    /// Triage score of a job, the priority first then how damaged the module is. `None` when the drones skip it.
+    pub fn score(&self, job: &RepairJob) -> Option<f32> {
+        let priority = self.priority(job.category);
+        (priority > 0).then_some(priority as f32 + job.damage)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepairCategory {
+    Engine,
+    HullBreach,
+    Weapon,
+    Other,
+}
+
+impl RepairCategory {
+    pub fn of(module_type: ModuleType, destroyed: bool) -> Self {
+        match module_type {
+            ModuleType::Engine => RepairCategory::Engine,
+            ModuleType::Wall | ModuleType::Door | ModuleType::Airlock if destroyed => RepairCategory::HullBreach,
+            ModuleType::Cannon => RepairCategory::Weapon,
+            _ => RepairCategory::Other,
+        }
+    }
+}
+
+/// A damaged or destroyed module a drone could work on.
+#[derive(Debug, Clone, Copy)]
+pub struct RepairJob {
+    pub cell: (i32, i32),
+    pub category: RepairCategory,
+    pub destroyed: bool,
+    /// Missing fraction of the structural points, 1 for a destroyed module.
+    pub damage: f32,
+}
+
+/// Drones stationed in the drone bays of a structure. Every few seconds a bay launches one of its docked drones to
+/// the most urgent damaged module nobody is working on. The drone steers over the structure grid to it, repairs it
+/// with the scrap until it is fixed or the scrap runs out, then flies back to its bay.
+pub struct RepairDronesPlugin;
+
+impl Plugin for RepairDronesPlugin {
+    fn build(&self, app: &mut App) {
+        let priorities = app.world().get_resource::<RepairPriorities>().cloned().unwrap_or_default();
+        app.insert_resource(priorities).add_systems(
+            Update,
+            (
+                spawn_repair_drones_system,
+                despawn_orphan_drones_system,
+                triage_system,
+                drone_flight_system,
+                drone_repair_system,
+            )
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+/// Paces the launches of a drone bay.
+#[derive(Component, Debug)]
+pub struct DroneLauncher {
+    pub timer: Timer,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DroneTask {
+    Docked,
+    /// Flying to the module at this cell.
+    Outbound((i32, i32)),
+    Repairing((i32, i32)),
+    Returning,
+}
+
+impl DroneTask {
+    pub fn target(&self) -> Option<(i32, i32)> {
+        match self {
+            DroneTask::Outbound(cell) | DroneTask::Repairing(cell) => Some(*cell),
+            DroneTask::Docked | DroneTask::Returning => None,
+        }
+    }
+}
+
+/// A repair drone, child of the structure of its bay so it moves along with it.
+#[derive(Component, Debug)]
+pub struct RepairDrone {
+    pub bay: Entity,
+    pub structure: Entity,
+    pub bay_cell: (i32, i32),
+    /// Cell the drone is over, or last went through.
+    pub cell: (i32, i32),
+    pub task: DroneTask,
+    /// Velocity in the frame of the structure.
+    pub velocity: Vec2,
+    path: VecDeque<(i32, i32)>,
+}
+
+/// Cells from `from` to `to`, along the rows then the columns, so the drones stay over the grid of the structure.
+fn hull_path(from: (i32, i32), to: (i32, i32)) -> VecDeque<(i32, i32)> {
+    let mut path = VecDeque::new();
+    let (mut x, mut y) = from;
+    while x != to.0 {
+        x += (to.0 - x).signum();
+        path.push_back((x, y));
+    }
+    while y != to.1 {
+        y += (to.1 - y).signum();
+        path.push_back((x, y));
+    }
+    path
+}
+
+/// Seek-and-arrive steering: the acceleration turning `velocity` toward `target` at full speed, braking to stop on
+/// it when `arrive` is set.
+fn steering(position: Vec2, velocity: Vec2, target: Vec2, arrive: bool) -> Vec2 {
+    let to_target = target - position;
+    let speed =
+        if arrive { DRONE_MAX_SPEED * (to_target.length() / DRONE_SLOWING_RADIUS).min(1.0) } else { DRONE_MAX_SPEED };
+    let desired_velocity = to_target.normalize_or_zero() * speed;
+    (desired_velocity - velocity).clamp_length_max(DRONE_MAX_ACCELERATION)
+}
+
+/// Damaged and destroyed modules of a structure.
+fn repair_jobs(
+    children: &Children,
+    modules_query: &Query<(&Module, &ModuleMaterial)>,
+    destroyed_modules: Option<&DestroyedModules>,
+) -> Vec<RepairJob> {
+    let damaged = children
+        .iter()
+        .filter_map(|child| modules_query.get(*child).ok())
+        .filter(|(_, material)| material.structural_points < material.max_structural_points)
+        .map(|(module, material)| RepairJob {
+            cell: module.inner_grid_pos,
+            category: RepairCategory::of(module.module_type, false),
+            destroyed: false,
+            damage: 1.0 - material.structural_points / material.max_structural_points,
+        });
+    let destroyed = destroyed_modules.into_iter().flat_map(|destroyed| destroyed.0.iter()).map(|(cell, module)| {
+        RepairJob { cell: *cell, category: RepairCategory::of(module.module_type, true), destroyed: true, damage: 1.0 }
+    });
+    damaged.chain(destroyed).collect()
+}
+
+fn spawn_repair_drones_system(
+    bays_query: Query<(Entity, &Module, &Parent), Added<DroneBayModule>>,
+    structures_query: Query<&Structure>,
+    game_assets: Res<GameAssets>,
+    mut commands: Commands,
+) {
+    for (bay_entity, module, parent) in &bays_query {
+        let Ok(structure) = structures_query.get(parent.get()) else {
+            continue;
+        };
+        let bay_cell = module.inner_grid_pos;
+        let translation = structure.grid_cell_center_local_position(bay_cell.0, bay_cell.1).extend(DRONE_Z);
+        commands
+            .entity(bay_entity)
+            .insert(DroneLauncher { timer: Timer::from_seconds(DRONE_LAUNCH_INTERVAL, TimerMode::Once) });
+
+        for _ in 0..DRONES_PER_BAY {
+            let drone = commands
+                .spawn((
+                    RepairDrone {
+                        bay: bay_entity,
+                        structure: parent.get(),
+                        bay_cell,
+                        cell: bay_cell,
+                        task: DroneTask::Docked,
+                        velocity: Vec2::ZERO,
+                        path: VecDeque::new(),
+                    },
+                    MaterialMesh2dBundle {
+                        mesh: game_assets.unit_disc.clone(),
+                        material: game_assets.repair_drone_material.clone(),
+                        transform: Transform::from_translation(translation).with_scale(Vec3::splat(DRONE_RADIUS)),
+                        ..default()
+                    },
+                    Name::new("Repair drone"),
+                ))
+                .id();
+            commands.entity(parent.get()).add_child(drone);
+        }
+    }
+}
+
+/// The drones go down with their bay, a rebuilt bay comes with new ones.
+fn despawn_orphan_drones_system(
+    drones_query: Query<(Entity, &RepairDrone)>,
+    bays_query: Query<(), With<DroneBayModule>>,
+    mut commands: Commands,
+) {
+    for (drone_entity, drone) in &drones_query {
+        if !bays_query.contains(drone.bay) {
+            commands.entity(drone_entity).despawn_recursive();
+        }
+    }
+}
+
+/// Launches a docked drone of every ready bay to the most urgent repair no other drone took.
+fn triage_system(
+    mut drones_query: Query<&mut RepairDrone>,
+    mut launchers_query: Query<&mut DroneLauncher>,
+    structures_query: Query<(&Children, Option<&DestroyedModules>), With<Structure>>,
+    modules_query: Query<(&Module, &ModuleMaterial)>,
+    priorities: Res<RepairPriorities>,
+    scrap: Res<Scrap>,
+    time: Res<Time>,
+) {
+    for mut launcher in &mut launchers_query {
+        launcher.timer.tick(time.delta());
+    }
+    let mut claimed: HashSet<(Entity, (i32, i32))> =
+        drones_query.iter().filter_map(|drone| drone.task.target().map(|cell| (drone.structure, cell))).collect();
+
+    for mut drone in &mut drones_query {
+        if drone.task != DroneTask::Docked {
+            continue;
+        }
+        let Ok(mut launcher) = launchers_query.get_mut(drone.bay) else {
+            continue;
+        };
+        if !launcher.timer.finished() {
+            continue;
+        }
+        let Ok((children, destroyed_modules)) = structures_query.get(drone.structure) else {
+            continue;
+        };
+
+        let best = repair_jobs(children, &modules_query, destroyed_modules)
+            .into_iter()
+            .filter(|job| !claimed.contains(&(drone.structure, job.cell)) && scrap.can_repair(job.destroyed))
+            .filter_map(|job| priorities.score(&job).map(|score| (job, score)))
+            .max_by(|(_, score1), (_, score2)| score1.total_cmp(score2));
+
+        if let Some((job, _)) = best {
+            launcher.timer.reset();
+            claimed.insert((drone.structure, job.cell));
+            drone.path = hull_path(drone.cell, job.cell);
+            drone.task = DroneTask::Outbound(job.cell);
+            debug!("Repair drone sent to {:?} at {:?}", job.category, job.cell);
+        }
+    }
+}
+
+/// Steers the drones through the cells of their path, they arrive on the last one.
+fn drone_flight_system(
+    mut drones_query: Query<(&mut RepairDrone, &mut Transform)>,
+    structures_query: Query<&Structure>,
+    time: Res<Time>,
+) {
+    let delta_seconds = time.delta_seconds();
+
+    for (mut drone, mut transform) in &mut drones_query {
+        let Ok(structure) = structures_query.get(drone.structure) else {
+            continue;
+        };
+
+        if let Some(&next_cell) = drone.path.front() {
+            let arrive = drone.path.len() == 1;
+            let position = transform.translation.truncate();
+            let target = structure.grid_cell_center_local_position(next_cell.0, next_cell.1);
+
+            let acceleration = steering(position, drone.velocity, target, arrive);
+            drone.velocity = (drone.velocity + acceleration * delta_seconds).clamp_length_max(DRONE_MAX_SPEED);
+            let position = position + drone.velocity * delta_seconds;
+            transform.translation = position.extend(DRONE_Z);
+
+            let distance = position.distance(target);
+            let reached = if arrive {
+                distance <= DRONE_WAYPOINT_RADIUS && drone.velocity.length() <= DRONE_ARRIVED_SPEED
+            } else {
+                distance <= DRONE_WAYPOINT_RADIUS
+            };
+            if reached {
+                if arrive {
+                    transform.translation = target.extend(DRONE_Z);
+                    drone.velocity = Vec2::ZERO;
+                }
+                drone.cell = next_cell;
+                drone.path.pop_front();
+            }
+        }
+
+        if drone.path.is_empty() {
+            drone.task = match drone.task {
+                DroneTask::Outbound(cell) => DroneTask::Repairing(cell),
+                DroneTask::Returning => DroneTask::Docked,
+                task => task,
+            };
+        }
+    }
+}
+
+/// Works on the target of the drones that reached it, and sends them back once it is fixed or the scrap ran out.
+fn drone_repair_system(
+    mut drones_query: Query<&mut RepairDrone>,
+    structures_query: Query<(&Structure, Option<&DestroyedModules>)>,
+    modules_query: Query<&ModuleMaterial>,
+    scrap: Res<Scrap>,
+    mut repair_writer: EventWriter<RepairEvent>,
+) {
+    for mut drone in &mut drones_query {
+        let DroneTask::Repairing(cell) = drone.task else {
+            continue;
+        };
+        let Ok((structure, destroyed_modules)) = structures_query.get(drone.structure) else {
+            continue;
+        };
+
+        let damaged = structure
+            .module_at(cell)
+            .and_then(|module_entity| modules_query.get(module_entity).ok())
+            .is_some_and(|material| material.structural_points < material.max_structural_points);
+        let destroyed = destroyed_modules.is_some_and(|destroyed| destroyed.0.contains_key(&cell));
+
+        if (damaged || destroyed) && scrap.can_repair(destroyed) {
+            repair_writer.send(RepairEvent { structure_entity: drone.structure, cell });
+        } else {
+            drone.path = hull_path(drone.cell, drone.bay_cell);
+            drone.task = DroneTask::Returning;
+        }
+    }
+}
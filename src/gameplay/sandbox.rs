@@ -0,0 +1,173 @@
+use crate::core::prelude::*;
+use crate::gameplay::building::BuildMode;
+use crate::gameplay::livery::Livery;
+use crate::gameplay::movement::Aboard;
+use crate::gameplay::target_drones::*;
+use crate::world::prelude::*;
+
+use crate::prelude::*;
+
+const SANDBOX_TOGGLE_KEY: KeyCode = KeyCode::F6;
+const SANDBOX_ARENA_CENTER: Vec2 = Vec2::new(20_000.0, 20_000.0); // far from everything else in the world
+const TARGET_DRONES: usize = 4;
+const TARGET_DRONE_DISTANCE: f32 = 80.0; // meters from the arena center
+const TARGET_DRONE_SPEED: f32 = 2.0; // m/s
+
+/// Test drive of the structure being edited: press F6 in build mode to fly a copy of it alone in an arena with
+/// target drones, press F6 again to go back to editing.
+pub struct SandboxPlugin;
+
+impl Plugin for SandboxPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<SandboxState>()
+            .add_systems(Update, sandbox_toggle_system.run_if(in_state(GameState::InGame)))
+            .add_systems(OnEnter(SandboxState::TestDrive), enter_sandbox)
+            .add_systems(OnExit(SandboxState::TestDrive), exit_sandbox);
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
+pub enum SandboxState {
+    #[default]
+    Off,
+    TestDrive,
+}
+
+/// Where the test drive came from, so the player can be put back in place.
+#[derive(Resource, Debug)]
+pub struct SandboxSession {
+    pub edited_structure: Entity,
+    /// Position of the player relative to the edited structure.
+    pub player_local_position: Vec2,
+}
+
+/// Everything spawned for the test drive, despawned when it ends.
+#[derive(Component, Debug, Default)]
+pub struct SandboxEntity;
+
+fn sandbox_toggle_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    state: Res<State<SandboxState>>,
+    mut next_state: ResMut<NextState<SandboxState>>,
+    build_mode: Res<BuildMode>,
+    player_resource: Res<PlayerResource>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    structures_query: Query<&Transform, With<Structure>>,
+    mut commands: Commands,
+) {
+    if !keys.just_pressed(SANDBOX_TOGGLE_KEY) {
+        return;
+    }
+
+    match state.get() {
+        SandboxState::TestDrive => next_state.set(SandboxState::Off),
+        SandboxState::Off => {
+            // The test drive starts from the editor, on the edited structure
+            if !build_mode.active {
+                return;
+            }
+            let Some(edited_structure) = player_resource.inside_structure else {
+                return;
+            };
+            let (Ok(player_transform), Ok(structure_transform)) =
+                (player_query.get_single(), structures_query.get(edited_structure))
+            else {
+                return;
+            };
+
+            let player_local_position = structure_transform
+                .compute_affine()
+                .inverse()
+                .transform_point3(player_transform.translation())
+                .truncate();
+            commands.insert_resource(SandboxSession { edited_structure, player_local_position });
+            next_state.set(SandboxState::TestDrive);
+        }
+    }
+}
+
+/// Spawns a copy of the edited structure in the arena, with the player on board, and target drones around it.
+fn enter_sandbox(
+    session: Option<Res<SandboxSession>>,
+    structures_query: Query<(&Structure, &Livery, &Children)>,
+    module_query: Query<&Module>,
+    player_query: Query<Entity, With<Player>>,
+    mut build_mode: ResMut<BuildMode>,
+    mut commands: Commands,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    game_assets: Res<GameAssets>,
+    module_registry: Res<ModuleRegistry>,
+) {
+    let Some(session) = session else {
+        return;
+    };
+    let Ok((structure, livery, children)) = structures_query.get(session.edited_structure) else {
+        return;
+    };
+
+    let mut design = structure.to_structure_data(module_query.iter_many(children));
+    design.world_pos = SANDBOX_ARENA_CENTER.to_array();
+    design.livery = livery.clone();
+    let test_structure = spawn_structure(&mut commands, &mut materials, &game_assets, &module_registry, &design);
+    commands.entity(test_structure).insert(SandboxEntity);
+
+    for i in 0..TARGET_DRONES {
+        let angle = i as f32 / TARGET_DRONES as f32 * std::f32::consts::TAU;
+        let position = SANDBOX_ARENA_CENTER + Vec2::from_angle(angle) * TARGET_DRONE_DISTANCE;
+        // Half of the drones circle around the arena center
+        let motion = if i % 2 == 0 {
+            TargetDroneMotion::Stationary { position }
+        } else {
+            TargetDroneMotion::Orbit { center: SANDBOX_ARENA_CENTER, speed: TARGET_DRONE_SPEED }
+        };
+        let drone = spawn_target_drone(&mut commands, &mut materials, &game_assets, &module_registry, position, motion);
+        commands.entity(drone).insert(SandboxEntity);
+    }
+
+    // The player is picked up by the test structure once inside its grid
+    for player_entity in &player_query {
+        let position = SANDBOX_ARENA_CENTER + session.player_local_position;
+        commands.entity(player_entity).remove_parent_in_place().remove::<Aboard>().insert((
+            RigidBody::Dynamic,
+            LinearVelocity::ZERO,
+            Transform::from_translation(position.extend(5.0)),
+        ));
+    }
+    build_mode.active = false;
+    info!("Test drive started");
+}
+
+/// Removes the arena and puts the player back where they were editing.
+fn exit_sandbox(
+    session: Option<Res<SandboxSession>>,
+    sandbox_query: Query<Entity, With<SandboxEntity>>,
+    structures_query: Query<&Transform, With<Structure>>,
+    player_query: Query<Entity, With<Player>>,
+    mut player_resource: ResMut<PlayerResource>,
+    mut build_mode: ResMut<BuildMode>,
+    mut commands: Commands,
+) {
+    // The player may be a child of the test structure, detach it before clearing the arena
+    let position = session
+        .as_ref()
+        .and_then(|session| {
+            let structure_transform = structures_query.get(session.edited_structure).ok()?;
+            Some(structure_transform.transform_point(session.player_local_position.extend(0.0)).truncate())
+        })
+        .unwrap_or_default();
+    for player_entity in &player_query {
+        commands.entity(player_entity).remove_parent_in_place().remove::<Aboard>().insert((
+            RigidBody::Dynamic,
+            LinearVelocity::ZERO,
+            Transform::from_translation(position.extend(5.0)),
+        ));
+    }
+
+    for entity in &sandbox_query {
+        commands.entity(entity).despawn_recursive();
+    }
+    player_resource.is_controlling_structure = false;
+    build_mode.active = true;
+    commands.remove_resource::<SandboxSession>();
+    info!("Test drive ended");
+}
@@ -0,0 +1,167 @@
+use crate::core::prelude::*;
+use crate::gameplay::ai::{AiPilot, AiStance};
+use crate::world::prelude::*;
+
+use crate::prelude::*;
+use bevy::utils::HashMap;
+
+pub const SCAN_CHANNEL_TIME: f32 = 3.0; // seconds the target must stay in range before it is revealed
+pub const SCAN_DURATION: f32 = 20.0; // seconds the modules of a scanned structure stay revealed
+const SCAN_RANGE: f32 = 300.0; // meters
+
+/// Scans revealing the materials and the health of the modules of another structure for a while, once the scanner
+/// kept it in range during the channel time. The player scans the nearest structure with the scan key, the AI pilots
+/// scan the structure they attack and aim at its weakest module once it is revealed.
+pub struct ScanPlugin;
+
+impl Plugin for ScanPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ScanCompletedEvent>().add_systems(
+            Update,
+            (player_scan_system, ai_scan_system, channel_scans_system, expire_reveals_system)
+                .chain()
+                .after(InGameSet::UserInput)
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+/// Scan in progress by this structure.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct ScanChannel {
+    pub target: Entity,
+    pub elapsed: f32,
+}
+
+impl ScanChannel {
+    pub fn new(target: Entity) -> Self {
+        Self { target, elapsed: 0.0 }
+    }
+
+    pub fn progress(&self) -> f32 {
+        (self.elapsed / SCAN_CHANNEL_TIME).min(1.0)
+    }
+}
+
+/// Structures whose modules this structure sees through, with the seconds left.
+#[derive(Component, Debug, Default, Clone)]
+pub struct ScanReveals(pub HashMap<Entity, f32>);
+
+impl ScanReveals {
+    pub fn reveals(&self, target: Entity) -> bool {
+        self.0.contains_key(&target)
+    }
+}
+
+#[derive(Event, Debug)]
+pub struct ScanCompletedEvent {
+    pub scanner: Entity,
+    pub target: Entity,
+}
+
+/// The scan key starts scanning the nearest other structure in range.
+fn player_scan_system(
+    mut input_reader: EventReader<InputAction>,
+    controlled_query: Query<(Entity, &Transform), With<ControlledByPlayer>>,
+    structures_query: Query<(Entity, &Transform), With<Structure>>,
+    player_resource: Res<PlayerResource>,
+    mut commands: Commands,
+) {
+    if !input_reader.read().any(|event| matches!(event, InputAction::Scan)) {
+        return;
+    }
+    let Ok((scanner, scanner_transform)) = controlled_query.get_single() else {
+        return;
+    };
+    if !player_resource.is_controlling_structure {
+        return;
+    }
+
+    let position = scanner_transform.translation.truncate();
+    let nearest = structures_query
+        .iter()
+        .filter(|(structure_entity, _)| *structure_entity != scanner)
+        .map(|(structure_entity, transform)| (structure_entity, transform.translation.truncate().distance(position)))
+        .filter(|(_, distance)| *distance <= SCAN_RANGE)
+        .min_by(|(_, distance1), (_, distance2)| distance1.total_cmp(distance2));
+    match nearest {
+        Some((target, _)) => {
+            debug!("Scanning {:?}", target);
+            commands.entity(scanner).insert(ScanChannel::new(target));
+        }
+        None => debug!("No structure in scan range"),
+    }
+}
+
+/// Attacking pilots scan their target structure when they do not see through it yet.
+fn ai_scan_system(
+    pilots_query: Query<(Entity, &AiPilot, Option<&ScanReveals>), Without<ScanChannel>>,
+    structures_query: Query<(), With<Structure>>,
+    player_resource: Res<PlayerResource>,
+    mut commands: Commands,
+) {
+    for (scanner, pilot, reveals) in &pilots_query {
+        if pilot.stance != AiStance::Attack {
+            continue;
+        }
+        // Aiming at the player means aiming at the structure they are aboard
+        let target =
+            pilot.target.filter(|target| structures_query.contains(*target)).or(player_resource.inside_structure);
+        let Some(target) = target.filter(|target| *target != scanner) else {
+            continue;
+        };
+        if !reveals.is_some_and(|reveals| reveals.reveals(target)) {
+            commands.entity(scanner).insert(ScanChannel::new(target));
+        }
+    }
+}
+
+/// Advances the scans whose target is still in range, and reveals it once the channel time is over.
+fn channel_scans_system(
+    mut scanners_query: Query<(Entity, &mut ScanChannel, &Transform, Option<&mut ScanReveals>)>,
+    targets_query: Query<&Transform, With<Structure>>,
+    time: Res<Time>,
+    mut completed_writer: EventWriter<ScanCompletedEvent>,
+    mut commands: Commands,
+) {
+    for (scanner, mut channel, scanner_transform, reveals) in &mut scanners_query {
+        let in_range = targets_query.get(channel.target).is_ok_and(|target_transform| {
+            target_transform.translation.truncate().distance(scanner_transform.translation.truncate()) <= SCAN_RANGE
+        });
+        if !in_range {
+            debug!("Scan of {:?} lost", channel.target);
+            commands.entity(scanner).remove::<ScanChannel>();
+            continue;
+        }
+
+        channel.elapsed += time.delta_seconds();
+        if channel.elapsed < SCAN_CHANNEL_TIME {
+            continue;
+        }
+
+        match reveals {
+            Some(mut reveals) => {
+                reveals.0.insert(channel.target, SCAN_DURATION);
+            }
+            None => {
+                commands.entity(scanner).insert(ScanReveals(HashMap::from([(channel.target, SCAN_DURATION)])));
+            }
+        }
+        completed_writer.send(ScanCompletedEvent { scanner, target: channel.target });
+        commands.entity(scanner).remove::<ScanChannel>();
+    }
+}
+
+fn expire_reveals_system(
+    mut reveals_query: Query<&mut ScanReveals>,
+    targets_query: Query<(), With<Structure>>,
+    time: Res<Time>,
+) {
+    let delta_time = time.delta_seconds();
+    for mut reveals in &mut reveals_query {
+        reveals.0.retain(|target, remaining| {
+            *remaining -= delta_time;
+            *remaining > 0.0 && targets_query.contains(*target)
+        });
+    }
+}
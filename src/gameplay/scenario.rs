@@ -0,0 +1,253 @@
+use crate::core::asset_validation::validate_structures;
+use crate::core::prelude::*;
+use crate::gameplay::derelicts::DerelictGenerator;
+use crate::gameplay::factions::Faction;
+use crate::gameplay::health::{PlayerDiedEvent, PlayerSpawnPoint};
+use crate::gameplay::movement::Aboard;
+use crate::gameplay::offscreen_battles::OffscreenBattles;
+use crate::world::prelude::*;
+
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Folder listed by the scenario menu, next to the executable.
+pub const SCENARIOS_DIRECTORY: &str = "scenarios";
+pub const SCENARIO_EXTENSION: &str = "ron";
+/// Command line flag starting the game on a scenario: `--scenario scenarios/pirate_ambush.ron`.
+pub const SCENARIO_ARG: &str = "--scenario";
+
+/// Custom battles shared as RON files: the structures to fight with, their factions, positions and velocities, and
+/// the conditions ending the battle. Loading one replaces the world and seeds the random generators, so the same
+/// file always plays out the same way.
+pub struct ScenarioPlugin;
+
+impl Plugin for ScenarioPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<LoadScenarioEvent>().add_event::<ScenarioEndedEvent>().add_systems(
+            Update,
+            (
+                start_scenario_from_args_system.run_if(resource_exists::<StartupScenario>),
+                load_scenario_system.run_if(on_event::<LoadScenarioEvent>()),
+                check_scenario_outcome_system.run_if(resource_exists::<ActiveScenario>),
+            )
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+/// A scenario file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scenario {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Seed of the random generators, the default simulation seed when there is none.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    #[serde(default = "default_player_position")]
+    pub player_position: [f32; 2],
+    pub structures: Vec<StructureData>,
+    /// The battle is won once all of them are met.
+    #[serde(default)]
+    pub victory: Vec<ScenarioCondition>,
+    /// The battle is lost as soon as one of them is met.
+    #[serde(default)]
+    pub defeat: Vec<ScenarioCondition>,
+}
+
+fn default_player_position() -> [f32; 2] {
+    PlayerSpawnPoint::default().0.to_array()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ScenarioCondition {
+    /// Every module of the structures of this faction is destroyed.
+    FactionDestroyed(String),
+    /// Seconds since the scenario started.
+    Elapsed(f32),
+    PlayerDied,
+}
+
+#[derive(Debug, Error)]
+pub enum ScenarioError {
+    #[error("Could not read the scenario: {0}")]
+    Io(#[from] io::Error),
+    #[error("Invalid scenario: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("The scenario has no structures")]
+    NoStructures,
+    #[error("Invalid structures: {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))]
+    InvalidStructures(Vec<ValidationError>),
+}
+
+impl Scenario {
+    pub fn load(path: impl AsRef<Path>, module_registry: &ModuleRegistry) -> Result<Self, ScenarioError> {
+        let content = fs::read_to_string(path)?;
+        let scenario: Scenario = ron::from_str(&content)?;
+        if scenario.structures.is_empty() {
+            return Err(ScenarioError::NoStructures);
+        }
+        let errors = validate_structures(&StructuresData { structures: scenario.structures.clone() }, module_registry);
+        if !errors.is_empty() {
+            return Err(ScenarioError::InvalidStructures(errors));
+        }
+        Ok(scenario)
+    }
+}
+
+/// The scenario files of the scenarios folder, sorted by name.
+pub fn list_scenarios() -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(SCENARIOS_DIRECTORY) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == SCENARIO_EXTENSION))
+        .collect();
+    paths.sort();
+    paths
+}
+
+/// The scenario given on the command line, if any.
+pub fn scenario_from_args(args: impl IntoIterator<Item = String>) -> Option<PathBuf> {
+    let mut args = args.into_iter();
+    args.by_ref().find(|arg| arg == SCENARIO_ARG)?;
+    args.next().map(PathBuf::from)
+}
+
+/// Scenario to load once the game starts, given on the command line.
+#[derive(Resource, Debug, Clone)]
+pub struct StartupScenario(pub PathBuf);
+
+#[derive(Event, Debug, Clone)]
+pub struct LoadScenarioEvent {
+    pub path: PathBuf,
+}
+
+/// The scenario being played and how far it went.
+#[derive(Resource, Debug)]
+pub struct ActiveScenario {
+    pub scenario: Scenario,
+    pub elapsed: f32,
+    pub player_deaths: u32,
+}
+
+impl ActiveScenario {
+    fn is_met(&self, condition: &ScenarioCondition, destroyed_factions: &[&String]) -> bool {
+        match condition {
+            ScenarioCondition::FactionDestroyed(faction) => destroyed_factions.contains(&faction),
+            ScenarioCondition::Elapsed(seconds) => self.elapsed >= *seconds,
+            ScenarioCondition::PlayerDied => self.player_deaths > 0,
+        }
+    }
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct ScenarioEndedEvent {
+    pub name: String,
+    pub victory: bool,
+}
+
+fn start_scenario_from_args_system(
+    startup_scenario: Res<StartupScenario>,
+    mut load_writer: EventWriter<LoadScenarioEvent>,
+    mut commands: Commands,
+) {
+    load_writer.send(LoadScenarioEvent { path: startup_scenario.0.clone() });
+    commands.remove_resource::<StartupScenario>();
+}
+
+/// Replaces the world with the structures of the scenario.
+fn load_scenario_system(
+    mut event_reader: EventReader<LoadScenarioEvent>,
+    structures_query: Query<Entity, With<Structure>>,
+    wreck_query: Query<Entity, (With<Module>, Without<Parent>)>,
+    player_query: Query<Entity, With<Player>>,
+    mut player_resource: ResMut<PlayerResource>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    game_assets: Res<GameAssets>,
+    module_registry: Res<ModuleRegistry>,
+    mut commands: Commands,
+) {
+    let Some(event) = event_reader.read().last() else {
+        return;
+    };
+    let scenario = match Scenario::load(&event.path, &module_registry) {
+        Ok(scenario) => scenario,
+        Err(error) => {
+            error!("Failed to load the scenario {:?}: {}", event.path, error);
+            return;
+        }
+    };
+
+    let player_position = Vec2::from_array(scenario.player_position);
+    for player_entity in &player_query {
+        commands.entity(player_entity).remove_parent_in_place().remove::<Aboard>().insert((
+            RigidBody::Dynamic,
+            LinearVelocity::ZERO,
+            Transform::from_translation(player_position.extend(5.0)),
+        ));
+    }
+    *player_resource = PlayerResource::default();
+
+    for entity in structures_query.iter().chain(wreck_query.iter()) {
+        commands.entity(entity).despawn_recursive();
+    }
+    for structure_data in &scenario.structures {
+        spawn_structure(&mut commands, &mut materials, &game_assets, &module_registry, structure_data);
+    }
+
+    let seed = scenario.seed.unwrap_or(DEFAULT_SIMULATION_SEED);
+    commands.insert_resource(NameGenerator::new(seed));
+    commands.insert_resource(OffscreenBattles::with_seed(seed));
+    commands.insert_resource(DerelictGenerator::new(seed));
+    commands.insert_resource(PlayerSpawnPoint(player_position));
+
+    info!("Scenario \"{}\" loaded from {:?}", scenario.name, event.path);
+    commands.insert_resource(ActiveScenario { scenario, elapsed: 0.0, player_deaths: 0 });
+}
+
+/// Ends the scenario once it is won or lost.
+fn check_scenario_outcome_system(
+    mut active_scenario: ResMut<ActiveScenario>,
+    mut died_reader: EventReader<PlayerDiedEvent>,
+    structures_query: Query<(&Faction, Option<&Children>), With<Structure>>,
+    modules_query: Query<(), With<Module>>,
+    time: Res<Time>,
+    mut ended_writer: EventWriter<ScenarioEndedEvent>,
+    mut commands: Commands,
+) {
+    active_scenario.elapsed += time.delta_seconds();
+    active_scenario.player_deaths += died_reader.read().count() as u32;
+
+    // Factions named by the conditions whose structures have no module left
+    let scenario = &active_scenario.scenario;
+    let named_factions = scenario.victory.iter().chain(&scenario.defeat).filter_map(|condition| match condition {
+        ScenarioCondition::FactionDestroyed(faction) => Some(faction),
+        _ => None,
+    });
+    let destroyed_factions: Vec<&String> = named_factions
+        .filter(|faction| {
+            !structures_query.iter().any(|(structure_faction, children)| {
+                structure_faction.0 == **faction
+                    && children.is_some_and(|children| children.iter().any(|child| modules_query.contains(*child)))
+            })
+        })
+        .collect();
+
+    let defeat = scenario.defeat.iter().any(|condition| active_scenario.is_met(condition, &destroyed_factions));
+    let victory = !scenario.victory.is_empty()
+        && scenario.victory.iter().all(|condition| active_scenario.is_met(condition, &destroyed_factions));
+    if !defeat && !victory {
+        return;
+    }
+
+    info!("Scenario \"{}\" {}", scenario.name, if victory && !defeat { "won" } else { "lost" });
+    ended_writer.send(ScenarioEndedEvent { name: scenario.name.clone(), victory: victory && !defeat });
+    commands.remove_resource::<ActiveScenario>();
+}
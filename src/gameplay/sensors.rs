@@ -0,0 +1,160 @@
+use crate::core::prelude::*;
+use crate::gameplay::structures_combat::Projectile;
+use crate::gameplay::wrecks::{has_line_of_sight, Wreck};
+use crate::world::prelude::*;
+
+use crate::prelude::*;
+
+const SENSOR_SWEEP_INTERVAL: f32 = 0.2; // seconds between two sensor sweeps
+const STRUCTURE_SENSOR_RANGE: f32 = 250.0; // meters seen by a structure without sensor modules
+const SENSOR_MODULE_RANGE: f32 = 350.0; // meters added by every sensor module
+const PLAYER_SENSOR_RANGE: f32 = 150.0; // meters seen by the player floating outside of any structure
+
+/// Fog of war: only the structures and projectiles within the sensor range of the structure the player flies or is
+/// aboard, or of the player themselves in open space, are shown. Sensor modules extend the range of their structure
+/// and wrecks hide what lies behind them. Newly detected structures send a `ContactDetectedEvent`.
+pub struct SensorPlugin;
+
+impl Plugin for SensorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Contacts>()
+            .insert_resource(SensorSweepTimer(Timer::from_seconds(SENSOR_SWEEP_INTERVAL, TimerMode::Repeating)))
+            .add_event::<ContactDetectedEvent>()
+            .add_systems(
+                Update,
+                (update_sensor_range_system, sensor_sweep_system, hide_undetected_system)
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+/// Meters a structure sees around it.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct SensorRange(pub f32);
+
+impl SensorRange {
+    pub fn with_sensor_modules(count: usize) -> Self {
+        Self(STRUCTURE_SENSOR_RANGE + SENSOR_MODULE_RANGE * count as f32)
+    }
+}
+
+/// What the sensors of the player see.
+#[derive(Resource, Debug, Default)]
+pub struct Contacts {
+    /// Structure the player flies or is aboard, or the player entity in open space. `None` while the player is dead,
+    /// then nothing is hidden.
+    pub observer: Option<Entity>,
+    /// Structures and projectiles within the sensor range of the observer.
+    pub detected: HashSet<Entity>,
+}
+
+impl Contacts {
+    pub fn is_detected(&self, entity: Entity) -> bool {
+        self.observer.is_none() || self.observer == Some(entity) || self.detected.contains(&entity)
+    }
+}
+
+/// Sent when a structure comes within the sensor range of the player.
+#[derive(Event, Debug)]
+pub struct ContactDetectedEvent {
+    pub observer: Entity,
+    pub contact: Entity,
+}
+
+#[derive(Resource)]
+struct SensorSweepTimer(Timer);
+
+fn update_sensor_range_system(
+    structures_query: Query<(Entity, &Structure), Changed<Structure>>,
+    modules_query: Query<&Module>,
+    mut commands: Commands,
+) {
+    for (structure_entity, structure) in &structures_query {
+        let sensor_modules = structure
+            .modules()
+            .filter_map(|(_, module_entity)| modules_query.get(module_entity).ok())
+            .filter(|module| module.module_type == ModuleType::Sensor)
+            .count();
+        commands.entity(structure_entity).insert(SensorRange::with_sensor_modules(sensor_modules));
+    }
+}
+
+fn sensor_sweep_system(
+    mut sweep_timer: ResMut<SensorSweepTimer>,
+    time: Res<Time>,
+    mut contacts: ResMut<Contacts>,
+    controlled_query: Query<Entity, With<ControlledByPlayer>>,
+    player_resource: Res<PlayerResource>,
+    player_query: Query<(Entity, &GlobalTransform), With<Player>>,
+    observers_query: Query<(&GlobalTransform, Option<&SensorRange>)>,
+    structures_query: Query<(Entity, &GlobalTransform), With<Structure>>,
+    projectiles_query: Query<(Entity, &GlobalTransform), With<Projectile>>,
+    wreck_query: Query<(), With<Wreck>>,
+    spatial_query: SpatialQuery,
+    mut detected_writer: EventWriter<ContactDetectedEvent>,
+) {
+    if !sweep_timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let observer = controlled_query
+        .get_single()
+        .ok()
+        .or(player_resource.inside_structure)
+        .or(player_query.get_single().ok().map(|(player_entity, _)| player_entity));
+    let Some((observer, (observer_transform, sensor_range))) =
+        observer.and_then(|observer| observers_query.get(observer).ok().map(|sensor| (observer, sensor)))
+    else {
+        contacts.observer = None;
+        contacts.detected.clear();
+        return;
+    };
+
+    let center = observer_transform.translation().truncate();
+    let range = sensor_range.map_or(PLAYER_SENSOR_RANGE, |sensor_range| sensor_range.0);
+    let in_range = |transform: &GlobalTransform| transform.translation().truncate().distance(center) <= range;
+
+    let mut detected = HashSet::new();
+    for (structure_entity, structure_transform) in &structures_query {
+        if structure_entity == observer || !in_range(structure_transform) {
+            continue;
+        }
+        // Wrecks are cover, see `has_line_of_sight`
+        if !wreck_query.contains(structure_entity)
+            && !has_line_of_sight(&spatial_query, center, structure_transform.translation().truncate(), &wreck_query)
+        {
+            continue;
+        }
+        detected.insert(structure_entity);
+        // Switching observers, like boarding another structure, detects again what it sees
+        if contacts.observer != Some(observer) || !contacts.detected.contains(&structure_entity) {
+            detected_writer.send(ContactDetectedEvent { observer, contact: structure_entity });
+        }
+    }
+    detected.extend(
+        projectiles_query.iter().filter(|(_, transform)| in_range(transform)).map(|(projectile, _)| projectile),
+    );
+
+    contacts.observer = Some(observer);
+    contacts.detected = detected;
+}
+
+/// Hides the structures and projectiles the sensors do not see, the player's own structure always stays visible.
+fn hide_undetected_system(
+    contacts: Res<Contacts>,
+    player_resource: Res<PlayerResource>,
+    mut visibility_query: Query<(Entity, &mut Visibility), Or<(With<Structure>, With<Projectile>)>>,
+) {
+    if !contacts.is_changed() {
+        return;
+    }
+
+    for (entity, mut visibility) in &mut visibility_query {
+        let shown = contacts.is_detected(entity) || player_resource.inside_structure == Some(entity);
+        let target = if shown { Visibility::Visible } else { Visibility::Hidden };
+        if *visibility != target {
+            *visibility = target;
+        }
+    }
+}
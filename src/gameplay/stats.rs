@@ -0,0 +1,115 @@
+use crate::core::prelude::*;
+use crate::gameplay::destruction::StructureDestroyedEvent;
+use crate::gameplay::structures_combat::ProjectileOwner;
+use crate::world::prelude::*;
+
+use crate::prelude::*;
+
+const MODULE_KILL_SCORE: u32 = 10;
+const STRUCTURE_KILL_SCORE: u32 = 100;
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameStats>().add_systems(
+            Update,
+            (
+                count_shots_fired_system,
+                attribute_damage_system,
+                attribute_kills_system,
+                attribute_structure_kills_system,
+            )
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+/// Combat record of a single structure.
+#[derive(Debug, Default, Clone)]
+pub struct CombatStats {
+    pub shots_fired: u32,
+    pub hits: u32,
+    pub damage_dealt: f32,
+    pub damage_taken: f32,
+    pub modules_destroyed: u32,
+    pub modules_lost: u32,
+    pub structures_destroyed: u32,
+    /// Points earned by destroying modules and structures.
+    pub score: u32,
+}
+
+/// Damage and kills attributed to the structure that fired each projectile.
+/// Scoreboards, bounties and AI threat evaluation should all read from here.
+#[derive(Resource, Debug, Default)]
+pub struct GameStats {
+    pub structures: HashMap<Entity, CombatStats>,
+}
+
+impl GameStats {
+    pub fn get(&self, structure: Entity) -> Option<&CombatStats> {
+        self.structures.get(&structure)
+    }
+
+    fn entry(&mut self, structure: Entity) -> &mut CombatStats {
+        self.structures.entry(structure).or_default()
+    }
+
+    /// Damage dealt by `attacker`, useful to decide who is the biggest threat.
+    pub fn damage_dealt_by(&self, attacker: Entity) -> f32 {
+        self.get(attacker).map(|stats| stats.damage_dealt).unwrap_or_default()
+    }
+}
+
+fn count_shots_fired_system(query: Query<&ProjectileOwner, Added<ProjectileOwner>>, mut stats: ResMut<GameStats>) {
+    for owner in &query {
+        stats.entry(owner.structure).shots_fired += 1;
+    }
+}
+
+fn attribute_damage_system(
+    mut event_reader: EventReader<ModuleTookDamageEvent>,
+    parent_query: Query<&Parent, With<Module>>,
+    mut stats: ResMut<GameStats>,
+) {
+    for event in event_reader.read() {
+        if let Some(attacker) = event.source {
+            let attacker_stats = stats.entry(attacker);
+            attacker_stats.hits += 1;
+            attacker_stats.damage_dealt += event.damage;
+        }
+        if let Ok(parent) = parent_query.get(event.module_entity) {
+            stats.entry(parent.get()).damage_taken += event.damage;
+        }
+    }
+}
+
+fn attribute_kills_system(
+    mut event_reader: EventReader<ModuleDestroyedEvent>,
+    parent_query: Query<&Parent, With<Module>>,
+    mut stats: ResMut<GameStats>,
+) {
+    for event in event_reader.read() {
+        if let Some(attacker) = event.source {
+            let attacker_stats = stats.entry(attacker);
+            attacker_stats.modules_destroyed += 1;
+            attacker_stats.score += MODULE_KILL_SCORE;
+        }
+        if let Ok(parent) = parent_query.get(event.destroyed_entity) {
+            stats.entry(parent.get()).modules_lost += 1;
+        }
+    }
+}
+
+fn attribute_structure_kills_system(
+    mut event_reader: EventReader<StructureDestroyedEvent>,
+    mut stats: ResMut<GameStats>,
+) {
+    for event in event_reader.read() {
+        if let Some(attacker) = event.source {
+            let attacker_stats = stats.entry(attacker);
+            attacker_stats.structures_destroyed += 1;
+            attacker_stats.score += STRUCTURE_KILL_SCORE;
+        }
+    }
+}
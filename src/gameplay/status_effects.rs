@@ -0,0 +1,194 @@
+use crate::core::prelude::*;
+use crate::world::prelude::*;
+
+use bevy::prelude::*;
+
+const BURNING_DURATION: f32 = 10.0; // seconds
+const EMP_DURATION: f32 = 8.0; // seconds
+const BREACH_DURATION: f32 = 20.0; // seconds, until the crew patches the hull
+const BURNING_DAMAGE_RATE: f32 = 5.0; // structural points/s lost by a burning module
+const BREACH_VENT_RATE: f32 = 0.2; // oxygen/s vented from each cell next to a breached module
+
+/// Timed conditions of the modules: burning ones lose structural points, EMP-disabled ones stop working and breached
+/// ones vent the air of the cells around them. Any system applies one with an `ApplyStatusEffectEvent`, the systems
+/// driving the modules check `is_disabled` before using them.
+pub struct StatusEffectsPlugin;
+
+impl Plugin for StatusEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ApplyStatusEffectEvent>().add_systems(
+            FixedUpdate,
+            (apply_status_effects_system, tick_status_effects_system).chain().run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StatusEffectKind {
+    Burning,
+    EmpDisabled,
+    Breached,
+}
+
+impl StatusEffectKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            StatusEffectKind::Burning => "Burning",
+            StatusEffectKind::EmpDisabled => "EMP disabled",
+            StatusEffectKind::Breached => "Breached",
+        }
+    }
+
+    /// Seconds the effect lasts when applied without a duration.
+    pub fn default_duration(&self) -> f32 {
+        match self {
+            StatusEffectKind::Burning => BURNING_DURATION,
+            StatusEffectKind::EmpDisabled => EMP_DURATION,
+            StatusEffectKind::Breached => BREACH_DURATION,
+        }
+    }
+
+    /// Whether a module under this effect stops working.
+    pub fn disables(&self) -> bool {
+        matches!(self, StatusEffectKind::EmpDisabled)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct StatusEffect {
+    pub kind: StatusEffectKind,
+    pub remaining: f32,
+}
+
+/// Status effects currently affecting a module, removed once they all expired.
+#[derive(Component, Debug, Default)]
+pub struct StatusEffects(pub Vec<StatusEffect>);
+
+impl StatusEffects {
+    pub fn has(&self, kind: StatusEffectKind) -> bool {
+        self.0.iter().any(|effect| effect.kind == kind)
+    }
+
+    pub fn is_disabled(&self) -> bool {
+        self.0.iter().any(|effect| effect.kind.disables())
+    }
+
+    /// Applying an effect the module already has extends it to the longest of both durations.
+    pub fn apply(&mut self, kind: StatusEffectKind, duration: f32) {
+        match self.0.iter_mut().find(|effect| effect.kind == kind) {
+            Some(effect) => effect.remaining = effect.remaining.max(duration),
+            None => self.0.push(StatusEffect { kind, remaining: duration }),
+        }
+    }
+}
+
+/// Whether a module with these optional status effects is disabled, see `StatusEffects::is_disabled`.
+pub fn is_disabled(status_effects: Option<&StatusEffects>) -> bool {
+    status_effects.is_some_and(StatusEffects::is_disabled)
+}
+
+/// Applies a status effect to a module, for `duration` seconds or the default duration of the effect.
+#[derive(Event, Debug)]
+pub struct ApplyStatusEffectEvent {
+    pub module_entity: Entity,
+    pub kind: StatusEffectKind,
+    pub duration: Option<f32>,
+}
+
+impl ApplyStatusEffectEvent {
+    pub fn new(module_entity: Entity, kind: StatusEffectKind) -> Self {
+        Self { module_entity, kind, duration: None }
+    }
+}
+
+fn apply_status_effects_system(
+    mut event_reader: EventReader<ApplyStatusEffectEvent>,
+    mut status_effects_query: Query<Option<&mut StatusEffects>, With<Module>>,
+    mut commands: Commands,
+) {
+    // Effects inserted this tick are only visible to the queries once the commands are applied
+    let mut inserted: Vec<(Entity, StatusEffects)> = Vec::new();
+    for event in event_reader.read() {
+        let duration = event.duration.unwrap_or_else(|| event.kind.default_duration());
+        match status_effects_query.get_mut(event.module_entity) {
+            Ok(Some(mut status_effects)) => status_effects.apply(event.kind, duration),
+            Ok(None) => match inserted.iter_mut().find(|(entity, _)| *entity == event.module_entity) {
+                Some((_, status_effects)) => status_effects.apply(event.kind, duration),
+                None => {
+                    let mut status_effects = StatusEffects::default();
+                    status_effects.apply(event.kind, duration);
+                    inserted.push((event.module_entity, status_effects));
+                }
+            },
+            // The module was destroyed
+            Err(_) => continue,
+        }
+        debug!("Module {:?} is {} for {:.0}s", event.module_entity, event.kind.name(), duration);
+    }
+    for (module_entity, status_effects) in inserted {
+        if let Some(mut module_commands) = commands.get_entity(module_entity) {
+            module_commands.insert(status_effects);
+        }
+    }
+}
+
+/// Counts down the status effects and runs their effect for this tick.
+fn tick_status_effects_system(
+    mut modules_query: Query<(Entity, &Module, &mut ModuleMaterial, &mut StatusEffects, &Parent)>,
+    mut structures_query: Query<(&Structure, &mut Pressurization)>,
+    mut damage_writer: EventWriter<ModuleTookDamageEvent>,
+    mut destroyed_writer: EventWriter<ModuleDestroyedEvent>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    let delta_time = time.delta_seconds();
+
+    for (module_entity, module, mut module_material, mut status_effects, parent) in &mut modules_query {
+        if module_material.structural_points <= 0.0 {
+            continue;
+        }
+
+        if status_effects.has(StatusEffectKind::Burning) {
+            let damage = BURNING_DAMAGE_RATE * delta_time;
+            module_material.structural_points -= damage;
+            damage_writer.send(ModuleTookDamageEvent {
+                module_entity,
+                damage,
+                remaining_points: module_material.structural_points,
+                source: None,
+                critical: false,
+            });
+            if module_material.structural_points <= 0.0 {
+                destroyed_writer.send(ModuleDestroyedEvent {
+                    destroyed_entity: module_entity,
+                    inner_grid_pos: module.inner_grid_pos,
+                    source: None,
+                });
+            }
+        }
+
+        if status_effects.has(StatusEffectKind::Breached) {
+            if let Ok((structure, mut pressurization)) = structures_query.get_mut(parent.get()) {
+                for cell in module.cells() {
+                    for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+                        let neighbour = (cell.0 + dx, cell.1 + dy);
+                        if structure.module_at(neighbour).is_some() {
+                            continue;
+                        }
+                        if let Some(oxygen) = pressurization.oxygen.get_mut(&neighbour) {
+                            *oxygen = (*oxygen - BREACH_VENT_RATE * delta_time).max(0.0);
+                        }
+                    }
+                }
+            }
+        }
+
+        for effect in &mut status_effects.0 {
+            effect.remaining -= delta_time;
+        }
+        status_effects.0.retain(|effect| effect.remaining > 0.0);
+        if status_effects.0.is_empty() {
+            commands.entity(module_entity).remove::<StatusEffects>();
+        }
+    }
+}
@@ -1,30 +1,140 @@
+use crate::configs::collision_layers::CollisionLayersConfig;
 use crate::configs::config::UNIT_SCALE;
 use crate::core::prelude::*;
+use crate::core::profiling::{
+    EntityCountDiagnosticAppExt, ProfileScope, DEPRESSURIZATION, MODULE_DESTRUCTION, PROJECTILE_HITS,
+};
+use crate::gameplay::debris::spawn_debris;
+use crate::gameplay::degradation::{performance, ModulePerformance};
+use crate::gameplay::heat::HeatStore;
+use crate::gameplay::medical::{InjuryCause, InjuryEvent};
+use crate::gameplay::movement::structure_point_velocity;
+use crate::gameplay::power::PowerConsumer;
+use crate::gameplay::status_effects::{is_disabled, ApplyStatusEffectEvent, StatusEffectKind, StatusEffects};
+use crate::gameplay::targeting::{gimbaled_aim, lead_position, TargetLock};
+use crate::gameplay::upgrades::{tier_output, ModuleTier};
+use crate::gameplay::world_bounds::TravelLimit;
+use crate::gameplay::wrecks::Wreck;
 use crate::world::prelude::*;
 
 use crate::prelude::*;
-
-const PROJECTILE_LIFETIME: f32 = 1.0;
+use bevy::diagnostic::Diagnostics;
+
+const CANNON_MUZZLE_VELOCITY: f32 = 500.0; // m/s
+const CANNON_RANGE: f32 = 500.0; // meters
+const SPINAL_RANGE: f32 = 1500.0; // meters
+const ACCELERATOR_VELOCITY_PER_SEGMENT: f32 = 250.0; // m/s added by each segment of a spinal weapon
+const MAX_ACCELERATOR_SEGMENTS: f32 = 12.0; // longer spinal weapons do not fire any faster
+const IMPACT_MOMENTUM_TRANSFER: f32 = 1.0; // fraction of the projectile momentum given to the structure hit
+const RICOCHET_MAX_COS: f32 = 0.26; // cosine of the angle of incidence under which rounds bounce off, about 75 degrees
+const RICOCHET_SPEED_RETAINED: f32 = 0.6; // fraction of its speed a round keeps when bouncing off
+const OVER_PENETRATION_DAMAGE: f32 = 0.5; // fraction of the damage left over that carries on to the module behind
+const MAX_PENETRATION_CELLS: usize = 4; // cells crossed by a round punching through a structure
+const RECOIL_COMPENSATION_PER_MODULE: f32 = 0.25; // fraction of the recoil absorbed by each compensator
+const MAX_RECOIL_COMPENSATION: f32 = 0.75;
 
 pub struct StructuresCombatPlugin;
 
 impl Plugin for StructuresCombatPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, handle_module_destroyed_system.run_if(on_event::<ModuleDestroyedEvent>()))
+        app.init_resource::<SelectedWeaponGroup>()
+            .add_event::<FireCannonsEvent>()
+            .add_event::<CannonFiredEvent>()
+            .add_event::<StructureHitEvent>()
+            .add_systems(Update, handle_module_destroyed_system.run_if(on_event::<ModuleDestroyedEvent>()))
             .add_systems(
                 Update,
                 handle_depressurization_system
                     .run_if(on_event::<StructureDepressurizationEvent>())
                     .after(PhysicsSet::Sync),
             )
+            .add_systems(Update, select_weapon_group_system.run_if(in_state(GameState::InGame)))
             .add_systems(FixedUpdate, structure_shoot_system.run_if(in_state(GameState::InGame)))
+            .add_systems(PostProcessCollisions, ignore_own_projectiles_system)
             .add_systems(
                 Update,
                 (projectile_hit_system, projectile_lifetime_system).chain().run_if(in_state(GameState::InGame)),
-            );
+            )
+            .register_module_type::<RecoilCompensator>(
+                ModuleDefinition::new("Recoil compensator", 'K').with_color(Color::from(LIME)),
+            )
+            .register_entity_count_diagnostic::<Projectile>("projectiles")
+            .register_entity_count_diagnostic::<Wreck>("wrecks");
+    }
+}
+
+/// Kinds of guns of a structure, each firing rounds that expire at its own range.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum WeaponGroup {
+    #[default]
+    Cannons,
+    /// The spinal weapons made of accelerators.
+    Spinal,
+}
+
+impl WeaponGroup {
+    pub fn name(&self) -> &'static str {
+        match self {
+            WeaponGroup::Cannons => "Cannons",
+            WeaponGroup::Spinal => "Spinal",
+        }
+    }
+
+    /// Distance the rounds of this group travel before they expire, in meters.
+    pub fn range(&self) -> f32 {
+        match self {
+            WeaponGroup::Cannons => CANNON_RANGE,
+            WeaponGroup::Spinal => SPINAL_RANGE,
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            WeaponGroup::Cannons => WeaponGroup::Spinal,
+            WeaponGroup::Spinal => WeaponGroup::Cannons,
+        }
     }
 }
 
+/// Weapon group fired by the shoot input of the player.
+#[derive(Resource, Debug, Default)]
+pub struct SelectedWeaponGroup(pub WeaponGroup);
+
+/// Fires every powered gun of a structure not flown by the player, the player fires with the shoot input.
+#[derive(Event, Debug)]
+pub struct FireCannonsEvent {
+    pub structure_entity: Entity,
+}
+
+/// Sent for every projectile leaving a cannon.
+#[derive(Event, Debug)]
+pub struct CannonFiredEvent {
+    pub structure_entity: Entity,
+    /// Muzzle of the cannon, in world space.
+    pub position: Vec2,
+    pub direction: Vec2,
+}
+
+/// Sent when a projectile hits a module of a structure, at the impact point in world space.
+#[derive(Event, Debug)]
+pub struct StructureHitEvent {
+    pub structure_entity: Entity,
+    pub module_entity: Entity,
+    pub position: Vec2,
+    pub damage: f32,
+    pub critical: bool,
+    pub material_type: ModuleMaterialType,
+}
+
+/// Module absorbing part of the recoil of the cannons of its structure.
+#[derive(Component, Debug, Default)]
+pub struct RecoilCompensator;
+
+/// Fraction of the cannons recoil actually applied to a structure with this many compensators.
+fn recoil_factor(compensators: usize) -> f32 {
+    1.0 - (compensators as f32 * RECOIL_COMPENSATION_PER_MODULE).min(MAX_RECOIL_COMPENSATION)
+}
+
 #[derive(Debug, Default)]
 enum ProjectileMaterialType {
     #[default]
@@ -57,6 +167,15 @@ impl ProjectileMaterialType {
         }
     }
 
+    /// Status effect left on the modules hit by this kind of projectile.
+    fn status_effect(&self) -> Option<StatusEffectKind> {
+        match self {
+            ProjectileMaterialType::Ballistic => None,
+            ProjectileMaterialType::Explosive => Some(StatusEffectKind::Burning),
+            ProjectileMaterialType::Energy => Some(StatusEffectKind::EmpDisabled),
+        }
+    }
+
     fn size(&self) -> f32 {
         match self {
             ProjectileMaterialType::Ballistic => 0.5, // Desired diameter in meters (1 units in game, or 1 meter)
@@ -73,6 +192,8 @@ struct ProjectilePhysics {
     pub size: f32, // Diameter in meters
     pub area: f32, // Area in square meters
     pub material_type: ProjectileMaterialType,
+    /// Multiplier of the damage dealt on impact, from the tier of the gun.
+    pub damage_scale: f32,
 }
 
 impl ProjectilePhysics {
@@ -108,6 +229,7 @@ impl ProjectilePhysics {
             mass,              // Mass in game units
             size: diameter,    // Size in game units (pixels)
             material_type,
+            damage_scale: scaling_factor,
         }
     }
 
@@ -152,21 +274,199 @@ impl ProjectilePhysics {
     }
 }
 
+/// Damage dealt by a projectile hitting a module of the given material at this speed, and whether the hit is
+/// critical. The kinetic energy of the projectile is scaled by how dense and hard it is compared to the module.
+fn impact_damage(
+    projectile_physics: &ProjectilePhysics,
+    velocity_mps: f32,
+    module_material_type: ModuleMaterialType,
+) -> (f32, bool) {
+    // Calculate the kinetic energy of the projectile (Joules)
+    let projectile_kinetic_energy = 0.5 * projectile_physics.mass * velocity_mps.powi(2);
+
+    // Retrieve the material's properties for the module and projectile
+    let material_properties = module_material_type.properties();
+    let projectile_properties = projectile_physics.material_type.properties();
+
+    let material_strength = material_properties.yield_strength;
+
+    // Factor in the projectile's density and yield strength
+    let density_factor = projectile_properties.density / material_properties.density;
+    let hardness_factor = projectile_properties.yield_strength / material_properties.yield_strength;
+
+    // Calculate the adjusted damage
+    let damage = (projectile_kinetic_energy * density_factor * hardness_factor) / material_strength;
+    (damage * projectile_physics.damage_scale, projectile_kinetic_energy > material_properties.damage_threshold)
+}
+
+/// Seconds a round lives, so that it covers the range of its weapon at its muzzle velocity.
+pub fn projectile_lifetime(range: f32, muzzle_velocity: f32) -> f32 {
+    range / muzzle_velocity.max(f32::EPSILON)
+}
+
+/// How a round meets the face of the module it strikes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Impact {
+    /// Glancing blow: the round bounces off with this velocity and deals this fraction of its damage.
+    Ricochet(Vec2, f32),
+    /// The round bites in and deals this fraction of its damage, less on sloped faces.
+    Penetrating(f32),
+}
+
+/// Outward normal of the face of a module cell struck `offset` away from the center of the cell.
+fn hit_face_normal(offset: Vec2) -> Vec2 {
+    if offset.x.abs() >= offset.y.abs() {
+        Vec2::new(offset.x.signum(), 0.0)
+    } else {
+        Vec2::new(0.0, offset.y.signum())
+    }
+}
+
+/// Compares the velocity of a round with the face it struck, both in the space of the structure hit.
+fn impact(velocity: Vec2, face_normal: Vec2) -> Impact {
+    let cos_incidence = (-velocity.normalize_or_zero()).dot(face_normal);
+    // Grazing a corner or already inside the module
+    if cos_incidence <= 0.0 {
+        return Impact::Penetrating(1.0);
+    }
+    if cos_incidence < RICOCHET_MAX_COS {
+        let bounce = velocity - 2.0 * velocity.dot(face_normal) * face_normal;
+        return Impact::Ricochet(bounce * RICOCHET_SPEED_RETAINED, cos_incidence);
+    }
+    Impact::Penetrating(cos_incidence)
+}
+
+/// Cells of a structure behind `cell` along `direction` in the space of the structure, nearest first.
+fn cells_behind(structure: &Structure, cell: (i32, i32), direction: Vec2) -> Vec<(i32, i32)> {
+    let step = direction.normalize_or_zero() * structure.grid.cell_size / 2.0;
+    if step == Vec2::ZERO {
+        return Vec::new();
+    }
+
+    let mut cells = Vec::new();
+    let mut position = structure.grid_cell_center_local_position(cell.0, cell.1);
+    let mut last_cell = cell;
+    while cells.len() < MAX_PENETRATION_CELLS {
+        position += step;
+        let next_cell = structure.local_to_grid(position);
+        if !structure.is_within_grid_bounds(next_cell.0, next_cell.1) {
+            break;
+        }
+        if next_cell != last_cell {
+            cells.push(next_cell);
+            last_cell = next_cell;
+        }
+    }
+    cells
+}
+
+/// Muzzle velocity of a cannon in perfect condition, in m/s.
+pub fn cannon_muzzle_velocity(cannon_performance: f32) -> f32 {
+    CANNON_MUZZLE_VELOCITY * cannon_performance
+}
+
+/// Muzzle velocity of a spinal weapon, every working accelerator segment speeding the round up further.
+/// The damage of the round grows along with its kinetic energy.
+pub fn spinal_muzzle_velocity(working_segments: f32) -> f32 {
+    CANNON_MUZZLE_VELOCITY + ACCELERATOR_VELOCITY_PER_SEGMENT * working_segments.min(MAX_ACCELERATOR_SEGMENTS)
+}
+
+/// A spinal weapon: a line of consecutive accelerators facing the same way, firing from the front one.
+#[derive(Debug)]
+pub struct SpinalWeapon<'a> {
+    pub muzzle_transform: &'a Transform,
+    pub orientation: Orientation,
+    pub segments: u32,
+    /// Segments counted by their performance, the unpowered and disabled ones do not count.
+    pub working_segments: f32,
+}
+
+/// Assembles the accelerators of a structure into spinal weapons.
+pub fn spinal_weapons<'a>(
+    childrens: &Children,
+    accelerator_query: &'a Query<
+        (&Module, &Transform, Option<&PowerConsumer>, Option<&ModulePerformance>, Option<&StatusEffects>),
+        With<AcceleratorModule>,
+    >,
+) -> Vec<SpinalWeapon<'a>> {
+    let accelerators: HashMap<(i32, i32), (&Transform, Orientation, f32)> = accelerator_query
+        .iter_many(childrens)
+        .map(|(module, transform, power, module_performance, status_effects)| {
+            let working = if power.is_some_and(|power| !power.powered) || is_disabled(status_effects) {
+                0.0
+            } else {
+                performance(module_performance)
+            };
+            (module.inner_grid_pos, (transform, module.orientation, working))
+        })
+        .collect();
+
+    let mut weapons = Vec::new();
+    for (&(x, y), &(muzzle_transform, orientation, _)) in &accelerators {
+        let (dx, dy) = facing_cell_offset(orientation.quarter_turns());
+        let in_line =
+            |cell| accelerators.get(&cell).filter(|(_, segment_orientation, _)| *segment_orientation == orientation);
+        // Only the front segment fires, the others feed it
+        if in_line((x + dx, y + dy)).is_some() {
+            continue;
+        }
+        let mut weapon = SpinalWeapon { muzzle_transform, orientation, segments: 0, working_segments: 0.0 };
+        let mut cell = (x, y);
+        while let Some((_, _, working)) = in_line(cell) {
+            weapon.segments += 1;
+            weapon.working_segments += working;
+            cell = (cell.0 - dx, cell.1 - dy);
+        }
+        weapons.push(weapon);
+    }
+    weapons
+}
+
+/// Damage a shot of a cannon in this condition deals to a module of the given material, and whether it is critical.
+/// Projectiles keep their muzzle velocity in flight, so the damage does not drop with range until they expire.
+pub fn predicted_cannon_damage(cannon_performance: f32, module_material_type: ModuleMaterialType) -> (f32, bool) {
+    impact_damage(&ProjectilePhysics::ballistic(1.0), cannon_muzzle_velocity(cannon_performance), module_material_type)
+}
+
 #[derive(Component, Deref, DerefMut)]
-struct Projectile(Timer);
+pub struct Projectile(Timer);
+
+/// Who fired a projectile, every damage it deals is attributed to this owner.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct ProjectileOwner {
+    pub structure: Entity,
+    pub player: Option<Entity>,
+}
 
 #[derive(Bundle)]
 struct ProjectileBundle {
     projectile: Projectile,
+    owner: ProjectileOwner,
+    travel_limit: TravelLimit,
     projectile_physics: ProjectilePhysics,
     rigid_body: RigidBody,
     collider: Collider,
+    collision_layers: CollisionLayers,
     collider_density: ColliderDensity,
     mesh_bundle: MaterialMesh2dBundle<ColorMaterial>,
     impulse: ExternalImpulse,
     locked_axes: LockedAxes,
 }
 
+/// Projectiles pass through the modules of the structure that fired them, they are spawned right at its cannons.
+fn ignore_own_projectiles_system(mut collisions: ResMut<Collisions>, owners_query: Query<&ProjectileOwner>) {
+    let fired_by = |projectile_entity: Entity, body_entity: Option<Entity>| {
+        owners_query
+            .get(projectile_entity)
+            .ok()
+            .zip(body_entity)
+            .is_some_and(|(owner, body_entity)| owner.structure == body_entity)
+    };
+    collisions.retain(|contacts| {
+        !fired_by(contacts.entity1, contacts.body_entity2) && !fired_by(contacts.entity2, contacts.body_entity1)
+    });
+}
+
 /// This function is used to find the entity that matches the query.
 /// Given a query if the entity is found, it returns the entity, otherwise it returns `None`.
 fn find_matching_entity<T: Component>(
@@ -186,28 +486,29 @@ fn find_matching_entity<T: Component>(
 // Helper function to despawn an entity
 fn despawn_entity(entity: Entity, commands: &mut Commands) {
     if commands.get_entity(entity).is_some() {
-        commands.entity(entity).despawn();
+        commands.entity(entity).despawn_recursive();
     }
 }
 
 fn handle_depressurization_system(
     mut event_reader: EventReader<StructureDepressurizationEvent>,
-    mut parent_query: Query<(&Children, &mut Pressurization, &mut Structure, &Transform)>,
-    modules_query: Query<(Entity, &Module, &Transform)>,
+    mut parent_query: Query<(&mut Pressurization, &mut Structure, &Transform)>,
+    modules_query: Query<&Transform, With<Module>>,
     mut commands: Commands,
+    mut diagnostics: Diagnostics,
 ) {
+    let scope = ProfileScope::enter(&DEPRESSURIZATION);
     for event in event_reader.read() {
         // Ensure we are handling the correct structure
-        if let Ok((children, mut pressurization, mut depressurized_structure, structure_transform)) =
+        if let Ok((mut pressurization, mut depressurized_structure, structure_transform)) =
             parent_query.get_mut(event.depressurized_structure)
         {
-            let neighboring_modules =
-                depressurized_structure.find_neighbors_of_exposed_modules(&pressurization.exposed_cells);
+            // Only the modules around the breached rooms are blown away
+            let neighboring_modules = depressurized_structure.find_neighbors_of_exposed_modules(&event.breached_cells);
 
-            for child in children.iter() {
-                if let Ok((module_entity, module, module_transform)) = modules_query.get(*child) {
-                    // Check if the module is in an exposed cell
-                    if neighboring_modules.contains(&module.inner_grid_pos) {
+            for cell in neighboring_modules {
+                if let Some(module_entity) = depressurized_structure.module_at(cell) {
+                    if let Ok(module_transform) = modules_query.get(module_entity) {
                         // Calculate the direction of the force (from the structure's center to the module)
                         let direction_3d = (module_transform.translation - structure_transform.translation).normalize();
                         let direction = Vec2::new(direction_3d.x, direction_3d.y);
@@ -224,62 +525,91 @@ fn handle_depressurization_system(
                         commands.entity(module_entity).remove::<ColliderDensity>();
                         commands.entity(module_entity).insert(RigidBody::Dynamic);
                         commands.entity(module_entity).insert(Mass(20000.0));
+                        commands.entity(module_entity).insert((Wreck, CollisionLayersConfig::wreck()));
 
                         // Set cell type to empty without this check_pressurization will not work properly
-                        depressurized_structure
-                            .grid
-                            .set_cell_type_to_empty(module.inner_grid_pos.0, module.inner_grid_pos.1);
+                        depressurized_structure.remove_module_at(cell);
                     }
                 }
             }
-            let exposed_cells = depressurized_structure.check_pressurization();
-            pressurization.exposed_cells = exposed_cells.clone();
+            let rooms = depressurized_structure.check_pressurization();
+            pressurization.update_rooms(rooms);
         }
     }
+    scope.finish(&mut diagnostics);
 }
 
-fn handle_module_destroyed_system(
+pub(crate) fn handle_module_destroyed_system(
     parent: Query<&Parent>,
-    mut parent_query: Query<(Entity, &mut Structure, &mut Pressurization)>,
+    mut parent_query: Query<(
+        Entity,
+        &mut Structure,
+        &mut Pressurization,
+        &Transform,
+        &LinearVelocity,
+        &AngularVelocity,
+        &CenterOfMass,
+    )>,
+    module_query: Query<(&GlobalTransform, &ModuleMaterial, &Handle<ColorMaterial>)>,
     mut event_reader: EventReader<ModuleDestroyedEvent>,
     mut event_writer: EventWriter<StructureDepressurizationEvent>,
     mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut diagnostics: Diagnostics,
 ) {
+    let scope = ProfileScope::enter(&MODULE_DESTRUCTION);
     // read teh event
     for event in event_reader.read() {
         // get the entity that was destroyed
         let module_destroyed = event.destroyed_entity;
         if let Ok(structure_parent) = parent.get(module_destroyed) {
-            if let Ok((structure_entity, mut structure_attacked, mut pressurization)) =
-                parent_query.get_mut(**structure_parent)
+            if let Ok((
+                structure_entity,
+                mut structure_attacked,
+                mut pressurization,
+                structure_transform,
+                structure_velocity,
+                structure_angular_velocity,
+                center_of_mass,
+            )) = parent_query.get_mut(**structure_parent)
             {
                 let module_inner_grid_pos = event.inner_grid_pos;
                 // Remove from grid and check pressurization
-                structure_attacked.grid.set_cell_type_to_empty(module_inner_grid_pos.0, module_inner_grid_pos.1);
-
-                // Get the adjacent cells to the destroyed module
-                let adjacent_cells = structure_attacked.get_adjacent_cells(module_inner_grid_pos);
-
-                // Check if any adjacent cell is in the exposed_cells set from Pressurization
-                let mut any_exposed = false;
-                for adjacent_cell in adjacent_cells {
-                    if !pressurization.exposed_cells.contains(&adjacent_cell) {
-                        // if the module hit does not have near exposed cells, then could be a room pressurized or another module.
-                        // we need to check if is a room or another module to call the event
-                        if let Some(grid_cell) = structure_attacked.grid.get(adjacent_cell.0, adjacent_cell.1) {
-                            if matches!(grid_cell.cell_type, CellType::Empty) {
-                                // if the cell is empty, then is a room
-                                any_exposed = true;
-                                break;
-                            }
-                        }
-                    }
+                structure_attacked.remove_module_at(module_inner_grid_pos);
+
+                // Any sealed room now connected to space through the destroyed module is breached
+                let rooms = structure_attacked.check_pressurization();
+                let breached_cells = pressurization.update_rooms(rooms);
+
+                if !breached_cells.is_empty() {
+                    event_writer.send(StructureDepressurizationEvent {
+                        depressurized_structure: structure_entity,
+                        breached_cells,
+                    });
                 }
-                let exposed_cells = structure_attacked.check_pressurization();
-                pressurization.exposed_cells = exposed_cells.clone();
 
-                if any_exposed {
-                    event_writer.send(StructureDepressurizationEvent { depressurized_structure: structure_entity });
+                // Leave the module behind as debris moving along with the structure
+                if let Ok((module_transform, module_material, material_handle)) = module_query.get(module_destroyed) {
+                    let velocity = structure_point_velocity(
+                        structure_velocity,
+                        structure_angular_velocity,
+                        structure_transform,
+                        center_of_mass,
+                        module_transform.translation().truncate(),
+                    );
+                    let color = materials.get(material_handle).map(|material| material.color).unwrap_or(Color::WHITE);
+
+                    spawn_debris(
+                        &mut commands,
+                        &game_assets,
+                        &mut materials,
+                        module_transform,
+                        &module_material.material_type,
+                        color,
+                        velocity,
+                        structure_angular_velocity.0,
+                    );
                 }
 
                 commands.entity(module_destroyed).remove_parent_in_place();
@@ -287,6 +617,7 @@ fn handle_module_destroyed_system(
             }
         }
     }
+    scope.finish(&mut diagnostics);
 }
 
 /// This system ticks the `Timer` on the entity with the `projectile_entity`
@@ -307,152 +638,367 @@ fn projectile_lifetime_system(
 // TODO: Make a system to detect the collisions and emit an event of structure hit, this system will only listen to the event.
 fn projectile_hit_system(
     mut collision_event_reader: EventReader<CollisionStarted>,
-    projectile_physics_query: Query<(&LinearVelocity, &ProjectilePhysics), With<Projectile>>,
+    mut projectile_physics_query: Query<
+        (&mut LinearVelocity, &ProjectilePhysics, &Transform, Option<&ProjectileOwner>),
+        With<Projectile>,
+    >,
+    module_parent_query: Query<&Parent, With<Module>>,
+    mut structure_impulse_query: Query<(&mut ExternalImpulse, &Transform, &CenterOfMass, &Structure)>,
     mut module_physics_query: Query<&mut ModuleMaterial>,
     mut projectile_query: Query<&mut Projectile>,
     mut module_query: Query<&mut Module>,
+    mut wreck_query: Query<&mut Wreck>,
+    mut player_query: Query<&mut Player>,
     mut commands: Commands,
     mut event_writer: EventWriter<ModuleDestroyedEvent>,
+    mut damage_writer: EventWriter<ModuleTookDamageEvent>,
+    mut hit_writer: EventWriter<StructureHitEvent>,
+    mut injury_writer: EventWriter<InjuryEvent>,
+    mut status_effect_writer: EventWriter<ApplyStatusEffectEvent>,
+    mut diagnostics: Diagnostics,
 ) {
+    let scope = ProfileScope::enter(&PROJECTILE_HITS);
     for CollisionStarted(entity1, entity2) in collision_event_reader.read() {
         if let Some(projectile_entity) = find_matching_entity(*entity1, *entity2, &mut projectile_query) {
-            if let Some(module_entity) = find_matching_entity(*entity1, *entity2, &mut module_query) {
-                if let Some(module) = module_query.get(module_entity).ok() {
-                    if let Ok((projectile_vel, projectile_physics)) = projectile_physics_query.get(projectile_entity) {
-                        if let Ok(mut module_material) = module_physics_query.get_mut(module_entity) {
-                            // No need to scale the velocity; it's already in m/s.
-                            let velocity_mps = (projectile_vel.0.length());
-
-                            // Calculate the kinetic energy of the projectile (Joules)
-                            let projectile_kinetic_energy = 0.5 * projectile_physics.mass * velocity_mps.powi(2);
-
-                            // Retrieve the material's properties for the module and projectile
-                            let material_properties = module_material.material_type.properties();
-                            let projectile_properties = projectile_physics.material_type.properties();
-
-                            let material_strength = material_properties.yield_strength;
-
-                            // Factor in the projectile's density and yield strength
-                            let density_factor = projectile_properties.density / material_properties.density;
-                            let hardness_factor =
-                                projectile_properties.yield_strength / material_properties.yield_strength;
-
-                            // Calculate the adjusted damage
-                            let damage =
-                                (projectile_kinetic_energy * density_factor * hardness_factor) / material_strength;
-
-                            // Update the module's structural points
-                            let structural_points_before = module_material.structural_points;
-                            module_material.structural_points -= damage;
-
-                            // Check if the module is destroyed
-                            let is_destroyed = module_material.structural_points <= 0.0;
-                            if is_destroyed {
-                                event_writer.send(ModuleDestroyedEvent {
-                                    destroyed_entity: module_entity,
-                                    inner_grid_pos: module.inner_grid_pos,
-                                });
+            // Wrecks act as cover and absorb the projectile without taking damage
+            if find_matching_entity(*entity1, *entity2, &mut wreck_query).is_some() {
+                despawn_entity(projectile_entity, &mut commands);
+                continue;
+            }
+            if let Some(player_entity) = find_matching_entity(*entity1, *entity2, &mut player_query) {
+                injury_writer.send(InjuryEvent { entity: player_entity, cause: InjuryCause::Combat });
+                despawn_entity(projectile_entity, &mut commands);
+                continue;
+            }
+            let Some(module_entity) = find_matching_entity(*entity1, *entity2, &mut module_query) else {
+                continue;
+            };
+            let (Ok(module), Ok(module_material)) =
+                (module_query.get(module_entity), module_physics_query.get(module_entity))
+            else {
+                continue;
+            };
+            let Ok((mut projectile_vel, projectile_physics, projectile_transform, owner)) =
+                projectile_physics_query.get_mut(projectile_entity)
+            else {
+                continue;
+            };
+            let impact_position = projectile_transform.translation.truncate();
+
+            // No need to scale the velocity; it's already in m/s.
+            let velocity_mps = projectile_vel.0.length();
+            let (damage, critical) = impact_damage(projectile_physics, velocity_mps, module_material.material_type);
+            let status_effect = projectile_physics.material_type.status_effect();
+            // Modules struck by the round and the damage each one takes, the first one hit first
+            let mut hits = vec![(module_entity, damage, critical)];
+            let mut ricochet_velocity = None;
+
+            let structure_entity = module_parent_query.get(module_entity).ok().map(|parent| parent.get());
+            if let Some((mut impulse, structure_transform, center_of_mass, structure)) =
+                structure_entity.and_then(|structure_entity| structure_impulse_query.get_mut(structure_entity).ok())
+            {
+                // The angle of incidence is measured against the face of the module, in the space of the structure
+                let to_local =
+                    |vector: Vec2| structure_transform.rotation.inverse().mul_vec3(vector.extend(0.0)).truncate();
+                let local_velocity = to_local(projectile_vel.0);
+                let offset = to_local(impact_position - structure_transform.translation.truncate())
+                    - structure.grid_cell_center_local_position(module.inner_grid_pos.0, module.inner_grid_pos.1);
+
+                // Fraction of the momentum of the round given to the structure, a ricochet only deflects it
+                let momentum_transferred = match impact(local_velocity, hit_face_normal(offset)) {
+                    Impact::Ricochet(local_bounce, fraction) => {
+                        hits[0] = (module_entity, damage * fraction, false);
+                        ricochet_velocity =
+                            Some(structure_transform.rotation.mul_vec3(local_bounce.extend(0.0)).truncate());
+                        fraction
+                    }
+                    Impact::Penetrating(fraction) => {
+                        hits[0].1 = damage * fraction;
+                        // Rounds with more energy than the module can take punch through to the modules behind,
+                        // the rooms in between do not slow them down
+                        let mut leftover = hits[0].1 - module_material.structural_points.max(0.0);
+                        for cell in cells_behind(structure, module.inner_grid_pos, local_velocity) {
+                            let Some(behind_entity) = structure.module_at(cell) else {
+                                continue;
+                            };
+                            let Ok(behind_material) = module_physics_query.get(behind_entity) else {
+                                continue;
+                            };
+                            leftover *= OVER_PENETRATION_DAMAGE;
+                            if leftover <= 0.0 {
+                                break;
                             }
-
-                            // // Debug output with all relevant information
-                            // debug!(
-                            //     "Collision Detected!\n\
-                            //     Velocity: {:?} m/s\n\
-                            //     Projectile Kinetic Energy: {:.2} J (joules)\n\
-                            //     Module Material: {:?}\n\
-                            //     Material Strength: {:.2} J\n\
-                            //     Material Density: {:.2} kg/m²\n\
-                            //     Projectile Material Density: {:.2} kg/m²\n\
-                            //     Projectile Material Strength: {:.2} J\n\
-                            //     Module Structural Points Before: {:.2}\n\
-                            //     Damage Applied: {:.2}\n\
-                            //     Module Structural Points After: {:.2} {}\n",
-                            //     velocity_mps,
-                            //     projectile_kinetic_energy,
-                            //     module_material.material_type,
-                            //     material_strength,
-                            //     material_properties.density,
-                            //     projectile_properties.density,
-                            //     projectile_properties.yield_strength,
-                            //     structural_points_before,
-                            //     damage,
-                            //     module_material.structural_points,
-                            //     if is_destroyed { "(Destroyed)" } else { "" },
-                            // );
-
-                            despawn_entity(projectile_entity, &mut commands);
+                            hits.push((behind_entity, leftover, false));
+                            leftover -= behind_material.structural_points.max(0.0);
                         }
+                        1.0
                     }
+                };
+
+                // The hit pushes the structure at the impact point, so off-center hits also make it spin
+                let world_center_of_mass = structure_transform.translation.truncate()
+                    + structure_transform.rotation.mul_vec3(center_of_mass.0.extend(0.0)).truncate();
+                impulse.apply_impulse_at_point(
+                    projectile_vel.0 * projectile_physics.mass * IMPACT_MOMENTUM_TRANSFER * momentum_transferred,
+                    impact_position,
+                    world_center_of_mass,
+                );
+            }
+
+            let source = owner.map(|owner| owner.structure);
+            for (hit_entity, damage, critical) in hits {
+                let (Ok(module), Ok(mut module_material)) =
+                    (module_query.get(hit_entity), module_physics_query.get_mut(hit_entity))
+                else {
+                    continue;
+                };
+
+                // Update the module's structural points
+                module_material.structural_points -= damage;
+                damage_writer.send(ModuleTookDamageEvent {
+                    module_entity: hit_entity,
+                    damage,
+                    remaining_points: module_material.structural_points,
+                    source,
+                    critical,
+                });
+                if let Some(structure_entity) = structure_entity {
+                    hit_writer.send(StructureHitEvent {
+                        structure_entity,
+                        module_entity: hit_entity,
+                        position: impact_position,
+                        damage,
+                        critical,
+                        material_type: module_material.material_type,
+                    });
+                }
+
+                // Check if the module is destroyed
+                let is_destroyed = module_material.structural_points <= 0.0;
+                if is_destroyed {
+                    event_writer.send(ModuleDestroyedEvent {
+                        destroyed_entity: hit_entity,
+                        inner_grid_pos: module.inner_grid_pos,
+                        source,
+                    });
+                    continue;
+                }
+
+                // Surviving modules keep the effect of the projectile, critical hits also breach their hull
+                if let Some(kind) = status_effect {
+                    status_effect_writer.send(ApplyStatusEffectEvent::new(hit_entity, kind));
+                }
+                if critical {
+                    status_effect_writer.send(ApplyStatusEffectEvent::new(hit_entity, StatusEffectKind::Breached));
                 }
             }
+
+            match ricochet_velocity {
+                Some(velocity) => projectile_vel.0 = velocity,
+                None => despawn_entity(projectile_entity, &mut commands),
+            }
+        }
+    }
+    scope.finish(&mut diagnostics);
+}
+
+fn select_weapon_group_system(
+    mut input_reader: EventReader<InputAction>,
+    mut selected_weapon_group: ResMut<SelectedWeaponGroup>,
+) {
+    for event in input_reader.read() {
+        if matches!(event, InputAction::CycleWeaponGroup) {
+            selected_weapon_group.0 = selected_weapon_group.0.next();
+            debug!("Selected weapon group: {}", selected_weapon_group.0.name());
         }
     }
 }
 
 fn structure_shoot_system(
-    mut query: Query<(&Transform, &Children), With<ControlledByPlayer>>,
-    child_query: Query<(&Module, &Transform)>,
+    mut query: Query<(
+        Entity,
+        &Transform,
+        &Children,
+        Option<&ControlledByPlayer>,
+        &mut ExternalImpulse,
+        &CenterOfMass,
+        Option<&TargetLock>,
+    )>,
+    targets_query: Query<(&Transform, &LinearVelocity), With<Structure>>,
+    child_query: Query<
+        (
+            &Module,
+            &Transform,
+            Option<&PowerConsumer>,
+            Option<&ModulePerformance>,
+            Option<&ModuleTier>,
+            Option<&StatusEffects>,
+        ),
+        With<CannonModule>,
+    >,
+    accelerator_query: Query<
+        (&Module, &Transform, Option<&PowerConsumer>, Option<&ModulePerformance>, Option<&StatusEffects>),
+        With<AcceleratorModule>,
+    >,
+    compensator_query: Query<(), With<RecoilCompensator>>,
+    heat_query: Query<&HeatStore>,
     mut input_reader: EventReader<InputAction>,
+    mut fire_reader: EventReader<FireCannonsEvent>,
+    mut fired_writer: EventWriter<CannonFiredEvent>,
+    selected_weapon_group: Res<SelectedWeaponGroup>,
     mut commands: Commands,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    mut meshes: ResMut<Assets<Mesh>>,
+    game_assets: Res<GameAssets>,
 ) {
+    // The shoot input fires the selected weapon group of the structure flown by the player, the other structures
+    // fire all their guns through events
+    let mut shooters: Vec<(Entity, Option<WeaponGroup>)> =
+        fire_reader.read().map(|event| (event.structure_entity, None)).collect();
     for event in input_reader.read() {
-        match event {
-            InputAction::Shoot => {
-                for (structure_transform, childrens) in query.iter() {
-                    for child in childrens {
-                        if let Ok((module, module_transform)) = child_query.get(*child) {
-                            if matches!(module.module_type, ModuleType::Cannon) {
-                                // Determine the forward direction of the module in world space
-                                let forward_direction = structure_transform
-                                    .rotation
-                                    .mul_vec3(module_transform.rotation.mul_vec3(Vec3::Y))
-                                    .normalize();
-
-                                // Calculate the global position of the cannon module
-                                let cannon_position = structure_transform.translation
-                                    + structure_transform.rotation.mul_vec3(module_transform.translation);
-
-                                // Determine the spawn position a little in front of the cannon
-                                let spawn_position = cannon_position + forward_direction * 3.0;
-
-                                // Create the projectile physics object
-                                let projectile_physics = ProjectilePhysics::ballistic(1.0);
-
-                                let projectile_density = projectile_physics.density();
-
-                                // Desired velocity in meters per second (m/s)
-                                let desired_velocity_mps = 500.0;
-
-                                // Calculate the impulse force using ProjectilePhysics
-                                let impulse_force =
-                                    projectile_physics.impulse_force(desired_velocity_mps, forward_direction);
-
-                                let projectile_size = projectile_physics.size;
-
-                                commands.spawn(ProjectileBundle {
-                                    projectile: Projectile(Timer::from_seconds(PROJECTILE_LIFETIME, TimerMode::Once)),
-                                    projectile_physics,
-                                    rigid_body: RigidBody::Dynamic,
-                                    collider: Collider::circle(projectile_size / 2.0),
-                                    collider_density: ColliderDensity(projectile_density),
-                                    mesh_bundle: MaterialMesh2dBundle {
-                                        material: materials.add(ColorMaterial::from(Color::from(WHITE))),
-                                        mesh: meshes.add(Circle { radius: projectile_size / 2.0 }).into(),
-                                        transform: Transform { translation: spawn_position, ..default() },
-                                        visibility: Visibility::Inherited,
-                                        ..default()
-                                    },
-                                    impulse: ExternalImpulse::new(impulse_force.truncate()).with_persistence(false),
-                                    locked_axes: LockedAxes::ROTATION_LOCKED,
-                                });
-                            }
-                        }
+        if matches!(event, InputAction::Shoot) {
+            shooters.extend(
+                query
+                    .iter()
+                    .filter(|(.., controlled_by, _, _, _)| controlled_by.is_some())
+                    .map(|(entity, ..)| (entity, Some(selected_weapon_group.0))),
+            );
+        }
+    }
+
+    for (shooter, weapon_group) in shooters {
+        // Overheated structures wait for their weapons to cool down
+        if heat_query.get(shooter).is_ok_and(HeatStore::is_overheated) {
+            continue;
+        }
+        if let Ok((
+            structure_entity,
+            structure_transform,
+            childrens,
+            controlled_by,
+            mut recoil,
+            center_of_mass,
+            target_lock,
+        )) = query.get_mut(shooter)
+        {
+            let compensators = childrens.iter().filter(|child| compensator_query.contains(**child)).count();
+            let world_center_of_mass = structure_transform.translation.truncate()
+                + structure_transform.rotation.mul_vec3(center_of_mass.0.extend(0.0)).truncate();
+            let owner = ProjectileOwner {
+                structure: structure_entity,
+                player: controlled_by.map(|controlled_by| controlled_by.player_entity),
+            };
+
+            // Cannons aim at the lead position of a locked target when auto aim is on
+            let aimed_target =
+                target_lock.filter(|lock| lock.auto_aim).and_then(|lock| targets_query.get(lock.target).ok()).map(
+                    |(target_transform, target_velocity)| (target_transform.translation.truncate(), target_velocity.0),
+                );
+
+            // Every gun fired, with its muzzle module and orientation, muzzle velocity, range, damage multiplier and
+            // whether it can aim
+            let fires = |group: WeaponGroup| weapon_group.is_none_or(|weapon_group| weapon_group == group);
+            let mut guns: Vec<(&Transform, Orientation, f32, f32, f32, bool)> = Vec::new();
+            for child in childrens.iter().filter(|_| fires(WeaponGroup::Cannons)) {
+                if let Ok((module, module_transform, power, module_performance, module_tier, status_effects)) =
+                    child_query.get(*child)
+                {
+                    // Cannons without power or disabled by a status effect cannot fire, badly damaged ones jam
+                    let performance = performance(module_performance);
+                    if power.is_some_and(|power| !power.powered) || is_disabled(status_effects) || performance <= 0.0 {
+                        continue;
                     }
+                    // Damaged cannons fire slower
+                    guns.push((
+                        module_transform,
+                        module.orientation,
+                        cannon_muzzle_velocity(performance),
+                        WeaponGroup::Cannons.range(),
+                        tier_output(module_tier),
+                        true,
+                    ));
                 }
             }
-            _ => {}
+            if fires(WeaponGroup::Spinal) {
+                for spinal_weapon in spinal_weapons(childrens, &accelerator_query) {
+                    if spinal_weapon.working_segments > 0.0 {
+                        guns.push((
+                            spinal_weapon.muzzle_transform,
+                            spinal_weapon.orientation,
+                            spinal_muzzle_velocity(spinal_weapon.working_segments),
+                            WeaponGroup::Spinal.range(),
+                            1.0,
+                            false,
+                        ));
+                    }
+                }
+            }
+
+            for (module_transform, orientation, muzzle_velocity, range, damage_scale, aimable) in guns {
+                // Guns fire along the module orientation in world space
+                let mut forward_direction =
+                    structure_transform.rotation.mul_vec3(orientation.forward().extend(0.0)).normalize();
+
+                // Calculate the global position of the muzzle module
+                let cannon_position = structure_transform.translation
+                    + structure_transform.rotation.mul_vec3(module_transform.translation);
+
+                // Out of the gimbal, the cannon keeps firing straight
+                let aim = aimed_target.filter(|_| aimable).and_then(|(target_position, target_velocity)| {
+                    let muzzle = cannon_position.truncate();
+                    let lead = lead_position(muzzle, target_position, target_velocity, muzzle_velocity)?;
+                    gimbaled_aim(forward_direction.truncate(), muzzle, lead)
+                });
+                if let Some(aim) = aim {
+                    forward_direction = aim.extend(0.0);
+                }
+
+                // Determine the spawn position a little in front of the muzzle
+                let spawn_position = cannon_position + forward_direction * 3.0;
+
+                // Create the projectile physics object
+                let projectile_physics = ProjectilePhysics::ballistic(damage_scale);
+
+                let projectile_density = projectile_physics.density();
+
+                // Calculate the impulse force using ProjectilePhysics
+                let impulse_force = projectile_physics.impulse_force(muzzle_velocity, forward_direction);
+
+                let projectile_size = projectile_physics.size;
+
+                // Equal and opposite push on the structure, at the muzzle so off-center guns also make it spin
+                recoil.apply_impulse_at_point(
+                    -impulse_force.truncate() * recoil_factor(compensators),
+                    cannon_position.truncate(),
+                    world_center_of_mass,
+                );
+
+                fired_writer.send(CannonFiredEvent {
+                    structure_entity,
+                    position: spawn_position.truncate(),
+                    direction: forward_direction.truncate(),
+                });
+                commands.spawn(ProjectileBundle {
+                    projectile: Projectile(Timer::from_seconds(
+                        projectile_lifetime(range, muzzle_velocity),
+                        TimerMode::Once,
+                    )),
+                    owner,
+                    travel_limit: TravelLimit::new(spawn_position.truncate(), range),
+                    projectile_physics,
+                    rigid_body: RigidBody::Dynamic,
+                    collider: Collider::circle(projectile_size / 2.0),
+                    collision_layers: CollisionLayersConfig::projectile(),
+                    collider_density: ColliderDensity(projectile_density),
+                    mesh_bundle: MaterialMesh2dBundle {
+                        material: game_assets.projectile_material.clone(),
+                        mesh: game_assets.projectile_mesh.clone(),
+                        transform: Transform { translation: spawn_position, ..default() },
+                        visibility: Visibility::Inherited,
+                        ..default()
+                    },
+                    impulse: ExternalImpulse::new(impulse_force.truncate()).with_persistence(false),
+                    locked_axes: LockedAxes::ROTATION_LOCKED,
+                });
+            }
         }
     }
 }
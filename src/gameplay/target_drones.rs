@@ -0,0 +1,158 @@
+use crate::core::asset_loader::StructureData;
+use crate::core::prelude::*;
+use crate::gameplay::livery::Livery;
+use crate::world::prelude::*;
+
+use crate::prelude::*;
+
+const TARGET_DRONE_LAYOUT: [&str; 2] = ["WW", "WW"];
+const DRONE_HEALTH_BAR_SIZE: Vec2 = Vec2::new(8.0, 0.8); // meters
+const DRONE_HEALTH_BAR_OFFSET: Vec3 = Vec3::new(0.0, 9.0, 10.0); // meters above the drone, drawn over the modules
+
+/// Structures used as targets to test weapons, with a health bar showing what is left of them.
+pub struct TargetDronePlugin;
+
+impl Plugin for TargetDronePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (target_drone_motion_system, spawn_drone_health_bars_system, update_drone_health_bars_system)
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TargetDroneMotion {
+    /// Holds its position, even after being pushed by impacts.
+    Stationary { position: Vec2 },
+    /// Circles around a point at a constant speed, in m/s.
+    Orbit { center: Vec2, speed: f32 },
+}
+
+#[derive(Component, Debug)]
+pub struct TargetDrone {
+    pub motion: TargetDroneMotion,
+}
+
+/// Upright health bar following a drone, the bar is not parented so it does not spin with it.
+#[derive(Component, Debug)]
+struct DroneHealthBar {
+    drone: Entity,
+    max_structural_points: f32,
+}
+
+#[derive(Component, Debug)]
+struct DroneHealthBarFill;
+
+/// Spawns a small target structure at `position`.
+pub fn spawn_target_drone(
+    commands: &mut Commands,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    game_assets: &GameAssets,
+    module_registry: &ModuleRegistry,
+    position: Vec2,
+    motion: TargetDroneMotion,
+) -> Entity {
+    let drone_data = StructureData {
+        world_pos: position.to_array(),
+        structure: TARGET_DRONE_LAYOUT.iter().map(|row| row.to_string()).collect(),
+        orientations: Vec::new(),
+        crew: 0,
+        rotation: 0.0,
+        velocity: [0.0, 0.0],
+        livery: Livery::default(),
+        faction: None,
+    };
+    let drone_entity = spawn_structure(commands, materials, game_assets, module_registry, &drone_data);
+    commands.entity(drone_entity).insert(TargetDrone { motion });
+    drone_entity
+}
+
+fn target_drone_motion_system(mut drones_query: Query<(&TargetDrone, &Transform, &mut LinearVelocity)>) {
+    for (drone, transform, mut velocity) in &mut drones_query {
+        let position = transform.translation.truncate();
+        velocity.0 = match drone.motion {
+            // Springs back to its position
+            TargetDroneMotion::Stationary { position: anchor } => anchor - position,
+            TargetDroneMotion::Orbit { center, speed } => (position - center).normalize_or_zero().perp() * speed,
+        };
+    }
+}
+
+fn spawn_drone_health_bars_system(
+    drones_query: Query<(Entity, &Children), Added<Children>>,
+    target_query: Query<(), With<TargetDrone>>,
+    modules_query: Query<&ModuleMaterial>,
+    mut commands: Commands,
+) {
+    for (drone_entity, children) in &drones_query {
+        if !target_query.contains(drone_entity) {
+            continue;
+        }
+        let max_structural_points: f32 =
+            modules_query.iter_many(children).map(|module_material| module_material.max_structural_points).sum();
+
+        commands
+            .spawn((
+                DroneHealthBar { drone: drone_entity, max_structural_points },
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::srgba(0.0, 0.0, 0.0, 0.6),
+                        custom_size: Some(DRONE_HEALTH_BAR_SIZE),
+                        ..default()
+                    },
+                    ..default()
+                },
+            ))
+            .with_children(|bar| {
+                bar.spawn((
+                    DroneHealthBarFill,
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: Color::srgb(0.0, 1.0, 0.0),
+                            custom_size: Some(DRONE_HEALTH_BAR_SIZE),
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(0.0, 0.0, 0.1),
+                        ..default()
+                    },
+                ));
+            });
+    }
+}
+
+fn update_drone_health_bars_system(
+    mut bars_query: Query<(Entity, &DroneHealthBar, &mut Transform, &Children), Without<DroneHealthBarFill>>,
+    drones_query: Query<(&Transform, &Children), (With<TargetDrone>, Without<DroneHealthBar>)>,
+    modules_query: Query<&ModuleMaterial>,
+    mut fill_query: Query<(&mut Sprite, &mut Transform), (With<DroneHealthBarFill>, Without<TargetDrone>)>,
+    mut commands: Commands,
+) {
+    for (bar_entity, health_bar, mut bar_transform, bar_children) in &mut bars_query {
+        let Ok((drone_transform, drone_children)) = drones_query.get(health_bar.drone) else {
+            commands.entity(bar_entity).despawn_recursive();
+            continue;
+        };
+        bar_transform.translation = drone_transform.translation + DRONE_HEALTH_BAR_OFFSET;
+
+        let structural_points: f32 =
+            modules_query.iter_many(drone_children).map(|module_material| module_material.structural_points).sum();
+        let ratio = if health_bar.max_structural_points > 0.0 {
+            (structural_points / health_bar.max_structural_points).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        for &fill_entity in bar_children {
+            if let Ok((mut sprite, mut transform)) = fill_query.get_mut(fill_entity) {
+                let fill_width = DRONE_HEALTH_BAR_SIZE.x * ratio;
+                sprite.custom_size = Some(Vec2::new(fill_width, DRONE_HEALTH_BAR_SIZE.y));
+                sprite.color = Color::srgb(1.0 - ratio, ratio, 0.0);
+                // Keep the bar anchored on its left side
+                transform.translation.x = (fill_width - DRONE_HEALTH_BAR_SIZE.x) / 2.0;
+            }
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use crate::core::prelude::*;
+use crate::gameplay::factions::Faction;
+use crate::gameplay::sensors::Contacts;
+use crate::gameplay::wrecks::Wreck;
+use crate::world::prelude::*;
+
+use crate::prelude::*;
+
+pub const CANNON_GIMBAL_ANGLE: f32 = 0.44; // radians, about 25 degrees, cannons turn this far off their facing to aim
+
+/// Target locking for the structure flown by the player: the cycle target key locks the enemy structures within
+/// sensor range one after the other, from the nearest. The auto aim key lets the cannons aim at the lead position of
+/// the locked target. The lock is lost with the target or once the sensors stop seeing it.
+pub struct TargetingPlugin;
+
+impl Plugin for TargetingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                cycle_target_system,
+                toggle_auto_aim_system,
+                release_undetected_lock_system.run_if(resource_changed::<Contacts>),
+            )
+                .chain()
+                .after(InGameSet::UserInput)
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+/// Structure locked by this structure.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct TargetLock {
+    pub target: Entity,
+    /// The cannons aim at the lead position of the target instead of firing straight.
+    pub auto_aim: bool,
+}
+
+/// Where to aim from `shooter` for a round flying at `projectile_speed` to meet a target at `target` moving at
+/// `relative_velocity` from the round's frame, `None` when the round cannot catch up with it. The rounds do not carry
+/// the velocity of the structure firing them, their frame is space itself.
+pub fn lead_position(shooter: Vec2, target: Vec2, relative_velocity: Vec2, projectile_speed: f32) -> Option<Vec2> {
+    let offset = target - shooter;
+    // |offset + relative_velocity * t| = projectile_speed * t
+    let a = relative_velocity.length_squared() - projectile_speed * projectile_speed;
+    let b = 2.0 * offset.dot(relative_velocity);
+    let c = offset.length_squared();
+
+    let time = if a.abs() < f32::EPSILON {
+        (b < 0.0).then(|| -c / b)?
+    } else {
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let root = discriminant.sqrt();
+        [(-b - root) / (2.0 * a), (-b + root) / (2.0 * a)].into_iter().filter(|time| *time > 0.0).reduce(f32::min)?
+    };
+    Some(target + relative_velocity * time)
+}
+
+/// Direction a gun facing `forward` fires to hit the lead position, or `None` when it is out of the gimbal.
+pub fn gimbaled_aim(forward: Vec2, muzzle: Vec2, lead: Vec2) -> Option<Vec2> {
+    let aim = (lead - muzzle).try_normalize()?;
+    (forward.angle_between(aim).abs() <= CANNON_GIMBAL_ANGLE).then_some(aim)
+}
+
+fn cycle_target_system(
+    mut input_reader: EventReader<InputAction>,
+    controlled_query: Query<
+        (Entity, &GlobalTransform, Option<&Faction>, Option<&TargetLock>),
+        With<ControlledByPlayer>,
+    >,
+    structures_query: Query<(Entity, &GlobalTransform, Option<&Faction>), (With<Structure>, Without<Wreck>)>,
+    contacts: Res<Contacts>,
+    mut commands: Commands,
+) {
+    if !input_reader.read().any(|event| matches!(event, InputAction::CycleTarget)) {
+        return;
+    }
+    let Ok((locker, locker_transform, locker_faction, current_lock)) = controlled_query.get_single() else {
+        return;
+    };
+
+    // Structures of the same faction are friends, structures without one are fair game
+    let position = locker_transform.translation().truncate();
+    let mut enemies: Vec<(Entity, f32)> = structures_query
+        .iter()
+        .filter(|(structure, ..)| *structure != locker && contacts.detected.contains(structure))
+        .filter(|(.., faction)| locker_faction.is_none() || *faction != locker_faction)
+        .map(|(structure, transform, _)| (structure, transform.translation().truncate().distance(position)))
+        .collect();
+    enemies.sort_by(|(_, distance1), (_, distance2)| distance1.total_cmp(distance2));
+
+    let next = match current_lock.and_then(|lock| enemies.iter().position(|(enemy, _)| *enemy == lock.target)) {
+        Some(index) => enemies.get(index + 1).or(enemies.first()),
+        None => enemies.first(),
+    };
+    match next {
+        Some((target, _)) => {
+            let auto_aim = current_lock.is_some_and(|lock| lock.auto_aim);
+            commands.entity(locker).insert(TargetLock { target: *target, auto_aim });
+            info!("Target locked: {:?}", target);
+        }
+        None => {
+            commands.entity(locker).remove::<TargetLock>();
+            info!("No enemy in sensor range to lock");
+        }
+    }
+}
+
+fn toggle_auto_aim_system(
+    mut input_reader: EventReader<InputAction>,
+    mut locks_query: Query<&mut TargetLock, With<ControlledByPlayer>>,
+) {
+    if !input_reader.read().any(|event| matches!(event, InputAction::ToggleAutoAim)) {
+        return;
+    }
+    let Ok(mut lock) = locks_query.get_single_mut() else {
+        info!("Lock a target first to aim at it");
+        return;
+    };
+    lock.auto_aim = !lock.auto_aim;
+    info!("Cannons auto aim {}", if lock.auto_aim { "on" } else { "off" });
+}
+
+/// Releases the lock of the player once the sensors stop seeing the target. The locks on a despawned target are
+/// released by the despawn audit.
+fn release_undetected_lock_system(
+    locks_query: Query<(Entity, &TargetLock), With<ControlledByPlayer>>,
+    contacts: Res<Contacts>,
+    mut commands: Commands,
+) {
+    // Only the player relies on the sensor contacts
+    for (locker, lock) in &locks_query {
+        if !contacts.is_detected(lock.target) {
+            commands.entity(locker).remove::<TargetLock>();
+            debug!("Target lock on {:?} lost", lock.target);
+        }
+    }
+}
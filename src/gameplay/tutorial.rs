@@ -0,0 +1,281 @@
+use crate::core::prelude::*;
+use crate::gameplay::structures_combat::ProjectileOwner;
+use crate::world::prelude::*;
+
+use crate::prelude::*;
+
+const HINT_OFFSET: Vec3 = Vec3::new(0.0, 6.0, 10.0); // meters above the anchor, drawn over the modules
+const HINT_TEXT_SCALE: f32 = 0.1; // text is rendered at a readable font size then scaled down to meters
+const HINT_FONT_SIZE: f32 = 24.0;
+const HINT_BUBBLE_PADDING: f32 = 0.6; // meters
+const HINT_ARROW_SIZE: f32 = 1.2; // meters
+const HINT_FADE_START: f32 = 40.0; // meters from the player where hints start to fade
+const HINT_FADE_END: f32 = 120.0; // meters from the player where hints are invisible
+const HINT_BUBBLE_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.75);
+const HINT_ACCENT_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+const FLY_STEP_SPEED: f32 = 2.0; // m/s the controlled structure must reach to complete the flying step
+const ORE_STEP_DISTANCE: f32 = 30.0; // meters
+
+/// Guides new players with hints anchored on the entities they need to interact with.
+/// Each step shows a single hint, which is removed and replaced by the next one when the step is completed.
+pub struct TutorialPlugin;
+
+impl Plugin for TutorialPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Tutorial>().add_systems(
+            Update,
+            (
+                complete_tutorial_steps_system,
+                spawn_tutorial_hint_system,
+                follow_hint_anchor_system,
+                fade_hints_by_distance_system,
+            )
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TutorialStep {
+    TakeControl,
+    FlyStructure,
+    FireCannon,
+    ReachOre,
+}
+
+impl TutorialStep {
+    pub fn text(&self) -> &'static str {
+        match self {
+            TutorialStep::TakeControl => "Walk to the command center and press Space to take control",
+            TutorialStep::FlyStructure => "Use W/A/S/D to fire the engines",
+            TutorialStep::FireCannon => "Press G to fire the cannons",
+            TutorialStep::ReachOre => "Fly to the ore node",
+        }
+    }
+}
+
+/// Progress of the player through the tutorial.
+#[derive(Resource, Debug)]
+pub struct Tutorial {
+    pub enabled: bool,
+    pub steps: Vec<TutorialStep>,
+    pub completed: HashSet<TutorialStep>,
+}
+
+impl Default for Tutorial {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            steps: vec![
+                TutorialStep::TakeControl,
+                TutorialStep::FlyStructure,
+                TutorialStep::FireCannon,
+                TutorialStep::ReachOre,
+            ],
+            completed: HashSet::new(),
+        }
+    }
+}
+
+impl Tutorial {
+    /// The first step not completed yet, `None` once the tutorial is over.
+    pub fn current_step(&self) -> Option<TutorialStep> {
+        if !self.enabled {
+            return None;
+        }
+        self.steps.iter().copied().find(|step| !self.completed.contains(step))
+    }
+
+    pub fn complete(&mut self, step: TutorialStep) {
+        if self.completed.insert(step) {
+            info!("Tutorial step completed: {:?}", step);
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.steps.iter().all(|step| self.completed.contains(step))
+    }
+}
+
+/// An arrow and a text bubble following an entity of the world.
+#[derive(Component, Debug)]
+pub struct TutorialHint {
+    pub step: TutorialStep,
+    pub anchor: Entity,
+}
+
+/// Parts of a hint faded with the distance, with their fully visible alpha.
+#[derive(Component, Debug)]
+struct HintFade {
+    alpha: f32,
+}
+
+fn complete_tutorial_steps_system(
+    mut tutorial: ResMut<Tutorial>,
+    player_resource: Res<PlayerResource>,
+    controlled_structure_query: Query<&LinearVelocity, (With<Structure>, With<ControlledByPlayer>)>,
+    fired_projectiles_query: Query<&ProjectileOwner, Added<ProjectileOwner>>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    ore_query: Query<&GlobalTransform, With<Ore>>,
+) {
+    let Some(step) = tutorial.current_step() else {
+        return;
+    };
+
+    let completed = match step {
+        TutorialStep::TakeControl => player_resource.is_controlling_structure,
+        TutorialStep::FlyStructure => {
+            controlled_structure_query.iter().any(|velocity| velocity.0.length() > FLY_STEP_SPEED)
+        }
+        TutorialStep::FireCannon => fired_projectiles_query.iter().any(|owner| owner.player.is_some()),
+        TutorialStep::ReachOre => {
+            let Ok(player_transform) = player_query.get_single() else {
+                return;
+            };
+            // Nothing to reach in worlds without ore
+            ore_query.is_empty()
+                || ore_query.iter().any(|ore_transform| {
+                    ore_transform.translation().distance(player_transform.translation()) < ORE_STEP_DISTANCE
+                })
+        }
+    };
+
+    if completed {
+        tutorial.complete(step);
+    }
+}
+
+/// Keeps a single hint for the current step, anchored on the closest relevant entity.
+fn spawn_tutorial_hint_system(
+    tutorial: Res<Tutorial>,
+    hints_query: Query<(Entity, &TutorialHint)>,
+    entities_query: Query<Entity>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    command_centers_query: Query<(Entity, &GlobalTransform), With<CommandCenterModule>>,
+    engines_query: Query<(Entity, &GlobalTransform), With<EngineModule>>,
+    cannons_query: Query<(Entity, &GlobalTransform), With<CannonModule>>,
+    ore_query: Query<(Entity, &GlobalTransform), With<Ore>>,
+    mut commands: Commands,
+) {
+    let current_step = tutorial.current_step();
+
+    let mut has_hint = false;
+    for (hint_entity, hint) in &hints_query {
+        if Some(hint.step) != current_step || !entities_query.contains(hint.anchor) {
+            commands.entity(hint_entity).despawn_recursive();
+        } else {
+            has_hint = true;
+        }
+    }
+
+    let Some(step) = current_step else {
+        return;
+    };
+    if has_hint {
+        return;
+    }
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+
+    let candidates: Vec<(Entity, &GlobalTransform)> = match step {
+        TutorialStep::TakeControl => command_centers_query.iter().collect(),
+        TutorialStep::FlyStructure => engines_query.iter().collect(),
+        TutorialStep::FireCannon => cannons_query.iter().collect(),
+        TutorialStep::ReachOre => ore_query.iter().collect(),
+    };
+    let player_position = player_transform.translation();
+    let Some((anchor, _)) = candidates.into_iter().min_by(|(_, a), (_, b)| {
+        a.translation().distance(player_position).total_cmp(&b.translation().distance(player_position))
+    }) else {
+        return;
+    };
+
+    spawn_hint(&mut commands, step, anchor);
+}
+
+fn spawn_hint(commands: &mut Commands, step: TutorialStep, anchor: Entity) {
+    let text_style = TextStyle { font_size: HINT_FONT_SIZE, color: Color::WHITE, ..default() };
+    // Rough text size, the bubble does not need to be exact
+    let text_width = step.text().chars().count() as f32 * HINT_FONT_SIZE * 0.5 * HINT_TEXT_SCALE;
+    let text_height = HINT_FONT_SIZE * HINT_TEXT_SCALE;
+
+    commands.spawn((TutorialHint { step, anchor }, SpatialBundle::default())).with_children(|hint| {
+        hint.spawn((
+            HintFade { alpha: HINT_BUBBLE_COLOR.alpha() },
+            SpriteBundle {
+                sprite: Sprite {
+                    color: HINT_BUBBLE_COLOR,
+                    custom_size: Some(Vec2::new(
+                        text_width + HINT_BUBBLE_PADDING * 2.0,
+                        text_height + HINT_BUBBLE_PADDING * 2.0,
+                    )),
+                    ..default()
+                },
+                ..default()
+            },
+        ));
+        hint.spawn((
+            HintFade { alpha: 1.0 },
+            Text2dBundle {
+                text: Text::from_section(step.text(), text_style),
+                transform: Transform::from_xyz(0.0, 0.0, 0.1).with_scale(Vec3::splat(HINT_TEXT_SCALE)),
+                ..default()
+            },
+        ));
+        // A rotated square below the bubble pointing down to the anchor
+        hint.spawn((
+            HintFade { alpha: 1.0 },
+            SpriteBundle {
+                sprite: Sprite {
+                    color: HINT_ACCENT_COLOR,
+                    custom_size: Some(Vec2::splat(HINT_ARROW_SIZE)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0.0, -(text_height / 2.0 + HINT_BUBBLE_PADDING + HINT_ARROW_SIZE), 0.0)
+                    .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4)),
+                ..default()
+            },
+        ));
+    });
+}
+
+/// Hints are not children of their anchor so they stay upright when structures rotate.
+fn follow_hint_anchor_system(
+    mut hints_query: Query<(&TutorialHint, &mut Transform)>,
+    anchors_query: Query<&GlobalTransform>,
+) {
+    for (hint, mut transform) in &mut hints_query {
+        if let Ok(anchor_transform) = anchors_query.get(hint.anchor) {
+            transform.translation = anchor_transform.translation() + HINT_OFFSET;
+        }
+    }
+}
+
+fn fade_hints_by_distance_system(
+    hints_query: Query<(&Transform, &Children), With<TutorialHint>>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    mut sprites_query: Query<(&HintFade, &mut Sprite)>,
+    mut texts_query: Query<(&HintFade, &mut Text)>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+
+    for (transform, children) in &hints_query {
+        let distance = transform.translation.truncate().distance(player_transform.translation().truncate());
+        let visibility = 1.0 - ((distance - HINT_FADE_START) / (HINT_FADE_END - HINT_FADE_START)).clamp(0.0, 1.0);
+
+        for &child in children {
+            if let Ok((fade, mut sprite)) = sprites_query.get_mut(child) {
+                sprite.color.set_alpha(fade.alpha * visibility);
+            }
+            if let Ok((fade, mut text)) = texts_query.get_mut(child) {
+                for section in &mut text.sections {
+                    section.style.color.set_alpha(fade.alpha * visibility);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use crate::core::prelude::*;
+use crate::gameplay::building::BuildMode;
+use crate::gameplay::livery::{Livery, Paint};
+use crate::gameplay::repair::Scrap;
+use crate::world::prelude::*;
+
+use crate::prelude::*;
+
+const UPGRADE_KEY: KeyCode = KeyCode::KeyI;
+pub const MAX_MODULE_TIER: u8 = 3;
+const UPGRADE_SCRAP_COST: f32 = 40.0; // scrap per tier reached, a tier 3 module costs 120 scrap from tier 2
+const STRUCTURAL_POINTS_PER_TIER: f32 = 0.5; // fraction of the tier 1 structural points gained per tier
+const OUTPUT_PER_TIER: f32 = 0.25; // fraction of the tier 1 cannon damage and engine thrust gained per tier
+const TIER_TINT_STRENGTH: f32 = 0.2; // how much of the tier color covers the painted module, per tier
+
+/// Module upgrades: press I in build mode to upgrade the hovered module for scrap, up to tier 3.
+/// Every tier hardens the module like a stronger alloy, raising its structural points, and makes cannons hit harder
+/// and engines push harder. Upgraded modules are tinted by their tier, on top of the livery of their structure.
+pub struct UpgradesPlugin;
+
+impl Plugin for UpgradesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<UpgradeModuleEvent>().add_systems(
+            Update,
+            (upgrade_input_system, upgrade_module_system).chain().run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+/// Asks to upgrade the module in a cell of a structure to its next tier.
+#[derive(Event, Debug)]
+pub struct UpgradeModuleEvent {
+    pub structure_entity: Entity,
+    pub cell: (i32, i32),
+}
+
+/// Upgrade tier of a module, from 1 to `MAX_MODULE_TIER`. Modules without one are tier 1.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModuleTier(pub u8);
+
+impl Default for ModuleTier {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+impl ModuleTier {
+    pub fn next(&self) -> Option<ModuleTier> {
+        (self.0 < MAX_MODULE_TIER).then_some(ModuleTier(self.0 + 1))
+    }
+
+    /// Scrap spent to reach this tier.
+    pub fn cost(&self) -> f32 {
+        UPGRADE_SCRAP_COST * self.0 as f32
+    }
+
+    /// Structural points of the module relative to tier 1.
+    pub fn structural_multiplier(&self) -> f32 {
+        1.0 + STRUCTURAL_POINTS_PER_TIER * self.0.saturating_sub(1) as f32
+    }
+
+    /// Cannon damage and engine thrust relative to tier 1.
+    pub fn output_multiplier(&self) -> f32 {
+        1.0 + OUTPUT_PER_TIER * self.0.saturating_sub(1) as f32
+    }
+
+    /// Mixes the color of the tier into the color of a module, tier 1 keeps its color.
+    pub fn tint(&self, color: Color) -> Color {
+        let tier_color = match self.0 {
+            0 | 1 => return color,
+            2 => Color::from(SILVER),
+            _ => Color::from(GOLD),
+        };
+        color.mix(&tier_color, TIER_TINT_STRENGTH * (self.0 - 1) as f32).with_alpha(color.alpha())
+    }
+}
+
+/// Output multiplier of an optional `ModuleTier`, see `ModuleTier::output_multiplier`.
+pub fn tier_output(module_tier: Option<&ModuleTier>) -> f32 {
+    module_tier.map_or(1.0, ModuleTier::output_multiplier)
+}
+
+/// Color of a painted module with the tint of its tier, if it has one.
+pub fn tier_tint(color: Color, module_tier: Option<&ModuleTier>) -> Color {
+    module_tier.map_or(color, |module_tier| module_tier.tint(color))
+}
+
+fn upgrade_input_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    build_mode: Res<BuildMode>,
+    mut event_writer: EventWriter<UpgradeModuleEvent>,
+) {
+    if !build_mode.active || !keys.just_pressed(UPGRADE_KEY) {
+        return;
+    }
+    if let Some(target) = &build_mode.target {
+        event_writer.send(UpgradeModuleEvent { structure_entity: target.structure_entity, cell: target.cell });
+    }
+}
+
+/// Raises the tier of the module in place, its structural points grow in the same proportion so a damaged module
+/// stays as damaged.
+fn upgrade_module_system(
+    mut event_reader: EventReader<UpgradeModuleEvent>,
+    structures_query: Query<(&Structure, Option<&Livery>)>,
+    mut modules_query: Query<(&mut ModuleMaterial, Option<&ModuleTier>, Option<&mut Paint>), With<Module>>,
+    mut scrap: ResMut<Scrap>,
+    mut commands: Commands,
+) {
+    for event in event_reader.read() {
+        let Ok((structure, livery)) = structures_query.get(event.structure_entity) else {
+            continue;
+        };
+        let Some(module_entity) = structure.module_at(event.cell) else {
+            continue;
+        };
+        let Ok((mut module_material, module_tier, paint)) = modules_query.get_mut(module_entity) else {
+            continue;
+        };
+
+        let tier = module_tier.copied().unwrap_or_default();
+        let Some(next_tier) = tier.next() else {
+            debug!("Module {:?} is already at the highest tier", module_entity);
+            continue;
+        };
+        if scrap.amount < next_tier.cost() {
+            debug!("Not enough scrap to upgrade module {:?} ({:.0} needed)", module_entity, next_tier.cost());
+            continue;
+        }
+        scrap.amount -= next_tier.cost();
+
+        let ratio = next_tier.structural_multiplier() / tier.structural_multiplier();
+        module_material.max_structural_points *= ratio;
+        module_material.structural_points *= ratio;
+
+        // Repainting also mixes the damage tint into the new color
+        if let Some(mut paint) = paint {
+            let color = livery.map_or(paint.base, |livery| livery.paint(paint.base));
+            paint.color = next_tier.tint(color);
+        }
+        commands.entity(module_entity).insert(next_tier);
+        debug!("Upgraded module {:?} to tier {}", module_entity, next_tier.0);
+    }
+}
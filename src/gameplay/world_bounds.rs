@@ -0,0 +1,250 @@
+use crate::core::prelude::*;
+use crate::gameplay::debris::Debris;
+use crate::gameplay::structures_combat::Projectile;
+use crate::gameplay::wrecks::Wreck;
+use crate::world::prelude::*;
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const WARNING_INTERVAL: f32 = 5.0; // seconds between two warnings while the player stays out of bounds
+
+/// What happens to the structures flying out of the world bounds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OutOfBoundsBehavior {
+    /// They come back from the opposite edge.
+    #[default]
+    Wrap,
+    /// They bounce on the edge like on a wall.
+    Bounce,
+}
+
+/// Size of the playable area and how the strays are handled, read from the settings file.
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorldBoundsSettings {
+    /// Space left around the level grid, the world is the grid grown by this margin on every side, in meters.
+    pub margin: f32,
+    pub structures: OutOfBoundsBehavior,
+    /// Distance debris and modules blown off a structure drift before being despawned, in meters.
+    pub max_stray_travel: f32,
+    /// Distance to the edge under which the player is warned, in meters.
+    pub warning_margin: f32,
+    /// Acceleration pulling the player and the structure they fly back inside, in m/s².
+    pub turnaround_assist: f32,
+}
+
+impl Default for WorldBoundsSettings {
+    fn default() -> Self {
+        Self {
+            margin: 2000.0,
+            structures: OutOfBoundsBehavior::Wrap,
+            max_stray_travel: 1500.0,
+            warning_margin: 100.0,
+            turnaround_assist: 4.0,
+        }
+    }
+}
+
+/// Keeps the simulation inside the world bounds, fitted around the level grid: projectiles, debris and modules blown
+/// off a structure are despawned once they leave them or travel too far, the other structures wrap around or bounce,
+/// and the player is warned then steered back when they get out.
+pub struct WorldBoundsPlugin;
+
+impl Plugin for WorldBoundsPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = app.world().get_resource::<WorldBoundsSettings>().cloned().unwrap_or_default();
+        app.insert_resource(WorldBounds::from_settings(&settings))
+            .insert_resource(settings)
+            .add_event::<LeavingWorldBoundsEvent>()
+            .add_systems(OnEnter(GameState::BuildingStructures), fit_bounds_to_grid_system)
+            .add_systems(
+                FixedUpdate,
+                (limit_stray_travel_system, despawn_strays_system, contain_structures_system, turnaround_assist_system)
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(Update, warn_player_system.run_if(in_state(GameState::InGame)));
+    }
+}
+
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct WorldBounds {
+    pub rect: Rect,
+    pub margin: f32,
+    pub structures: OutOfBoundsBehavior,
+    pub max_stray_travel: f32,
+    pub warning_margin: f32,
+    pub turnaround_assist: f32,
+}
+
+impl WorldBounds {
+    /// Bounds of the margin alone, until the level grid they surround is built.
+    pub fn from_settings(settings: &WorldBoundsSettings) -> Self {
+        Self {
+            rect: Rect::from_center_half_size(Vec2::ZERO, Vec2::splat(settings.margin)),
+            margin: settings.margin,
+            structures: settings.structures,
+            max_stray_travel: settings.max_stray_travel,
+            warning_margin: settings.warning_margin,
+            turnaround_assist: settings.turnaround_assist,
+        }
+    }
+
+    /// Fits the bounds around the level grid, centered on the origin like the grid cells.
+    pub fn fit_to_grid(&mut self, grid: &Grid) {
+        let half_size = Vec2::new(grid.width as f32, grid.height as f32) * grid.cell_size / 2.0;
+        self.rect = Rect::from_center_half_size(Vec2::ZERO, half_size + self.margin);
+    }
+
+    pub fn contains(&self, position: Vec2) -> bool {
+        self.rect.contains(position)
+    }
+
+    /// Whether the position is out of the bounds or closer to their edge than the warning margin.
+    pub fn is_near_edge(&self, position: Vec2) -> bool {
+        !self.rect.inflate(-self.warning_margin).contains(position)
+    }
+
+    /// The position brought back inside from the opposite edge.
+    pub fn wrap(&self, position: Vec2) -> Vec2 {
+        let size = self.rect.size();
+        let wrapped = (position - self.rect.min).rem_euclid(size);
+        self.rect.min + wrapped
+    }
+
+    /// The position clamped on the edge and the velocity reflected away from it.
+    pub fn bounce(&self, position: Vec2, velocity: Vec2) -> (Vec2, Vec2) {
+        let mut velocity = velocity;
+        if (position.x < self.rect.min.x && velocity.x < 0.0) || (position.x > self.rect.max.x && velocity.x > 0.0) {
+            velocity.x = -velocity.x;
+        }
+        if (position.y < self.rect.min.y && velocity.y < 0.0) || (position.y > self.rect.max.y && velocity.y > 0.0) {
+            velocity.y = -velocity.y;
+        }
+        (position.clamp(self.rect.min, self.rect.max), velocity)
+    }
+}
+
+/// Sent when the player gets close to the edge of the world, and again every few seconds while they stay there.
+#[derive(Event, Debug)]
+pub struct LeavingWorldBoundsEvent {
+    pub player_entity: Entity,
+    pub outside: bool,
+}
+
+/// Longest distance an entity may travel from where it started before being despawned.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct TravelLimit {
+    pub origin: Vec2,
+    pub max_distance: f32,
+}
+
+impl TravelLimit {
+    pub fn new(origin: Vec2, max_distance: f32) -> Self {
+        Self { origin, max_distance }
+    }
+
+    pub fn is_exceeded(&self, position: Vec2) -> bool {
+        self.origin.distance_squared(position) > self.max_distance * self.max_distance
+    }
+}
+
+fn fit_bounds_to_grid_system(grid: Option<Res<Grid>>, mut bounds: ResMut<WorldBounds>) {
+    if let Some(grid) = grid {
+        bounds.fit_to_grid(&grid);
+        debug!("World bounds fitted around the level grid: {:?}", bounds.rect);
+    }
+}
+
+/// Debris and modules blown off a structure get a travel limit from where they broke off. The projectiles get theirs
+/// from the range of their weapon when fired.
+fn limit_stray_travel_system(
+    strays_query: Query<
+        (Entity, &Position),
+        (Or<(Added<Debris>, (Added<Wreck>, With<Module>, Without<Parent>))>, Without<TravelLimit>),
+    >,
+    bounds: Res<WorldBounds>,
+    mut commands: Commands,
+) {
+    for (entity, position) in &strays_query {
+        commands.entity(entity).insert(TravelLimit::new(position.0, bounds.max_stray_travel));
+    }
+}
+
+fn despawn_strays_system(
+    strays_query: Query<
+        (Entity, &Position, Option<&TravelLimit>),
+        Or<(With<Projectile>, With<Debris>, (With<Wreck>, With<Module>, Without<Parent>))>,
+    >,
+    bounds: Res<WorldBounds>,
+    mut commands: Commands,
+) {
+    for (entity, position, travel_limit) in &strays_query {
+        let too_far = travel_limit.is_some_and(|travel_limit| travel_limit.is_exceeded(position.0));
+        if too_far || !bounds.contains(position.0) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn contain_structures_system(
+    mut structures_query: Query<(&mut Position, &mut LinearVelocity), (With<Structure>, Without<ControlledByPlayer>)>,
+    bounds: Res<WorldBounds>,
+) {
+    for (mut position, mut velocity) in &mut structures_query {
+        if bounds.contains(position.0) {
+            continue;
+        }
+        match bounds.structures {
+            OutOfBoundsBehavior::Wrap => position.0 = bounds.wrap(position.0),
+            OutOfBoundsBehavior::Bounce => (position.0, velocity.0) = bounds.bounce(position.0, velocity.0),
+        }
+    }
+}
+
+/// Pulls the player, or the structure they fly, back towards the world once they are out of the bounds.
+fn turnaround_assist_system(
+    mut player_query: Query<(&Position, &mut LinearVelocity), (With<Player>, Without<ControlledByPlayer>)>,
+    mut controlled_query: Query<(&Position, &mut LinearVelocity), (With<ControlledByPlayer>, Without<Player>)>,
+    bounds: Res<WorldBounds>,
+    time: Res<Time>,
+) {
+    let assist = bounds.turnaround_assist * time.delta_seconds();
+    for (position, mut velocity) in player_query.iter_mut().chain(controlled_query.iter_mut()) {
+        if bounds.contains(position.0) {
+            continue;
+        }
+        let inward = (bounds.rect.center() - position.0).normalize_or_zero();
+        velocity.0 += inward * assist;
+    }
+}
+
+fn warn_player_system(
+    player_query: Query<(Entity, &GlobalTransform), With<Player>>,
+    bounds: Res<WorldBounds>,
+    time: Res<Time>,
+    mut warning_cooldown: Local<Option<Timer>>,
+    mut event_writer: EventWriter<LeavingWorldBoundsEvent>,
+) {
+    let Ok((player_entity, player_transform)) = player_query.get_single() else {
+        return;
+    };
+    let position = player_transform.translation().truncate();
+    if !bounds.is_near_edge(position) {
+        *warning_cooldown = None;
+        return;
+    }
+
+    let warn = match warning_cooldown.as_mut() {
+        Some(cooldown) => cooldown.tick(time.delta()).just_finished(),
+        None => {
+            *warning_cooldown = Some(Timer::from_seconds(WARNING_INTERVAL, TimerMode::Repeating));
+            true
+        }
+    };
+    if warn {
+        event_writer.send(LeavingWorldBoundsEvent { player_entity, outside: !bounds.contains(position) });
+    }
+}
@@ -0,0 +1,25 @@
+use crate::prelude::*;
+
+/// Marks a detached piece of a structure floating in space.
+/// Wrecks absorb projectiles and block line of sight, so wreck fields can be used as cover.
+#[derive(Component, Debug, Default)]
+pub struct Wreck;
+
+/// Returns `true` when no wreck lies on the segment between `from` and `to`.
+/// Meant to be used by sensors and targeting to decide if a contact can be seen.
+pub fn has_line_of_sight(
+    spatial_query: &SpatialQuery,
+    from: Vec2,
+    to: Vec2,
+    wreck_query: &Query<(), With<Wreck>>,
+) -> bool {
+    let Ok(direction) = Dir2::new(to - from) else {
+        return true;
+    };
+    let distance = from.distance(to);
+
+    spatial_query
+        .ray_hits(from, direction, distance, u32::MAX, true, SpatialQueryFilter::default())
+        .iter()
+        .all(|hit| !wreck_query.contains(hit.entity))
+}
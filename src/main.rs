@@ -1,32 +1,34 @@
 use my_game::configs::prelude::*;
+use my_game::gameplay::scenario::{scenario_from_args, StartupScenario};
 use my_game::prelude::*;
 
 fn main() {
-    App::new()
-        .add_plugins(
-            DefaultPlugins
-                .set(WindowPlugin {
-                    primary_window: Some(Window {
-                        title: "My Game Window".into(),
-                        name: Some("bevy.app".into()),
-                        resolution: (WINDOW_WIDTH, WINDOW_HEIGHT).into(),
-                        present_mode: PresentMode::Immediate,
-                        ..default()
-                    }),
-                    ..default()
-                })
-                .set(LogPlugin {
-                    filter: "info,my_game::player=debug,my_game::grid=debug,my_game::structure=debug,my_game::movement=debug,my_game::modules=debug,my_game::structure_combat=debug".into(),
+    let config = ConfigPlugin::load(SETTINGS_PATH);
+    let settings = config.settings.clone();
+
+    let mut app = App::new();
+    app.add_plugins(
+        DefaultPlugins
+            .set(WindowPlugin {
+                primary_window: Some(Window {
+                    title: "My Game Window".into(),
+                    name: Some("bevy.app".into()),
+                    resolution: (settings.window.width, settings.window.height).into(),
+                    present_mode: settings.window.present_mode,
                     ..default()
                 }),
-        )
-        .add_plugins(PhysicsPlugins::default().with_length_unit(UNIT_SCALE))
-        .insert_resource(Gravity(DEFAULT_GRAVITY))
-        .add_plugins((
-            LoadersPlugins,
-            GamePlugins { debug_enable: true },
-            UtilityPlugins { debug_enable: true },
-        ))
-        //.add_plugins(WorldInspectorPlugin::new())
-        .run();
+                ..default()
+            })
+            .set(LogPlugin { filter: settings.debug.log_filter.clone(), ..default() }),
+    )
+    .add_plugins(PhysicsPlugins::default().with_length_unit(UNIT_SCALE))
+    .add_plugins((config, LoadersPlugins, GamePlugins { debug_enable: settings.debug.enabled }, UtilityPlugins));
+
+    #[cfg(feature = "dev-tools")]
+    app.add_plugins(bevy_inspector_egui::quick::WorldInspectorPlugin::new());
+
+    if let Some(path) = scenario_from_args(std::env::args()) {
+        app.insert_resource(StartupScenario(path));
+    }
+    app.run();
 }
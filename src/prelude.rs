@@ -11,4 +11,4 @@ pub use bevy::utils::tracing::field::debug;
 pub use bevy::window::PresentMode;
 pub use log::debug;
 pub use std::any::Any;
-pub use std::collections::{HashSet, VecDeque};
+pub use std::collections::{HashMap, HashSet, VecDeque};
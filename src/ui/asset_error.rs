@@ -0,0 +1,115 @@
+use crate::core::asset_loader::{AssetStore, DataSettings, Level, StructuresData};
+use crate::core::asset_validation::AssetLoadError;
+use crate::core::state::GameState;
+use bevy::prelude::*;
+
+const RETRY_KEY: KeyCode = KeyCode::KeyR;
+const ERROR_COLOR: Color = Color::srgb(1.0, 0.4, 0.35);
+
+/// Lists what is wrong in the data files instead of crashing, press R to reload them once fixed.
+pub struct AssetErrorScreenPlugin;
+
+impl Plugin for AssetErrorScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::AssetError), spawn_asset_error_screen)
+            .add_systems(OnExit(GameState::AssetError), despawn_asset_error_screen)
+            .add_systems(
+                Update,
+                (list_asset_errors_system, retry_asset_loading_system).run_if(in_state(GameState::AssetError)),
+            );
+    }
+}
+
+#[derive(Component)]
+struct AssetErrorScreen;
+
+#[derive(Component)]
+struct AssetErrorList;
+
+fn spawn_asset_error_screen(mut commands: Commands, data_settings: Res<DataSettings>) {
+    // The world camera is only spawned once the assets are valid
+    commands.spawn((AssetErrorScreen, Camera2dBundle::default()));
+
+    commands
+        .spawn((
+            AssetErrorScreen,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(40.0)),
+                    row_gap: Val::Px(12.0),
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::srgb(0.08, 0.08, 0.1)),
+                ..default()
+            },
+        ))
+        .with_children(|screen| {
+            screen.spawn(TextBundle::from_section(
+                "The game data could not be loaded",
+                TextStyle { font_size: 32.0, color: Color::WHITE, ..default() },
+            ));
+            screen.spawn(TextBundle::from_section(
+                format!(
+                    "Fix the files below then press R to reload {} and {}",
+                    data_settings.level, data_settings.structures
+                ),
+                TextStyle { font_size: 18.0, color: Color::srgb(0.7, 0.7, 0.7), ..default() },
+            ));
+            screen.spawn((
+                AssetErrorList,
+                NodeBundle { style: Style { flex_direction: FlexDirection::Column, ..default() }, ..default() },
+            ));
+        });
+}
+
+fn despawn_asset_error_screen(mut commands: Commands, screen_query: Query<Entity, With<AssetErrorScreen>>) {
+    for entity in &screen_query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn list_asset_errors_system(
+    mut event_reader: EventReader<AssetLoadError>,
+    list_query: Query<Entity, With<AssetErrorList>>,
+    mut commands: Commands,
+) {
+    let Ok(list_entity) = list_query.get_single() else {
+        return;
+    };
+
+    for event in event_reader.read() {
+        commands.entity(list_entity).with_children(|list| {
+            for error in &event.errors {
+                list.spawn(TextBundle::from_section(
+                    format!("{}: {}", event.path, error),
+                    TextStyle { font_size: 16.0, color: ERROR_COLOR, ..default() },
+                ));
+            }
+        });
+    }
+}
+
+fn retry_asset_loading_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    asset_server: Res<AssetServer>,
+    data_settings: Res<DataSettings>,
+    asset_store: Res<AssetStore>,
+    mut levels: ResMut<Assets<Level>>,
+    mut structures_assets: ResMut<Assets<StructuresData>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !keys.just_pressed(RETRY_KEY) {
+        return;
+    }
+
+    info!("Reloading the game data");
+    // The invalid versions stay loaded until the new ones are in, they must not be validated again meanwhile
+    levels.remove(&asset_store.level);
+    structures_assets.remove(&asset_store.structures);
+    asset_server.reload(data_settings.level.clone());
+    asset_server.reload(data_settings.structures.clone());
+    next_state.set(GameState::LoadingAssets);
+}
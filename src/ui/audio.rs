@@ -0,0 +1,251 @@
+use crate::core::state::GameState;
+use crate::gameplay::movement::MovementSettings;
+use crate::gameplay::structures_combat::CannonFiredEvent;
+use crate::ui::culling::CameraView;
+use crate::world::prelude::*;
+use avian2d::prelude::ExternalForce;
+use bevy::audio::Volume;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+
+/// Sound effects preloaded by name, their paths are relative to the assets folder. The systems below look up
+/// `cannon_fire`, `module_destroyed`, `depressurization` and `engine_hum` and stay silent until those are listed here.
+pub const SOUND_EFFECTS: &[(&str, &str)] = &[];
+
+const MIN_AUDIBLE_VOLUME: f32 = 0.01; // quieter sounds are not played at all
+const CANNON_FIRE_VOLUME: f32 = 0.8;
+const MODULE_DESTROYED_VOLUME: f32 = 1.0;
+const DEPRESSURIZATION_VOLUME: f32 = 0.5; // for a single breached cell
+const DEPRESSURIZATION_FULL_CELLS: f32 = 10.0; // breached cells hissing at full volume
+const ENGINE_HUM_VOLUME: f32 = 0.4; // with the engines at full thrust
+const ENGINE_HUM_FULL_ENGINES: f32 = 4.0; // engines firing at once for the hum to be the loudest
+const ENGINE_HUM_FADE_RATE: f32 = 3.0; // fraction of the change of volume reached per second
+
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioSettings {
+    /// Volume of every sound, from 0.0 (muted) to 1.0.
+    pub master_volume: f32,
+    /// Volume of the sound effects, on top of the master volume.
+    pub sfx_volume: f32,
+    /// Distance to the camera in meters at which the sound effects fade out completely.
+    pub hearing_distance: f32,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self { master_volume: 1.0, sfx_volume: 0.8, hearing_distance: 150.0 }
+    }
+}
+
+impl AudioSettings {
+    /// Volume of a sound effect played at `position` and heard from `listener`, fading with the distance.
+    pub fn sfx_volume_at(&self, listener: Vec2, position: Vec2) -> f32 {
+        let attenuation = (1.0 - listener.distance(position) / self.hearing_distance.max(f32::EPSILON)).max(0.0);
+        self.master_volume * self.sfx_volume * attenuation * attenuation
+    }
+}
+
+/// Plays the sound effects of the game: cannon fire, module destruction, depressurization hiss and the hum of the
+/// engines thrusting. The sounds are positional, they fade with the distance to the center of the view. The impact
+/// sounds come from `ImpactSoundsPlugin` and go through `play_sound_at` too.
+pub struct AudioPlugin;
+
+impl Plugin for AudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AudioSettings>().add_systems(
+            Update,
+            (
+                cannon_fire_sounds_system,
+                module_destroyed_sounds_system,
+                depressurization_sounds_system,
+                attach_engine_hum_system,
+                engine_hum_system,
+            )
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+
+    fn finish(&self, app: &mut App) {
+        app.init_resource::<SoundEffects>();
+    }
+}
+
+/// Handles of the `SOUND_EFFECTS`, loaded once and cloned every time one is played.
+#[derive(Resource, Debug, Clone)]
+pub struct SoundEffects {
+    sounds: HashMap<&'static str, Handle<AudioSource>>,
+}
+
+impl FromWorld for SoundEffects {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        Self { sounds: SOUND_EFFECTS.iter().map(|(name, path)| (*name, asset_server.load(*path))).collect() }
+    }
+}
+
+impl SoundEffects {
+    pub fn get(&self, name: &str) -> Option<Handle<AudioSource>> {
+        self.sounds.get(name).cloned()
+    }
+}
+
+/// Looping hum of the engines of a structure, played by the `sound_entity` child and following their thrust.
+#[derive(Component, Debug)]
+pub struct EngineHum {
+    sound_entity: Entity,
+    volume: f32,
+}
+
+/// Plays a one shot sound effect at a world position, skipped when too far from the view to be heard.
+pub fn play_sound_at(
+    commands: &mut Commands,
+    audio_settings: &AudioSettings,
+    camera_view: &CameraView,
+    source: Handle<AudioSource>,
+    position: Vec2,
+    volume: f32,
+    speed: f32,
+) {
+    let volume = volume * audio_settings.sfx_volume_at(camera_view.area.center(), position);
+    if volume < MIN_AUDIBLE_VOLUME {
+        return;
+    }
+    commands.spawn(AudioBundle {
+        source,
+        settings: PlaybackSettings::DESPAWN.with_volume(Volume::new(volume)).with_speed(speed),
+    });
+}
+
+fn cannon_fire_sounds_system(
+    mut event_reader: EventReader<CannonFiredEvent>,
+    sound_effects: Res<SoundEffects>,
+    audio_settings: Res<AudioSettings>,
+    camera_view: Res<CameraView>,
+    mut commands: Commands,
+) {
+    let Some(sound) = sound_effects.get("cannon_fire") else {
+        return;
+    };
+    for event in event_reader.read() {
+        play_sound_at(
+            &mut commands,
+            &audio_settings,
+            &camera_view,
+            sound.clone(),
+            event.position,
+            CANNON_FIRE_VOLUME,
+            1.0,
+        );
+    }
+}
+
+fn module_destroyed_sounds_system(
+    mut event_reader: EventReader<ModuleDestroyedEvent>,
+    modules_query: Query<&GlobalTransform>,
+    sound_effects: Res<SoundEffects>,
+    audio_settings: Res<AudioSettings>,
+    camera_view: Res<CameraView>,
+    mut commands: Commands,
+) {
+    let Some(sound) = sound_effects.get("module_destroyed") else {
+        return;
+    };
+    for event in event_reader.read() {
+        // Nothing to locate once the module is gone
+        let Ok(module_transform) = modules_query.get(event.destroyed_entity) else {
+            continue;
+        };
+        play_sound_at(
+            &mut commands,
+            &audio_settings,
+            &camera_view,
+            sound.clone(),
+            module_transform.translation().truncate(),
+            MODULE_DESTROYED_VOLUME,
+            1.0,
+        );
+    }
+}
+
+/// Air hissing out of the breached rooms, from the middle of the breached cells and louder the more of them.
+fn depressurization_sounds_system(
+    mut event_reader: EventReader<StructureDepressurizationEvent>,
+    structures_query: Query<(&Structure, &Transform)>,
+    sound_effects: Res<SoundEffects>,
+    audio_settings: Res<AudioSettings>,
+    camera_view: Res<CameraView>,
+    mut commands: Commands,
+) {
+    let Some(sound) = sound_effects.get("depressurization") else {
+        return;
+    };
+    for event in event_reader.read() {
+        let Ok((structure, structure_transform)) = structures_query.get(event.depressurized_structure) else {
+            continue;
+        };
+        if event.breached_cells.is_empty() {
+            continue;
+        }
+        let cells = event.breached_cells.len() as f32;
+        let position = event
+            .breached_cells
+            .iter()
+            .map(|(x, y)| structure.grid_cell_center_world_position(*x, *y, structure_transform))
+            .sum::<Vec2>()
+            / cells;
+        let volume =
+            DEPRESSURIZATION_VOLUME + (1.0 - DEPRESSURIZATION_VOLUME) * (cells / DEPRESSURIZATION_FULL_CELLS).min(1.0);
+        play_sound_at(&mut commands, &audio_settings, &camera_view, sound.clone(), position, volume, 1.0);
+    }
+}
+
+fn attach_engine_hum_system(
+    structures_query: Query<(Entity, &Children), (With<Structure>, Without<EngineHum>)>,
+    engines_query: Query<(), With<EngineModule>>,
+    sound_effects: Res<SoundEffects>,
+    mut commands: Commands,
+) {
+    let Some(sound) = sound_effects.get("engine_hum") else {
+        return;
+    };
+    for (structure_entity, children) in &structures_query {
+        if !children.iter().any(|child| engines_query.contains(*child)) {
+            continue;
+        }
+        // Starts silent, the volume follows the thrust
+        let sound_entity = commands
+            .spawn(AudioBundle { source: sound.clone(), settings: PlaybackSettings::LOOP.with_volume(Volume::ZERO) })
+            .set_parent(structure_entity)
+            .id();
+        commands.entity(structure_entity).insert(EngineHum { sound_entity, volume: 0.0 });
+    }
+}
+
+/// Sets the volume of the hum of every structure from the thrust of its engines and its distance to the view.
+fn engine_hum_system(
+    mut structures_query: Query<(&mut EngineHum, &ExternalForce, &GlobalTransform)>,
+    sinks_query: Query<&AudioSink>,
+    audio_settings: Res<AudioSettings>,
+    movement_settings: Res<MovementSettings>,
+    camera_view: Res<CameraView>,
+    time: Res<Time>,
+) {
+    let full_thrust = movement_settings.engine_thrust * ENGINE_HUM_FULL_ENGINES;
+
+    for (mut engine_hum, external_force, structure_transform) in &mut structures_query {
+        let thrust = (external_force.force().length() / full_thrust.max(f32::EPSILON)).min(1.0);
+        let target = ENGINE_HUM_VOLUME
+            * thrust
+            * audio_settings.sfx_volume_at(camera_view.area.center(), structure_transform.translation().truncate());
+        // Engines spool up and down instead of cutting in and out
+        let fade = (ENGINE_HUM_FADE_RATE * time.delta_seconds()).min(1.0);
+        engine_hum.volume += (target - engine_hum.volume) * fade;
+
+        // The sink only exists once the sound started playing
+        if let Ok(sink) = sinks_query.get(engine_hum.sound_entity) {
+            sink.set_volume(engine_hum.volume);
+        }
+    }
+}
@@ -1,19 +1,38 @@
+use crate::core::inputs::KeyBindings;
+use crate::core::schedule::InGameSet;
 use crate::core::state::GameState;
 use crate::world::prelude::*;
 use avian2d::prelude::*;
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
 use bevy::prelude::*;
 use bevy::render::camera::ScalingMode;
+use serde::{Deserialize, Serialize};
 
 pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(PlayerResource::default())
+            .init_resource::<CameraSettings>()
+            .init_resource::<CameraMode>()
+            // The player does not move while the camera is flying around
+            .configure_sets(Update, InGameSet::UserInput.run_if(resource_equals(CameraMode::Follow)))
             .add_systems(OnEnter(GameState::BuildingStructures), spawn_camera)
+            .add_systems(
+                Update,
+                (
+                    camera_zoom_system,
+                    toggle_camera_follow_mode_system,
+                    toggle_camera_mode_system,
+                    spectator_pan_system.run_if(resource_equals(CameraMode::Spectator)),
+                )
+                    .run_if(in_state(GameState::InGame)),
+            )
             .add_systems(
                 PostUpdate,
                 (update_player_camera, update_structure_camera)
                     .run_if(in_state(GameState::InGame))
+                    .run_if(resource_equals(CameraMode::Follow))
                     .after(PhysicsSet::Sync)
                     .before(TransformSystem::TransformPropagate),
             );
@@ -22,6 +41,64 @@ impl Plugin for CameraPlugin {
 
 /// Camera lerp factor.
 const CAM_LERP_FACTOR: f32 = 2.0;
+const PIXELS_PER_SCROLL_LINE: f32 = 20.0;
+const SPECTATOR_PAN_SPEED: f32 = 1000.0; // multiplied by the projection scale, so panning feels the same at any zoom
+
+/// What drives the camera position.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CameraMode {
+    /// Follows the player, or the structure they control.
+    #[default]
+    Follow,
+    /// Detached debug camera panned with WASD, the player inputs are ignored.
+    Spectator,
+}
+
+/// How the camera catches up with its target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CameraFollowMode {
+    /// The camera is locked on the target.
+    HardLock,
+    /// The camera lerps toward the target.
+    #[default]
+    Smooth,
+}
+
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CameraSettings {
+    pub follow_mode: CameraFollowMode,
+    pub lerp_factor: f32,
+    /// Orthographic scale bounds, a lower scale is more zoomed in.
+    pub min_scale: f32,
+    pub max_scale: f32,
+    /// Scale change per mouse wheel line.
+    pub zoom_speed: f32,
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        Self {
+            follow_mode: CameraFollowMode::default(),
+            lerp_factor: CAM_LERP_FACTOR,
+            min_scale: 0.02,
+            max_scale: 0.5,
+            zoom_speed: 0.1,
+        }
+    }
+}
+
+impl CameraSettings {
+    /// Moves the camera toward the target according to the follow mode.
+    fn follow(&self, camera: &mut Transform, target: Vec3, delta_seconds: f32) {
+        let target = Vec3::new(target.x, target.y, camera.translation.z);
+        camera.translation = match self.follow_mode {
+            CameraFollowMode::HardLock => target,
+            CameraFollowMode::Smooth => camera.translation.lerp(target, (delta_seconds * self.lerp_factor).min(1.0)),
+        };
+    }
+}
+
 fn spawn_camera(mut commands: Commands) {
     commands.spawn(Camera2dBundle {
         transform: Transform::from_translation(Vec3::new(0.0, 0.0, 1000.0)),
@@ -30,12 +107,90 @@ fn spawn_camera(mut commands: Commands) {
     });
 }
 
+/// Zooms the camera with the mouse wheel.
+fn camera_zoom_system(
+    mut wheel_reader: EventReader<MouseWheel>,
+    mut camera: Query<&mut OrthographicProjection, With<Camera2d>>,
+    settings: Res<CameraSettings>,
+) {
+    let scroll: f32 = wheel_reader
+        .read()
+        .map(|wheel| match wheel.unit {
+            MouseScrollUnit::Line => wheel.y,
+            MouseScrollUnit::Pixel => wheel.y / PIXELS_PER_SCROLL_LINE,
+        })
+        .sum();
+    if scroll == 0.0 {
+        return;
+    }
+
+    let Ok(mut projection) = camera.get_single_mut() else {
+        return;
+    };
+
+    // Scrolling up zooms in
+    let scale = projection.scale * (1.0 - scroll * settings.zoom_speed);
+    projection.scale = scale.clamp(settings.min_scale, settings.max_scale);
+}
+
+fn toggle_camera_mode_system(keys: Res<ButtonInput<KeyCode>>, mut camera_mode: ResMut<CameraMode>) {
+    // Ctrl+F2 toggles a debug overlay instead
+    if keys.just_pressed(KeyCode::F2) && !keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        *camera_mode = match *camera_mode {
+            CameraMode::Follow => CameraMode::Spectator,
+            CameraMode::Spectator => CameraMode::Follow,
+        };
+        debug!("Camera mode: {:?}", *camera_mode);
+    }
+}
+
+fn spectator_pan_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut camera: Query<(&mut Transform, &OrthographicProjection), With<Camera2d>>,
+    time: Res<Time<Real>>,
+) {
+    let Ok((mut camera_transform, projection)) = camera.get_single_mut() else {
+        return;
+    };
+
+    let mut direction = Vec2::ZERO;
+    if keys.pressed(bindings.move_up) {
+        direction.y += 1.0;
+    }
+    if keys.pressed(bindings.move_down) {
+        direction.y -= 1.0;
+    }
+    if keys.pressed(bindings.move_left) {
+        direction.x -= 1.0;
+    }
+    if keys.pressed(bindings.move_right) {
+        direction.x += 1.0;
+    }
+
+    // Real time so the camera can still move while the game is paused or slowed down
+    let offset = direction.normalize_or_zero() * SPECTATOR_PAN_SPEED * projection.scale * time.delta_seconds();
+    camera_transform.translation += offset.extend(0.0);
+}
+
+fn toggle_camera_follow_mode_system(keys: Res<ButtonInput<KeyCode>>, mut settings: ResMut<CameraSettings>) {
+    // Ctrl+C copies the build mode selection
+    if keys.just_pressed(KeyCode::KeyC) && !keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        settings.follow_mode = match settings.follow_mode {
+            CameraFollowMode::HardLock => CameraFollowMode::Smooth,
+            CameraFollowMode::Smooth => CameraFollowMode::HardLock,
+        };
+        debug!("Camera follow mode: {:?}", settings.follow_mode);
+    }
+}
+
 /// Update the camera position by tracking the player.
 fn update_player_camera(
     mut camera: Query<&mut Transform, (With<Camera2d>, Without<Player>)>,
     player: Query<&GlobalTransform, (With<Player>, Without<Camera2d>)>,
     time: Res<Time>,
     player_resource: Res<PlayerResource>,
+    settings: Res<CameraSettings>,
 ) {
     if player_resource.is_controlling_structure {
         return;
@@ -49,15 +204,12 @@ fn update_player_camera(
         return;
     };
 
-    let Vec3 { x, y, .. } = player.translation();
-    let direction = Vec3::new(x, y, camera.translation.z);
-
     // Applies a smooth effect to camera movement using interpolation between
     // the camera position and the player position on the x and y axes.
     // Here we use the in-game time, to get the elapsed time (in seconds)
     // since the previous update. This avoids jittery movement when tracking
     // the player.
-    camera.translation = camera.translation.lerp(direction, time.delta_seconds() * CAM_LERP_FACTOR);
+    settings.follow(&mut camera, player.translation(), time.delta_seconds());
 }
 
 fn update_structure_camera(
@@ -65,6 +217,7 @@ fn update_structure_camera(
     structure: Query<(&GlobalTransform, &LinearVelocity), (With<ControlledByPlayer>, Without<Camera2d>)>,
     time: Res<Time>,
     player_resource: Res<PlayerResource>,
+    settings: Res<CameraSettings>,
 ) {
     if !player_resource.is_controlling_structure {
         return;
@@ -75,9 +228,6 @@ fn update_structure_camera(
     };
 
     for (structure, linear_vel) in structure.iter() {
-        let Vec3 { x, y, .. } = structure.translation();
-        let direction = Vec3::new(x, y, camera.translation.z);
-
-        camera.translation = direction;
+        settings.follow(&mut camera, structure.translation(), time.delta_seconds());
     }
 }
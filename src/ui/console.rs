@@ -0,0 +1,324 @@
+use crate::core::asset_loader::{AssetStore, StructuresData};
+use crate::core::game_assets::GameAssets;
+use crate::core::state::GameState;
+use crate::gameplay::cargo::{CargoKind, CargoStorage};
+use crate::world::prelude::*;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::{ButtonState, InputSystem};
+use bevy::prelude::*;
+use thiserror::Error;
+
+const CONSOLE_KEY: KeyCode = KeyCode::Backquote;
+const CONSOLE_MAX_LINES: usize = 12;
+const CONSOLE_FONT_SIZE: f32 = 14.0;
+const CONSOLE_INPUT_COLOR: Color = Color::srgb(1.0, 0.85, 0.3);
+
+const HELP: &[&str] = &[
+    "help: lists the commands",
+    "clear: clears the console",
+    "spawn_structure <prefab> <x> <y>: spawns the structure at index <prefab> of the structures data file",
+    "give_ore <amount>: stores ore in the structure flown or boarded by the player",
+    "set_state <state>: switches to LoadingAssets, BuildingGrid, BuildingStructures, InGame, Paused or Editor",
+    "damage <entity> <amount>: damages a module, the entity is written like 12v1",
+];
+
+/// Developer console toggled with the backquote key, running commands typed in to spawn structures, fill the cargo,
+/// switch the game state or damage modules through the same events as the game. While it is open, the keyboard only
+/// types in the console.
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Console>()
+            .add_event::<ConsoleCommandEvent>()
+            .add_systems(Startup, spawn_console)
+            .add_systems(PreUpdate, console_input_system.after(InputSystem))
+            .add_systems(
+                Update,
+                (execute_console_commands_system, show_console_system.run_if(resource_changed::<Console>)).chain(),
+            );
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConsoleCommand {
+    Help,
+    Clear,
+    SpawnStructure { prefab: usize, position: Vec2 },
+    GiveOre(f32),
+    SetState(GameState),
+    Damage { module_entity: Entity, amount: f32 },
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ConsoleError {
+    #[error("Unknown command {0}, type help for the list")]
+    UnknownCommand(String),
+    #[error("Missing argument <{0}>")]
+    MissingArgument(&'static str),
+    #[error("Invalid <{argument}>: {value}")]
+    InvalidArgument { argument: &'static str, value: String },
+}
+
+impl std::str::FromStr for ConsoleCommand {
+    type Err = ConsoleError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut words = line.split_whitespace();
+        let command = match words.next().unwrap_or_default() {
+            "help" => ConsoleCommand::Help,
+            "clear" => ConsoleCommand::Clear,
+            "spawn_structure" => ConsoleCommand::SpawnStructure {
+                prefab: parse_argument(words.next(), "prefab")?,
+                position: Vec2::new(parse_argument(words.next(), "x")?, parse_argument(words.next(), "y")?),
+            },
+            "give_ore" => ConsoleCommand::GiveOre(parse_argument(words.next(), "amount")?),
+            "set_state" => ConsoleCommand::SetState(parse_game_state(words.next())?),
+            "damage" => ConsoleCommand::Damage {
+                module_entity: parse_entity(words.next())?,
+                amount: parse_positive_argument(words.next(), "amount")?,
+            },
+            unknown => return Err(ConsoleError::UnknownCommand(unknown.to_string())),
+        };
+        Ok(command)
+    }
+}
+
+fn parse_argument<T: std::str::FromStr>(word: Option<&str>, argument: &'static str) -> Result<T, ConsoleError> {
+    let word = word.ok_or(ConsoleError::MissingArgument(argument))?;
+    word.parse().map_err(|_| ConsoleError::InvalidArgument { argument, value: word.to_string() })
+}
+
+fn parse_positive_argument(word: Option<&str>, argument: &'static str) -> Result<f32, ConsoleError> {
+    let value: f32 = parse_argument(word, argument)?;
+    if value.is_nan() || value <= 0.0 {
+        return Err(ConsoleError::InvalidArgument { argument, value: value.to_string() });
+    }
+    Ok(value)
+}
+
+fn parse_game_state(word: Option<&str>) -> Result<GameState, ConsoleError> {
+    let word = word.ok_or(ConsoleError::MissingArgument("state"))?;
+    match word {
+        "LoadingAssets" => Ok(GameState::LoadingAssets),
+        "BuildingGrid" => Ok(GameState::BuildingGrid),
+        "BuildingStructures" => Ok(GameState::BuildingStructures),
+        "InGame" => Ok(GameState::InGame),
+        "Paused" => Ok(GameState::Paused),
+        "Editor" => Ok(GameState::Editor),
+        _ => Err(ConsoleError::InvalidArgument { argument: "state", value: word.to_string() }),
+    }
+}
+
+/// Reads an entity the way it is logged, `<index>v<generation>`, or just its index for the first generation.
+fn parse_entity(word: Option<&str>) -> Result<Entity, ConsoleError> {
+    let word = word.ok_or(ConsoleError::MissingArgument("entity"))?;
+    let invalid = || ConsoleError::InvalidArgument { argument: "entity", value: word.to_string() };
+    let (index, generation) = word.split_once('v').unwrap_or((word, "1"));
+    let index: u32 = index.parse().map_err(|_| invalid())?;
+    let generation: u32 = generation.parse().map_err(|_| invalid())?;
+    Entity::try_from_bits(((generation as u64) << 32) | index as u64).map_err(|_| invalid())
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct Console {
+    pub open: bool,
+    pub input: String,
+    /// Commands typed in and their results, the oldest first.
+    pub lines: Vec<String>,
+}
+
+impl Console {
+    pub fn print(&mut self, line: impl Into<String>) {
+        self.lines.push(line.into());
+        let overflow = self.lines.len().saturating_sub(CONSOLE_MAX_LINES);
+        self.lines.drain(..overflow);
+    }
+}
+
+/// Sent when a command is entered in the console.
+#[derive(Event, Debug, Clone)]
+pub struct ConsoleCommandEvent(pub ConsoleCommand);
+
+#[derive(Component)]
+struct ConsoleRoot;
+
+#[derive(Component)]
+struct ConsoleText;
+
+fn spawn_console(mut commands: Commands) {
+    commands
+        .spawn((
+            ConsoleRoot,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    bottom: Val::Px(0.0),
+                    padding: UiRect::all(Val::Px(8.0)),
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+                visibility: Visibility::Hidden,
+                z_index: ZIndex::Global(100),
+                ..default()
+            },
+        ))
+        .with_children(|console| {
+            console.spawn((
+                ConsoleText,
+                TextBundle::from_sections([
+                    TextSection::new("", TextStyle { font_size: CONSOLE_FONT_SIZE, color: Color::WHITE, ..default() }),
+                    TextSection::new(
+                        "",
+                        TextStyle { font_size: CONSOLE_FONT_SIZE, color: CONSOLE_INPUT_COLOR, ..default() },
+                    ),
+                ]),
+            ));
+        });
+}
+
+/// Types the keyboard input in the open console, then clears the pressed keys so the rest of the game ignores them.
+fn console_input_system(
+    mut keyboard_reader: EventReader<KeyboardInput>,
+    mut keys: ResMut<ButtonInput<KeyCode>>,
+    mut console: ResMut<Console>,
+    mut command_writer: EventWriter<ConsoleCommandEvent>,
+) {
+    if keys.just_pressed(CONSOLE_KEY) {
+        console.open = !console.open;
+        keys.reset_all();
+        keyboard_reader.clear();
+        return;
+    }
+    if !console.open {
+        keyboard_reader.clear();
+        return;
+    }
+
+    for event in keyboard_reader.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        match (&event.logical_key, event.key_code) {
+            (_, KeyCode::Escape) => console.open = false,
+            (_, KeyCode::Backspace) => {
+                console.input.pop();
+            }
+            (_, KeyCode::Enter | KeyCode::NumpadEnter) => {
+                let line = std::mem::take(&mut console.input);
+                if line.trim().is_empty() {
+                    continue;
+                }
+                console.print(format!("> {line}"));
+                match line.parse::<ConsoleCommand>() {
+                    Ok(command) => {
+                        command_writer.send(ConsoleCommandEvent(command));
+                    }
+                    Err(error) => console.print(error.to_string()),
+                }
+            }
+            (Key::Character(text), _) => console.input.push_str(text),
+            (Key::Space, _) => console.input.push(' '),
+            _ => {}
+        }
+    }
+    keys.reset_all();
+}
+
+fn execute_console_commands_system(
+    mut command_reader: EventReader<ConsoleCommandEvent>,
+    mut console: ResMut<Console>,
+    mut cargo_query: Query<&mut CargoStorage>,
+    controlled_query: Query<Entity, With<ControlledByPlayer>>,
+    mut modules_query: Query<(&mut ModuleMaterial, &Module)>,
+    player_resource: Res<PlayerResource>,
+    asset_store: Res<AssetStore>,
+    structures_assets: Res<Assets<StructuresData>>,
+    game_assets: Res<GameAssets>,
+    module_registry: Res<ModuleRegistry>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut damage_writer: EventWriter<ModuleTookDamageEvent>,
+    mut destroyed_writer: EventWriter<ModuleDestroyedEvent>,
+    mut commands: Commands,
+) {
+    for ConsoleCommandEvent(command) in command_reader.read() {
+        match command {
+            ConsoleCommand::Help => HELP.iter().for_each(|line| console.print(*line)),
+            ConsoleCommand::Clear => console.lines.clear(),
+            ConsoleCommand::SpawnStructure { prefab, position } => {
+                let Some(structures) = structures_assets.get(&asset_store.structures) else {
+                    console.print("The structures are not loaded yet");
+                    continue;
+                };
+                let Some(structure_data) = structures.structures.get(*prefab) else {
+                    console.print(format!("No prefab {prefab}, there are {}", structures.structures.len()));
+                    continue;
+                };
+                let mut structure_data = structure_data.clone();
+                structure_data.world_pos = position.to_array();
+                let structure_entity =
+                    spawn_structure(&mut commands, &mut materials, &game_assets, &module_registry, &structure_data);
+                console.print(format!("Spawned structure {structure_entity} at ({}, {})", position.x, position.y));
+            }
+            ConsoleCommand::GiveOre(amount) => {
+                let structure_entity = controlled_query.get_single().ok().or(player_resource.inside_structure);
+                let Some(mut cargo) = structure_entity.and_then(|entity| cargo_query.get_mut(entity).ok()) else {
+                    console.print("The player is not aboard a structure with cargo holds");
+                    continue;
+                };
+                let stored = cargo.store(CargoKind::Ore, *amount);
+                console.print(format!("Stored {stored:.0} ore, {:.0} free", cargo.free_space()));
+            }
+            ConsoleCommand::SetState(state) => {
+                next_state.set(*state);
+                console.print(format!("Switching to {state:?}"));
+            }
+            ConsoleCommand::Damage { module_entity, amount } => {
+                let Ok((mut module_material, module)) = modules_query.get_mut(*module_entity) else {
+                    console.print(format!("No module {module_entity}"));
+                    continue;
+                };
+                // Its destruction was already sent, it is despawned at the end of the frame
+                if module_material.structural_points <= 0.0 {
+                    console.print(format!("Module {module_entity} is already destroyed"));
+                    continue;
+                }
+                module_material.structural_points -= amount;
+                damage_writer.send(ModuleTookDamageEvent {
+                    module_entity: *module_entity,
+                    damage: *amount,
+                    remaining_points: module_material.structural_points,
+                    source: None,
+                    critical: false,
+                });
+                if module_material.structural_points <= 0.0 {
+                    destroyed_writer.send(ModuleDestroyedEvent {
+                        destroyed_entity: *module_entity,
+                        inner_grid_pos: module.inner_grid_pos,
+                        source: None,
+                    });
+                }
+                console.print(format!(
+                    "Module {module_entity} has {:.0} points left",
+                    module_material.structural_points.max(0.0)
+                ));
+            }
+        }
+    }
+}
+
+fn show_console_system(
+    console: Res<Console>,
+    mut root_query: Query<&mut Visibility, With<ConsoleRoot>>,
+    mut text_query: Query<&mut Text, With<ConsoleText>>,
+) {
+    if let Ok(mut visibility) = root_query.get_single_mut() {
+        *visibility = if console.open { Visibility::Inherited } else { Visibility::Hidden };
+    }
+    if let Ok(mut text) = text_query.get_single_mut() {
+        text.sections[0].value = console.lines.iter().map(|line| format!("{line}\n")).collect();
+        text.sections[1].value = format!("> {}_", console.input);
+    }
+}
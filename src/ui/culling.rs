@@ -0,0 +1,68 @@
+use crate::core::profiling::EntityCountDiagnosticAppExt;
+use crate::core::state::GameState;
+use bevy::prelude::*;
+
+const CULLING_MARGIN: f32 = 20.0; // meters kept around the view before culling cosmetic entities
+
+pub struct CullingPlugin;
+
+impl Plugin for CullingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraView>()
+            .add_systems(
+                PostUpdate,
+                (update_camera_view_system, cull_offscreen_cosmetics_system)
+                    .chain()
+                    .after(TransformSystem::TransformPropagate)
+                    .run_if(in_state(GameState::InGame)),
+            )
+            .register_entity_count_diagnostic::<Cosmetic>("cosmetics");
+    }
+}
+
+/// The world area currently seen by the camera.
+/// Spawners of purely visual entities should check it first and skip what nobody would see.
+#[derive(Resource, Debug, Default)]
+pub struct CameraView {
+    pub area: Rect,
+}
+
+impl CameraView {
+    /// Returns `true` if the point is inside the view, or less than `margin` away from it.
+    pub fn is_visible(&self, point: Vec2, margin: f32) -> bool {
+        self.area.inflate(margin).contains(point)
+    }
+
+    /// Whether a cosmetic entity at this point is worth spawning.
+    pub fn should_spawn_cosmetic(&self, point: Vec2) -> bool {
+        self.is_visible(point, CULLING_MARGIN)
+    }
+}
+
+/// Marks a purely visual entity (popup, exhaust, particle...) that is despawned as soon as it leaves the view.
+#[derive(Component, Debug, Default)]
+pub struct Cosmetic;
+
+fn update_camera_view_system(
+    camera_query: Query<(&GlobalTransform, &OrthographicProjection), With<Camera2d>>,
+    mut camera_view: ResMut<CameraView>,
+) {
+    let Ok((camera_transform, projection)) = camera_query.get_single() else {
+        return;
+    };
+
+    let center = camera_transform.translation().truncate();
+    camera_view.area = Rect::from_center_size(center + projection.area.center(), projection.area.size());
+}
+
+fn cull_offscreen_cosmetics_system(
+    cosmetics_query: Query<(Entity, &GlobalTransform), With<Cosmetic>>,
+    camera_view: Res<CameraView>,
+    mut commands: Commands,
+) {
+    for (entity, transform) in &cosmetics_query {
+        if !camera_view.is_visible(transform.translation().truncate(), CULLING_MARGIN) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
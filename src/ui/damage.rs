@@ -0,0 +1,100 @@
+use crate::core::game_assets::GameAssets;
+use crate::core::state::GameState;
+use crate::ui::culling::CameraView;
+use crate::ui::world_text::*;
+use crate::world::prelude::*;
+use bevy::prelude::*;
+
+const POPUP_LIFETIME: f32 = 1.0; // seconds
+const POPUP_MERGE_WINDOW: f32 = 0.4; // seconds during which new hits on the same module add to the popup
+const POPUP_RISE_SPEED: f32 = 3.0; // m/s
+const POPUP_FONT_SIZE: f32 = 20.0;
+const CRITICAL_FONT_SIZE: f32 = 30.0;
+const POPUP_COLOR: Color = Color::srgb(1.0, 0.9, 0.6);
+const CRITICAL_COLOR: Color = Color::srgb(1.0, 0.25, 0.1);
+
+/// Floating numbers showing the damage taken by modules, drawn with the `WorldTextPlugin`.
+pub struct DamagePopupPlugin;
+
+impl Plugin for DamagePopupPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, spawn_damage_popups_system.run_if(in_state(GameState::InGame)));
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct DamagePopup {
+    pub module_entity: Entity,
+    pub total_damage: f32,
+    pub critical: bool,
+}
+
+/// Formats a damage value with at most 3 significant digits, like "7.5", "340" or "12.3k".
+pub fn format_damage(damage: f32) -> String {
+    let (value, suffix) = match damage.abs() {
+        d if d >= 1_000_000.0 => (damage / 1_000_000.0, "M"),
+        d if d >= 1_000.0 => (damage / 1_000.0, "k"),
+        _ => (damage, ""),
+    };
+
+    if value.abs() >= 100.0 || value.fract().abs() < 0.05 {
+        format!("{:.0}{}", value, suffix)
+    } else {
+        format!("{:.1}{}", value, suffix)
+    }
+}
+
+fn popup_style(critical: bool, game_assets: &GameAssets) -> TextStyle {
+    let font = game_assets.font.clone();
+    if critical {
+        TextStyle { font, font_size: CRITICAL_FONT_SIZE, color: CRITICAL_COLOR }
+    } else {
+        TextStyle { font, font_size: POPUP_FONT_SIZE, color: POPUP_COLOR }
+    }
+}
+
+/// Spawns a popup per damaged module, hits landing shortly after are added to the existing popup.
+fn spawn_damage_popups_system(
+    mut event_reader: EventReader<ModuleTookDamageEvent>,
+    modules_query: Query<&GlobalTransform, With<Module>>,
+    mut popups_query: Query<(&mut DamagePopup, &mut WorldText, &mut Text)>,
+    camera_view: Res<CameraView>,
+    game_assets: Res<GameAssets>,
+    mut commands: Commands,
+) {
+    for event in event_reader.read() {
+        let merged_popup = popups_query.iter_mut().find(|(popup, world_text, _)| {
+            popup.module_entity == event.module_entity && world_text.lifetime.elapsed_secs() < POPUP_MERGE_WINDOW
+        });
+        if let Some((mut popup, mut world_text, mut text)) = merged_popup {
+            popup.total_damage += event.damage;
+            popup.critical |= event.critical;
+            world_text.restart();
+            text.sections[0].value = format_damage(popup.total_damage);
+            text.sections[0].style = popup_style(popup.critical, &game_assets);
+            continue;
+        }
+
+        let Ok(module_transform) = modules_query.get(event.module_entity) else {
+            continue;
+        };
+        let animation = if event.critical {
+            WorldTextAnimation::Pop(POPUP_RISE_SPEED)
+        } else {
+            WorldTextAnimation::Rise(POPUP_RISE_SPEED)
+        };
+        let text_event =
+            SpawnWorldTextEvent::new(module_transform.translation().truncate(), format_damage(event.damage))
+                .with_style(popup_style(event.critical, &game_assets))
+                .with_lifetime(POPUP_LIFETIME)
+                .with_animation(animation);
+
+        if let Some(popup_entity) = spawn_world_text(&mut commands, &camera_view, &text_event) {
+            commands.entity(popup_entity).insert(DamagePopup {
+                module_entity: event.module_entity,
+                total_damage: event.damage,
+                critical: event.critical,
+            });
+        }
+    }
+}
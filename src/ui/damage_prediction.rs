@@ -0,0 +1,124 @@
+use crate::core::state::GameState;
+use crate::gameplay::degradation::{performance, ModulePerformance};
+use crate::gameplay::docking::DockedStructures;
+use crate::gameplay::power::PowerConsumer;
+use crate::gameplay::structures_combat::{predicted_cannon_damage, WeaponGroup};
+use crate::ui::damage::format_damage;
+use crate::world::prelude::*;
+use bevy::prelude::*;
+
+const TOOLTIP_OFFSET: Vec2 = Vec2::new(16.0, 16.0); // pixels from the cursor
+const CRITICAL_COLOR: Color = Color::srgb(1.0, 0.6, 0.2);
+const OUT_OF_RANGE_COLOR: Color = Color::srgb(0.6, 0.6, 0.6);
+
+/// While flying a structure, hovering the cursor over a module of another structure shows the damage a shot of the
+/// cannons would deal to it, from their condition, the distance and the module material, to learn how the
+/// energy-based damage model plays out.
+pub struct DamagePredictionPlugin;
+
+impl Plugin for DamagePredictionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::BuildingStructures), spawn_damage_prediction_tooltip)
+            .add_systems(Update, update_damage_prediction_system.run_if(in_state(GameState::InGame)));
+    }
+}
+
+#[derive(Component)]
+struct DamagePredictionTooltip;
+
+fn spawn_damage_prediction_tooltip(mut commands: Commands) {
+    commands.spawn((
+        DamagePredictionTooltip,
+        TextBundle::from_section("", TextStyle { font_size: 14.0, color: Color::WHITE, ..default() })
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                padding: UiRect::all(Val::Px(4.0)),
+                ..default()
+            })
+            .with_background_color(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+        Visibility::Hidden,
+    ));
+}
+
+fn update_damage_prediction_system(
+    mut tooltip_query: Query<(&mut Text, &mut Style, &mut Visibility), With<DamagePredictionTooltip>>,
+    windows_query: Query<&Window>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    player_resource: Res<PlayerResource>,
+    controlled_query: Query<(Entity, &Transform, &Children, Option<&DockedStructures>), With<ControlledByPlayer>>,
+    structures_query: Query<(Entity, &Structure, &Transform)>,
+    cannons_query: Query<(Option<&PowerConsumer>, Option<&ModulePerformance>), With<CannonModule>>,
+    modules_query: Query<(&Module, &ModuleMaterial)>,
+) {
+    let Ok((mut text, mut style, mut visibility)) = tooltip_query.get_single_mut() else {
+        return;
+    };
+    visibility.set_if_neq(Visibility::Hidden);
+
+    if !player_resource.is_controlling_structure {
+        return;
+    }
+    let Ok((controlled_entity, controlled_transform, children, docked)) = controlled_query.get_single() else {
+        return;
+    };
+    let Ok((camera, camera_transform)) = camera_query.get_single() else {
+        return;
+    };
+    let Some((cursor, cursor_position)) =
+        windows_query.get_single().ok().and_then(|window| window.cursor_position()).and_then(|cursor| {
+            camera.viewport_to_world_2d(camera_transform, cursor).map(|cursor_position| (cursor, cursor_position))
+        })
+    else {
+        return;
+    };
+
+    // Module of another structure under the cursor, the docked structures fly along and are not targets
+    let is_own =
+        |entity: Entity| entity == controlled_entity || docked.is_some_and(|docked| docked.0.contains(&entity));
+    let Some((module, module_material)) = structures_query
+        .iter()
+        .filter(|(structure_entity, ..)| !is_own(*structure_entity))
+        .filter_map(|(_, structure, structure_transform)| {
+            structure.module_at(structure.world_to_grid(cursor_position.extend(0.0), structure_transform))
+        })
+        .find_map(|module_entity| modules_query.get(module_entity).ok())
+    else {
+        return;
+    };
+
+    // The cannon in the best condition gives the shot to expect
+    let Some(cannon_performance) = children
+        .iter()
+        .filter_map(|child| cannons_query.get(*child).ok())
+        .filter(|(power, _)| power.is_none_or(|power| power.powered))
+        .map(|(_, module_performance)| performance(module_performance))
+        .filter(|cannon_performance| *cannon_performance > 0.0)
+        .max_by(|performance1, performance2| performance1.total_cmp(performance2))
+    else {
+        return;
+    };
+
+    let distance = controlled_transform.translation.truncate().distance(cursor_position);
+    let range = WeaponGroup::Cannons.range();
+    let (damage, critical) = predicted_cannon_damage(cannon_performance, module_material.material_type);
+    let shots = (module_material.structural_points / damage).ceil().max(1.0);
+
+    let (value, color) = if distance > range {
+        (format!("{}: out of range ({:.0}/{:.0} m)", module.module_type.name(), distance, range), OUT_OF_RANGE_COLOR)
+    } else {
+        let value = format!(
+            "{} ({:?}): {} per shot{}, {} shots to break",
+            module.module_type.name(),
+            module_material.material_type,
+            format_damage(damage),
+            if critical { " critical" } else { "" },
+            shots,
+        );
+        (value, if critical { CRITICAL_COLOR } else { Color::WHITE })
+    };
+    text.sections[0].value = value;
+    text.sections[0].style.color = color;
+    style.left = Val::Px(cursor.x + TOOLTIP_OFFSET.x);
+    style.top = Val::Px(cursor.y + TOOLTIP_OFFSET.y);
+    visibility.set_if_neq(Visibility::Inherited);
+}
@@ -1,13 +1,21 @@
-use avian2d::prelude::PhysicsDebugPlugin;
-use bevy::app::{App, Plugin, Startup};
+use crate::core::debug_overlays::DebugOverlays;
+use avian2d::prelude::{PhysicsDebugPlugin, PhysicsGizmos};
+use bevy::app::{App, Plugin, Update};
 use bevy::ecs::schedule::{LogLevel, ScheduleBuildSettings};
-use bevy::prelude::{default, Commands, Update};
+use bevy::prelude::*;
 use iyes_perf_ui::prelude::*;
 
+// Held with Ctrl, F2 and F3 alone already switch the camera mode and the profiler
+const TOGGLE_GRID_KEY: KeyCode = KeyCode::F1;
+const TOGGLE_PRESSURIZATION_KEY: KeyCode = KeyCode::F2;
+const TOGGLE_PHYSICS_KEY: KeyCode = KeyCode::F3;
+const TOGGLE_PERF_UI_KEY: KeyCode = KeyCode::F4;
+
+/// Debug overlays toggled at runtime with Ctrl+F1 (grids), Ctrl+F2 (pressurization and power), Ctrl+F3 (physics)
+/// and Ctrl+F4 (performance), starting from the `debug.enabled` setting.
 #[derive(Default)]
-pub struct DebugPlugin {
-    pub enable: bool,
-}
+pub struct DebugPlugin;
+
 impl Plugin for DebugPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(PerfUiPlugin)
@@ -18,16 +26,55 @@ impl Plugin for DebugPlugin {
         app.edit_schedule(Update, |schedule| {
             schedule.set_build_settings(ScheduleBuildSettings { ambiguity_detection: LogLevel::Warn, ..default() });
         });
-        if self.enable {
-            app.add_systems(Startup, debug_startup).add_plugins(PhysicsDebugPlugin::default());
-        }
+        app.init_resource::<DebugOverlays>().add_plugins(PhysicsDebugPlugin::default()).add_systems(
+            Update,
+            (
+                toggle_debug_overlays_system,
+                (show_physics_gizmos_system, show_perf_ui_system).run_if(resource_changed::<DebugOverlays>),
+            )
+                .chain(),
+        );
+    }
+}
+
+fn toggle_debug_overlays_system(keys: Res<ButtonInput<KeyCode>>, mut overlays: ResMut<DebugOverlays>) {
+    if !keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+
+    if keys.just_pressed(TOGGLE_GRID_KEY) {
+        overlays.grid = !overlays.grid;
+    }
+    if keys.just_pressed(TOGGLE_PRESSURIZATION_KEY) {
+        overlays.pressurization = !overlays.pressurization;
+    }
+    if keys.just_pressed(TOGGLE_PHYSICS_KEY) {
+        overlays.physics = !overlays.physics;
+    }
+    if keys.just_pressed(TOGGLE_PERF_UI_KEY) {
+        overlays.perf_ui = !overlays.perf_ui;
     }
 }
 
-fn debug_startup(mut commands: Commands) {
-    commands.spawn((
-        PerfUiRoot { display_labels: false, layout_horizontal: true, ..Default::default() },
-        // PerfUiEntryFPSWorst::default(),
-        PerfUiEntryFPS::default(),
-    ));
+fn show_physics_gizmos_system(overlays: Res<DebugOverlays>, mut config_store: ResMut<GizmoConfigStore>) {
+    config_store.config_mut::<PhysicsGizmos>().0.enabled = overlays.physics;
+}
+
+fn show_perf_ui_system(
+    overlays: Res<DebugOverlays>,
+    perf_ui_query: Query<Entity, With<PerfUiRoot>>,
+    mut commands: Commands,
+) {
+    match (overlays.perf_ui, perf_ui_query.get_single()) {
+        (true, Err(_)) => {
+            commands.spawn((
+                PerfUiRoot { display_labels: false, layout_horizontal: true, ..Default::default() },
+                // PerfUiEntryFPSWorst::default(),
+                PerfUiEntryFPS::default(),
+                PerfUiEntryEntityCount::default(),
+            ));
+        }
+        (false, Ok(perf_ui_entity)) => commands.entity(perf_ui_entity).despawn_recursive(),
+        _ => {}
+    }
 }
@@ -0,0 +1,144 @@
+use crate::core::state::GameState;
+use bevy::prelude::*;
+
+const BUTTON_COLOR: Color = Color::srgb(0.25, 0.25, 0.25);
+const BUTTON_HOVERED_COLOR: Color = Color::srgb(0.35, 0.35, 0.35);
+const OPTION_KEYS: [KeyCode; 9] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+/// A panel showing what someone says with the answers the player can pick, with the mouse, the number keys or the
+/// menu navigation. Opening a dialogue replaces the current one, picking an answer closes it.
+pub struct DialoguePlugin;
+
+impl Plugin for DialoguePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<OpenDialogueEvent>().add_event::<DialogueChoiceEvent>().add_systems(
+            Update,
+            (open_dialogue_system, dialogue_choice_system).chain().run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct OpenDialogueEvent {
+    /// Identifies the dialogue in the choice event, so each feature only handles its own dialogues.
+    pub topic: &'static str,
+    /// Entity the dialogue is about, given back with the choice.
+    pub subject: Option<Entity>,
+    pub speaker: String,
+    pub text: String,
+    pub options: Vec<String>,
+}
+
+/// Sent when the player picks an answer, `choice` is its index in the dialogue options.
+#[derive(Event, Debug, Clone)]
+pub struct DialogueChoiceEvent {
+    pub topic: &'static str,
+    pub subject: Option<Entity>,
+    pub choice: usize,
+}
+
+#[derive(Component, Debug)]
+struct DialoguePanel {
+    topic: &'static str,
+    subject: Option<Entity>,
+}
+
+#[derive(Component, Debug)]
+struct DialogueOption(usize);
+
+fn open_dialogue_system(
+    mut event_reader: EventReader<OpenDialogueEvent>,
+    panel_query: Query<Entity, With<DialoguePanel>>,
+    mut commands: Commands,
+) {
+    let Some(event) = event_reader.read().last() else {
+        return;
+    };
+    for panel_entity in &panel_query {
+        commands.entity(panel_entity).despawn_recursive();
+    }
+
+    let text_style = TextStyle { font_size: 16.0, color: Color::WHITE, ..default() };
+    commands
+        .spawn((
+            DialoguePanel { topic: event.topic, subject: event.subject },
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(40.0),
+                    left: Val::Percent(30.0),
+                    width: Val::Percent(40.0),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(10.0)),
+                    row_gap: Val::Px(6.0),
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.85)),
+                ..default()
+            },
+        ))
+        .with_children(|panel| {
+            panel.spawn(TextBundle::from_section(
+                event.speaker.clone(),
+                TextStyle { color: Color::srgb(1.0, 0.85, 0.3), ..text_style.clone() },
+            ));
+            panel.spawn(TextBundle::from_section(event.text.clone(), text_style.clone()));
+
+            for (index, option) in event.options.iter().enumerate() {
+                panel
+                    .spawn((
+                        DialogueOption(index),
+                        ButtonBundle {
+                            style: Style { padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)), ..default() },
+                            background_color: BackgroundColor(BUTTON_COLOR),
+                            ..default()
+                        },
+                    ))
+                    .with_children(|button| {
+                        button
+                            .spawn(TextBundle::from_section(format!("{}. {}", index + 1, option), text_style.clone()));
+                    });
+            }
+        });
+}
+
+fn dialogue_choice_system(
+    mut button_query: Query<(&Interaction, &DialogueOption, &mut BackgroundColor), Changed<Interaction>>,
+    options_query: Query<&DialogueOption>,
+    panel_query: Query<(Entity, &DialoguePanel, &Children)>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut choice_writer: EventWriter<DialogueChoiceEvent>,
+    mut commands: Commands,
+) {
+    let Ok((panel_entity, panel, children)) = panel_query.get_single() else {
+        return;
+    };
+
+    let mut choice = OPTION_KEYS
+        .iter()
+        .position(|key| keys.just_pressed(*key))
+        .filter(|index| options_query.iter_many(children).any(|option| option.0 == *index));
+
+    for (interaction, option, mut background_color) in &mut button_query {
+        match interaction {
+            Interaction::Pressed => choice = Some(option.0),
+            Interaction::Hovered => *background_color = BackgroundColor(BUTTON_HOVERED_COLOR),
+            Interaction::None => *background_color = BackgroundColor(BUTTON_COLOR),
+        }
+    }
+
+    if let Some(choice) = choice {
+        choice_writer.send(DialogueChoiceEvent { topic: panel.topic, subject: panel.subject, choice });
+        commands.entity(panel_entity).despawn_recursive();
+    }
+}
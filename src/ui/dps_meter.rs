@@ -0,0 +1,138 @@
+use crate::core::state::GameState;
+use crate::gameplay::sandbox::SandboxState;
+use crate::ui::damage::format_damage;
+use crate::world::prelude::*;
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+const DPS_WINDOW: f32 = 5.0; // seconds of damage averaged by the meter
+const DPS_METER_TOGGLE_KEY: KeyCode = KeyCode::F7;
+
+/// Damage per second dealt by the controlled structure, shown with F7 and during sandbox test drives.
+pub struct DpsMeterPlugin;
+
+impl Plugin for DpsMeterPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DpsMeter>().add_systems(OnEnter(SandboxState::TestDrive), show_dps_meter).add_systems(
+            Update,
+            (record_damage_system, toggle_dps_meter_system, update_dps_meter_system)
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+/// Damage dealt over a sliding window, with the totals since the last reset.
+#[derive(Resource, Debug, Default)]
+pub struct DpsMeter {
+    /// Elapsed time and damage of every hit in the window.
+    samples: VecDeque<(f32, f32)>,
+    pub total_damage: f32,
+    pub peak_dps: f32,
+}
+
+impl DpsMeter {
+    pub fn record(&mut self, now: f32, damage: f32) {
+        self.samples.push_back((now, damage));
+        self.total_damage += damage;
+    }
+
+    /// Drops the hits older than the window and returns the average damage per second over it.
+    pub fn dps(&mut self, now: f32) -> f32 {
+        while self.samples.front().is_some_and(|(time, _)| now - time > DPS_WINDOW) {
+            self.samples.pop_front();
+        }
+        let dps = self.samples.iter().map(|(_, damage)| damage).sum::<f32>() / DPS_WINDOW;
+        self.peak_dps = self.peak_dps.max(dps);
+        dps
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[derive(Component)]
+struct DpsMeterOverlay;
+
+fn record_damage_system(
+    mut event_reader: EventReader<ModuleTookDamageEvent>,
+    controlled_structure_query: Query<Entity, (With<Structure>, With<ControlledByPlayer>)>,
+    mut dps_meter: ResMut<DpsMeter>,
+    time: Res<Time>,
+) {
+    let Ok(controlled_structure) = controlled_structure_query.get_single() else {
+        event_reader.clear();
+        return;
+    };
+
+    let now = time.elapsed_seconds();
+    for event in event_reader.read() {
+        if event.source == Some(controlled_structure) {
+            dps_meter.record(now, event.damage);
+        }
+    }
+}
+
+fn spawn_dps_meter_overlay(commands: &mut Commands) {
+    commands.spawn((
+        DpsMeterOverlay,
+        TextBundle::from_section("DPS", TextStyle { font_size: 16.0, color: Color::WHITE, ..default() })
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                right: Val::Px(10.0),
+                bottom: Val::Px(10.0),
+                padding: UiRect::all(Val::Px(8.0)),
+                ..default()
+            })
+            .with_background_color(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+    ));
+}
+
+fn show_dps_meter(
+    overlay_query: Query<(), With<DpsMeterOverlay>>,
+    mut dps_meter: ResMut<DpsMeter>,
+    mut commands: Commands,
+) {
+    dps_meter.reset();
+    if overlay_query.is_empty() {
+        spawn_dps_meter_overlay(&mut commands);
+    }
+}
+
+fn toggle_dps_meter_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    overlay_query: Query<Entity, With<DpsMeterOverlay>>,
+    mut dps_meter: ResMut<DpsMeter>,
+    mut commands: Commands,
+) {
+    if !keys.just_pressed(DPS_METER_TOGGLE_KEY) {
+        return;
+    }
+
+    if let Ok(overlay_entity) = overlay_query.get_single() {
+        commands.entity(overlay_entity).despawn_recursive();
+    } else {
+        dps_meter.reset();
+        spawn_dps_meter_overlay(&mut commands);
+    }
+}
+
+fn update_dps_meter_system(
+    mut overlay_query: Query<&mut Text, With<DpsMeterOverlay>>,
+    mut dps_meter: ResMut<DpsMeter>,
+    time: Res<Time>,
+) {
+    let Ok(mut text) = overlay_query.get_single_mut() else {
+        return;
+    };
+
+    let dps = dps_meter.dps(time.elapsed_seconds());
+    text.sections[0].value = format!(
+        "DPS ({:.0}s) {}\nPeak {}\nTotal {}",
+        DPS_WINDOW,
+        format_damage(dps),
+        format_damage(dps_meter.peak_dps),
+        format_damage(dps_meter.total_damage)
+    );
+}
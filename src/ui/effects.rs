@@ -0,0 +1,263 @@
+use crate::core::game_assets::GameAssets;
+use crate::core::state::GameState;
+use crate::gameplay::structures_combat::{CannonFiredEvent, Projectile, StructureHitEvent};
+use crate::ui::culling::CameraView;
+use bevy::prelude::*;
+use bevy::sprite::MaterialMesh2dBundle;
+
+const FLASH_Z: f32 = 8.0; // over the modules and projectiles
+const TRAIL_Z: f32 = 4.0; // under the projectiles
+const MUZZLE_FLASH_LIFETIME: f32 = 0.08; // seconds
+const MUZZLE_FLASH_RADIUS: f32 = 1.6; // meters
+const MUZZLE_FLASH_COLOR: Color = Color::srgb(1.0, 0.95, 0.7);
+const IMPACT_FLASH_LIFETIME: f32 = 0.18; // seconds
+const IMPACT_FLASH_RADIUS: f32 = 2.5; // meters
+const CRITICAL_FLASH_RADIUS: f32 = 4.5; // meters
+const IMPACT_CORE_COLOR: Color = Color::srgb(1.0, 1.0, 0.9);
+const IMPACT_HALO_COLOR: Color = Color::srgb(1.0, 0.55, 0.15);
+const TRAIL_INTERVAL: f32 = 0.02; // seconds between two puffs of a projectile trail
+const TRAIL_LIFETIME: f32 = 0.25; // seconds
+const TRAIL_RADIUS: f32 = 0.5; // meters
+const TRAIL_COLOR: Color = Color::srgba(1.0, 0.8, 0.5, 0.5);
+const SPARKS: u32 = 6; // per hit, twice as many on critical hits
+const SPARK_LIFETIME: f32 = 0.35; // seconds
+const SPARK_RADIUS: f32 = 0.3; // meters
+const SPARK_SPEED: f32 = 30.0; // m/s
+const SPARK_DRAG: f32 = 4.0; // fraction of the speed lost per second
+const SPARK_COLOR: Color = Color::srgb(1.0, 0.85, 0.4);
+const CHIPS: u32 = 3; // slow fragments of hull per hit
+const CHIP_LIFETIME: f32 = 0.8; // seconds
+const CHIP_RADIUS: f32 = 0.45; // meters
+const CHIP_SPEED: f32 = 8.0; // m/s
+const CHIP_DRAG: f32 = 1.5; // fraction of the speed lost per second
+const CHIP_COLOR: Color = Color::srgb(0.45, 0.45, 0.5);
+const SCATTER_ANGLE: f32 = 2.0; // radians of the cone the particles fly out in
+const MAX_POOLED_EFFECTS: usize = 512; // idle effect entities kept for reuse
+
+/// Short lived visual effects making combat readable: muzzle flashes at the cannons when they fire, fading trails
+/// behind the projectiles, and light flashes with sparks and hull chips flying off at the impact points.
+/// Effects are bright discs growing, drifting and fading out. Their entities are pooled, a finished effect is hidden
+/// and reused by the next one instead of being despawned, since heavy fights spawn hundreds of them every second.
+pub struct EffectsPlugin;
+
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EffectPool>().add_systems(
+            Update,
+            (
+                spawn_muzzle_flashes_system,
+                spawn_impact_effects_system,
+                attach_projectile_trails_system,
+                spawn_projectile_trails_system,
+                animate_effects_system,
+            )
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+/// A disc growing from `start_radius` to `end_radius` and drifting while fading out, released to the pool once its
+/// timer is finished.
+#[derive(Component, Debug)]
+pub struct Effect {
+    timer: Timer,
+    start_radius: f32,
+    end_radius: f32,
+    color: Color,
+    velocity: Vec2,
+    /// Fraction of the velocity lost per second.
+    drag: f32,
+}
+
+impl Effect {
+    pub fn new(lifetime: f32, start_radius: f32, end_radius: f32, color: Color) -> Self {
+        Self {
+            timer: Timer::from_seconds(lifetime, TimerMode::Once),
+            start_radius,
+            end_radius,
+            color,
+            velocity: Vec2::ZERO,
+            drag: 0.0,
+        }
+    }
+
+    pub fn with_velocity(mut self, velocity: Vec2, drag: f32) -> Self {
+        self.velocity = velocity;
+        self.drag = drag;
+        self
+    }
+}
+
+/// Hidden effect entities waiting to be reused, each with its own material.
+#[derive(Resource, Debug, Default)]
+pub struct EffectPool(Vec<Entity>);
+
+/// Drops a fading puff behind the projectile at a fixed interval.
+#[derive(Component, Debug)]
+pub struct ProjectileTrail {
+    timer: Timer,
+}
+
+fn spawn_effect(
+    commands: &mut Commands,
+    pool: &mut EffectPool,
+    game_assets: &GameAssets,
+    materials: &mut Assets<ColorMaterial>,
+    position: Vec3,
+    effect: Effect,
+) {
+    let transform = Transform::from_translation(position).with_scale(Vec3::splat(effect.start_radius));
+
+    // The pooled entities may have been cleared along with the world
+    while let Some(entity) = pool.0.pop() {
+        if let Some(mut entity_commands) = commands.get_entity(entity) {
+            entity_commands.insert((transform, Visibility::Inherited, effect));
+            return;
+        }
+    }
+
+    // Every effect fades on its own, they cannot share a material
+    let material = materials.add(ColorMaterial::from(effect.color));
+    commands.spawn((
+        MaterialMesh2dBundle { mesh: game_assets.unit_disc.clone(), material, transform, ..default() },
+        effect,
+    ));
+}
+
+fn spawn_muzzle_flashes_system(
+    mut event_reader: EventReader<CannonFiredEvent>,
+    camera_view: Res<CameraView>,
+    mut pool: ResMut<EffectPool>,
+    game_assets: Res<GameAssets>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut commands: Commands,
+) {
+    for event in event_reader.read() {
+        if !camera_view.should_spawn_cosmetic(event.position) {
+            continue;
+        }
+        let flash =
+            Effect::new(MUZZLE_FLASH_LIFETIME, MUZZLE_FLASH_RADIUS, MUZZLE_FLASH_RADIUS * 0.5, MUZZLE_FLASH_COLOR);
+        // Centered a little ahead of the muzzle, like the burning propellant
+        let position = event.position + event.direction * MUZZLE_FLASH_RADIUS * 0.5;
+        spawn_effect(&mut commands, &mut pool, &game_assets, &mut materials, position.extend(FLASH_Z), flash);
+    }
+}
+
+fn spawn_impact_effects_system(
+    mut event_reader: EventReader<StructureHitEvent>,
+    structures_query: Query<&GlobalTransform>,
+    camera_view: Res<CameraView>,
+    mut pool: ResMut<EffectPool>,
+    game_assets: Res<GameAssets>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut commands: Commands,
+) {
+    for event in event_reader.read() {
+        if !camera_view.should_spawn_cosmetic(event.position) {
+            continue;
+        }
+        let position = event.position.extend(FLASH_Z);
+        let radius = if event.critical { CRITICAL_FLASH_RADIUS } else { IMPACT_FLASH_RADIUS };
+        // A wide orange halo lighting the hull around a small white hot core
+        let halo = Effect::new(IMPACT_FLASH_LIFETIME, radius * 0.5, radius, IMPACT_HALO_COLOR.with_alpha(0.6));
+        let core = Effect::new(IMPACT_FLASH_LIFETIME * 0.6, radius * 0.3, radius * 0.15, IMPACT_CORE_COLOR);
+        spawn_effect(&mut commands, &mut pool, &game_assets, &mut materials, position, halo);
+        spawn_effect(&mut commands, &mut pool, &game_assets, &mut materials, position + Vec3::Z * 0.1, core);
+
+        // Particles fly off the hull, away from the center of the structure hit
+        let outward = structures_query
+            .get(event.structure_entity)
+            .map(|transform| (event.position - transform.translation().truncate()).normalize_or_zero())
+            .ok()
+            .filter(|outward| *outward != Vec2::ZERO)
+            .unwrap_or(Vec2::Y);
+        // The hit position picks where the cone starts, so two hits do not look the same
+        let offset = (event.position.x * 12.9898 + event.position.y * 78.233).sin().fract().abs();
+
+        let sparks = if event.critical { SPARKS * 2 } else { SPARKS };
+        for index in 0..sparks {
+            let direction = scatter_direction(outward, index, sparks, offset);
+            let speed = SPARK_SPEED * (0.6 + 0.4 * ((index as f32 + offset) * 0.618).fract());
+            let spark = Effect::new(SPARK_LIFETIME, SPARK_RADIUS, SPARK_RADIUS * 0.3, SPARK_COLOR)
+                .with_velocity(direction * speed, SPARK_DRAG);
+            spawn_effect(&mut commands, &mut pool, &game_assets, &mut materials, position, spark);
+        }
+        for index in 0..CHIPS {
+            let direction = scatter_direction(outward, index, CHIPS, 1.0 - offset);
+            let chip = Effect::new(CHIP_LIFETIME, CHIP_RADIUS, CHIP_RADIUS * 0.6, CHIP_COLOR)
+                .with_velocity(direction * CHIP_SPEED, CHIP_DRAG);
+            spawn_effect(&mut commands, &mut pool, &game_assets, &mut materials, position - Vec3::Z * 0.1, chip);
+        }
+    }
+}
+
+/// Direction of the `index`th of `count` particles, spread over the scatter cone around `outward`.
+fn scatter_direction(outward: Vec2, index: u32, count: u32, offset: f32) -> Vec2 {
+    let fraction = ((index as f32 + offset) / count as f32).fract();
+    let angle = (fraction - 0.5) * SCATTER_ANGLE;
+    Vec2::from_angle(angle).rotate(outward)
+}
+
+fn attach_projectile_trails_system(projectiles_query: Query<Entity, Added<Projectile>>, mut commands: Commands) {
+    for projectile_entity in &projectiles_query {
+        commands
+            .entity(projectile_entity)
+            .insert(ProjectileTrail { timer: Timer::from_seconds(TRAIL_INTERVAL, TimerMode::Repeating) });
+    }
+}
+
+fn spawn_projectile_trails_system(
+    mut projectiles_query: Query<(&GlobalTransform, &mut ProjectileTrail)>,
+    camera_view: Res<CameraView>,
+    mut pool: ResMut<EffectPool>,
+    game_assets: Res<GameAssets>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (transform, mut trail) in &mut projectiles_query {
+        if !trail.timer.tick(time.delta()).just_finished() {
+            continue;
+        }
+        let position = transform.translation().truncate();
+        if !camera_view.should_spawn_cosmetic(position) {
+            continue;
+        }
+        let puff = Effect::new(TRAIL_LIFETIME, TRAIL_RADIUS, TRAIL_RADIUS * 0.2, TRAIL_COLOR);
+        spawn_effect(&mut commands, &mut pool, &game_assets, &mut materials, position.extend(TRAIL_Z), puff);
+    }
+}
+
+fn animate_effects_system(
+    mut effects_query: Query<(Entity, &mut Effect, &mut Transform, &Handle<ColorMaterial>)>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut pool: ResMut<EffectPool>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    let delta_time = time.delta_seconds();
+
+    for (entity, mut effect, mut transform, material_handle) in &mut effects_query {
+        if effect.timer.tick(time.delta()).finished() {
+            if pool.0.len() < MAX_POOLED_EFFECTS {
+                commands.entity(entity).remove::<Effect>().insert(Visibility::Hidden);
+                pool.0.push(entity);
+            } else {
+                commands.entity(entity).despawn_recursive();
+            }
+            continue;
+        }
+
+        let velocity = effect.velocity * (1.0 - effect.drag * delta_time).max(0.0);
+        effect.velocity = velocity;
+        transform.translation += (velocity * delta_time).extend(0.0);
+
+        let progress = effect.timer.fraction();
+        transform.scale = Vec3::splat(effect.start_radius.lerp(effect.end_radius, progress));
+        if let Some(material) = materials.get_mut(material_handle) {
+            material.color = effect.color.with_alpha(effect.color.alpha() * (1.0 - progress));
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use bevy::prelude::*;
+use bevy::ui::UiSystem;
+
+const FOCUS_OUTLINE_WIDTH: f32 = 2.0; // pixels
+const FOCUS_OUTLINE_COLOR: Color = Color::srgb(1.0, 0.85, 0.3);
+
+/// Keyboard and gamepad navigation between the buttons on screen, shared by every menu.
+///
+/// The arrow keys or the d-pad move the focus, which wraps around at both ends, and enter or the south button
+/// presses the focused button. Pressing sets its `Interaction` like a mouse click would, so the menus keep handling
+/// their buttons in a single place.
+pub struct FocusNavigationPlugin;
+
+impl Plugin for FocusNavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FocusState>()
+            .add_systems(PreUpdate, (focus_navigation_system, focus_highlight_system).chain().after(UiSystem::Focus));
+    }
+}
+
+/// The button selected by the keyboard or gamepad.
+#[derive(Resource, Debug, Default)]
+pub struct FocusState {
+    pub focused: Option<Entity>,
+    /// Button pressed by the navigation last frame, released on the next one.
+    pressed: Option<Entity>,
+    /// Button that had the outline, to clear it when the focus moves.
+    highlighted: Option<Entity>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FocusInput {
+    Previous,
+    Next,
+    Press,
+}
+
+fn read_focus_input(
+    keys: &ButtonInput<KeyCode>,
+    gamepads: &Gamepads,
+    gamepad_buttons: &ButtonInput<GamepadButton>,
+) -> Option<FocusInput> {
+    let gamepad_pressed = |button_type| {
+        gamepads.iter().any(|gamepad| gamepad_buttons.just_pressed(GamepadButton::new(gamepad, button_type)))
+    };
+
+    if keys.any_just_pressed([KeyCode::ArrowUp, KeyCode::ArrowLeft])
+        || gamepad_pressed(GamepadButtonType::DPadUp)
+        || gamepad_pressed(GamepadButtonType::DPadLeft)
+    {
+        Some(FocusInput::Previous)
+    } else if keys.any_just_pressed([KeyCode::ArrowDown, KeyCode::ArrowRight])
+        || gamepad_pressed(GamepadButtonType::DPadDown)
+        || gamepad_pressed(GamepadButtonType::DPadRight)
+    {
+        Some(FocusInput::Next)
+    } else if keys.any_just_pressed([KeyCode::Enter, KeyCode::NumpadEnter]) || gamepad_pressed(GamepadButtonType::South)
+    {
+        Some(FocusInput::Press)
+    } else {
+        None
+    }
+}
+
+fn focus_navigation_system(
+    mut buttons_query: Query<(Entity, &mut Interaction, &GlobalTransform, &ViewVisibility), With<Button>>,
+    mut focus: ResMut<FocusState>,
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+) {
+    // Release the button pressed last frame, the menus have handled it by now
+    if let Some(pressed) = focus.pressed.take() {
+        if let Ok((_, mut interaction, _, _)) = buttons_query.get_mut(pressed) {
+            if *interaction == Interaction::Pressed {
+                *interaction = Interaction::None;
+            }
+        }
+    }
+
+    // Buttons in reading order, top to bottom then left to right
+    let mut buttons: Vec<(Entity, Vec2)> = buttons_query
+        .iter()
+        .filter(|(_, _, _, visibility)| visibility.get())
+        .map(|(entity, _, transform, _)| (entity, transform.translation().truncate()))
+        .collect();
+    buttons.sort_by(|(_, a), (_, b)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+
+    // Hovering a button with the mouse moves the focus too, so both can be used together
+    let hovered = buttons_query
+        .iter_mut()
+        .find(|(_, interaction, _, _)| interaction.is_changed() && **interaction == Interaction::Hovered)
+        .map(|(entity, _, _, _)| entity);
+    if hovered.is_some() {
+        focus.focused = hovered;
+    }
+
+    let focused_index = focus.focused.and_then(|focused| buttons.iter().position(|(entity, _)| *entity == focused));
+    if focused_index.is_none() {
+        // The focused button was closed with its menu
+        focus.focused = None;
+    }
+
+    let Some(input) = read_focus_input(&keys, &gamepads, &gamepad_buttons) else {
+        return;
+    };
+    if buttons.is_empty() {
+        return;
+    }
+
+    let count = buttons.len();
+    match (input, focused_index) {
+        (FocusInput::Previous, Some(index)) => focus.focused = Some(buttons[(index + count - 1) % count].0),
+        (FocusInput::Next, Some(index)) => focus.focused = Some(buttons[(index + 1) % count].0),
+        (FocusInput::Previous, None) => focus.focused = Some(buttons[count - 1].0),
+        (FocusInput::Next, None) => focus.focused = Some(buttons[0].0),
+        (FocusInput::Press, Some(index)) => {
+            let entity = buttons[index].0;
+            if let Ok((_, mut interaction, _, _)) = buttons_query.get_mut(entity) {
+                *interaction = Interaction::Pressed;
+                focus.pressed = Some(entity);
+            }
+        }
+        (FocusInput::Press, None) => {}
+    }
+}
+
+fn focus_highlight_system(mut focus: ResMut<FocusState>, mut commands: Commands) {
+    if focus.highlighted == focus.focused {
+        return;
+    }
+
+    if let Some(previous) = focus.highlighted {
+        if let Some(mut entity_commands) = commands.get_entity(previous) {
+            entity_commands.remove::<Outline>();
+        }
+    }
+    if let Some(focused) = focus.focused {
+        commands.entity(focused).insert(Outline::new(
+            Val::Px(FOCUS_OUTLINE_WIDTH),
+            Val::Px(FOCUS_OUTLINE_WIDTH),
+            FOCUS_OUTLINE_COLOR,
+        ));
+    }
+    focus.highlighted = focus.focused;
+}
@@ -0,0 +1,139 @@
+use crate::core::asset_loader::DataAssetLoader;
+use crate::core::state::GameState;
+use crate::gameplay::power::PowerConsumer;
+use crate::gameplay::structures_combat::StructureHitEvent;
+use crate::ui::audio::{play_sound_at, AudioSettings};
+use crate::ui::culling::CameraView;
+use crate::world::prelude::*;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::Deserialize;
+
+pub const SOUND_BANKS_PATH: &str = "data/sound_banks.ron";
+
+const LOUD_DAMAGE: f32 = 100.0; // damage of a hit played at full volume
+const QUIET_VOLUME: f32 = 0.3; // volume of the lightest hits
+const HEAVY_HIT_SPEED: f32 = 0.85; // playback speed of the heaviest hits, deeper than the light ones
+
+/// Impact sounds of the projectile hits, picked from the sound bank of the material of the module hit and the tier
+/// of the damage dealt: steel clangs, wood cracks, and modules carrying power add an electric zap. Louder and deeper
+/// as the damage grows, and fading with the distance like every sound effect. The banks are read from
+/// `data/sound_banks.ron`.
+pub struct ImpactSoundsPlugin;
+
+impl Plugin for ImpactSoundsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ImpactSounds>()
+            .init_asset::<SoundBanks>()
+            .init_asset_loader::<DataAssetLoader<SoundBanks>>()
+            .add_systems(Startup, load_sound_banks)
+            .add_systems(
+                Update,
+                (apply_sound_banks_system, play_impact_sounds_system).chain().run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+/// Sounds of a bank played from `min_damage` on, until the next tier.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SoundTier {
+    pub min_damage: f32,
+    pub sounds: Vec<String>,
+}
+
+#[derive(Asset, TypePath, Debug, Clone, Default, Deserialize)]
+pub struct SoundBanks {
+    pub materials: HashMap<ModuleMaterialType, Vec<SoundTier>>,
+    /// Played over the material bank when the module hit carries power.
+    pub energy: Vec<SoundTier>,
+}
+
+#[derive(Resource)]
+struct SoundBanksHandle(Handle<SoundBanks>);
+
+/// Loaded sounds of every tier of the banks.
+#[derive(Resource, Debug, Default)]
+pub struct ImpactSounds {
+    materials: HashMap<ModuleMaterialType, Vec<(f32, Vec<Handle<AudioSource>>)>>,
+    energy: Vec<(f32, Vec<Handle<AudioSource>>)>,
+    /// Rotates through the sounds of a tier so the same hit does not repeat the same sample.
+    next_variant: usize,
+}
+
+impl ImpactSounds {
+    fn pick(&mut self, tiers: &[(f32, Vec<Handle<AudioSource>>)], damage: f32) -> Option<Handle<AudioSource>> {
+        let (_, sounds) = tiers.iter().rev().find(|(min_damage, _)| damage >= *min_damage)?;
+        if sounds.is_empty() {
+            return None;
+        }
+        self.next_variant = self.next_variant.wrapping_add(1);
+        Some(sounds[self.next_variant % sounds.len()].clone())
+    }
+}
+
+fn load_tiers(asset_server: &AssetServer, tiers: &[SoundTier]) -> Vec<(f32, Vec<Handle<AudioSource>>)> {
+    let mut tiers: Vec<(f32, Vec<Handle<AudioSource>>)> = tiers
+        .iter()
+        .map(|tier| (tier.min_damage, tier.sounds.iter().map(|path| asset_server.load(path.clone())).collect()))
+        .collect();
+    tiers.sort_by(|(damage1, _), (damage2, _)| damage1.total_cmp(damage2));
+    tiers
+}
+
+fn load_sound_banks(asset_server: Res<AssetServer>, mut commands: Commands) {
+    commands.insert_resource(SoundBanksHandle(asset_server.load(SOUND_BANKS_PATH)));
+}
+
+/// Loads the sounds of the banks whenever the data file is (re)loaded.
+fn apply_sound_banks_system(
+    mut asset_events: EventReader<AssetEvent<SoundBanks>>,
+    handle: Res<SoundBanksHandle>,
+    banks_assets: Res<Assets<SoundBanks>>,
+    asset_server: Res<AssetServer>,
+    mut impact_sounds: ResMut<ImpactSounds>,
+) {
+    if !asset_events.read().any(|event| event.is_loaded_with_dependencies(&handle.0)) {
+        return;
+    }
+    let Some(banks) = banks_assets.get(&handle.0) else {
+        return;
+    };
+
+    impact_sounds.materials = banks
+        .materials
+        .iter()
+        .map(|(material_type, tiers)| (*material_type, load_tiers(&asset_server, tiers)))
+        .collect();
+    impact_sounds.energy = load_tiers(&asset_server, &banks.energy);
+    info!("Impact sound banks loaded for {} materials", impact_sounds.materials.len());
+}
+
+fn play_impact_sounds_system(
+    mut hit_reader: EventReader<StructureHitEvent>,
+    powered_query: Query<(Option<&PowerConsumer>, Has<ReactorModule>)>,
+    mut impact_sounds: ResMut<ImpactSounds>,
+    audio_settings: Res<AudioSettings>,
+    camera_view: Res<CameraView>,
+    mut commands: Commands,
+) {
+    for event in hit_reader.read() {
+        let intensity = (event.damage / LOUD_DAMAGE).clamp(0.0, 1.0);
+        let volume = QUIET_VOLUME + (1.0 - QUIET_VOLUME) * intensity;
+        let speed = 1.0 - (1.0 - HEAVY_HIT_SPEED) * intensity;
+
+        let material_tiers = impact_sounds.materials.get(&event.material_type).cloned().unwrap_or_default();
+        if let Some(sound) = impact_sounds.pick(&material_tiers, event.damage) {
+            play_sound_at(&mut commands, &audio_settings, &camera_view, sound, event.position, volume, speed);
+        }
+
+        let energized = powered_query
+            .get(event.module_entity)
+            .is_ok_and(|(power, is_reactor)| is_reactor || power.is_some_and(|power| power.powered));
+        if energized {
+            let energy_tiers = impact_sounds.energy.clone();
+            if let Some(sound) = impact_sounds.pick(&energy_tiers, event.damage) {
+                play_sound_at(&mut commands, &audio_settings, &camera_view, sound, event.position, volume, speed);
+            }
+        }
+    }
+}
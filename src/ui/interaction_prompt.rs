@@ -0,0 +1,96 @@
+use crate::core::state::GameState;
+use crate::gameplay::docking::DockingPort;
+use crate::gameplay::doors::Door;
+use crate::world::prelude::*;
+use bevy::prelude::*;
+
+const PROMPT_OFFSET: Vec3 = Vec3::new(0.0, 4.0, 10.0); // meters above the module, drawn over the modules
+const PROMPT_TEXT_SCALE: f32 = 0.1; // text is rendered at a readable font size then scaled down to meters
+const PROMPT_FONT_SIZE: f32 = 18.0;
+
+/// Shows what the space key does when the player can use an `Interactable` module.
+pub struct InteractionPromptPlugin;
+
+impl Plugin for InteractionPromptPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::BuildingStructures), spawn_interaction_prompt)
+            .add_systems(Update, update_interaction_prompt_system.run_if(in_state(GameState::InGame)));
+    }
+}
+
+#[derive(Component)]
+struct InteractionPrompt;
+
+fn spawn_interaction_prompt(mut commands: Commands) {
+    commands.spawn((
+        InteractionPrompt,
+        Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle { font_size: PROMPT_FONT_SIZE, color: Color::srgb(1.0, 1.0, 0.6), ..default() },
+            ),
+            transform: Transform::from_scale(Vec3::splat(PROMPT_TEXT_SCALE)),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+    ));
+}
+
+fn prompt_text(
+    interactable: &Interactable,
+    module: &Module,
+    player_entity: Entity,
+    door: Option<&Door>,
+    docking_port: Option<&DockingPort>,
+) -> &'static str {
+    match interactable.kind {
+        InteractionKind::Control if module.entity_connected == Some(player_entity) => "Press SPACE to release control",
+        InteractionKind::Control => "Press SPACE to control",
+        InteractionKind::ToggleDoor if door.is_some_and(|door| door.open) => "Press SPACE to close",
+        InteractionKind::ToggleDoor => "Press SPACE to open",
+        InteractionKind::Undock if docking_port.is_some_and(|port| port.docked_to.is_some()) => "Press SPACE to undock",
+        InteractionKind::Undock => "Fly against another docking port to dock",
+        InteractionKind::TransferCargo => "Press SPACE to pick up or drop cargo",
+    }
+}
+
+/// Places the prompt above the first module the player can use in the structure they are in.
+fn update_interaction_prompt_system(
+    mut prompt_query: Query<(&mut Text, &mut Transform, &mut Visibility), With<InteractionPrompt>>,
+    player_query: Query<(Entity, &GlobalTransform), With<Player>>,
+    player_resource: Res<PlayerResource>,
+    structures_query: Query<(&Structure, &Transform, &Children), Without<InteractionPrompt>>,
+    interactables_query: Query<(&Interactable, &Module, &GlobalTransform, Option<&Door>, Option<&DockingPort>)>,
+) {
+    let Ok((mut text, mut prompt_transform, mut visibility)) = prompt_query.get_single_mut() else {
+        return;
+    };
+
+    let focused = player_query.get_single().ok().and_then(|(player_entity, player_transform)| {
+        let (structure, structure_transform, children) =
+            structures_query.get(player_resource.inside_structure?).ok()?;
+        let player_cell = structure.world_to_grid(player_transform.translation(), structure_transform);
+
+        interactables_query.iter_many(children).find_map(
+            |(interactable, module, module_transform, door, docking_port)| {
+                interactable.is_reachable_from(module, player_cell).then(|| {
+                    (
+                        prompt_text(interactable, module, player_entity, door, docking_port),
+                        module_transform.translation(),
+                    )
+                })
+            },
+        )
+    });
+
+    let Some((prompt, position)) = focused else {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    };
+
+    if text.sections[0].value != prompt {
+        text.sections[0].value = prompt.to_string();
+    }
+    prompt_transform.translation = position + PROMPT_OFFSET;
+    visibility.set_if_neq(Visibility::Inherited);
+}
@@ -0,0 +1,136 @@
+use crate::core::state::GameState;
+use crate::gameplay::journal::Journal;
+use crate::ui::minimap::{Minimap, MinimapSettings};
+use bevy::prelude::*;
+
+const JOURNAL_KEY: KeyCode = KeyCode::KeyJ;
+const JOURNAL_PAGE_SIZE: usize = 8;
+const JOURNAL_WIDTH: f32 = 360.0;
+const JOURNAL_TITLE_COLOR: Color = Color::srgb(1.0, 0.85, 0.3);
+const JOURNAL_DETAIL_COLOR: Color = Color::srgb(0.6, 0.6, 0.6);
+
+/// Pages of the captain's journal shown over the minimap: J opens it while the map is shown, Page Up and Page Down
+/// browse the older and newer entries.
+pub struct JournalPanelPlugin;
+
+impl Plugin for JournalPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<JournalPage>()
+            .add_systems(OnEnter(GameState::BuildingStructures), spawn_journal_panel)
+            .add_systems(
+                Update,
+                (journal_panel_input_system, show_journal_panel_system).chain().run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+/// Page of the journal shown, counted from the newest entries.
+#[derive(Resource, Debug, Default)]
+struct JournalPage(usize);
+
+#[derive(Component)]
+struct JournalPanel;
+
+fn spawn_journal_panel(mut commands: Commands, minimap_settings: Res<MinimapSettings>) {
+    commands.spawn((
+        JournalPanel,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(10.0),
+                bottom: Val::Px(minimap_settings.size + 20.0),
+                width: Val::Px(JOURNAL_WIDTH),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(8.0)),
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+            background_color: BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.75)),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+    ));
+}
+
+fn journal_panel_input_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    minimap_query: Query<&Visibility, (With<Minimap>, Without<JournalPanel>)>,
+    mut panel_query: Query<&mut Visibility, With<JournalPanel>>,
+    journal: Res<Journal>,
+    mut page: ResMut<JournalPage>,
+) {
+    let Ok(mut panel_visibility) = panel_query.get_single_mut() else {
+        return;
+    };
+    let map_shown = minimap_query.get_single().is_ok_and(|visibility| *visibility != Visibility::Hidden);
+
+    // Closes along with the map
+    if !map_shown {
+        panel_visibility.set_if_neq(Visibility::Hidden);
+        return;
+    }
+    if keys.just_pressed(JOURNAL_KEY) {
+        *panel_visibility =
+            if *panel_visibility == Visibility::Hidden { Visibility::Inherited } else { Visibility::Hidden };
+        page.0 = 0;
+    }
+
+    let last_page = journal.entries.len().saturating_sub(1) / JOURNAL_PAGE_SIZE;
+    if keys.just_pressed(KeyCode::PageUp) && page.0 < last_page {
+        page.0 += 1;
+    }
+    if keys.just_pressed(KeyCode::PageDown) && page.0 > 0 {
+        page.0 -= 1;
+    }
+}
+
+fn show_journal_panel_system(
+    panel_query: Query<(Entity, &Visibility), With<JournalPanel>>,
+    journal: Res<Journal>,
+    page: Res<JournalPage>,
+    mut commands: Commands,
+) {
+    if !journal.is_changed() && !page.is_changed() {
+        return;
+    }
+    let Ok((panel_entity, visibility)) = panel_query.get_single() else {
+        return;
+    };
+    // The play time counts up every frame, only rebuild the lines of an open journal
+    if *visibility == Visibility::Hidden {
+        return;
+    }
+
+    let pages = journal.entries.len().div_ceil(JOURNAL_PAGE_SIZE).max(1);
+    let newest = journal.entries.len().saturating_sub(page.0 * JOURNAL_PAGE_SIZE);
+    let oldest = newest.saturating_sub(JOURNAL_PAGE_SIZE);
+
+    let mut panel = commands.entity(panel_entity);
+    panel.despawn_descendants();
+    panel.with_children(|panel| {
+        panel.spawn(TextBundle::from_section(
+            format!("Captain's journal ({}/{})", pages - page.0, pages),
+            TextStyle { font_size: 16.0, color: JOURNAL_TITLE_COLOR, ..default() },
+        ));
+        if journal.entries.is_empty() {
+            panel.spawn(TextBundle::from_section(
+                "Nothing worth writing down yet",
+                TextStyle { font_size: 14.0, color: JOURNAL_DETAIL_COLOR, ..default() },
+            ));
+        }
+        // The newest entries on top
+        for entry in journal.entries[oldest..newest].iter().rev() {
+            panel.spawn(TextBundle::from_sections([
+                TextSection::new(
+                    format!("{} ", entry.timestamp()),
+                    TextStyle { font_size: 14.0, color: JOURNAL_DETAIL_COLOR, ..default() },
+                ),
+                TextSection::new(entry.text.clone(), TextStyle { font_size: 14.0, color: Color::WHITE, ..default() }),
+                TextSection::new(
+                    format!(" ({:.0}, {:.0})", entry.position[0], entry.position[1]),
+                    TextStyle { font_size: 14.0, color: JOURNAL_DETAIL_COLOR, ..default() },
+                ),
+            ]));
+        }
+    });
+}
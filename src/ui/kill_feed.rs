@@ -0,0 +1,144 @@
+use crate::core::state::GameState;
+use crate::world::prelude::*;
+use bevy::prelude::*;
+
+const KILL_FEED_MAX_ENTRIES: usize = 6;
+const KILL_FEED_ENTRY_LIFETIME: f32 = 6.0; // seconds
+const PLAYER_LOSS_COLOR: Color = Color::srgb(1.0, 0.35, 0.3);
+const PLAYER_KILL_COLOR: Color = Color::srgb(0.4, 1.0, 0.4);
+const OTHER_COLOR: Color = Color::srgb(0.75, 0.75, 0.75);
+
+/// Lists the last modules destroyed in the corner of the screen, colored by who lost them.
+pub struct KillFeedPlugin;
+
+impl Plugin for KillFeedPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::BuildingStructures), spawn_kill_feed).add_systems(
+            Update,
+            (add_kill_feed_entries_system, expire_kill_feed_entries_system).chain().run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+#[derive(Component)]
+struct KillFeed;
+
+#[derive(Component)]
+struct KillFeedEntry(Timer);
+
+fn spawn_kill_feed(mut commands: Commands) {
+    commands.spawn((
+        KillFeed,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                right: Val::Px(10.0),
+                top: Val::Px(250.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::FlexEnd,
+                row_gap: Val::Px(2.0),
+                ..default()
+            },
+            ..default()
+        },
+    ));
+}
+
+/// Describes where a module sits in its structure, the first row being the bow.
+fn module_location(structure: &Structure, cell: (i32, i32)) -> &'static str {
+    let width = structure.grid.width as f32;
+    let height = structure.grid.height as f32;
+    // Offsets from the center of the grid, from -0.5 to 0.5
+    let x = (cell.0 as f32 + 0.5) / width - 0.5;
+    let y = (cell.1 as f32 + 0.5) / height - 0.5;
+
+    if x.abs() < 0.2 && y.abs() < 0.2 {
+        "center"
+    } else if y.abs() >= x.abs() {
+        if y < 0.0 {
+            "bow"
+        } else {
+            "aft"
+        }
+    } else if x < 0.0 {
+        "port"
+    } else {
+        "starboard"
+    }
+}
+
+fn add_kill_feed_entries_system(
+    mut event_reader: EventReader<ModuleDestroyedEvent>,
+    modules_query: Query<(&Module, &Parent)>,
+    structures_query: Query<(&Structure, Option<&StructureName>, Has<ControlledByPlayer>)>,
+    controlled_query: Query<Entity, With<ControlledByPlayer>>,
+    kill_feed_query: Query<(Entity, Option<&Children>), With<KillFeed>>,
+    module_registry: Res<ModuleRegistry>,
+    mut commands: Commands,
+) {
+    let Ok((kill_feed_entity, entries)) = kill_feed_query.get_single() else {
+        return;
+    };
+    // The oldest entries are at the top of the feed
+    let mut oldest_entries = entries.map(|entries| entries.to_vec()).unwrap_or_default().into_iter();
+    let mut entries_count = oldest_entries.len();
+    let controlled_structure = controlled_query.get_single().ok();
+
+    for event in event_reader.read() {
+        let Ok((module, parent)) = modules_query.get(event.destroyed_entity) else {
+            continue;
+        };
+        let Ok((structure, name, is_player)) = structures_query.get(parent.get()) else {
+            continue;
+        };
+
+        let module_name = match module.module_type {
+            ModuleType::Custom(symbol) => module_registry.get(symbol).map_or("Module", |definition| definition.name),
+            module_type => module_type.name(),
+        };
+        let location = module_location(structure, event.inner_grid_pos);
+        let ship = name.map_or("Structure", |name| name.ship.as_str());
+        let (text, color) = if is_player {
+            (format!("You lost {} ({})", module_name, location), PLAYER_LOSS_COLOR)
+        } else if event.source.is_some() && event.source == controlled_structure {
+            (format!("{} lost {} ({})", ship, module_name, location), PLAYER_KILL_COLOR)
+        } else {
+            (format!("{} lost {} ({})", ship, module_name, location), OTHER_COLOR)
+        };
+
+        entries_count += 1;
+        if entries_count > KILL_FEED_MAX_ENTRIES {
+            if let Some(oldest) = oldest_entries.next() {
+                commands.entity(oldest).despawn_recursive();
+                entries_count -= 1;
+            }
+        }
+
+        commands.entity(kill_feed_entity).with_children(|kill_feed| {
+            kill_feed.spawn((
+                KillFeedEntry(Timer::from_seconds(KILL_FEED_ENTRY_LIFETIME, TimerMode::Once)),
+                TextBundle::from_section(text, TextStyle { font_size: 14.0, color, ..default() })
+                    .with_style(Style { padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)), ..default() })
+                    .with_background_color(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+            ));
+        });
+    }
+}
+
+fn expire_kill_feed_entries_system(
+    mut entries_query: Query<(Entity, &mut KillFeedEntry, &mut Text)>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, mut entry, mut text) in &mut entries_query {
+        if entry.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        // Fade out during the last second
+        let alpha = entry.0.remaining_secs().min(1.0);
+        for section in &mut text.sections {
+            section.style.color.set_alpha(alpha);
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use crate::core::inputs::InputAction;
+use crate::core::schedule::InGameSet;
+use crate::core::state::GameState;
+use crate::gameplay::sensors::Contacts;
+use crate::gameplay::structures_combat::Projectile;
+use crate::world::prelude::*;
+use bevy::color::palettes::css::*;
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+
+const MINIMAP_REFRESH_INTERVAL: f32 = 0.1; // seconds between dots updates
+const STRUCTURE_DOT_SIZE: f32 = 6.0;
+const DOT_SIZE: f32 = 3.0;
+
+/// Shows the whole world grid in a corner panel, with structures, ore, projectiles and the player as dots.
+/// Only the structures and projectiles detected by the sensors are shown.
+/// Clicking it sets the waypoint of the autopilot.
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MinimapSettings>()
+            .insert_resource(MinimapRefreshTimer(Timer::from_seconds(MINIMAP_REFRESH_INTERVAL, TimerMode::Repeating)))
+            .add_systems(OnEnter(GameState::BuildingStructures), spawn_minimap)
+            .add_systems(Update, (toggle_minimap_system, update_minimap_system).run_if(in_state(GameState::InGame)))
+            .add_systems(Update, minimap_waypoint_system.in_set(InGameSet::UserInput));
+    }
+}
+
+#[derive(Resource, Debug)]
+pub struct MinimapSettings {
+    /// Size in pixels of the longest side of the minimap.
+    pub size: f32,
+    pub toggle_key: KeyCode,
+}
+
+impl Default for MinimapSettings {
+    fn default() -> Self {
+        Self { size: 200.0, toggle_key: KeyCode::KeyM }
+    }
+}
+
+#[derive(Component)]
+pub struct Minimap;
+
+#[derive(Resource)]
+struct MinimapRefreshTimer(Timer);
+
+fn spawn_minimap(mut commands: Commands) {
+    commands.spawn((
+        Minimap,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(10.0),
+                bottom: Val::Px(10.0),
+                border: UiRect::all(Val::Px(1.0)),
+                ..default()
+            },
+            background_color: BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+            border_color: BorderColor(Color::srgb(0.5, 0.5, 0.5)),
+            ..default()
+        },
+        Interaction::default(),
+        RelativeCursorPosition::default(),
+    ));
+}
+
+fn toggle_minimap_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<MinimapSettings>,
+    mut minimap_query: Query<&mut Visibility, With<Minimap>>,
+) {
+    if !keys.just_pressed(settings.toggle_key) {
+        return;
+    }
+
+    for mut visibility in &mut minimap_query {
+        *visibility = match *visibility {
+            Visibility::Hidden => Visibility::Inherited,
+            _ => Visibility::Hidden,
+        };
+    }
+}
+
+/// Sends the world position under a click on the minimap as the waypoint of the autopilot.
+fn minimap_waypoint_system(
+    minimap_query: Query<(&Interaction, &RelativeCursorPosition), (With<Minimap>, Changed<Interaction>)>,
+    grid: Res<Grid>,
+    mut input_event_writer: EventWriter<InputAction>,
+) {
+    let Ok((interaction, relative_cursor)) = minimap_query.get_single() else {
+        return;
+    };
+    let Some(normalized) = relative_cursor.normalized.filter(|_| *interaction == Interaction::Pressed) else {
+        return;
+    };
+
+    // UI positions start at the top left, world positions are centered on the grid
+    let world_size = Vec2::new(grid.width as f32, grid.height as f32) * grid.cell_size;
+    let target =
+        Vec2::new(normalized.x * world_size.x - world_size.x / 2.0, world_size.y / 2.0 - normalized.y * world_size.y);
+    input_event_writer.send(InputAction::SetWaypoint(target));
+}
+
+/// Respawns the minimap dots at the current positions of the tracked entities.
+fn update_minimap_system(
+    mut minimap_query: Query<(Entity, &mut Style, &Visibility), With<Minimap>>,
+    grid: Res<Grid>,
+    settings: Res<MinimapSettings>,
+    mut refresh_timer: ResMut<MinimapRefreshTimer>,
+    time: Res<Time>,
+    structures_query: Query<(Entity, &GlobalTransform, Has<ControlledByPlayer>), With<Structure>>,
+    ore_query: Query<&GlobalTransform, With<Ore>>,
+    projectile_query: Query<(Entity, &GlobalTransform), With<Projectile>>,
+    contacts: Res<Contacts>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    mut commands: Commands,
+) {
+    if !refresh_timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+    let Ok((minimap_entity, mut style, visibility)) = minimap_query.get_single_mut() else {
+        return;
+    };
+    if *visibility == Visibility::Hidden {
+        return;
+    }
+
+    let world_size = Vec2::new(grid.width as f32, grid.height as f32) * grid.cell_size;
+    let scale = settings.size / world_size.max_element();
+    let map_size = world_size * scale;
+    style.width = Val::Px(map_size.x);
+    style.height = Val::Px(map_size.y);
+
+    // World positions are centered on the grid, UI positions start at the top left
+    let to_map = |transform: &GlobalTransform| {
+        let position = transform.translation().truncate();
+        Vec2::new(position.x + world_size.x / 2.0, world_size.y / 2.0 - position.y) * scale
+    };
+
+    let mut dots: Vec<(Vec2, f32, Color)> = Vec::new();
+    for (structure_entity, transform, controlled) in &structures_query {
+        if !contacts.is_detected(structure_entity) {
+            continue;
+        }
+        let color = if controlled { Color::from(DODGER_BLUE) } else { Color::from(LIGHT_GRAY) };
+        dots.push((to_map(transform), STRUCTURE_DOT_SIZE, color));
+    }
+    dots.extend(ore_query.iter().map(|transform| (to_map(transform), DOT_SIZE, Color::from(LIME))));
+    dots.extend(
+        projectile_query
+            .iter()
+            .filter(|(projectile, _)| contacts.is_detected(*projectile))
+            .map(|(_, transform)| (to_map(transform), DOT_SIZE, Color::from(WHITE))),
+    );
+    dots.extend(player_query.iter().map(|transform| (to_map(transform), DOT_SIZE, Color::from(YELLOW))));
+
+    commands.entity(minimap_entity).despawn_descendants().with_children(|minimap| {
+        for (position, size, color) in dots {
+            if position.x < 0.0 || position.y < 0.0 || position.x > map_size.x || position.y > map_size.y {
+                continue;
+            }
+            minimap.spawn(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(position.x - size / 2.0),
+                    top: Val::Px(position.y - size / 2.0),
+                    width: Val::Px(size),
+                    height: Val::Px(size),
+                    ..default()
+                },
+                background_color: BackgroundColor(color),
+                ..default()
+            });
+        }
+    });
+}
@@ -1,3 +1,29 @@
+pub mod asset_error;
+pub mod audio;
 pub mod camera;
+pub mod console;
+pub mod culling;
+pub mod damage;
+pub mod damage_prediction;
 pub mod debug;
+pub mod dialogue;
+pub mod dps_meter;
+pub mod effects;
+pub mod focus;
+pub mod impact_sounds;
+pub mod interaction_prompt;
+pub mod journal;
+pub mod kill_feed;
+pub mod minimap;
+pub mod module_health;
 pub mod prelude;
+pub mod profiler;
+pub mod range_ring;
+pub mod save_menu;
+pub mod scan_overlay;
+pub mod scenario_menu;
+pub mod smoke_overlay;
+pub mod structure_hud;
+pub mod target_lock;
+pub mod toasts;
+pub mod world_text;
@@ -0,0 +1,166 @@
+use crate::core::state::GameState;
+use crate::gameplay::livery::{paint_modules_system, Paint};
+use crate::world::prelude::*;
+use bevy::prelude::*;
+
+const DAMAGED_TINT: Color = Color::srgb(0.15, 0.05, 0.05); // color of a module about to break
+const HEALTH_BAR_HEIGHT: f32 = 0.5;
+const HEALTH_BAR_BACKGROUND: Color = Color::srgba(0.0, 0.0, 0.0, 0.6);
+
+/// Darkens damaged modules and optionally shows a small health bar above them.
+pub struct ModuleHealthVisualPlugin {
+    pub health_bars: bool,
+}
+
+impl Default for ModuleHealthVisualPlugin {
+    fn default() -> Self {
+        Self { health_bars: true }
+    }
+}
+
+impl Plugin for ModuleHealthVisualPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (store_module_base_color_system, tint_damaged_modules_system)
+                .chain()
+                .after(paint_modules_system)
+                .run_if(in_state(GameState::InGame)),
+        );
+
+        if self.health_bars {
+            app.add_systems(
+                Update,
+                (spawn_health_bars_system, update_health_bars_system)
+                    .chain()
+                    .after(tint_damaged_modules_system)
+                    .run_if(in_state(GameState::InGame)),
+            );
+        }
+    }
+}
+
+/// Color of the module when intact, with its livery, the damage tint is mixed into it.
+#[derive(Component, Debug)]
+struct ModuleBaseColor(Color);
+
+#[derive(Component, Debug)]
+struct HealthBar {
+    width: f32,
+}
+
+#[derive(Component, Debug)]
+struct HealthBarFill;
+
+/// Stores the color of new modules, and of repainted modules whose damage tint is mixed again on the new paint.
+fn store_module_base_color_system(
+    query: Query<
+        (Entity, &ModuleMaterial, &Handle<ColorMaterial>, Option<&Paint>),
+        Or<(Added<Module>, Changed<Paint>)>,
+    >,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut commands: Commands,
+) {
+    for (entity, module_material, material_handle, paint) in &query {
+        if let Some(material) = materials.get_mut(material_handle) {
+            let base_color = paint.map_or(material.color, |paint| paint.color);
+            let tint = base_color.mix(&DAMAGED_TINT, 1.0 - module_material.health_ratio());
+            material.color = tint.with_alpha(material.color.alpha());
+            commands.entity(entity).insert(ModuleBaseColor(base_color));
+        }
+    }
+}
+
+/// Mixes the damage tint into damaged modules, repaired modules get their color back the same way.
+fn tint_damaged_modules_system(
+    query: Query<(&ModuleMaterial, &ModuleBaseColor, &Handle<ColorMaterial>), Changed<ModuleMaterial>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    for (module_material, base_color, material_handle) in &query {
+        if let Some(material) = materials.get_mut(material_handle) {
+            let tint = base_color.0.mix(&DAMAGED_TINT, 1.0 - module_material.health_ratio());
+            // Keep the transparency of open doors
+            material.color = tint.with_alpha(material.color.alpha());
+        }
+    }
+}
+
+fn spawn_health_bars_system(
+    mut event_reader: EventReader<ModuleTookDamageEvent>,
+    modules_query: Query<(&Parent, Option<&Children>), With<Module>>,
+    structures_query: Query<&Structure>,
+    health_bar_query: Query<(), With<HealthBar>>,
+    mut commands: Commands,
+) {
+    for event in event_reader.read() {
+        let Ok((parent, children)) = modules_query.get(event.module_entity) else {
+            continue;
+        };
+        if children.is_some_and(|children| children.iter().any(|child| health_bar_query.contains(*child))) {
+            continue;
+        }
+        let Ok(structure) = structures_query.get(parent.get()) else {
+            continue;
+        };
+        let width = structure.grid.cell_size * MODULE_MESH_SCALE_FACTOR;
+        let offset = Vec3::new(0.0, structure.grid.cell_size / 2.0, 2.0);
+
+        commands.entity(event.module_entity).with_children(|module| {
+            module
+                .spawn((
+                    HealthBar { width },
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: HEALTH_BAR_BACKGROUND,
+                            custom_size: Some(Vec2::new(width, HEALTH_BAR_HEIGHT)),
+                            ..default()
+                        },
+                        transform: Transform::from_translation(offset),
+                        ..default()
+                    },
+                ))
+                .with_children(|bar| {
+                    bar.spawn((
+                        HealthBarFill,
+                        SpriteBundle {
+                            sprite: Sprite {
+                                color: Color::srgb(0.0, 1.0, 0.0),
+                                custom_size: Some(Vec2::new(width, HEALTH_BAR_HEIGHT)),
+                                ..default()
+                            },
+                            transform: Transform::from_xyz(0.0, 0.0, 0.1),
+                            ..default()
+                        },
+                    ));
+                });
+        });
+    }
+}
+
+/// Resizes the health bars to the module health and removes them once the module is fully repaired.
+fn update_health_bars_system(
+    modules_query: Query<(&ModuleMaterial, &Children)>,
+    health_bar_query: Query<(Entity, &HealthBar, &Children)>,
+    mut fill_query: Query<(&mut Sprite, &mut Transform), With<HealthBarFill>>,
+    mut commands: Commands,
+) {
+    for (module_material, children) in &modules_query {
+        for (bar_entity, health_bar, bar_children) in health_bar_query.iter_many(children) {
+            let ratio = module_material.health_ratio();
+            if ratio >= 1.0 {
+                commands.entity(bar_entity).despawn_recursive();
+                continue;
+            }
+
+            for &fill_entity in bar_children {
+                if let Ok((mut sprite, mut transform)) = fill_query.get_mut(fill_entity) {
+                    let fill_width = health_bar.width * ratio;
+                    sprite.custom_size = Some(Vec2::new(fill_width, HEALTH_BAR_HEIGHT));
+                    sprite.color = Color::srgb(1.0 - ratio, ratio, 0.0);
+                    // Keep the bar anchored on its left side
+                    transform.translation.x = (fill_width - health_bar.width) / 2.0;
+                }
+            }
+        }
+    }
+}
@@ -1,2 +1,28 @@
+pub use super::asset_error::*;
+pub use super::audio::*;
 pub use super::camera::*;
+pub use super::console::*;
+pub use super::culling::*;
+pub use super::damage::*;
+pub use super::damage_prediction::*;
 pub use super::debug::*;
+pub use super::dialogue::*;
+pub use super::dps_meter::*;
+pub use super::effects::*;
+pub use super::focus::*;
+pub use super::impact_sounds::*;
+pub use super::interaction_prompt::*;
+pub use super::journal::*;
+pub use super::kill_feed::*;
+pub use super::minimap::*;
+pub use super::module_health::*;
+pub use super::profiler::*;
+pub use super::range_ring::*;
+pub use super::save_menu::*;
+pub use super::scan_overlay::*;
+pub use super::scenario_menu::*;
+pub use super::smoke_overlay::*;
+pub use super::structure_hud::*;
+pub use super::target_lock::*;
+pub use super::toasts::*;
+pub use super::world_text::*;
@@ -0,0 +1,106 @@
+use crate::core::profiling::{ENTITY_COUNT_PREFIX, PROFILE_PREFIX};
+use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
+use bevy::prelude::*;
+
+const PROFILER_REFRESH_INTERVAL: f32 = 0.5; // seconds between overlay text updates
+
+/// Overlay listing the smoothed frame cost of every profiled system and the entity count of every subsystem,
+/// toggled with F3.
+pub struct ProfilerOverlayPlugin;
+
+impl Plugin for ProfilerOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ProfilerRefreshTimer(Timer::from_seconds(PROFILER_REFRESH_INTERVAL, TimerMode::Repeating)))
+            .add_systems(Update, (toggle_profiler_overlay_system, update_profiler_overlay_system).chain());
+    }
+}
+
+#[derive(Component)]
+struct ProfilerOverlay;
+
+#[derive(Resource)]
+struct ProfilerRefreshTimer(Timer);
+
+fn toggle_profiler_overlay_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    overlay_query: Query<Entity, With<ProfilerOverlay>>,
+    mut commands: Commands,
+) {
+    // Ctrl+F3 toggles a debug overlay instead
+    if !keys.just_pressed(KeyCode::F3) || keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+
+    if let Ok(overlay_entity) = overlay_query.get_single() {
+        commands.entity(overlay_entity).despawn_recursive();
+        return;
+    }
+
+    commands.spawn((
+        ProfilerOverlay,
+        TextBundle::from_section("Profiler", TextStyle { font_size: 14.0, color: Color::WHITE, ..default() })
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                right: Val::Px(10.0),
+                top: Val::Px(40.0),
+                padding: UiRect::all(Val::Px(8.0)),
+                ..default()
+            })
+            .with_background_color(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+    ));
+}
+
+fn update_profiler_overlay_system(
+    mut overlay_query: Query<&mut Text, With<ProfilerOverlay>>,
+    diagnostics: Res<DiagnosticsStore>,
+    mut refresh_timer: ResMut<ProfilerRefreshTimer>,
+    time: Res<Time<Real>>,
+) {
+    if !refresh_timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+    let Ok(mut text) = overlay_query.get_single_mut() else {
+        return;
+    };
+
+    let frame_time = diagnostics.get(&FrameTimeDiagnosticsPlugin::FRAME_TIME).and_then(|d| d.smoothed());
+
+    let mut costs: Vec<(&str, f64)> = diagnostics
+        .iter()
+        .filter_map(|diagnostic| {
+            let name = diagnostic.path().as_str().strip_prefix(PROFILE_PREFIX)?;
+            Some((name, diagnostic.smoothed().unwrap_or_default()))
+        })
+        .collect();
+    // Most expensive systems first
+    costs.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let mut summary = match frame_time {
+        Some(frame_time) => format!("Profiler (F3) - frame {:.2}ms\n", frame_time),
+        None => "Profiler (F3)\n".to_string(),
+    };
+    for (name, cost) in costs {
+        match frame_time {
+            Some(frame_time) if frame_time > 0.0 => {
+                summary.push_str(&format!("{:<22}{:>7.3}ms {:>5.1}%\n", name, cost, cost / frame_time * 100.0))
+            }
+            _ => summary.push_str(&format!("{:<22}{:>7.3}ms\n", name, cost)),
+        }
+    }
+
+    let mut entity_counts: Vec<(&str, f64)> = diagnostics
+        .iter()
+        .filter_map(|diagnostic| {
+            let name = diagnostic.path().as_str().strip_prefix(ENTITY_COUNT_PREFIX)?;
+            Some((name, diagnostic.value().unwrap_or_default()))
+        })
+        .collect();
+    entity_counts.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    summary.push_str("Entities\n");
+    for (name, count) in entity_counts {
+        summary.push_str(&format!("{:<22}{:>7}\n", name, count as u64));
+    }
+
+    text.sections[0].value = summary;
+}
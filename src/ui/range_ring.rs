@@ -0,0 +1,45 @@
+use crate::core::state::GameState;
+use crate::gameplay::structures_combat::{SelectedWeaponGroup, WeaponGroup};
+use crate::world::prelude::*;
+use bevy::prelude::*;
+
+const RANGE_RING_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.12);
+const RANGE_RING_SEGMENTS: usize = 128; // the ring is large, it needs more segments than the default to look round
+
+/// Faint ring around the structure flown by the player at the range of its selected weapon group, showing how far
+/// its rounds fly before expiring.
+pub struct RangeRingPlugin;
+
+impl Plugin for RangeRingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, draw_range_ring_system.run_if(in_state(GameState::InGame)));
+    }
+}
+
+fn draw_range_ring_system(
+    mut gizmos: Gizmos,
+    controlled_query: Query<(&GlobalTransform, &Children), With<ControlledByPlayer>>,
+    guns_query: Query<(Has<CannonModule>, Has<AcceleratorModule>)>,
+    player_resource: Res<PlayerResource>,
+    selected_weapon_group: Res<SelectedWeaponGroup>,
+) {
+    if !player_resource.is_controlling_structure {
+        return;
+    }
+    let Ok((structure_transform, children)) = controlled_query.get_single() else {
+        return;
+    };
+
+    // No ring for a weapon group the structure does not carry
+    let armed = guns_query.iter_many(children).any(|(is_cannon, is_accelerator)| match selected_weapon_group.0 {
+        WeaponGroup::Cannons => is_cannon,
+        WeaponGroup::Spinal => is_accelerator,
+    });
+    if !armed {
+        return;
+    }
+
+    gizmos
+        .circle_2d(structure_transform.translation().truncate(), selected_weapon_group.0.range(), RANGE_RING_COLOR)
+        .resolution(RANGE_RING_SEGMENTS);
+}
@@ -0,0 +1,139 @@
+use crate::core::prelude::*;
+use bevy::prelude::*;
+use std::time::SystemTime;
+
+pub struct SaveMenuPlugin;
+
+impl Plugin for SaveMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (toggle_save_menu_system, save_menu_button_system)
+                .chain()
+                .run_if(in_state(GameState::InGame).or_else(in_state(GameState::Paused))),
+        );
+    }
+}
+
+#[derive(Component)]
+struct SaveMenu;
+
+#[derive(Component, Debug, Clone, Copy)]
+enum SaveMenuAction {
+    Save,
+    Load(usize),
+    Delete(usize),
+}
+
+const BUTTON_COLOR: Color = Color::srgb(0.25, 0.25, 0.25);
+const BUTTON_HOVERED_COLOR: Color = Color::srgb(0.35, 0.35, 0.35);
+
+fn toggle_save_menu_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    menu_query: Query<Entity, With<SaveMenu>>,
+    settings: Res<SaveSettings>,
+    mut commands: Commands,
+) {
+    if !keys.just_pressed(KeyCode::F5) {
+        return;
+    }
+
+    if let Ok(menu_entity) = menu_query.get_single() {
+        commands.entity(menu_entity).despawn_recursive();
+    } else {
+        spawn_save_menu(&mut commands, &settings);
+    }
+}
+
+fn spawn_save_menu(commands: &mut Commands, settings: &SaveSettings) {
+    let text_style = TextStyle { font_size: 16.0, color: Color::WHITE, ..default() };
+
+    commands
+        .spawn((
+            SaveMenu,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(10.0),
+                    top: Val::Px(40.0),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(8.0)),
+                    row_gap: Val::Px(4.0),
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+                ..default()
+            },
+        ))
+        .with_children(|menu| {
+            menu.spawn(TextBundle::from_section("Saves (F5 to close)", text_style.clone()));
+            spawn_button(menu, "Save now", SaveMenuAction::Save, &text_style);
+
+            let now = SystemTime::now();
+            for slot in settings.list_slots() {
+                let age = now.duration_since(slot.modified).map(|age| age.as_secs()).unwrap_or_default();
+
+                menu.spawn(NodeBundle {
+                    style: Style { flex_direction: FlexDirection::Row, column_gap: Val::Px(8.0), ..default() },
+                    ..default()
+                })
+                .with_children(|row| {
+                    row.spawn(TextBundle::from_section(
+                        format!("Slot {} - {}s ago", slot.index + 1, age),
+                        text_style.clone(),
+                    ));
+                    spawn_button(row, "Load", SaveMenuAction::Load(slot.index), &text_style);
+                    spawn_button(row, "Delete", SaveMenuAction::Delete(slot.index), &text_style);
+                });
+            }
+        });
+}
+
+fn spawn_button(parent: &mut ChildBuilder, label: &str, action: SaveMenuAction, text_style: &TextStyle) {
+    parent
+        .spawn((
+            action,
+            ButtonBundle {
+                style: Style { padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)), ..default() },
+                background_color: BackgroundColor(BUTTON_COLOR),
+                ..default()
+            },
+        ))
+        .with_children(|button| {
+            button.spawn(TextBundle::from_section(label, text_style.clone()));
+        });
+}
+
+fn save_menu_button_system(
+    mut button_query: Query<(&Interaction, &SaveMenuAction, &mut BackgroundColor), Changed<Interaction>>,
+    menu_query: Query<Entity, With<SaveMenu>>,
+    mut autosave_writer: EventWriter<AutosaveEvent>,
+    mut load_writer: EventWriter<LoadGameEvent>,
+    mut delete_writer: EventWriter<DeleteSaveEvent>,
+    mut commands: Commands,
+) {
+    for (interaction, action, mut background_color) in &mut button_query {
+        match interaction {
+            Interaction::Pressed => {
+                match action {
+                    SaveMenuAction::Save => {
+                        autosave_writer.send(AutosaveEvent);
+                    }
+                    SaveMenuAction::Load(slot) => {
+                        load_writer.send(LoadGameEvent { slot: *slot });
+                    }
+                    SaveMenuAction::Delete(slot) => {
+                        delete_writer.send(DeleteSaveEvent { slot: *slot });
+                    }
+                }
+
+                // The slot list is stale after any action, close the menu
+                for menu_entity in &menu_query {
+                    commands.entity(menu_entity).despawn_recursive();
+                }
+            }
+            Interaction::Hovered => *background_color = BackgroundColor(BUTTON_HOVERED_COLOR),
+            Interaction::None => *background_color = BackgroundColor(BUTTON_COLOR),
+        }
+    }
+}
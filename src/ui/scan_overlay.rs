@@ -0,0 +1,60 @@
+use crate::core::state::GameState;
+use crate::gameplay::scanning::ScanReveals;
+use crate::world::prelude::*;
+use bevy::color::palettes::css::{BURLYWOOD, LIGHT_SKY_BLUE, LIGHT_SLATE_GRAY, LIME, RED};
+use bevy::prelude::*;
+
+const MATERIAL_OUTLINE_SCALE: f32 = 0.9; // of the cell size
+const HEALTH_MARK_SCALE: f32 = 0.6; // of the cell size, for a module at full health
+
+/// Outlines the modules of the structures revealed by a scan of the controlled structure, colored by material, with
+/// an inner mark shrinking and turning red as they lose their structural points.
+pub struct ScanOverlayPlugin;
+
+impl Plugin for ScanOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, draw_scan_overlay_system.run_if(in_state(GameState::InGame)));
+    }
+}
+
+pub fn material_overlay_color(material_type: ModuleMaterialType) -> Color {
+    match material_type {
+        ModuleMaterialType::Steel => Color::from(LIGHT_SLATE_GRAY),
+        ModuleMaterialType::Wood => Color::from(BURLYWOOD),
+        ModuleMaterialType::Aluminum => Color::from(LIGHT_SKY_BLUE),
+    }
+}
+
+fn draw_scan_overlay_system(
+    mut gizmos: Gizmos,
+    controlled_query: Query<&ScanReveals, With<ControlledByPlayer>>,
+    structures_query: Query<(&Structure, &Transform, &Children)>,
+    modules_query: Query<(&Module, &ModuleMaterial)>,
+) {
+    let Ok(reveals) = controlled_query.get_single() else {
+        return;
+    };
+
+    for (structure, structure_transform, children) in structures_query.iter_many(reveals.0.keys()) {
+        let rotation = structure_transform.rotation.to_euler(EulerRot::XYZ).2;
+        for (module, module_material) in modules_query.iter_many(children) {
+            let (x, y) = module.inner_grid_pos;
+            let center = structure.grid_cell_center_world_position(x, y, structure_transform);
+            let cell_size = structure.grid.cell_size;
+            let health = module_material.health_ratio();
+
+            gizmos.rect_2d(
+                center,
+                rotation,
+                Vec2::splat(cell_size * MATERIAL_OUTLINE_SCALE),
+                material_overlay_color(module_material.material_type),
+            );
+            gizmos.rect_2d(
+                center,
+                rotation,
+                Vec2::splat(cell_size * HEALTH_MARK_SCALE * health.max(0.1)),
+                Color::from(RED).mix(&Color::from(LIME), health),
+            );
+        }
+    }
+}
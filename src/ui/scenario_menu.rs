@@ -0,0 +1,110 @@
+use crate::core::prelude::*;
+use crate::gameplay::scenario::{list_scenarios, LoadScenarioEvent, SCENARIOS_DIRECTORY};
+use bevy::prelude::*;
+use std::path::PathBuf;
+
+const SCENARIO_MENU_TOGGLE_KEY: KeyCode = KeyCode::F9;
+
+/// Lists the scenario files of the scenarios folder, picking one starts it.
+pub struct ScenarioMenuPlugin;
+
+impl Plugin for ScenarioMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (toggle_scenario_menu_system, scenario_menu_button_system).chain().run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+#[derive(Component)]
+struct ScenarioMenu;
+
+#[derive(Component, Debug, Clone)]
+struct ScenarioButton(PathBuf);
+
+const BUTTON_COLOR: Color = Color::srgb(0.25, 0.25, 0.25);
+const BUTTON_HOVERED_COLOR: Color = Color::srgb(0.35, 0.35, 0.35);
+
+fn toggle_scenario_menu_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    menu_query: Query<Entity, With<ScenarioMenu>>,
+    mut commands: Commands,
+) {
+    if !keys.just_pressed(SCENARIO_MENU_TOGGLE_KEY) {
+        return;
+    }
+
+    if let Ok(menu_entity) = menu_query.get_single() {
+        commands.entity(menu_entity).despawn_recursive();
+    } else {
+        spawn_scenario_menu(&mut commands);
+    }
+}
+
+fn spawn_scenario_menu(commands: &mut Commands) {
+    let text_style = TextStyle { font_size: 16.0, color: Color::WHITE, ..default() };
+    let scenarios = list_scenarios();
+
+    commands
+        .spawn((
+            ScenarioMenu,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(10.0),
+                    top: Val::Px(40.0),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(8.0)),
+                    row_gap: Val::Px(4.0),
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+                ..default()
+            },
+        ))
+        .with_children(|menu| {
+            menu.spawn(TextBundle::from_section("Scenarios (F9 to close)", text_style.clone()));
+            if scenarios.is_empty() {
+                menu.spawn(TextBundle::from_section(
+                    format!("No scenario in the {} folder", SCENARIOS_DIRECTORY),
+                    text_style.clone(),
+                ));
+            }
+
+            for path in scenarios {
+                let label = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+                menu.spawn((
+                    ScenarioButton(path),
+                    ButtonBundle {
+                        style: Style { padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)), ..default() },
+                        background_color: BackgroundColor(BUTTON_COLOR),
+                        ..default()
+                    },
+                ))
+                .with_children(|button| {
+                    button.spawn(TextBundle::from_section(label, text_style.clone()));
+                });
+            }
+        });
+}
+
+fn scenario_menu_button_system(
+    mut button_query: Query<(&Interaction, &ScenarioButton, &mut BackgroundColor), Changed<Interaction>>,
+    menu_query: Query<Entity, With<ScenarioMenu>>,
+    mut load_writer: EventWriter<LoadScenarioEvent>,
+    mut commands: Commands,
+) {
+    for (interaction, button, mut background_color) in &mut button_query {
+        match interaction {
+            Interaction::Pressed => {
+                load_writer.send(LoadScenarioEvent { path: button.0.clone() });
+                for menu_entity in &menu_query {
+                    commands.entity(menu_entity).despawn_recursive();
+                }
+            }
+            Interaction::Hovered => *background_color = BackgroundColor(BUTTON_HOVERED_COLOR),
+            Interaction::None => *background_color = BackgroundColor(BUTTON_COLOR),
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use crate::core::state::GameState;
+use crate::gameplay::contaminants::Contamination;
+use crate::world::prelude::*;
+use bevy::prelude::*;
+
+const SMOKE_COLOR: Srgba = Srgba::rgb(0.15, 0.15, 0.15);
+const COOLANT_COLOR: Srgba = Srgba::rgb(0.6, 0.85, 0.9);
+const MAX_OPACITY: f32 = 0.85; // in a saturated cell
+
+/// Veils the screen with the contaminants of the cell the player stands in: smoke darkens the view, coolant mist
+/// whitens it.
+pub struct SmokeOverlayPlugin;
+
+impl Plugin for SmokeOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::BuildingStructures), spawn_smoke_overlay)
+            .add_systems(Update, update_smoke_overlay_system.run_if(in_state(GameState::InGame)));
+    }
+}
+
+#[derive(Component)]
+struct SmokeOverlay;
+
+fn spawn_smoke_overlay(mut commands: Commands) {
+    commands.spawn((
+        SmokeOverlay,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            background_color: Color::NONE.into(),
+            ..default()
+        },
+    ));
+}
+
+fn update_smoke_overlay_system(
+    mut overlay_query: Query<&mut BackgroundColor, With<SmokeOverlay>>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    structures_query: Query<(&Transform, &Structure, &Contamination)>,
+    player_resource: Res<PlayerResource>,
+) {
+    let Ok(mut background_color) = overlay_query.get_single_mut() else {
+        return;
+    };
+
+    let levels = player_resource
+        .inside_structure
+        .and_then(|structure_entity| structures_query.get(structure_entity).ok())
+        .zip(player_query.get_single().ok())
+        .map(|((structure_transform, structure, contamination), player_transform)| {
+            contamination.levels_at(structure.world_to_grid(player_transform.translation(), structure_transform))
+        })
+        .unwrap_or_default();
+
+    let total = levels.smoke + levels.coolant;
+    let color = if total > 0.0 {
+        SMOKE_COLOR.mix(&COOLANT_COLOR, levels.coolant / total).with_alpha(levels.total() * MAX_OPACITY)
+    } else {
+        Srgba::NONE
+    };
+    background_color.set_if_neq(BackgroundColor(color.into()));
+}
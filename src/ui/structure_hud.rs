@@ -0,0 +1,195 @@
+use crate::core::state::GameState;
+use crate::gameplay::contaminants::{Contaminant, Contamination};
+use crate::gameplay::heat::HeatStore;
+use crate::gameplay::maneuvers::ManeuverQueue;
+use crate::gameplay::scanning::{ScanChannel, ScanReveals};
+use crate::gameplay::structures_combat::SelectedWeaponGroup;
+use crate::world::prelude::*;
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+/// Bar at the top of the screen describing the structure controlled by the player.
+pub struct StructureHudPlugin;
+
+impl Plugin for StructureHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<StructureHudSummary>()
+            .add_systems(OnEnter(GameState::BuildingStructures), spawn_structure_hud)
+            .add_systems(
+                Update,
+                (
+                    toggle_structure_hud_system,
+                    update_modules_summary_system,
+                    update_pressurization_summary_system,
+                    update_structure_hud_text_system,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+/// Aggregated state of the controlled structure, only recomputed when its modules or rooms change.
+#[derive(Resource, Debug, Default)]
+pub struct StructureHudSummary {
+    pub structural_points: f32,
+    pub max_structural_points: f32,
+    pub engines: u32,
+    pub cannons: u32,
+    pub sealed_rooms: u32,
+    pub rooms: u32,
+}
+
+#[derive(Component)]
+struct StructureHud;
+
+fn spawn_structure_hud(mut commands: Commands) {
+    commands.spawn((
+        StructureHud,
+        TextBundle::from_section("", TextStyle { font_size: 16.0, color: Color::WHITE, ..default() })
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.0),
+                left: Val::Percent(35.0),
+                padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                ..default()
+            })
+            .with_background_color(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+        Visibility::Hidden,
+    ));
+}
+
+fn toggle_structure_hud_system(
+    player_resource: Res<PlayerResource>,
+    mut hud_query: Query<&mut Visibility, With<StructureHud>>,
+) {
+    if !player_resource.is_changed() {
+        return;
+    }
+
+    for mut visibility in &mut hud_query {
+        let target = if player_resource.is_controlling_structure { Visibility::Inherited } else { Visibility::Hidden };
+        visibility.set_if_neq(target);
+    }
+}
+
+/// Sums the modules of the controlled structure when it changes, or when one of its modules is damaged or
+/// repaired.
+fn update_modules_summary_system(
+    controlled_structure_query: Query<(Entity, Ref<Children>, Ref<ControlledByPlayer>), With<Structure>>,
+    changed_modules_query: Query<&Parent, Changed<ModuleMaterial>>,
+    modules_query: Query<(&ModuleMaterial, Has<EngineModule>, Has<CannonModule>)>,
+    mut summary: ResMut<StructureHudSummary>,
+) {
+    let Ok((structure_entity, children, controlled)) = controlled_structure_query.get_single() else {
+        return;
+    };
+    let modules_changed = changed_modules_query.iter().any(|parent| parent.get() == structure_entity);
+    if !controlled.is_added() && !children.is_changed() && !modules_changed {
+        return;
+    }
+
+    let mut structural_points = 0.0;
+    let mut max_structural_points = 0.0;
+    let mut engines = 0;
+    let mut cannons = 0;
+    for (module_material, is_engine, is_cannon) in modules_query.iter_many(children.iter()) {
+        structural_points += module_material.structural_points;
+        max_structural_points += module_material.max_structural_points;
+        engines += is_engine as u32;
+        cannons += is_cannon as u32;
+    }
+
+    summary.structural_points = structural_points;
+    summary.max_structural_points = max_structural_points;
+    summary.engines = engines;
+    summary.cannons = cannons;
+}
+
+fn update_pressurization_summary_system(
+    controlled_structure_query: Query<(Ref<Pressurization>, Ref<ControlledByPlayer>), With<Structure>>,
+    mut summary: ResMut<StructureHudSummary>,
+) {
+    let Ok((pressurization, controlled)) = controlled_structure_query.get_single() else {
+        return;
+    };
+    if !pressurization.is_changed() && !controlled.is_added() {
+        return;
+    }
+
+    summary.rooms = pressurization.rooms.len() as u32;
+    summary.sealed_rooms = pressurization.rooms.values().filter(|room| !room.exposed).count() as u32;
+}
+
+fn update_structure_hud_text_system(
+    mut hud_query: Query<(&mut Text, &Visibility), With<StructureHud>>,
+    controlled_structure_query: Query<
+        (
+            &LinearVelocity,
+            &Pressurization,
+            Option<&Contamination>,
+            Option<&HeatStore>,
+            Option<&ManeuverQueue>,
+            Option<&ScanChannel>,
+            Option<&ScanReveals>,
+        ),
+        (With<Structure>, With<ControlledByPlayer>),
+    >,
+    summary: Res<StructureHudSummary>,
+    selected_weapon_group: Res<SelectedWeaponGroup>,
+) {
+    let Ok((mut text, visibility)) = hud_query.get_single_mut() else {
+        return;
+    };
+    if *visibility == Visibility::Hidden {
+        return;
+    }
+    let Ok((velocity, pressurization, contamination, heat_store, maneuver_queue, scan_channel, scan_reveals)) =
+        controlled_structure_query.get_single()
+    else {
+        return;
+    };
+
+    let hull = if summary.max_structural_points > 0.0 {
+        summary.structural_points / summary.max_structural_points * 100.0
+    } else {
+        0.0
+    };
+    text.sections[0].value = format!(
+        "Hull {:.0}% ({:.0}/{:.0})   Engines {}   Cannons {}   Speed {:.1} m/s   Sealed rooms {}/{}",
+        hull,
+        summary.structural_points,
+        summary.max_structural_points,
+        summary.engines,
+        summary.cannons,
+        velocity.0.length(),
+        summary.sealed_rooms,
+        summary.rooms,
+    );
+    text.sections[0].value +=
+        &format!("\nWeapons: {} ({:.0} m range)", selected_weapon_group.0.name(), selected_weapon_group.0.range());
+
+    if let Some(contamination) = contamination.filter(|contamination| !contamination.cells.is_empty()) {
+        let cells = pressurization.oxygen.len();
+        text.sections[0].value += &format!(
+            "\nAir: smoke {:.0}%   coolant {:.0}%",
+            contamination.average(Contaminant::Smoke, cells) * 100.0,
+            contamination.average(Contaminant::Coolant, cells) * 100.0,
+        );
+    }
+    if let Some(heat_store) = heat_store {
+        let status = if heat_store.is_overheated() { "   OVERHEATED" } else { "" };
+        text.sections[0].value += &format!("\nHeat {:.0}%{}", heat_store.fraction() * 100.0, status);
+    }
+    if let Some(maneuver_queue) = maneuver_queue {
+        let maneuvers: Vec<String> =
+            maneuver_queue.current().iter().chain(&maneuver_queue.queued).map(ToString::to_string).collect();
+        text.sections[0].value += &format!("\nManeuvers: {}", maneuvers.join(" > "));
+    }
+    if let Some(scan_channel) = scan_channel {
+        text.sections[0].value += &format!("\nScanning {:.0}%", scan_channel.progress() * 100.0);
+    }
+    if let Some(longest_reveal) = scan_reveals.and_then(|reveals| reveals.0.values().copied().reduce(f32::max)) {
+        text.sections[0].value += &format!("\nScan revealed for {:.0}s", longest_reveal);
+    }
+}
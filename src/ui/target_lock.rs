@@ -0,0 +1,44 @@
+use crate::core::state::GameState;
+use crate::gameplay::structures_combat::cannon_muzzle_velocity;
+use crate::gameplay::targeting::{lead_position, TargetLock};
+use crate::world::prelude::*;
+use avian2d::prelude::LinearVelocity;
+use bevy::prelude::*;
+
+const LOCK_COLOR: Color = Color::srgb(1.0, 0.3, 0.2);
+const AUTO_AIM_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+const LOCK_BRACKET_SIZE: f32 = 12.0; // meters
+const LEAD_MARKER_RADIUS: f32 = 1.5; // meters
+
+/// Brackets the structure locked by the player and marks the lead position the cannon rounds have to be fired at to
+/// hit it, yellow while the cannons auto aim.
+pub struct TargetLockIndicatorPlugin;
+
+impl Plugin for TargetLockIndicatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, draw_target_lock_system.run_if(in_state(GameState::InGame)));
+    }
+}
+
+fn draw_target_lock_system(
+    mut gizmos: Gizmos,
+    locker_query: Query<(&GlobalTransform, &TargetLock), With<ControlledByPlayer>>,
+    targets_query: Query<(&GlobalTransform, &LinearVelocity), With<Structure>>,
+) {
+    let Ok((locker_transform, lock)) = locker_query.get_single() else {
+        return;
+    };
+    let Ok((target_transform, target_velocity)) = targets_query.get(lock.target) else {
+        return;
+    };
+
+    let color = if lock.auto_aim { AUTO_AIM_COLOR } else { LOCK_COLOR };
+    let target = target_transform.translation().truncate();
+    gizmos.rect_2d(target, 0.0, Vec2::splat(LOCK_BRACKET_SIZE), color);
+
+    let shooter = locker_transform.translation().truncate();
+    if let Some(lead) = lead_position(shooter, target, target_velocity.0, cannon_muzzle_velocity(1.0)) {
+        gizmos.line_2d(target, lead, color.with_alpha(0.4));
+        gizmos.circle_2d(lead, LEAD_MARKER_RADIUS, color);
+    }
+}
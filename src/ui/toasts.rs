@@ -0,0 +1,220 @@
+use crate::core::state::GameState;
+use crate::gameplay::autopilot::AutopilotArrivedEvent;
+use crate::gameplay::cargo::{CargoTransferDirection, CargoTransferEvent};
+use crate::gameplay::crafting::{CraftedItem, ItemCraftedEvent};
+use crate::gameplay::heat::StructureOverheatedEvent;
+use crate::gameplay::scanning::{ScanCompletedEvent, SCAN_DURATION};
+use crate::gameplay::scenario::ScenarioEndedEvent;
+use crate::gameplay::world_bounds::LeavingWorldBoundsEvent;
+use crate::world::structures::ControlledByPlayer;
+use bevy::prelude::*;
+
+const TOAST_LIFETIME: f32 = 5.0; // seconds
+const TOAST_MAX_VISIBLE: usize = 3;
+const TOAST_TITLE_COLOR: Color = Color::srgb(1.0, 0.85, 0.3);
+
+/// Short notifications stacked at the top of the screen, like unlocked achievements.
+pub struct ToastPlugin;
+
+impl Plugin for ToastPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ToastEvent>()
+            .add_systems(OnEnter(GameState::BuildingStructures), spawn_toast_stack)
+            .add_systems(
+                Update,
+                (
+                    bounds_warning_toasts_system,
+                    autopilot_arrived_toasts_system,
+                    cargo_transfer_toasts_system,
+                    item_crafted_toasts_system,
+                    scan_completed_toasts_system,
+                    overheated_toasts_system,
+                    scenario_ended_toasts_system,
+                    show_toasts_system,
+                    expire_toasts_system,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct ToastEvent {
+    pub title: String,
+    pub message: String,
+}
+
+#[derive(Component)]
+struct ToastStack;
+
+#[derive(Component)]
+struct Toast(Timer);
+
+fn spawn_toast_stack(mut commands: Commands) {
+    commands.spawn((
+        ToastStack,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                top: Val::Px(60.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(6.0),
+                ..default()
+            },
+            ..default()
+        },
+    ));
+}
+
+fn show_toasts_system(
+    mut event_reader: EventReader<ToastEvent>,
+    stack_query: Query<(Entity, Option<&Children>), With<ToastStack>>,
+    mut commands: Commands,
+) {
+    let Ok((stack_entity, toasts)) = stack_query.get_single() else {
+        return;
+    };
+    let mut oldest_toasts = toasts.map(|toasts| toasts.to_vec()).unwrap_or_default().into_iter();
+    let mut toasts_count = oldest_toasts.len();
+
+    for event in event_reader.read() {
+        toasts_count += 1;
+        if toasts_count > TOAST_MAX_VISIBLE {
+            if let Some(oldest) = oldest_toasts.next() {
+                commands.entity(oldest).despawn_recursive();
+                toasts_count -= 1;
+            }
+        }
+
+        commands.entity(stack_entity).with_children(|stack| {
+            stack
+                .spawn((
+                    Toast(Timer::from_seconds(TOAST_LIFETIME, TimerMode::Once)),
+                    NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            padding: UiRect::axes(Val::Px(16.0), Val::Px(8.0)),
+                            ..default()
+                        },
+                        background_color: BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.75)),
+                        ..default()
+                    },
+                ))
+                .with_children(|toast| {
+                    toast.spawn(TextBundle::from_section(
+                        event.title.clone(),
+                        TextStyle { font_size: 18.0, color: TOAST_TITLE_COLOR, ..default() },
+                    ));
+                    toast.spawn(TextBundle::from_section(
+                        event.message.clone(),
+                        TextStyle { font_size: 14.0, color: Color::WHITE, ..default() },
+                    ));
+                });
+        });
+    }
+}
+
+fn bounds_warning_toasts_system(
+    mut event_reader: EventReader<LeavingWorldBoundsEvent>,
+    mut toast_writer: EventWriter<ToastEvent>,
+) {
+    for event in event_reader.read() {
+        let message =
+            if event.outside { "Turning you back towards the world" } else { "You are reaching the edge of the world" };
+        toast_writer.send(ToastEvent { title: "Out of bounds".to_string(), message: message.to_string() });
+    }
+}
+
+fn autopilot_arrived_toasts_system(
+    mut event_reader: EventReader<AutopilotArrivedEvent>,
+    mut toast_writer: EventWriter<ToastEvent>,
+) {
+    for event in event_reader.read() {
+        toast_writer.send(ToastEvent {
+            title: "Autopilot".to_string(),
+            message: format!("Arrived at ({:.0}, {:.0})", event.target.x, event.target.y),
+        });
+    }
+}
+
+fn cargo_transfer_toasts_system(
+    mut event_reader: EventReader<CargoTransferEvent>,
+    mut toast_writer: EventWriter<ToastEvent>,
+) {
+    for event in event_reader.read() {
+        let message = match event.direction {
+            CargoTransferDirection::PickedUp => format!("Picked up {:.0} {}", event.amount, event.kind),
+            CargoTransferDirection::Dropped if event.amount <= 0.0 => "The cargo holds are full".to_string(),
+            CargoTransferDirection::Dropped => format!("Dropped {:.0} {} in the holds", event.amount, event.kind),
+            // Salvaging and mining go on all the time, they show in the holds
+            CargoTransferDirection::Collected => continue,
+        };
+        toast_writer.send(ToastEvent { title: "Cargo".to_string(), message });
+    }
+}
+
+fn item_crafted_toasts_system(
+    mut event_reader: EventReader<ItemCraftedEvent>,
+    mut toast_writer: EventWriter<ToastEvent>,
+) {
+    for event in event_reader.read() {
+        let message = match event.item {
+            CraftedItem::Scrap => format!("{}: {} scrap", event.recipe, event.quantity),
+            CraftedItem::Material(material_type) => {
+                format!("{}: {} {:?}", event.recipe, event.quantity, material_type)
+            }
+        };
+        toast_writer.send(ToastEvent { title: "Refinery".to_string(), message });
+    }
+}
+
+fn scan_completed_toasts_system(
+    mut event_reader: EventReader<ScanCompletedEvent>,
+    controlled_query: Query<(), With<ControlledByPlayer>>,
+    mut toast_writer: EventWriter<ToastEvent>,
+) {
+    let player_scans = event_reader.read().filter(|event| controlled_query.contains(event.scanner)).count();
+    for _ in 0..player_scans {
+        toast_writer.send(ToastEvent {
+            title: "Scan complete".to_string(),
+            message: format!("Modules revealed for {:.0}s", SCAN_DURATION),
+        });
+    }
+}
+
+fn overheated_toasts_system(
+    mut event_reader: EventReader<StructureOverheatedEvent>,
+    controlled_query: Query<(), With<ControlledByPlayer>>,
+    mut toast_writer: EventWriter<ToastEvent>,
+) {
+    for event in event_reader.read() {
+        if controlled_query.contains(event.structure_entity) {
+            toast_writer.send(ToastEvent {
+                title: "Overheated".to_string(),
+                message: "Weapons offline until the ship cools down".to_string(),
+            });
+        }
+    }
+}
+
+fn scenario_ended_toasts_system(
+    mut event_reader: EventReader<ScenarioEndedEvent>,
+    mut toast_writer: EventWriter<ToastEvent>,
+) {
+    for event in event_reader.read() {
+        let title = if event.victory { "Victory" } else { "Defeat" };
+        toast_writer.send(ToastEvent { title: title.to_string(), message: event.name.clone() });
+    }
+}
+
+fn expire_toasts_system(mut toasts_query: Query<(Entity, &mut Toast)>, time: Res<Time>, mut commands: Commands) {
+    for (entity, mut toast) in &mut toasts_query {
+        if toast.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use crate::core::state::GameState;
+use crate::ui::culling::{CameraView, Cosmetic};
+use bevy::prelude::*;
+
+const WORLD_TEXT_SCALE: f32 = 0.1; // text is rendered at a readable font size then scaled down to meters
+const WORLD_TEXT_Z: f32 = 10.0; // above the structures and modules
+const DEFAULT_LIFETIME: f32 = 1.5; // seconds
+const DEFAULT_RISE_SPEED: f32 = 3.0; // m/s
+const POP_DURATION: f32 = 0.15; // seconds to grow to full size with `WorldTextAnimation::Pop`
+
+/// Floating text in the world, faded out over its lifetime.
+/// Send a `SpawnWorldTextEvent`, or call `spawn_world_text` when the text entity needs to be updated later.
+pub struct WorldTextPlugin;
+
+impl Plugin for WorldTextPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SpawnWorldTextEvent>().add_systems(
+            Update,
+            (spawn_world_text_system, animate_world_text_system).chain().run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WorldTextAnimation {
+    /// Stays in place.
+    Static,
+    /// Moves up at the given speed, in m/s.
+    Rise(f32),
+    /// Quickly grows to its size, then moves up at the given speed.
+    Pop(f32),
+}
+
+impl Default for WorldTextAnimation {
+    fn default() -> Self {
+        WorldTextAnimation::Rise(DEFAULT_RISE_SPEED)
+    }
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct SpawnWorldTextEvent {
+    pub position: Vec2,
+    pub text: String,
+    pub style: TextStyle,
+    /// Seconds before the text is despawned.
+    pub lifetime: f32,
+    pub animation: WorldTextAnimation,
+}
+
+impl SpawnWorldTextEvent {
+    pub fn new(position: Vec2, text: impl Into<String>) -> Self {
+        Self {
+            position,
+            text: text.into(),
+            style: TextStyle { font_size: 20.0, color: Color::WHITE, ..default() },
+            lifetime: DEFAULT_LIFETIME,
+            animation: WorldTextAnimation::default(),
+        }
+    }
+
+    pub fn with_style(mut self, style: TextStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn with_lifetime(mut self, lifetime: f32) -> Self {
+        self.lifetime = lifetime;
+        self
+    }
+
+    pub fn with_animation(mut self, animation: WorldTextAnimation) -> Self {
+        self.animation = animation;
+        self
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct WorldText {
+    pub lifetime: Timer,
+    pub animation: WorldTextAnimation,
+}
+
+impl WorldText {
+    /// Starts the lifetime over, used when the text is updated with new content.
+    pub fn restart(&mut self) {
+        self.lifetime.reset();
+    }
+}
+
+/// Spawns the floating text right away, returns `None` if it would not be seen by the camera.
+pub fn spawn_world_text(
+    commands: &mut Commands,
+    camera_view: &CameraView,
+    event: &SpawnWorldTextEvent,
+) -> Option<Entity> {
+    if !camera_view.should_spawn_cosmetic(event.position) {
+        return None;
+    }
+
+    let entity = commands
+        .spawn((
+            Cosmetic,
+            WorldText { lifetime: Timer::from_seconds(event.lifetime, TimerMode::Once), animation: event.animation },
+            Text2dBundle {
+                text: Text::from_section(event.text.clone(), event.style.clone()),
+                transform: Transform::from_translation(event.position.extend(WORLD_TEXT_Z))
+                    .with_scale(Vec3::splat(WORLD_TEXT_SCALE)),
+                ..default()
+            },
+        ))
+        .id();
+    Some(entity)
+}
+
+fn spawn_world_text_system(
+    mut event_reader: EventReader<SpawnWorldTextEvent>,
+    camera_view: Res<CameraView>,
+    mut commands: Commands,
+) {
+    for event in event_reader.read() {
+        spawn_world_text(&mut commands, &camera_view, event);
+    }
+}
+
+fn animate_world_text_system(
+    mut texts_query: Query<(Entity, &mut WorldText, &mut Transform, &mut Text)>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, mut world_text, mut transform, mut text) in &mut texts_query {
+        if world_text.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        match world_text.animation {
+            WorldTextAnimation::Static => {}
+            WorldTextAnimation::Rise(speed) => transform.translation.y += speed * time.delta_seconds(),
+            WorldTextAnimation::Pop(speed) => {
+                let growth = (world_text.lifetime.elapsed_secs() / POP_DURATION).min(1.0);
+                transform.scale = Vec3::splat(WORLD_TEXT_SCALE * (0.5 + 0.5 * growth));
+                transform.translation.y += speed * time.delta_seconds();
+            }
+        }
+
+        let alpha = 1.0 - world_text.lifetime.fraction();
+        for section in &mut text.sections {
+            section.style.color.set_alpha(alpha);
+        }
+    }
+}
@@ -1,34 +1,37 @@
-use crate::core::asset_loader::{AssetBlob, AssetStore, Level};
+use crate::configs::collision_layers::CollisionLayersConfig;
+use crate::core::asset_loader::{AssetStore, Level};
+use crate::core::debug_overlays::grid_overlay_shown;
+use crate::core::game_assets::GameAssets;
+use crate::core::profiling::{ProfileScope, GRID_UPDATES};
 use crate::core::state::GameState;
 use crate::world::player::{Player, PlayerResource};
 use avian2d::collision::Collider;
 use avian2d::prelude::{LinearVelocity, RigidBody};
+use bevy::color::palettes::css::*;
+use bevy::diagnostic::Diagnostics;
 use bevy::prelude::*;
-use bevy::{color::palettes::css::*, sprite::MaterialMesh2dBundle};
+use bevy::sprite::{MaterialMesh2dBundle, Mesh2dHandle};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Default)]
-pub struct GridPlugin {
-    pub debug_enable: bool,
-}
+pub struct GridPlugin;
 
 impl Plugin for GridPlugin {
     fn build(&self, app: &mut App) {
         app.init_gizmo_group::<MyGridGizmos>()
+            .register_type::<Grid>()
             .add_event::<PlayerGridChangeEvent>()
             .add_systems(OnEnter(GameState::BuildingGrid), setup_grid_from_file)
-            .add_systems(Update, detect_grid_updates.run_if(in_state(GameState::InGame)));
-
-        if self.debug_enable {
-            app.add_systems(
+            .add_systems(
                 Update,
-                (detect_grid_updates, debug_draw_grid, debug_draw_rects).chain().run_if(in_state(GameState::InGame)),
+                (detect_grid_updates, (debug_draw_grid, debug_draw_rects).run_if(grid_overlay_shown))
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
             );
-        }
     }
 }
 
-#[derive(Default, Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq, Reflect, Serialize, Deserialize)]
 pub enum CellType {
     #[default]
     Empty,
@@ -45,7 +48,8 @@ impl From<char> for CellType {
     }
 }
 
-#[derive(Resource, Default, Debug)]
+#[derive(Resource, Default, Debug, Clone, Reflect, Serialize, Deserialize)]
+#[reflect(Resource)]
 pub struct Grid {
     pub width: u32,
     pub height: u32,
@@ -53,7 +57,7 @@ pub struct Grid {
     pub cells: HashMap<(i32, i32), GridCell>,
 }
 
-#[derive(Debug, Resource)]
+#[derive(Debug, Clone, Resource, Reflect, Serialize, Deserialize)]
 pub struct GridCell {
     pub data: Option<Entity>,
     pub color: Srgba,
@@ -142,53 +146,50 @@ struct MyGridGizmos {}
 fn setup_grid_from_file(
     mut commands: Commands,
     asset_store: Res<AssetStore>,
-    blob_assets: Res<Assets<AssetBlob>>,
+    levels: Res<Assets<Level>>,
     mut next_state: ResMut<NextState<GameState>>,
     mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
+    game_assets: Res<GameAssets>,
 ) {
-    if let Some(blob) = blob_assets.get(&asset_store.level_blob) {
-        let level_data: String = String::from_utf8(blob.bytes.clone()).expect("Invalid UTF-8 data");
-        let level: Level = serde_json::from_str(&level_data).expect("Failed to deserialize level data");
-
-        let mut cells = HashMap::new();
-        debug!("Loading level with width: {}, height: {}, cell_size: {}", level.width, level.height, level.cell_size);
-        for (y, row) in level.world.iter().enumerate() {
-            for (x, cell) in row.chars().enumerate() {
-                let cell_type = CellType::from(cell);
-
-                let cell_world_pos = Vec3::new(
-                    (x as f32 * level.cell_size) - (level.width as f32 * level.cell_size) / 2.0 + level.cell_size / 2.0,
-                    (level.height as f32 * level.cell_size) / 2.0
-                        - (y as f32 * level.cell_size)
-                        - level.cell_size / 2.0,
-                    0.0,
-                );
-
-                commands.spawn((
-                    RigidBody::Static,
-                    Collider::rectangle(level.cell_size, level.cell_size),
-                    MaterialMesh2dBundle {
-                        mesh: meshes.add(Rectangle { half_size: Vec2::splat(level.cell_size / 2.0) }).into(),
-                        material: materials.add(Color::from(GREY)),
-                        transform: Transform {
-                            translation: Vec3::new(cell_world_pos.x, cell_world_pos.y, 0.0),
-                            ..default()
-                        },
+    // Only reachable once the level is loaded and validated
+    let Some(level) = levels.get(&asset_store.level) else {
+        return;
+    };
+    // The cell size comes from the level, every cell of the level shares the same mesh
+    let cell_mesh: Mesh2dHandle = meshes.add(Rectangle { half_size: Vec2::splat(level.cell_size / 2.0) }).into();
+    let mut cells = HashMap::new();
+    debug!("Loading level with width: {}, height: {}, cell_size: {}", level.width, level.height, level.cell_size);
+    for (y, row) in level.world.iter().enumerate() {
+        for (x, cell) in row.chars().enumerate() {
+            let cell_type = CellType::from(cell);
+
+            let cell_world_pos = Vec3::new(
+                (x as f32 * level.cell_size) - (level.width as f32 * level.cell_size) / 2.0 + level.cell_size / 2.0,
+                (level.height as f32 * level.cell_size) / 2.0 - (y as f32 * level.cell_size) - level.cell_size / 2.0,
+                0.0,
+            );
+
+            commands.spawn((
+                RigidBody::Static,
+                Collider::rectangle(level.cell_size, level.cell_size),
+                CollisionLayersConfig::level(),
+                MaterialMesh2dBundle {
+                    mesh: cell_mesh.clone(),
+                    material: game_assets.grid_cell_material.clone(),
+                    transform: Transform {
+                        translation: Vec3::new(cell_world_pos.x, cell_world_pos.y, 0.0),
                         ..default()
                     },
-                ));
+                    ..default()
+                },
+            ));
 
-                cells
-                    .insert((x as i32, y as i32), GridCell { data: None, color: Srgba::rgb(0.5, 0.5, 0.5), cell_type });
-            }
+            cells.insert((x as i32, y as i32), GridCell { data: None, color: Srgba::rgb(0.5, 0.5, 0.5), cell_type });
         }
-        let grid: Grid = Grid { width: level.width, height: level.height, cell_size: level.cell_size, cells };
-        commands.insert_resource(grid);
-        next_state.set(GameState::BuildingStructures);
-    } else {
-        panic!("Failed to load level asset");
     }
+    let grid: Grid = Grid { width: level.width, height: level.height, cell_size: level.cell_size, cells };
+    commands.insert_resource(grid);
+    next_state.set(GameState::BuildingStructures);
 }
 
 #[derive(Event, Debug)]
@@ -203,7 +204,9 @@ fn detect_grid_updates(
     mut grid: ResMut<Grid>,
     mut event_writer: EventWriter<PlayerGridChangeEvent>,
     mut player_grid_position: ResMut<PlayerResource>,
+    mut diagnostics: Diagnostics,
 ) {
+    let scope = ProfileScope::enter(&GRID_UPDATES);
     for (entity, transform) in &query {
         let (updated_grid_x, updated_grid_y) = grid.world_to_grid(transform.translation());
         let (old_grid_x, old_grid_y) = player_grid_position.grid_position;
@@ -223,6 +226,7 @@ fn detect_grid_updates(
             grid.update_data_position(entity, updated_grid_x, updated_grid_y, old_grid_x, old_grid_y);
         }
     }
+    scope.finish(&mut diagnostics);
 }
 
 fn debug_draw_grid(mut gizmos: Gizmos, grid: Res<Grid>) {
@@ -1,6 +1,9 @@
 pub mod grid;
+pub mod module_registry;
 pub mod modules;
+pub mod names;
 pub mod ore;
 pub mod player;
 pub mod prelude;
+pub mod spatial_index;
 pub mod structures;
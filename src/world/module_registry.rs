@@ -0,0 +1,175 @@
+use crate::core::game_assets::GameAssets;
+use crate::world::prelude::*;
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+/// Symbols used by the built-in module types in the structures data files, they cannot be registered again.
+const BUILTIN_SYMBOLS: [char; 16] = ['C', 'E', 'W', '!', 'Q', 'R', 'M', 'D', 'A', 'P', 'H', 'F', 'X', 'B', 'N', '#'];
+
+/// Describes a module type added by a plugin on top of the built-in ones.
+#[derive(Debug, Clone)]
+pub struct ModuleDefinition {
+    pub name: &'static str,
+    /// Character used for this module in the structures data files and the saves.
+    pub symbol: char,
+    pub color: Color,
+    pub material_type: ModuleMaterialType,
+    /// Cells covered by every module of this type, the built-in modules all cover a single cell.
+    pub footprint: Footprint,
+    /// Interactable modules have no collider and are walked over, like the command center.
+    pub interactable: bool,
+    /// Asset path of the icon shown for this module in the editor.
+    pub editor_icon: Option<&'static str>,
+    /// Custom spawn logic, run on the module entity after the marker component is inserted.
+    pub on_spawn: Option<fn(&mut EntityCommands)>,
+}
+
+impl ModuleDefinition {
+    pub fn new(name: &'static str, symbol: char) -> Self {
+        Self {
+            name,
+            symbol,
+            color: Color::WHITE,
+            material_type: ModuleMaterialType::default(),
+            footprint: Footprint::SINGLE,
+            interactable: false,
+            editor_icon: None,
+            on_spawn: None,
+        }
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_material(mut self, material_type: ModuleMaterialType) -> Self {
+        self.material_type = material_type;
+        self
+    }
+
+    pub fn with_footprint(mut self, footprint: Footprint) -> Self {
+        self.footprint = footprint;
+        self
+    }
+
+    pub fn interactable(mut self) -> Self {
+        self.interactable = true;
+        self
+    }
+
+    pub fn with_editor_icon(mut self, path: &'static str) -> Self {
+        self.editor_icon = Some(path);
+        self
+    }
+
+    pub fn with_on_spawn(mut self, on_spawn: fn(&mut EntityCommands)) -> Self {
+        self.on_spawn = Some(on_spawn);
+        self
+    }
+}
+
+struct RegisteredModule {
+    definition: ModuleDefinition,
+    insert_marker: fn(&mut EntityCommands),
+}
+
+/// Module types registered by plugins, by symbol.
+#[derive(Resource, Default)]
+pub struct ModuleRegistry {
+    modules: HashMap<char, RegisteredModule>,
+}
+
+impl ModuleRegistry {
+    pub fn get(&self, symbol: char) -> Option<&ModuleDefinition> {
+        self.modules.get(&symbol).map(|registered| &registered.definition)
+    }
+
+    /// Whether a symbol of the structures data files stands for a built-in or a registered module, or an empty cell.
+    pub fn is_known_symbol(&self, symbol: char) -> bool {
+        BUILTIN_SYMBOLS.contains(&symbol) || self.modules.contains_key(&symbol)
+    }
+
+    /// Cells covered by the modules written with this symbol.
+    pub fn footprint(&self, symbol: char) -> Footprint {
+        self.get(symbol).map_or(Footprint::SINGLE, |definition| definition.footprint)
+    }
+
+    pub fn definitions(&self) -> impl Iterator<Item = &ModuleDefinition> {
+        self.modules.values().map(|registered| &registered.definition)
+    }
+
+    /// Inserts the marker component of a registered module and runs its custom spawn logic.
+    pub fn apply_spawn_hooks(&self, symbol: char, entity_commands: &mut EntityCommands) {
+        let Some(registered) = self.modules.get(&symbol) else {
+            return;
+        };
+        (registered.insert_marker)(entity_commands);
+        if let Some(on_spawn) = registered.definition.on_spawn {
+            on_spawn(entity_commands);
+        }
+    }
+
+    /// Spawns a registered module in a structure cell, returns `None` if the symbol is unknown.
+    pub fn spawn(
+        &self,
+        commands: &mut Commands,
+        structure_entity: Entity,
+        structure_component: &mut Structure,
+        materials: &mut ResMut<Assets<ColorMaterial>>,
+        game_assets: &GameAssets,
+        symbol: char,
+        grid_pos: (i32, i32),
+        orientation: Orientation,
+        translation: Vec3,
+    ) -> Option<Entity> {
+        let definition = self.get(symbol)?;
+        let module_entity = spawn_module(
+            commands,
+            structure_entity,
+            structure_component,
+            materials,
+            game_assets,
+            ModuleType::Custom(symbol),
+            definition.color,
+            grid_pos,
+            orientation,
+            definition.footprint,
+            translation,
+            MODULE_MESH_SCALE_FACTOR,
+            definition.interactable,
+            definition.material_type,
+        );
+        self.apply_spawn_hooks(symbol, &mut commands.entity(module_entity));
+        Some(module_entity)
+    }
+}
+
+/// Lets plugins add their own module types.
+/// The marker component `T` is inserted on every module of this type, so the plugin can add its own systems
+/// querying it.
+pub trait RegisterModuleTypeAppExt {
+    fn register_module_type<T: Component + Default>(&mut self, definition: ModuleDefinition) -> &mut Self;
+}
+
+impl RegisterModuleTypeAppExt for App {
+    fn register_module_type<T: Component + Default>(&mut self, definition: ModuleDefinition) -> &mut Self {
+        let mut registry = self.world_mut().get_resource_or_insert_with(ModuleRegistry::default);
+
+        if BUILTIN_SYMBOLS.contains(&definition.symbol) || registry.modules.contains_key(&definition.symbol) {
+            warn!("Module type '{}' not registered, symbol '{}' is already used", definition.name, definition.symbol);
+        } else {
+            registry.modules.insert(
+                definition.symbol,
+                RegisteredModule {
+                    definition,
+                    insert_marker: |entity_commands| {
+                        entity_commands.insert(T::default());
+                    },
+                },
+            );
+        }
+        self
+    }
+}
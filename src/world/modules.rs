@@ -1,28 +1,388 @@
+use crate::configs::collision_layers::CollisionLayersConfig;
 use crate::configs::config::UNIT_SCALE;
+use crate::core::game_assets::GameAssets;
 use crate::world::prelude::*;
 use avian2d::prelude::*;
 use bevy::asset::Assets;
 use bevy::color::Color;
+use bevy::ecs::system::EntityCommands;
 use bevy::hierarchy::BuildChildren;
-use bevy::math::{Vec2, Vec3};
+use bevy::math::{EulerRot, Quat, Vec2, Vec3};
 use bevy::prelude::{
-    default, Bundle, Commands, Component, Entity, Event, Mesh, Rectangle, ResMut, Transform, Visibility,
+    default, Bundle, Commands, Component, Entity, Event, ReflectComponent, ResMut, Transform, Visibility,
 };
+use bevy::reflect::Reflect;
 use bevy::sprite::{ColorMaterial, MaterialMesh2dBundle};
+use serde::{Deserialize, Serialize};
 
 #[derive(Event)]
 pub struct ModuleDestroyedEvent {
     pub destroyed_entity: Entity,
     pub inner_grid_pos: (i32, i32),
+    /// Structure that dealt the final blow, if known.
+    pub source: Option<Entity>,
 }
 
-#[derive(Debug, Default)]
+#[derive(Event, Debug)]
+pub struct ModuleTookDamageEvent {
+    pub module_entity: Entity,
+    pub damage: f32,
+    pub remaining_points: f32,
+    /// Structure that dealt the damage, if known.
+    pub source: Option<Entity>,
+    /// The impact went over the damage threshold of the module material.
+    pub critical: bool,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Reflect, Serialize, Deserialize)]
 pub enum ModuleType {
     #[default]
     CommandCenter,
     Engine,
     Wall,
     Cannon,
+    CrewQuarters,
+    Reactor,
+    MedicalBay,
+    Door,
+    Airlock,
+    DockingPort,
+    CargoHold,
+    Refinery,
+    /// Segment of a spinal weapon, consecutive accelerators facing the same way fire as a single long gun.
+    Accelerator,
+    /// Houses the repair drones of its structure.
+    DroneBay,
+    /// Extends the sensor range of its structure.
+    Sensor,
+    /// A module type registered by a plugin, identified by its symbol in the `ModuleRegistry`.
+    Custom(char),
+}
+
+impl ModuleType {
+    /// Inserts the behavior marker component of this module type.
+    /// Systems query the markers instead of matching on the module type.
+    pub fn insert_behavior(&self, entity_commands: &mut EntityCommands) {
+        match self {
+            ModuleType::CommandCenter => {
+                entity_commands.insert((CommandCenterModule, Interactable::new(InteractionKind::Control)))
+            }
+            ModuleType::Engine => entity_commands.insert(EngineModule),
+            ModuleType::Wall => entity_commands.insert(WallModule),
+            ModuleType::Cannon => entity_commands.insert(CannonModule),
+            ModuleType::CrewQuarters => entity_commands.insert(CrewQuartersModule),
+            ModuleType::Reactor => entity_commands.insert(ReactorModule),
+            ModuleType::MedicalBay => entity_commands.insert(MedicalBayModule),
+            ModuleType::Door => entity_commands.insert((DoorModule, Interactable::new(InteractionKind::ToggleDoor))),
+            ModuleType::Airlock => {
+                entity_commands.insert((AirlockModule, Interactable::new(InteractionKind::ToggleDoor)))
+            }
+            ModuleType::DockingPort => {
+                entity_commands.insert((DockingPortModule, Interactable::new(InteractionKind::Undock)))
+            }
+            ModuleType::CargoHold => {
+                entity_commands.insert((CargoHoldModule, Interactable::new(InteractionKind::TransferCargo)))
+            }
+            ModuleType::Refinery => entity_commands.insert(RefineryModule),
+            ModuleType::Accelerator => entity_commands.insert(AcceleratorModule),
+            ModuleType::DroneBay => entity_commands.insert(DroneBayModule),
+            ModuleType::Sensor => entity_commands.insert(SensorModule),
+            // Registered module types get their marker from the `ModuleRegistry`
+            ModuleType::Custom(_) => entity_commands,
+        };
+    }
+
+    /// Human readable name of the module type, registered types are named by the `ModuleRegistry`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ModuleType::CommandCenter => "Command Center",
+            ModuleType::Engine => "Engine",
+            ModuleType::Wall => "Wall",
+            ModuleType::Cannon => "Cannon",
+            ModuleType::CrewQuarters => "Crew Quarters",
+            ModuleType::Reactor => "Reactor",
+            ModuleType::MedicalBay => "Medical Bay",
+            ModuleType::Door => "Door",
+            ModuleType::Airlock => "Airlock",
+            ModuleType::DockingPort => "Docking Port",
+            ModuleType::CargoHold => "Cargo Hold",
+            ModuleType::Refinery => "Refinery",
+            ModuleType::Accelerator => "Accelerator",
+            ModuleType::DroneBay => "Drone Bay",
+            ModuleType::Sensor => "Sensor",
+            ModuleType::Custom(_) => "Module",
+        }
+    }
+
+    /// The character used for this module type in the structures data files.
+    pub fn symbol(&self) -> char {
+        match self {
+            ModuleType::CommandCenter => 'C',
+            ModuleType::Engine => 'E',
+            ModuleType::Wall => 'W',
+            ModuleType::Cannon => '!',
+            ModuleType::CrewQuarters => 'Q',
+            ModuleType::Reactor => 'R',
+            ModuleType::MedicalBay => 'M',
+            ModuleType::Door => 'D',
+            ModuleType::Airlock => 'A',
+            ModuleType::DockingPort => 'P',
+            ModuleType::CargoHold => 'H',
+            ModuleType::Refinery => 'F',
+            ModuleType::Accelerator => 'X',
+            ModuleType::DroneBay => 'B',
+            ModuleType::Sensor => 'N',
+            ModuleType::Custom(symbol) => *symbol,
+        }
+    }
+
+    /// The built-in module type written with this character in the structures data files.
+    pub fn from_symbol(symbol: char) -> Option<Self> {
+        let module_type = match symbol {
+            'C' => ModuleType::CommandCenter,
+            'E' => ModuleType::Engine,
+            'W' => ModuleType::Wall,
+            '!' => ModuleType::Cannon,
+            'Q' => ModuleType::CrewQuarters,
+            'R' => ModuleType::Reactor,
+            'M' => ModuleType::MedicalBay,
+            'D' => ModuleType::Door,
+            'A' => ModuleType::Airlock,
+            'P' => ModuleType::DockingPort,
+            'H' => ModuleType::CargoHold,
+            'F' => ModuleType::Refinery,
+            'X' => ModuleType::Accelerator,
+            'B' => ModuleType::DroneBay,
+            'N' => ModuleType::Sensor,
+            _ => return None,
+        };
+        Some(module_type)
+    }
+
+    /// Material the built-in modules of this type are made of, registered types pick their own.
+    pub fn material_type(&self) -> ModuleMaterialType {
+        match self {
+            ModuleType::Cannon | ModuleType::Sensor => ModuleMaterialType::Aluminum,
+            _ => ModuleMaterialType::Steel,
+        }
+    }
+
+    /// Depth of the built-in modules of this type in their structure, the ones walked on are drawn under the player.
+    pub fn z_layer(&self) -> f32 {
+        match self {
+            ModuleType::CommandCenter | ModuleType::MedicalBay => -1.0,
+            _ => 1.0,
+        }
+    }
+
+    /// Whether the built-in modules of this type are spawned without a collider, for the player to step on them.
+    /// Registered types set it in their `ModuleDefinition`.
+    pub fn is_interactable(&self) -> bool {
+        matches!(
+            self,
+            ModuleType::CommandCenter | ModuleType::MedicalBay | ModuleType::DockingPort | ModuleType::CargoHold
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InteractionKind {
+    /// Take or release the control of the structure.
+    Control,
+    /// Open or close a door.
+    ToggleDoor,
+    /// Release the structure docked to a docking port.
+    Undock,
+    /// Pick up or drop the cargo the player carries.
+    TransferCargo,
+}
+
+impl InteractionKind {
+    /// Cells from where the player can interact, doors are used from the cells next to them.
+    pub fn reach(&self) -> i32 {
+        match self {
+            InteractionKind::Control | InteractionKind::Undock | InteractionKind::TransferCargo => 0,
+            InteractionKind::ToggleDoor => 1,
+        }
+    }
+}
+
+/// A module the player can use with the space key.
+#[derive(Component, Debug, Clone)]
+pub struct Interactable {
+    pub kind: InteractionKind,
+}
+
+impl Interactable {
+    pub fn new(kind: InteractionKind) -> Self {
+        Self { kind }
+    }
+
+    /// Checks if a player standing in `player_cell` can use `module`, counting from its nearest cell.
+    pub fn is_reachable_from(&self, module: &Module, player_cell: (i32, i32)) -> bool {
+        let distance = module.cells().map(|cell| (cell.0 - player_cell.0).abs() + (cell.1 - player_cell.1).abs()).min();
+        distance == Some(self.kind.reach())
+    }
+}
+
+#[derive(Component, Debug, Default)]
+pub struct CommandCenterModule;
+
+#[derive(Component, Debug, Default)]
+pub struct EngineModule;
+
+#[derive(Component, Debug, Default)]
+pub struct WallModule;
+
+#[derive(Component, Debug, Default)]
+pub struct CannonModule;
+
+#[derive(Component, Debug, Default)]
+pub struct CrewQuartersModule;
+
+#[derive(Component, Debug, Default)]
+pub struct ReactorModule;
+
+#[derive(Component, Debug, Default)]
+pub struct MedicalBayModule;
+
+#[derive(Component, Debug, Default)]
+pub struct DoorModule;
+
+#[derive(Component, Debug, Default)]
+pub struct AirlockModule;
+
+#[derive(Component, Debug, Default)]
+pub struct DockingPortModule;
+
+#[derive(Component, Debug, Default)]
+pub struct CargoHoldModule;
+
+#[derive(Component, Debug, Default)]
+pub struct RefineryModule;
+
+#[derive(Component, Debug, Default)]
+pub struct AcceleratorModule;
+
+#[derive(Component, Debug, Default)]
+pub struct DroneBayModule;
+
+#[derive(Component, Debug, Default)]
+pub struct SensorModule;
+
+/// Side of the structure grid a directional module faces, North is up the rows.
+/// Written as N, E, S or W in the structures data files.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Reflect, Serialize, Deserialize)]
+pub enum Orientation {
+    #[default]
+    #[serde(rename = "N")]
+    North,
+    #[serde(rename = "E")]
+    East,
+    #[serde(rename = "S")]
+    South,
+    #[serde(rename = "W")]
+    West,
+}
+
+impl Orientation {
+    /// Orientation of a module rotated by `quarter_turns` counterclockwise from North.
+    pub fn from_quarter_turns(quarter_turns: u8) -> Self {
+        match quarter_turns % 4 {
+            0 => Orientation::North,
+            1 => Orientation::West,
+            2 => Orientation::South,
+            _ => Orientation::East,
+        }
+    }
+
+    /// Quarter turns counterclockwise from North.
+    pub fn quarter_turns(&self) -> u8 {
+        match self {
+            Orientation::North => 0,
+            Orientation::West => 1,
+            Orientation::South => 2,
+            Orientation::East => 3,
+        }
+    }
+
+    /// Rotation of the module transform in its structure.
+    pub fn rotation(&self) -> Quat {
+        Quat::from_rotation_z(self.quarter_turns() as f32 * std::f32::consts::FRAC_PI_2)
+    }
+
+    /// Direction the module faces in the local space of its structure.
+    pub fn forward(&self) -> Vec2 {
+        match self {
+            Orientation::North => Vec2::Y,
+            Orientation::West => Vec2::NEG_X,
+            Orientation::South => Vec2::NEG_Y,
+            Orientation::East => Vec2::X,
+        }
+    }
+}
+
+/// Cells a module covers in its structure grid while facing North, the module mesh and collider are stretched over
+/// them. Written as the symbol of the module repeated in every covered cell in the structures data files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+pub struct Footprint {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Default for Footprint {
+    fn default() -> Self {
+        Footprint::SINGLE
+    }
+}
+
+impl Footprint {
+    pub const SINGLE: Footprint = Footprint { width: 1, height: 1 };
+
+    pub const fn new(width: u32, height: u32) -> Self {
+        Self { width, height }
+    }
+
+    pub fn is_single(&self) -> bool {
+        *self == Footprint::SINGLE
+    }
+
+    /// Columns and rows covered once the module is turned to `orientation`, East and West swap them.
+    pub fn oriented(&self, orientation: Orientation) -> (u32, u32) {
+        match orientation {
+            Orientation::North | Orientation::South => (self.width, self.height),
+            Orientation::East | Orientation::West => (self.height, self.width),
+        }
+    }
+
+    /// Every cell covered by a module anchored in its top left cell `anchor`, turned to `orientation`.
+    pub fn cells(&self, anchor: (i32, i32), orientation: Orientation) -> impl Iterator<Item = (i32, i32)> {
+        let (columns, rows) = self.oriented(orientation);
+        (0..rows as i32).flat_map(move |dy| (0..columns as i32).map(move |dx| (anchor.0 + dx, anchor.1 + dy)))
+    }
+
+    /// Offset from the center of the anchor cell to the center of the footprint, in the structure local space where
+    /// the rows go down.
+    pub fn center_offset(&self, orientation: Orientation, cell_size: f32) -> Vec2 {
+        let (columns, rows) = self.oriented(orientation);
+        Vec2::new(columns as f32 - 1.0, -(rows as f32 - 1.0)) * cell_size / 2.0
+    }
+}
+
+/// Quarter turns of a module rotated in its structure, counterclockwise.
+pub fn quarter_turns(rotation: Quat) -> u8 {
+    let angle = rotation.to_euler(EulerRot::XYZ).2;
+    (angle / std::f32::consts::FRAC_PI_2).round().rem_euclid(4.0) as u8
+}
+
+/// Offset to the neighbour cell a module rotated by `quarter_turns` faces, the grid rows go down.
+pub fn facing_cell_offset(quarter_turns: u8) -> (i32, i32) {
+    match quarter_turns % 4 {
+        0 => (0, -1),
+        1 => (-1, 0),
+        2 => (0, 1),
+        _ => (1, 0),
+    }
 }
 
 #[derive(Debug)]
@@ -32,7 +392,7 @@ pub struct MaterialProperties {
     pub density: f32,        // Density in kg/m^2
     pub damage_threshold: f32, // Damage threshold in Newtons
 }
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Reflect)]
 pub enum ModuleMaterialType {
     #[default]
     Steel,
@@ -63,26 +423,56 @@ impl ModuleMaterialType {
             },
         }
     }
+
+    /// Mass in kg of a module plate filling a cell of `cell_size` meters.
+    pub fn module_mass(&self, cell_size: f32) -> f32 {
+        self.properties().density * cell_size * cell_size
+    }
 }
 
-#[derive(Debug, Default, Component)]
+#[derive(Debug, Default, Clone, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component)]
 pub struct ModuleMaterial {
     pub structural_points: f32,
+    pub max_structural_points: f32,
     pub material_type: ModuleMaterialType,
 }
 
-#[derive(Debug, Default, Component)]
+impl ModuleMaterial {
+    /// Remaining structural points, from 0.0 (destroyed) to 1.0 (intact).
+    pub fn health_ratio(&self) -> f32 {
+        if self.max_structural_points <= 0.0 {
+            return 1.0;
+        }
+        (self.structural_points / self.max_structural_points).clamp(0.0, 1.0)
+    }
+}
+
+#[derive(Debug, Default, Clone, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component)]
 pub struct Module {
     pub width: f32,
     pub height: f32,
     pub entity_connected: Option<Entity>,
     pub module_type: ModuleType,
     pub inner_grid_pos: (i32, i32),
+    /// Facing of the module, the transform of the module is rotated to match.
+    pub orientation: Orientation,
+    /// Cells covered from `inner_grid_pos`, its top left cell.
+    pub footprint: Footprint,
+}
+
+impl Module {
+    /// Every cell of its structure grid the module covers.
+    pub fn cells(&self) -> impl Iterator<Item = (i32, i32)> {
+        self.footprint.cells(self.inner_grid_pos, self.orientation)
+    }
 }
 
 #[derive(Bundle)]
 pub struct ModuleBundleRigid {
     pub collider: Collider,
+    pub collision_layers: CollisionLayers,
     pub collider_density: ColliderDensity,
     pub module: Module,
     pub module_material: ModuleMaterial,
@@ -101,66 +491,118 @@ pub fn spawn_module(
     structure_entity: Entity,
     structure_component: &mut Structure,
     materials: &mut ResMut<Assets<ColorMaterial>>,
-    meshes: &mut ResMut<Assets<Mesh>>,
+    game_assets: &GameAssets,
     module_type: ModuleType,
     color: Color,
     grid_pos: (i32, i32),
+    orientation: Orientation,
+    footprint: Footprint,
     translation: Vec3,
     mesh_scale_factor: f32,
     interactable: bool,
     material_type: ModuleMaterialType,
-) {
+) -> Entity {
     let properties = material_type.properties();
 
     let unit_size = structure_component.grid.cell_size;
-    let volume = (unit_size * mesh_scale_factor).powi(2) * properties.thickness; // Consider thickness in volume
+    let area = (footprint.width * footprint.height) as f32;
+    let volume = (unit_size * mesh_scale_factor).powi(2) * area * properties.thickness; // Consider thickness in volume
     let structural_points =
         ((properties.yield_strength * volume * properties.density) / properties.damage_threshold) / UNIT_SCALE;
 
+    // `translation` is the center of the anchor cell, a larger module is centered on its footprint and its mesh and
+    // collider, both a cell wide, are stretched over it by the scale
+    let transform = Transform {
+        translation: translation + footprint.center_offset(orientation, unit_size).extend(0.0),
+        rotation: orientation.rotation(),
+        scale: Vec3::new(footprint.width as f32, footprint.height as f32, 1.0),
+    };
+
+    let mut module_entity = Entity::PLACEHOLDER;
     if !interactable {
         // Spawn the module entity
         commands.entity(structure_entity).with_children(|children| {
-            children.spawn(ModuleBundleRigid {
-                collider: Collider::rectangle(
-                    structure_component.grid.cell_size * mesh_scale_factor,
-                    structure_component.grid.cell_size * mesh_scale_factor,
-                ),
-                collider_density: ColliderDensity(volume * properties.density),
-                module: Module { module_type, inner_grid_pos: grid_pos, ..default() },
-                module_material: ModuleMaterial { structural_points, material_type },
-                mesh_bundle: MaterialMesh2dBundle {
-                    material: materials.add(ColorMaterial::from(color)),
-                    mesh: meshes
-                        .add(Rectangle {
-                            half_size: Vec2::splat((structure_component.grid.cell_size / 2.0) * mesh_scale_factor),
-                        })
-                        .into(),
-                    transform: Transform { translation, ..default() },
-                    visibility: Visibility::Inherited,
-                    ..default()
-                },
-                external_force: ExternalForce::default(),
-            });
+            module_entity = children
+                .spawn(ModuleBundleRigid {
+                    collider: Collider::rectangle(
+                        structure_component.grid.cell_size * mesh_scale_factor,
+                        structure_component.grid.cell_size * mesh_scale_factor,
+                    ),
+                    collision_layers: CollisionLayersConfig::module(),
+                    // Weighed by the structure, see `Structure::mass_properties`
+                    collider_density: ColliderDensity::ZERO,
+                    module: Module { module_type, inner_grid_pos: grid_pos, orientation, footprint, ..default() },
+                    module_material: ModuleMaterial {
+                        structural_points,
+                        max_structural_points: structural_points,
+                        material_type,
+                    },
+                    mesh_bundle: MaterialMesh2dBundle {
+                        material: materials.add(ColorMaterial::from(color)),
+                        // Every module has the same size, only their materials are recolored one by one
+                        mesh: game_assets.module_mesh.clone(),
+                        transform,
+                        visibility: Visibility::Inherited,
+                        ..default()
+                    },
+                    external_force: ExternalForce::default(),
+                })
+                .id();
         });
     } else {
         commands.entity(structure_entity).with_children(|children| {
-            children.spawn(ModuleBundleInteractable {
-                module: Module { module_type, inner_grid_pos: grid_pos, ..default() },
-                mesh_bundle: MaterialMesh2dBundle {
-                    material: materials.add(ColorMaterial::from(color)),
-                    mesh: meshes
-                        .add(Rectangle {
-                            half_size: Vec2::splat((structure_component.grid.cell_size / 2.0) * mesh_scale_factor),
-                        })
-                        .into(),
-                    transform: Transform { translation, ..default() },
-                    visibility: Visibility::Inherited,
-                    ..default()
-                },
-            });
+            module_entity = children
+                .spawn(ModuleBundleInteractable {
+                    module: Module { module_type, inner_grid_pos: grid_pos, orientation, footprint, ..default() },
+                    mesh_bundle: MaterialMesh2dBundle {
+                        material: materials.add(ColorMaterial::from(color)),
+                        mesh: game_assets.module_mesh.clone(),
+                        transform,
+                        visibility: Visibility::Inherited,
+                        ..default()
+                    },
+                })
+                .id();
         });
     }
 
-    structure_component.grid.insert(grid_pos.0, grid_pos.1, CellType::Module);
-    structure_component.density += properties.density;
+    module_type.insert_behavior(&mut commands.entity(module_entity));
+
+    for cell in footprint.cells(grid_pos, orientation) {
+        structure_component.insert_module(cell, module_entity);
+    }
+    module_entity
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn module_at(anchor: (i32, i32), footprint: Footprint) -> Module {
+        Module { inner_grid_pos: anchor, footprint, ..default() }
+    }
+
+    #[test]
+    fn multi_cell_modules_are_reached_from_their_nearest_cell() {
+        let module = module_at((2, 2), Footprint::new(2, 2));
+        let door = Interactable::new(InteractionKind::ToggleDoor);
+        let control = Interactable::new(InteractionKind::Control);
+
+        // Next to the bottom right cell, 2 cells away from the anchor
+        assert!(door.is_reachable_from(&module, (4, 3)));
+        assert!(!door.is_reachable_from(&module, (5, 3)));
+        // Standing on any covered cell
+        assert!(control.is_reachable_from(&module, (3, 3)));
+        assert!(!control.is_reachable_from(&module, (4, 3)));
+    }
+
+    #[test]
+    fn single_cell_modules_keep_their_reach() {
+        let module = module_at((2, 2), Footprint::SINGLE);
+        let door = Interactable::new(InteractionKind::ToggleDoor);
+
+        assert!(door.is_reachable_from(&module, (2, 1)));
+        assert!(!door.is_reachable_from(&module, (2, 2)));
+        assert!(!door.is_reachable_from(&module, (3, 3)));
+    }
 }
@@ -0,0 +1,143 @@
+use crate::core::rng::SeededRng;
+use crate::core::state::GameState;
+use crate::world::structures::Structure;
+use bevy::prelude::*;
+use std::fmt;
+
+const DEFAULT_NAME_SEED: u64 = 0x5EED_5A11_0F0F_0125;
+
+const SHIP_PREFIXES: [&str; 6] = ["ISV", "MSV", "CSS", "FTV", "RSV", "HMS"];
+const SHIP_ADJECTIVES: [&str; 16] = [
+    "Relentless",
+    "Silent",
+    "Crimson",
+    "Iron",
+    "Distant",
+    "Restless",
+    "Last",
+    "Golden",
+    "Wandering",
+    "Stubborn",
+    "Pale",
+    "Burning",
+    "Quiet",
+    "Lucky",
+    "Broken",
+    "Hollow",
+];
+const SHIP_NOUNS: [&str; 16] = [
+    "Heron",
+    "Anvil",
+    "Promise",
+    "Meridian",
+    "Lantern",
+    "Comet",
+    "Tortoise",
+    "Harbinger",
+    "Saint",
+    "Vagrant",
+    "Ember",
+    "Horizon",
+    "Kestrel",
+    "Bastion",
+    "Orphan",
+    "Tide",
+];
+const CAPTAIN_FIRST_NAMES: [&str; 16] = [
+    "Mira", "Tomas", "Ayo", "Lena", "Ravi", "Sofia", "Kenji", "Ines", "Dmitri", "Amara", "Jonah", "Yusuf", "Elena",
+    "Tariq", "Noor", "Hal",
+];
+const CAPTAIN_LAST_NAMES: [&str; 16] = [
+    "Okafor",
+    "Lindqvist",
+    "Moreau",
+    "Tanaka",
+    "Ibarra",
+    "Novak",
+    "Achebe",
+    "Reyes",
+    "Kowalski",
+    "Haddad",
+    "Varga",
+    "Osei",
+    "Brennan",
+    "Sato",
+    "Quill",
+    "Marsh",
+];
+
+/// Gives a ship name and a captain to every structure, so they can be told apart in the kill feed, the logs and
+/// radio hails. Names are drawn from a seeded generator, the same world always gets the same names.
+pub struct NamesPlugin {
+    pub seed: u64,
+}
+
+impl Default for NamesPlugin {
+    fn default() -> Self {
+        Self { seed: DEFAULT_NAME_SEED }
+    }
+}
+
+impl Plugin for NamesPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(NameGenerator::new(self.seed))
+            .add_systems(Update, name_structures_system.run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// Deterministic source of names, see `SeededRng`.
+#[derive(Resource, Debug, Clone)]
+pub struct NameGenerator {
+    rng: SeededRng,
+}
+
+impl NameGenerator {
+    pub fn new(seed: u64) -> Self {
+        Self { rng: SeededRng::new(seed) }
+    }
+
+    fn pick<'a>(&mut self, words: &[&'a str]) -> &'a str {
+        words[self.rng.index(words.len())]
+    }
+
+    pub fn ship_name(&mut self) -> String {
+        format!("{} {} {}", self.pick(&SHIP_PREFIXES), self.pick(&SHIP_ADJECTIVES), self.pick(&SHIP_NOUNS))
+    }
+
+    pub fn captain_name(&mut self) -> String {
+        format!("{} {}", self.pick(&CAPTAIN_FIRST_NAMES), self.pick(&CAPTAIN_LAST_NAMES))
+    }
+
+    pub fn structure_name(&mut self) -> StructureName {
+        StructureName { ship: self.ship_name(), captain: self.captain_name() }
+    }
+}
+
+/// Name of a structure and of its captain.
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct StructureName {
+    pub ship: String,
+    pub captain: String,
+}
+
+impl fmt::Display for StructureName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (Capt. {})", self.ship, self.captain)
+    }
+}
+
+fn name_structures_system(
+    structures_query: Query<Entity, (With<Structure>, Without<StructureName>)>,
+    mut name_generator: ResMut<NameGenerator>,
+    mut commands: Commands,
+) {
+    // Spawn order is stable, sort anyway so the names do not depend on the query order
+    let mut unnamed: Vec<Entity> = structures_query.iter().collect();
+    unnamed.sort();
+
+    for structure_entity in unnamed {
+        let name = name_generator.structure_name();
+        info!("Structure {:?} is the {}", structure_entity, name);
+        commands.entity(structure_entity).insert(name);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::configs::collision_layers::CollisionLayersConfig;
 use crate::world::grid::Grid;
 use avian2d::prelude::*;
 use bevy::prelude::*;
@@ -27,6 +28,7 @@ fn _spawn_ore(
         .spawn((
             RigidBody::Static,
             Collider::circle(10.0),
+            CollisionLayersConfig::environment(),
             Ore,
             MaterialMesh2dBundle {
                 mesh: meshes.add(Circle { radius: 10.0 }).into(),
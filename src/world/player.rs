@@ -1,5 +1,10 @@
-use crate::configs::config::UNIT_SCALE;
+use crate::configs::collision_layers::CollisionLayersConfig;
+use crate::core::game_assets::{GameAssets, PLAYER_RADIUS};
 use crate::core::state::GameState;
+use crate::gameplay::health::{Health, PlayerSpawnPoint, PLAYER_MAX_HEALTH};
+use crate::gameplay::jetpack::Jetpack;
+use crate::gameplay::life_support::Oxygen;
+use crate::gameplay::medical::Injury;
 use crate::world::grid::Grid;
 use avian2d::prelude::*;
 use bevy::prelude::*;
@@ -12,6 +17,7 @@ pub struct PlayerPlugin;
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(PlayerResource::default())
+            .register_type::<PlayerResource>()
             .add_systems(OnEnter(GameState::BuildingStructures), spawn_player);
     }
 }
@@ -19,7 +25,8 @@ impl Plugin for PlayerPlugin {
 #[derive(Component)]
 pub struct Player;
 
-#[derive(Resource, Default)]
+#[derive(Resource, Default, Reflect)]
+#[reflect(Resource)]
 pub struct PlayerResource {
     pub grid_position: (i32, i32),
     pub is_controlling_structure: bool,
@@ -28,8 +35,8 @@ pub struct PlayerResource {
 
 fn spawn_player(
     mut commands: Commands,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    mut meshes: ResMut<Assets<Mesh>>,
+    game_assets: Res<GameAssets>,
+    spawn_point: Res<PlayerSpawnPoint>,
     mut grid: ResMut<Grid>,
     mut player_grid_position: ResMut<PlayerResource>,
 ) {
@@ -41,14 +48,19 @@ fn spawn_player(
     let player_entity = commands
         .spawn((
             RigidBody::Dynamic,
-            Collider::circle(1.0 * UNIT_SCALE),
+            Collider::circle(PLAYER_RADIUS),
+            CollisionLayersConfig::player(),
             ColliderDensity(0.0),
             Mass(100.0),
             Player,
+            Injury::default(),
+            Health::new(PLAYER_MAX_HEALTH),
+            Oxygen::default(),
+            Jetpack::default(),
             MaterialMesh2dBundle {
-                mesh: meshes.add(Circle { radius: 1.0 * UNIT_SCALE }).into(),
-                material: materials.add(ColorMaterial::from(Color::WHITE)),
-                transform: Transform { translation: Vec3::new(-25.0, 0.0, 5.0), ..default() },
+                mesh: game_assets.player_mesh.clone(),
+                material: game_assets.player_material.clone(),
+                transform: Transform { translation: spawn_point.0.extend(5.0), ..default() },
                 visibility: Visibility::Visible,
                 ..default()
             },
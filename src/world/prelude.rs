@@ -1,7 +1,10 @@
 // src/world/prelude.rs
 
 pub use super::grid::*;
+pub use super::module_registry::*;
 pub use super::modules::*;
+pub use super::names::*;
 pub use super::ore::*;
 pub use super::player::*;
+pub use super::spatial_index::*;
 pub use super::structures::*;
@@ -0,0 +1,92 @@
+use crate::world::structures::Structure;
+
+use crate::prelude::*;
+
+const SPATIAL_BUCKET_SIZE: f32 = 100.0; // meters covered by each bucket side
+const SPATIAL_INDEX_MARGIN: f32 = 10.0; // meters added around structures, covers their movement until the next update
+
+/// Buckets the structures by the world cells their bounds overlap, so systems looking for the structures around a
+/// point only check a few of them instead of every structure in the world.
+#[derive(Resource, Debug, Default)]
+pub struct SpatialGridIndex {
+    buckets: HashMap<(i32, i32), Vec<Entity>>,
+    /// Buckets every indexed structure is in, to remove it when it moves.
+    structure_buckets: HashMap<Entity, Vec<(i32, i32)>>,
+}
+
+impl SpatialGridIndex {
+    pub fn bucket_of(position: Vec2) -> (i32, i32) {
+        let bucket = (position / SPATIAL_BUCKET_SIZE).floor();
+        (bucket.x as i32, bucket.y as i32)
+    }
+
+    /// Places the structure in every bucket overlapped by a circle around it.
+    pub fn insert(&mut self, structure_entity: Entity, center: Vec2, radius: f32) {
+        self.remove(structure_entity);
+
+        let (min_x, min_y) = Self::bucket_of(center - Vec2::splat(radius));
+        let (max_x, max_y) = Self::bucket_of(center + Vec2::splat(radius));
+        let buckets: Vec<(i32, i32)> = (min_x..=max_x).flat_map(|x| (min_y..=max_y).map(move |y| (x, y))).collect();
+
+        for bucket in &buckets {
+            self.buckets.entry(*bucket).or_default().push(structure_entity);
+        }
+        self.structure_buckets.insert(structure_entity, buckets);
+    }
+
+    pub fn remove(&mut self, structure_entity: Entity) {
+        let Some(buckets) = self.structure_buckets.remove(&structure_entity) else {
+            return;
+        };
+        for bucket in buckets {
+            if let Some(structures) = self.buckets.get_mut(&bucket) {
+                structures.retain(|entity| *entity != structure_entity);
+                if structures.is_empty() {
+                    self.buckets.remove(&bucket);
+                }
+            }
+        }
+    }
+
+    /// Structures whose bounds may contain the position.
+    pub fn structures_at(&self, position: Vec2) -> impl Iterator<Item = Entity> + '_ {
+        self.buckets.get(&Self::bucket_of(position)).into_iter().flatten().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.structure_buckets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.structure_buckets.is_empty()
+    }
+}
+
+/// Distance from the center of the structure to the corners of its grid.
+fn structure_radius(structure: &Structure) -> f32 {
+    Vec2::new(structure.grid.width as f32, structure.grid.height as f32).length() * structure.grid.cell_size / 2.0
+}
+
+/// Re-buckets the structures that moved or changed size, and forgets the despawned ones.
+pub(crate) fn update_spatial_grid_index_system(
+    structures_query: Query<(Entity, &Transform, &Structure), Or<(Changed<Transform>, Changed<Structure>)>>,
+    mut removed_structures: RemovedComponents<Structure>,
+    mut spatial_index: ResMut<SpatialGridIndex>,
+) {
+    for structure_entity in removed_structures.read() {
+        spatial_index.remove(structure_entity);
+    }
+
+    for (structure_entity, transform, structure) in &structures_query {
+        let center = transform.translation.truncate();
+        // Skip the structures still in the same buckets, which is most of them between two frames
+        let radius = structure_radius(structure) + SPATIAL_INDEX_MARGIN;
+        let unchanged = spatial_index.structure_buckets.get(&structure_entity).is_some_and(|buckets| {
+            buckets.first() == Some(&SpatialGridIndex::bucket_of(center - Vec2::splat(radius)))
+                && buckets.last() == Some(&SpatialGridIndex::bucket_of(center + Vec2::splat(radius)))
+        });
+        if !unchanged {
+            spatial_index.insert(structure_entity, center, radius);
+        }
+    }
+}
@@ -1,43 +1,62 @@
+use crate::configs::collision_layers::CollisionLayersConfig;
 use crate::configs::config::UNIT_SCALE;
 use crate::core::prelude::*;
+use crate::core::profiling::EntityCountDiagnosticAppExt;
 use crate::gameplay::prelude::*;
 use crate::world::prelude::*;
 
 use crate::prelude::*;
+use serde::{Deserialize, Serialize};
 
-const STRUCTURE_CELL_SIZE: f32 = 5.0 * UNIT_SCALE;
+pub const STRUCTURE_CELL_SIZE: f32 = 5.0 * UNIT_SCALE;
+pub const MODULE_MESH_SCALE_FACTOR: f32 = 0.90; // Modules are slightly smaller than their cell
 
 impl Plugin for StructuresPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<StructureInteractionEvent>()
+        app.init_resource::<ModuleRegistry>()
+            .init_resource::<SpatialGridIndex>()
+            .register_type::<Structure>()
+            .register_type::<Pressurization>()
+            .register_type::<Module>()
+            .register_type::<ModuleMaterial>()
+            .add_event::<StructureInteractionEvent>()
             .add_event::<StructureDepressurizationEvent>()
             .add_event::<ModuleDestroyedEvent>()
+            .add_event::<ModuleTookDamageEvent>()
+            .register_entity_count_diagnostic::<Module>("modules")
+            .register_entity_count_diagnostic::<Structure>("structures")
             .add_systems(
                 OnEnter(GameState::BuildingStructures),
                 (build_structures_from_file, build_pressurization_system).chain(),
             )
-            .add_systems(Update, control_command_center_system.run_if(in_state(GameState::InGame)))
+            .add_systems(
+                Update,
+                (control_command_center_system, rebuild_structure_collider_system, update_structure_mass_system)
+                    .run_if(in_state(GameState::InGame)),
+            )
             .add_systems(
                 PostUpdate,
                 (
+                    update_spatial_grid_index_system,
                     detect_player_inside_structure_system,
-                    make_player_child_of_structure_system.run_if(on_event::<StructureInteractionEvent>()),
+                    board_structure_system.run_if(on_event::<StructureInteractionEvent>()),
                 )
                     .chain()
                     .after(PhysicsSet::Sync)
                     .run_if(in_state(GameState::InGame)),
             );
 
-        if self.debug_enable {
-            app.add_systems(
-                PostUpdate,
-                (debug_draw_structure_grid, debug_draw_player_inside_structure_rect, debug_pressurization_system)
-                    .after(PhysicsSet::Sync)
-                    .chain()
-                    .run_if(in_state(GameState::InGame)),
-            );
-        }
-        app.add_plugins(StructuresCombatPlugin);
+        app.add_systems(
+            PostUpdate,
+            (
+                (debug_draw_structure_grid, debug_draw_player_inside_structure_rect).run_if(grid_overlay_shown),
+                debug_pressurization_system.run_if(pressurization_overlay_shown),
+            )
+                .after(PhysicsSet::Sync)
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        )
+        .add_plugins(StructuresCombatPlugin);
     }
 }
 
@@ -50,16 +69,85 @@ pub enum StructureInteractionEvent {
 #[derive(Event)]
 pub struct StructureDepressurizationEvent {
     pub depressurized_structure: Entity,
+    /// Cells of the rooms that were just breached.
+    pub breached_cells: HashSet<(i32, i32)>,
 }
 
-#[derive(Default)]
-pub struct StructuresPlugin {
-    pub debug_enable: bool,
+pub struct StructuresPlugin;
+
+const BREATHABLE_OXYGEN: f32 = 0.5;
+
+pub type RoomId = u32;
+
+/// A connected region of non module cells inside a structure.
+#[derive(Debug, Default, Clone, Reflect)]
+pub struct RoomState {
+    pub cells: HashSet<(i32, i32)>,
+    /// The room reaches the structure boundary and is open to space.
+    pub exposed: bool,
 }
 
-#[derive(Component)]
+#[derive(Component, Default, Reflect)]
+#[reflect(Component)]
 pub struct Pressurization {
-    pub exposed_cells: HashSet<(i32, i32)>,
+    pub rooms: HashMap<RoomId, RoomState>,
+    cell_rooms: HashMap<(i32, i32), RoomId>,
+    /// Oxygen of every non module cell, from 0.0 (vacuum) to 1.0 (fully pressurized).
+    pub oxygen: HashMap<(i32, i32), f32>,
+}
+
+impl Pressurization {
+    /// Runs the room segmentation on a structure, sealed rooms start full of oxygen.
+    pub fn from_structure(structure: &Structure) -> Self {
+        let mut pressurization = Self::default();
+        pressurization.update_rooms(structure.check_pressurization());
+        pressurization.oxygen = pressurization
+            .cell_rooms
+            .keys()
+            .map(|&cell_pos| (cell_pos, if pressurization.is_exposed(cell_pos) { 0.0 } else { 1.0 }))
+            .collect();
+        pressurization
+    }
+
+    /// Replaces the rooms with a new segmentation.
+    /// Returns the cells of the rooms that were sealed before and are now open to space.
+    pub fn update_rooms(&mut self, rooms: HashMap<RoomId, RoomState>) -> HashSet<(i32, i32)> {
+        let breached_cells = rooms
+            .values()
+            .filter(|room| room.exposed)
+            .flat_map(|room| room.cells.iter())
+            .filter(|cell| self.room_of(**cell).is_some_and(|room_id| !self.rooms[&room_id].exposed))
+            .copied()
+            .collect();
+
+        self.cell_rooms =
+            rooms.iter().flat_map(|(room_id, room)| room.cells.iter().map(move |cell| (*cell, *room_id))).collect();
+        self.rooms = rooms;
+
+        breached_cells
+    }
+
+    pub fn room_of(&self, cell: (i32, i32)) -> Option<RoomId> {
+        self.cell_rooms.get(&cell).copied()
+    }
+
+    /// Checks if the cell belongs to a room open to space.
+    pub fn is_exposed(&self, cell: (i32, i32)) -> bool {
+        self.room_of(cell).is_some_and(|room_id| self.rooms[&room_id].exposed)
+    }
+
+    pub fn exposed_cells(&self) -> impl Iterator<Item = &(i32, i32)> {
+        self.rooms.values().filter(|room| room.exposed).flat_map(|room| room.cells.iter())
+    }
+
+    pub fn oxygen_at(&self, cell: (i32, i32)) -> f32 {
+        self.oxygen.get(&cell).copied().unwrap_or(0.0)
+    }
+
+    /// Checks if a character can breathe without a suit in the given cell.
+    pub fn is_breathable(&self, cell: (i32, i32)) -> bool {
+        self.oxygen_at(cell) >= BREATHABLE_OXYGEN
+    }
 }
 
 #[derive(Component)]
@@ -75,16 +163,21 @@ struct StructureBundle {
     rigid_body: RigidBody,
     collider: Collider,
     collider_density: ColliderDensity,
+    mass_properties: MassPropertiesBundle,
     structure: Structure,
     spatial_bundle: SpatialBundle,
     collision_layers: CollisionLayers,
     pressurization: Pressurization,
+    crew: Crew,
+    external_impulse: ExternalImpulse,
 }
 
-#[derive(Component, Debug, Default)]
+#[derive(Component, Debug, Default, Clone, Reflect, Serialize, Deserialize)]
+#[reflect(Component)]
 pub struct Structure {
-    pub density: f32,
     pub grid: Grid,
+    /// Module entity in every cell holding one, kept in sync as modules are spawned and removed.
+    modules: HashMap<(i32, i32), Entity>,
 }
 
 impl Structure {
@@ -92,6 +185,36 @@ impl Structure {
         Structure { ..Default::default() }
     }
 
+    /// Returns the module entity in a cell of the grid.
+    pub fn module_at(&self, cell: (i32, i32)) -> Option<Entity> {
+        self.modules.get(&cell).copied()
+    }
+
+    /// Every module entity with its cell, a module covering several cells comes once for each of them.
+    pub fn modules(&self) -> impl Iterator<Item = ((i32, i32), Entity)> + '_ {
+        self.modules.iter().map(|(&cell, &module_entity)| (cell, module_entity))
+    }
+
+    /// Places a module entity in a cell, filling the cell in the grid.
+    pub fn insert_module(&mut self, cell: (i32, i32), module_entity: Entity) {
+        self.grid.insert(cell.0, cell.1, CellType::Module);
+        self.modules.insert(cell, module_entity);
+    }
+
+    /// Takes the module out of a cell, emptying the cell in the grid, and returns its entity.
+    /// Every other cell covered by the same module is emptied too.
+    pub fn remove_module_at(&mut self, cell: (i32, i32)) -> Option<Entity> {
+        self.grid.set_cell_type_to_empty(cell.0, cell.1);
+        let module_entity = self.modules.remove(&cell)?;
+        let covered: Vec<(i32, i32)> =
+            self.modules.iter().filter(|(_, &entity)| entity == module_entity).map(|(&covered, _)| covered).collect();
+        for covered in covered {
+            self.grid.set_cell_type_to_empty(covered.0, covered.1);
+            self.modules.remove(&covered);
+        }
+        Some(module_entity)
+    }
+
     /// After identifying the exposed cells, this method returns the modules adjacent to the exposed cells.
     pub fn find_neighbors_of_exposed_modules(&self, exposed_cells: &HashSet<(i32, i32)>) -> HashSet<(i32, i32)> {
         let mut neighboring_modules = HashSet::new();
@@ -140,9 +263,12 @@ impl Structure {
     }
 
     /// Converts a world position into the grid coordinates of the structure.
-    fn world_to_grid(&self, world_pos: Vec3, structure_transform: &Transform) -> (i32, i32) {
-        let local_pos = Structure::world_to_local_grid_position(world_pos.truncate(), structure_transform);
+    pub fn world_to_grid(&self, world_pos: Vec3, structure_transform: &Transform) -> (i32, i32) {
+        self.local_to_grid(Structure::world_to_local_grid_position(world_pos.truncate(), structure_transform))
+    }
 
+    /// Converts a position relative to the structure into its grid coordinates.
+    pub fn local_to_grid(&self, local_pos: Vec2) -> (i32, i32) {
         let grid_x =
             ((local_pos.x + (self.grid.width as f32 * self.grid.cell_size) / 2.0) / self.grid.cell_size).floor() as i32;
 
@@ -164,16 +290,74 @@ impl Structure {
         rotation_matrix * translated_player_pos
     }
 
+    /// Given grid cell coordinates, returns the position of the center of that cell relative to the structure.
+    pub fn grid_cell_center_local_position(&self, cell_x: i32, cell_y: i32) -> Vec2 {
+        // Take the flipped y-axis into account
+        Vec2::new(
+            (cell_x as f32 - self.grid.width as f32 / 2.0) * self.grid.cell_size + self.grid.cell_size / 2.0,
+            -((cell_y as f32 - self.grid.height as f32 / 2.0) * self.grid.cell_size + self.grid.cell_size / 2.0),
+        )
+    }
+
+    /// Mass, center of mass and angular inertia of the modules of the structure, each module weighing the mass of its
+    /// cell given by `module_mass`. `None` once no module is left.
+    pub fn mass_properties(&self, module_mass: impl Fn(Entity) -> f32) -> Option<MassPropertiesBundle> {
+        let modules: Vec<(Vec2, f32)> = self
+            .modules()
+            .map(|((x, y), module_entity)| (self.grid_cell_center_local_position(x, y), module_mass(module_entity)))
+            .filter(|(_, mass)| *mass > 0.0)
+            .collect();
+        let mass: f32 = modules.iter().map(|(_, mass)| mass).sum();
+        if mass <= 0.0 {
+            return None;
+        }
+
+        let center_of_mass = modules.iter().map(|(position, mass)| *position * *mass).sum::<Vec2>() / mass;
+        // Every module is a square plate, moved from its own center to the center of mass of the structure
+        let cell_inertia = self.grid.cell_size.powi(2) / 6.0;
+        let inertia: f32 = modules
+            .iter()
+            .map(|(position, module_mass)| module_mass * (cell_inertia + position.distance_squared(center_of_mass)))
+            .sum();
+
+        Some(MassPropertiesBundle {
+            mass: Mass(mass),
+            inverse_mass: InverseMass(1.0 / mass),
+            inertia: Inertia(inertia),
+            inverse_inertia: InverseInertia(1.0 / inertia.max(f32::EPSILON)),
+            center_of_mass: CenterOfMass(center_of_mass),
+        })
+    }
+
+    /// Builds a collider made of one square per module cell, so holes in the hull are not solid.
+    pub fn compound_collider(&self) -> Collider {
+        let shapes: Vec<(Vector, Rotation, Collider)> = self
+            .grid
+            .cells
+            .iter()
+            .filter(|(_, cell)| cell.cell_type == CellType::Module)
+            .map(|(&(x, y), _)| {
+                (
+                    self.grid_cell_center_local_position(x, y),
+                    Rotation::default(),
+                    Collider::rectangle(self.grid.cell_size, self.grid.cell_size),
+                )
+            })
+            .collect();
+
+        if shapes.is_empty() {
+            // A structure without any module left still needs a shape to keep its rigid body
+            return Collider::circle(self.grid.cell_size / 2.0);
+        }
+        Collider::compound(shapes)
+    }
+
     /// Given grid cell coordinates, returns the world position of the center of that cell.
     pub fn grid_cell_center_world_position(&self, cell_x: i32, cell_y: i32, structure_transform: &Transform) -> Vec2 {
         let structure_world_pos = structure_transform.translation.truncate();
         let z_rotation = structure_transform.rotation.to_euler(EulerRot::XYZ).2;
 
-        // Calculate the local position of the cell center, taking the flipped y-axis into account
-        let cell_local_pos = Vec2::new(
-            (cell_x as f32 - self.grid.width as f32 / 2.0) * self.grid.cell_size + self.grid.cell_size / 2.0,
-            -((cell_y as f32 - self.grid.height as f32 / 2.0) * self.grid.cell_size + self.grid.cell_size / 2.0),
-        );
+        let cell_local_pos = self.grid_cell_center_local_position(cell_x, cell_y);
 
         // Apply rotation to the cell's local position
         let rotated_cell_pos = Mat2::from_angle(z_rotation) * cell_local_pos;
@@ -182,212 +366,297 @@ impl Structure {
         structure_world_pos + rotated_cell_pos
     }
 
+    /// Writes the modules of the structure back in the data file format, at the origin and without crew.
+    pub fn to_structure_data<'a>(&self, modules: impl Iterator<Item = &'a Module>) -> StructureData {
+        let mut symbols: HashMap<(i32, i32), char> = HashMap::new();
+        let mut orientations = Vec::new();
+        for module in modules {
+            symbols.extend(module.cells().map(|cell| (cell, module.module_type.symbol())));
+            if module.orientation != Orientation::North {
+                orientations.push(ModuleOrientation { cell: module.inner_grid_pos, facing: module.orientation });
+            }
+        }
+
+        StructureData {
+            world_pos: [0.0, 0.0],
+            structure: layout_rows(self.grid.width, self.grid.height, &symbols),
+            orientations,
+            crew: 0,
+            rotation: 0.0,
+            velocity: [0.0, 0.0],
+            livery: Livery::default(),
+            faction: None,
+        }
+    }
+
+    /// Whether a character can stand in a cell: inside the grid and not filled by a module.
+    pub fn is_walkable(&self, cell: (i32, i32)) -> bool {
+        self.grid.get(cell.0, cell.1).is_some_and(|grid_cell| grid_cell.cell_type == CellType::Empty)
+    }
+
+    /// Shortest walk through the cells accepted by `is_passable` from `from` to the nearest cell accepted by `is_goal`,
+    /// `from` excluded. Empty when `from` is already a goal, `None` when no goal can be reached.
+    pub fn find_path(
+        &self,
+        from: (i32, i32),
+        is_passable: impl Fn((i32, i32)) -> bool,
+        is_goal: impl Fn((i32, i32)) -> bool,
+    ) -> Option<Vec<(i32, i32)>> {
+        let mut came_from: HashMap<(i32, i32), (i32, i32)> = HashMap::new();
+        let mut queue = VecDeque::from([from]);
+        came_from.insert(from, from);
+
+        while let Some(cell) = queue.pop_front() {
+            if is_goal(cell) {
+                let mut path = Vec::new();
+                let mut step = cell;
+                while step != from {
+                    path.push(step);
+                    step = came_from[&step];
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for next in self.get_adjacent_cells(cell) {
+                if is_passable(next) && !came_from.contains_key(&next) {
+                    came_from.insert(next, cell);
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+
     /// Checks if the given grid coordinates are within the bounds of the structure's grid.
     pub fn is_within_grid_bounds(&self, grid_x: i32, grid_y: i32) -> bool {
         grid_x >= 0 && grid_x < self.grid.width as i32 && grid_y >= 0 && grid_y < self.grid.height as i32
     }
 
-    /// Checks if the total structure is pressurized by performing a flood fill algorithm.
-    /// Returns all the cells that are exposed to space.
-    pub fn check_pressurization(&self) -> HashSet<(i32, i32)> {
+    /// Splits the structure into rooms by flood filling every connected region of non module cells.
+    /// A room touching the grid boundary is open to space, every other room is sealed.
+    pub fn check_pressurization(&self) -> HashMap<RoomId, RoomState> {
+        let mut rooms = HashMap::new();
         let mut visited = HashSet::new();
-        let mut queue = VecDeque::new();
-
-        // Start flood fill from all cells on the boundary that are not modules
-        for x in 0..self.grid.width as i32 {
-            for y in &[0, self.grid.height as i32 - 1] {
-                if let Some(cell) = self.grid.get(x, *y) {
-                    if cell.cell_type != CellType::Module {
-                        queue.push_back((x, *y));
-                    }
-                }
-            }
-        }
 
         for y in 0..self.grid.height as i32 {
-            for x in &[0, self.grid.width as i32 - 1] {
-                if let Some(cell) = self.grid.get(*x, y) {
-                    if cell.cell_type != CellType::Module {
-                        queue.push_back((*x, y));
-                    }
+            for x in 0..self.grid.width as i32 {
+                if visited.contains(&(x, y)) || !self.is_open_cell(x, y) {
+                    continue;
                 }
-            }
-        }
-
-        // Perform flood fill
-        while let Some((x, y)) = queue.pop_front() {
-            if visited.contains(&(x, y)) {
-                continue;
-            }
 
-            visited.insert((x, y));
+                let mut room = RoomState::default();
+                let mut queue = VecDeque::from([(x, y)]);
+                visited.insert((x, y));
 
-            for (dx, dy) in &[(-1, 0), (1, 0), (0, -1), (0, 1)] {
-                let nx = x + dx;
-                let ny = y + dy;
+                // Perform flood fill
+                while let Some((cx, cy)) = queue.pop_front() {
+                    room.cells.insert((cx, cy));
+                    if cx == 0 || cy == 0 || cx == self.grid.width as i32 - 1 || cy == self.grid.height as i32 - 1 {
+                        room.exposed = true;
+                    }
 
-                if self.is_within_grid_bounds(nx, ny) {
-                    if let Some(cell) = self.grid.get(nx, ny) {
-                        if cell.cell_type != CellType::Module && !visited.contains(&(nx, ny)) {
+                    for (dx, dy) in &[(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                        let (nx, ny) = (cx + dx, cy + dy);
+                        if self.is_open_cell(nx, ny) && visited.insert((nx, ny)) {
                             queue.push_back((nx, ny));
                         }
                     }
                 }
+
+                rooms.insert(rooms.len() as RoomId, room);
             }
         }
 
-        visited
+        rooms
     }
+
+    /// Checks if the cell is inside the grid and not blocked by a module.
+    fn is_open_cell(&self, x: i32, y: i32) -> bool {
+        self.is_within_grid_bounds(x, y) && self.grid.get(x, y).is_some_and(|cell| cell.cell_type != CellType::Module)
+    }
+}
+
+/// Rows of a structure layout in the data file format, the cells without a symbol are empty.
+pub fn layout_rows(width: u32, height: u32, symbols: &HashMap<(i32, i32), char>) -> Vec<String> {
+    (0..height as i32)
+        .map(|y| (0..width as i32).map(|x| symbols.get(&(x, y)).copied().unwrap_or('#')).collect())
+        .collect()
 }
 
 fn build_structures_from_file(
     mut commands: Commands,
     asset_store: Res<AssetStore>,
-    blob_assets: Res<Assets<AssetBlob>>,
+    structures_assets: Res<Assets<StructuresData>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
-    mut meshes: ResMut<Assets<Mesh>>,
+    game_assets: Res<GameAssets>,
+    module_registry: Res<ModuleRegistry>,
 ) {
-    if let Some(blob) = blob_assets.get(&asset_store.structures_blob) {
-        let structures_data: String = String::from_utf8(blob.bytes.clone()).expect("Invalid UTF-8 data");
-        let structures: StructuresData =
-            serde_json::from_str(&structures_data).expect("Failed to deserialize structures data");
-
-        for structure_data in structures.structures {
-            let mut structure_component = Structure::new();
-
-            let grid_width = structure_data.structure[0].len() as f32;
-            let grid_height = structure_data.structure.len() as f32;
+    let Some(structures) = structures_assets.get(&asset_store.structures) else {
+        return;
+    };
+    for structure_data in &structures.structures {
+        spawn_structure(&mut commands, &mut materials, &game_assets, &module_registry, structure_data);
+    }
+}
 
-            let mesh_scale_factor = 0.90; // Adjust this value to reduce the mesh size
+/// Spawns a structure and all its modules from its data description.
+pub fn spawn_structure(
+    commands: &mut Commands,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    game_assets: &GameAssets,
+    module_registry: &ModuleRegistry,
+    structure_data: &StructureData,
+) -> Entity {
+    let mut structure_component = Structure::new();
+
+    let grid_width = structure_data.structure[0].len() as f32;
+    let grid_height = structure_data.structure.len() as f32;
+
+    let mesh_scale_factor = MODULE_MESH_SCALE_FACTOR;
+
+    structure_component.grid = Grid::new(
+        grid_width as u32,   // Width of the structure
+        grid_height as u32,  // Height of the structure
+        STRUCTURE_CELL_SIZE, // Cell size
+    );
+
+    let structure_entity = commands.spawn_empty().id();
+    // Convert the world position from the JSON to a Vec3 for the transform
+    let world_pos = Vec3::new(structure_data.world_pos[0], structure_data.world_pos[1], 1.0);
+    let structure_transform =
+        Transform::from_translation(world_pos).with_rotation(Quat::from_rotation_z(structure_data.rotation));
+
+    let mut covered = HashSet::new();
+    for (y, row) in structure_data.structure.iter().enumerate() {
+        for (x, cell) in row.chars().enumerate() {
+            let x_translation = ((x as f32 - (grid_width / 2.0)) * structure_component.grid.cell_size)
+                + (structure_component.grid.cell_size / 2.0);
+            let y_translation = ((grid_height / 2.0) - y as f32) * structure_component.grid.cell_size
+                - (structure_component.grid.cell_size / 2.0);
+
+            // The other cells of a module covering several of them were filled with its anchor cell
+            if covered.contains(&(x as i32, y as i32)) {
+                continue;
+            }
+            let orientation = structure_data.orientation_at((x as i32, y as i32));
+            let footprint = module_registry.footprint(cell);
+            covered.extend(footprint.cells((x as i32, y as i32), orientation));
+
+            if let Some(module_type) = ModuleType::from_symbol(cell) {
+                spawn_module(
+                    commands,
+                    structure_entity,
+                    &mut structure_component,
+                    materials,
+                    game_assets,
+                    module_type,
+                    builtin_module_color(module_type),
+                    (x as i32, y as i32),
+                    orientation,
+                    footprint,
+                    Vec3::new(x_translation, y_translation, module_type.z_layer()),
+                    mesh_scale_factor,
+                    module_type.is_interactable(),
+                    module_type.material_type(),
+                );
+            } else if module_registry.get(cell).is_some() {
+                module_registry.spawn(
+                    commands,
+                    structure_entity,
+                    &mut structure_component,
+                    materials,
+                    game_assets,
+                    cell,
+                    (x as i32, y as i32),
+                    orientation,
+                    Vec3::new(x_translation, y_translation, 1.0),
+                );
+            } else {
+                // Insert an empty cell
+                structure_component.grid.insert(x as i32, y as i32, CellType::Empty);
+            }
+        }
+    }
 
-            structure_component.grid = Grid::new(
-                grid_width as u32,   // Width of the structure
-                grid_height as u32,  // Height of the structure
-                STRUCTURE_CELL_SIZE, // Cell size
-            );
+    let pressurization = Pressurization::from_structure(&structure_component);
+
+    // Insert the structure bundle
+    commands.entity(structure_entity).insert(StructureBundle {
+        rigid_body: RigidBody::Dynamic,
+        collision_layers: CollisionLayersConfig::hull(),
+        collider: structure_component.compound_collider(),
+        // The mass comes from the modules, see `update_structure_mass_system`
+        collider_density: ColliderDensity::ZERO,
+        mass_properties: MassPropertiesBundle::default(),
+        structure: structure_component,
+        spatial_bundle: SpatialBundle {
+            transform: structure_transform,
+            visibility: Visibility::Visible,
+            ..Default::default()
+        },
+        pressurization,
+        crew: Crew::new(structure_data.crew),
+        external_impulse: ExternalImpulse::default(),
+    });
+    commands.entity(structure_entity).insert((
+        LinearVelocity(Vec2::new(structure_data.velocity[0], structure_data.velocity[1])),
+        structure_data.livery.clone(),
+    ));
+    if let Some(faction) = &structure_data.faction {
+        commands.entity(structure_entity).insert(Faction(faction.clone()));
+    }
 
-            let structure_entity = commands.spawn_empty().id();
-            // Convert the world position from the JSON to a Vec3 for the transform
-            let world_pos = Vec3::new(structure_data.world_pos[0], structure_data.world_pos[1], 1.0);
-            let structure_transform = Transform::from_translation(world_pos);
-
-            for (y, row) in structure_data.structure.iter().enumerate() {
-                for (x, cell) in row.chars().enumerate() {
-                    let x_translation = ((x as f32 - (grid_width / 2.0)) * structure_component.grid.cell_size)
-                        + (structure_component.grid.cell_size / 2.0);
-                    let y_translation = ((grid_height / 2.0) - y as f32) * structure_component.grid.cell_size
-                        - (structure_component.grid.cell_size / 2.0);
-
-                    // Match the character to determine the type of module to spawn
-                    match cell {
-                        'E' => {
-                            spawn_module(
-                                &mut commands,
-                                structure_entity,
-                                &mut structure_component,
-                                &mut materials,
-                                &mut meshes,
-                                ModuleType::Engine,
-                                Color::from(RED),
-                                (x as i32, y as i32),
-                                Vec3::new(x_translation, y_translation, 1.0),
-                                mesh_scale_factor,
-                                false,
-                                ModuleMaterialType::Steel,
-                            );
-                        }
-                        'W' => {
-                            spawn_module(
-                                &mut commands,
-                                structure_entity,
-                                &mut structure_component,
-                                &mut materials,
-                                &mut meshes,
-                                ModuleType::Wall,
-                                Color::from(GREY),
-                                (x as i32, y as i32),
-                                Vec3::new(x_translation, y_translation, 1.0),
-                                mesh_scale_factor,
-                                false,
-                                ModuleMaterialType::Steel,
-                            );
-                        }
-                        'C' => {
-                            spawn_module(
-                                &mut commands,
-                                structure_entity,
-                                &mut structure_component,
-                                &mut materials,
-                                &mut meshes,
-                                ModuleType::CommandCenter,
-                                Color::from(BLUE),
-                                (x as i32, y as i32),
-                                Vec3::new(x_translation, y_translation, -1.0),
-                                mesh_scale_factor,
-                                true,
-                                ModuleMaterialType::Steel,
-                            );
-                        }
-                        '!' => {
-                            spawn_module(
-                                &mut commands,
-                                structure_entity,
-                                &mut structure_component,
-                                &mut materials,
-                                &mut meshes,
-                                ModuleType::Cannon,
-                                Color::from(PURPLE),
-                                (x as i32, y as i32),
-                                Vec3::new(x_translation, y_translation, 1.0),
-                                mesh_scale_factor,
-                                false,
-                                ModuleMaterialType::Aluminum,
-                            );
-                        }
-                        _ => {
-                            // Insert an empty cell
-                            structure_component.grid.insert(x as i32, y as i32, CellType::Empty);
-                        }
-                    };
-                }
-            }
+    structure_entity
+}
 
-            // Insert the structure bundle
-            commands.entity(structure_entity).insert(StructureBundle {
-                rigid_body: RigidBody::Dynamic,
-                collision_layers: CollisionLayers::NONE,
-                collider: Collider::rectangle(
-                    grid_width * structure_component.grid.cell_size,
-                    grid_height * structure_component.grid.cell_size,
-                ),
-                collider_density: ColliderDensity(structure_component.density),
-                structure: structure_component,
-                spatial_bundle: SpatialBundle {
-                    transform: Transform::from_translation(structure_transform.translation),
-                    visibility: Visibility::Visible,
-                    ..Default::default()
-                },
-                pressurization: Pressurization { exposed_cells: HashSet::new() },
-            });
-        }
-    } else {
-        panic!("Failed to load structures asset");
+/// Regenerates the structure collider when modules are added or removed from its grid.
+fn rebuild_structure_collider_system(mut structures_query: Query<(&Structure, &mut Collider), Changed<Structure>>) {
+    for (structure, mut collider) in &mut structures_query {
+        *collider = structure.compound_collider();
     }
 }
 
-fn make_player_child_of_structure_system(
-    mut event_reader: EventReader<StructureInteractionEvent>,
-    mut command: Commands,
+/// Weighs the structure from the materials and the positions of its modules whenever they are added or removed, so a
+/// ship missing part of its hull turns around a shifted center and gets lighter.
+fn update_structure_mass_system(
+    mut structures_query: Query<
+        (&Structure, &mut Mass, &mut InverseMass, &mut Inertia, &mut InverseInertia, &mut CenterOfMass),
+        Changed<Structure>,
+    >,
+    materials_query: Query<&ModuleMaterial>,
 ) {
+    for (structure, mut mass, mut inverse_mass, mut inertia, mut inverse_inertia, mut center_of_mass) in
+        &mut structures_query
+    {
+        // Interactable modules carry no material, they weigh like the default one
+        let module_mass = |module_entity| {
+            let material_type = materials_query.get(module_entity).map(|material| material.material_type);
+            material_type.unwrap_or_default().module_mass(structure.grid.cell_size)
+        };
+        // A structure without any module keeps its last mass
+        let Some(properties) = structure.mass_properties(module_mass) else {
+            continue;
+        };
+        *mass = properties.mass;
+        *inverse_mass = properties.inverse_mass;
+        *inertia = properties.inertia;
+        *inverse_inertia = properties.inverse_inertia;
+        *center_of_mass = properties.center_of_mass;
+    }
+}
+
+/// Players walking inside a structure keep their own rigid body and move with it through `Aboard`, parenting a
+/// dynamic body to another one would make the hierarchy and the physics engine fight over its position.
+fn board_structure_system(mut event_reader: EventReader<StructureInteractionEvent>, mut command: Commands) {
     for event in event_reader.read() {
         match event {
             StructureInteractionEvent::PlayerEntered { player_entity, structure_entity } => {
-                command.entity(*player_entity).set_parent_in_place(*structure_entity);
-                debug!("Player is now a child of the structure.");
+                command.entity(*player_entity).insert(Aboard::new(*structure_entity));
+                debug!("Player is now aboard the structure.");
             }
             StructureInteractionEvent::PlayerExited { player_entity, structure_entity: _ } => {
-                command.entity(*player_entity).remove_parent_in_place();
-                debug!("Player is no longer a child of the structure.");
+                command.entity(*player_entity).remove::<Aboard>();
+                debug!("Player is no longer aboard the structure.");
             }
         }
     }
@@ -398,8 +667,7 @@ fn build_pressurization_system(
     mut next_state: ResMut<NextState<GameState>>,
 ) {
     for (mut pressurization, structure) in structures_query.iter_mut() {
-        let exposed_cells = structure.check_pressurization();
-        pressurization.exposed_cells = exposed_cells.clone();
+        *pressurization = Pressurization::from_structure(structure);
     }
     next_state.set(GameState::InGame);
 }
@@ -408,13 +676,21 @@ fn control_command_center_system(
     mut event_reader: EventReader<InputAction>,
     mut player_query: Query<(Entity, &GlobalTransform, &mut LinearVelocity), With<Player>>,
     mut command: Commands,
-    mut parent_query: Query<(Entity, &Structure, &Transform, &Children)>,
-    mut module_query: Query<&mut Module>,
+    parent_query: Query<
+        (Entity, &Structure, &Transform, &LinearVelocity, &AngularVelocity, &CenterOfMass),
+        Without<Player>,
+    >,
+    mut module_query: Query<(&mut Module, &Interactable)>,
     mut player_resource: ResMut<PlayerResource>,
+    spatial_index: Res<SpatialGridIndex>,
 ) {
     //loop for player pos
     for (player_entity, player_transform, mut player_velocity) in &mut player_query {
-        for (structure_entity, structure, structure_transform, children) in &mut parent_query {
+        // Only the structures around the player can have a command center under them
+        let nearby_structures = spatial_index.structures_at(player_transform.translation().truncate());
+        for (structure_entity, structure, structure_transform, linear_velocity, angular_velocity, center_of_mass) in
+            parent_query.iter_many(nearby_structures)
+        {
             // Convert the adjusted position to grid coordinates
             let (player_grid_x, player_grid_y) =
                 structure.world_to_grid(player_transform.translation(), structure_transform);
@@ -422,11 +698,12 @@ fn control_command_center_system(
             // Check if the player's grid coordinates are within the grid's bounds
             if structure.is_within_grid_bounds(player_grid_x, player_grid_y) {
                 // Player is inside the structure's grid at this point.
-                // Check if the player is in a Command Center and if so, check if the player is already controlling it
-                for child in children {
-                    if let Ok(mut module) = module_query.get_mut(*child) {
-                        if matches!(module.module_type, ModuleType::CommandCenter)
-                            && matches!((module.inner_grid_pos.0, module.inner_grid_pos.1), (x, y) if x == player_grid_x && y == player_grid_y)
+                // Check if the player is on a control module and if so, check if the player is already controlling it
+                // Control modules have no reach, only the module under the player can be used
+                if let Some(module_entity) = structure.module_at((player_grid_x, player_grid_y)) {
+                    if let Ok((mut module, interactable)) = module_query.get_mut(module_entity) {
+                        if interactable.kind == InteractionKind::Control
+                            && interactable.is_reachable_from(&module, (player_grid_x, player_grid_y))
                         {
                             // Player can control or release the Command Center by pressing the spacebar.
                             for event in event_reader.read() {
@@ -439,8 +716,11 @@ fn control_command_center_system(
                                         *player_velocity = LinearVelocity::ZERO;
                                         // let's insert the PlayerControlled component to the structure
                                         command.entity(structure_entity).insert(ControlledByPlayer { player_entity });
-                                        // let's remove the RigidBody component from the player to make it static relative to the structure
-                                        command.entity(player_entity).remove::<RigidBody>();
+                                        // Without a body the pilot is carried by the structure as its child
+                                        command
+                                            .entity(player_entity)
+                                            .remove::<RigidBody>()
+                                            .set_parent_in_place(structure_entity);
                                         // Update the player resource to indicate that the player is controlling a structure
                                         player_resource.is_controlling_structure = true;
                                     } else if module.entity_connected == Some(player_entity) {
@@ -450,7 +730,18 @@ fn control_command_center_system(
 
                                         // let's remove the PlayerControlled component from the structure
                                         command.entity(structure_entity).remove::<ControlledByPlayer>();
-                                        command.entity(player_entity).insert(RigidBody::Dynamic);
+                                        // Back on their feet the player moves along with the structure
+                                        *player_velocity = LinearVelocity(structure_point_velocity(
+                                            linear_velocity,
+                                            angular_velocity,
+                                            structure_transform,
+                                            center_of_mass,
+                                            player_transform.translation().truncate(),
+                                        ));
+                                        command
+                                            .entity(player_entity)
+                                            .remove_parent_in_place()
+                                            .insert(RigidBody::Dynamic);
                                         // Update the player resource to indicate that the player is not controlling a structure
                                         player_resource.is_controlling_structure = false;
                                     }
@@ -471,9 +762,15 @@ fn detect_player_inside_structure_system(
     structures_query: Query<(Entity, &Transform, &Structure)>,
     mut event_writer: EventWriter<StructureInteractionEvent>,
     mut player_resource: ResMut<PlayerResource>,
+    spatial_index: Res<SpatialGridIndex>,
 ) {
     for (player_entity, player_transform, _player) in &player_query {
-        for (structure_entity, structure_transform, structure) in &structures_query {
+        // The structure the player is in is always checked, so leaving it is noticed even when far from it
+        let mut nearby_structures: Vec<Entity> =
+            spatial_index.structures_at(player_transform.translation().truncate()).collect();
+        nearby_structures.extend(player_resource.inside_structure.filter(|entity| !nearby_structures.contains(entity)));
+
+        for (structure_entity, structure_transform, structure) in structures_query.iter_many(&nearby_structures) {
             // Convert player's world position to the structure's grid coordinates
             let (player_grid_x, player_grid_y) =
                 structure.world_to_grid(player_transform.translation(), structure_transform);
@@ -547,7 +844,6 @@ fn debug_draw_player_inside_structure_rect(
 fn debug_pressurization_system(mut gizmos: Gizmos, query: Query<(&Transform, &Pressurization, &Structure)>) {
     for (structure_transform, pressurization, structure) in query.iter() {
         let grid = &structure.grid;
-        let exposed_cells = &pressurization.exposed_cells;
 
         // Iterate over all cells in the grid
         for y in 0..grid.height as i32 {
@@ -559,7 +855,7 @@ fn debug_pressurization_system(mut gizmos: Gizmos, query: Query<(&Transform, &Pr
                         continue;
                     }
 
-                    let is_pressurized = !exposed_cells.contains(&(x, y));
+                    let is_pressurized = !pressurization.is_exposed((x, y));
 
                     // Determine the cell color based on pressurization status
                     let color = if is_pressurized {
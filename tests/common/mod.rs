@@ -0,0 +1,27 @@
+use my_game::configs::prelude::*;
+use my_game::core::prelude::*;
+use my_game::prelude::*;
+
+/// Frames allowed to load the data files and build the world before giving up.
+const MAX_LOADING_FRAMES: usize = 600;
+
+/// A headless app with the default settings, stepped until the world is built and the game started.
+pub fn headless_app_in_game() -> App {
+    let mut app = App::new();
+    app.add_plugins(HeadlessPlugins::default());
+    app.finish();
+    app.cleanup();
+
+    for _ in 0..MAX_LOADING_FRAMES {
+        if is_in_game(&app) {
+            break;
+        }
+        app.update();
+    }
+    assert!(is_in_game(&app), "the headless world did not reach the game in {} frames", MAX_LOADING_FRAMES);
+    app
+}
+
+fn is_in_game(app: &App) -> bool {
+    matches!(app.world().get_resource::<State<GameState>>().map(State::get), Some(GameState::InGame))
+}
@@ -0,0 +1,34 @@
+mod common;
+
+use my_game::prelude::*;
+use my_game::world::prelude::*;
+
+/// Frames simulated once in game, about 10 seconds at the headless timestep.
+const SIMULATED_FRAMES: usize = 600;
+
+/// Transforms of every structure after `SIMULATED_FRAMES` frames in game, in spawn order.
+fn simulate() -> Vec<(Entity, Transform)> {
+    let mut app = common::headless_app_in_game();
+
+    for _ in 0..SIMULATED_FRAMES {
+        app.update();
+    }
+
+    let mut structures: Vec<(Entity, Transform)> = app
+        .world_mut()
+        .query_filtered::<(Entity, &Transform), With<Structure>>()
+        .iter(app.world())
+        .map(|(entity, transform)| (entity, *transform))
+        .collect();
+    structures.sort_by_key(|(entity, _)| *entity);
+    structures
+}
+
+#[test]
+fn same_frames_give_the_same_structure_transforms() {
+    let first_run = simulate();
+    let second_run = simulate();
+
+    assert!(!first_run.is_empty(), "the headless world has no structure");
+    assert_eq!(first_run, second_run);
+}
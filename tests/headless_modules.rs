@@ -0,0 +1,28 @@
+mod common;
+
+use my_game::gameplay::prelude::*;
+use my_game::prelude::*;
+use my_game::world::prelude::*;
+
+#[test]
+fn life_support_covers_its_whole_footprint() {
+    let mut app = common::headless_app_in_game();
+
+    let life_supports: Vec<(Entity, Module, Entity)> = app
+        .world_mut()
+        .query_filtered::<(Entity, &Module, &Parent), With<LifeSupportModule>>()
+        .iter(app.world())
+        .map(|(entity, module, parent)| (entity, module.clone(), parent.get()))
+        .collect();
+    assert!(!life_supports.is_empty(), "no structure has a life support");
+
+    for (module_entity, module, structure_entity) in life_supports {
+        assert_eq!(module.footprint, Footprint::new(2, 2));
+        let structure = app.world().get::<Structure>(structure_entity).expect("the module has no structure");
+        let cells: Vec<(i32, i32)> = module.cells().collect();
+        assert_eq!(cells.len(), 4);
+        for cell in cells {
+            assert_eq!(structure.module_at(cell), Some(module_entity), "cell {:?} is not covered", cell);
+        }
+    }
+}